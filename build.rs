@@ -7,7 +7,7 @@
 }
 */
 
-use std::{error::Error, fs};
+use std::{env, error::Error, fs, path::PathBuf};
 
 static OUT_DIR: &str = "src/rpc";
 
@@ -19,9 +19,17 @@ fn main() -> Result<(), Box<dyn Error>> {
     ];
 
     fs::create_dir_all(OUT_DIR).unwrap();
+    // 提供 gRPC reflection 使用的描述檔
+    let descriptor_path = PathBuf::from(env::var("OUT_DIR")?).join("descriptor.bin");
     tonic_build::configure()
         .build_server(true)
         .out_dir(OUT_DIR)
+        .file_descriptor_set_path(descriptor_path)
+        // HTTP/JSON API 直接以 stock 的訊息作為查詢參數與回應
+        .type_attribute(
+            ".stock",
+            "#[derive(serde::Serialize, serde::Deserialize)] #[serde(default)]",
+        )
         .compile_protos(&protos, &["./etc/proto"])?;
 
     rerun(&protos);
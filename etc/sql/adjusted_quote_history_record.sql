@@ -0,0 +1,22 @@
+create table if not exists public.adjusted_quote_history_record
+(
+    security_code         varchar(24)              default ''::character varying                   not null
+        primary key,
+    maximum_price         numeric(18, 4)           default 0                                       not null,
+    maximum_price_date_on date                     default '1970-01-01'::date                      not null,
+    minimum_price         numeric(18, 4)           default 0                                       not null,
+    minimum_price_date_on date                     default '1970-01-01'::date                      not null,
+    processed_date        date                     default '1970-01-01'::date                      not null,
+    processed_factor      numeric(24, 12)          default 1                                       not null,
+    created_time          timestamp with time zone default ('now'::text)::timestamp with time zone not null,
+    updated_time          timestamp with time zone default ('now'::text)::timestamp with time zone not null
+);
+
+comment on table public.adjusted_quote_history_record is '以除權息還原股價計算的歷史最高、最低價，原始股價的歷史高低價在 quote_history_record';
+comment on column public.adjusted_quote_history_record.security_code is '股票代號';
+comment on column public.adjusted_quote_history_record.maximum_price is '還原股價的歷史最高價';
+comment on column public.adjusted_quote_history_record.maximum_price_date_on is '還原股價的歷史最高價出現在哪一天';
+comment on column public.adjusted_quote_history_record.minimum_price is '還原股價的歷史最低價';
+comment on column public.adjusted_quote_history_record.minimum_price_date_on is '還原股價的歷史最低價出現在哪一天';
+comment on column public.adjusted_quote_history_record.processed_date is '已計算到哪一天的收盤數據，下次從隔天開始計算';
+comment on column public.adjusted_quote_history_record.processed_factor is '計算時 processed_date 的累積調整因子，之後有新的除權息時以此換算已記錄的高低價';
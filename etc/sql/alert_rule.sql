@@ -0,0 +1,35 @@
+create table if not exists public.alert_rule
+(
+    serial              bigserial
+        primary key,
+    name                varchar(128)             default ''::character varying                   not null,
+    security_code       varchar(24)              default ''::character varying                   not null,
+    condition           jsonb                                                                    not null,
+    cooldown_minutes    integer                  default 300                                     not null,
+    enabled             boolean                  default true                                    not null,
+    last_triggered_time timestamp with time zone,
+    created_time        timestamp with time zone default ('now'::text)::timestamp with time zone not null,
+    updated_time        timestamp with time zone default ('now'::text)::timestamp with time zone not null
+);
+
+comment on table public.alert_rule is '股價提醒規則';
+comment on column public.alert_rule.name is '規則名稱';
+comment on column public.alert_rule.security_code is '股票代號';
+comment on column public.alert_rule.condition is '觸發條件，例如 {"type":"all","conditions":[{"type":"change_percent","op":"<=","value":-5},{"type":"estimate","band":"cheap","op":"<="}]}';
+comment on column public.alert_rule.cooldown_minutes is '觸發後幾分鐘內不再重複提醒';
+comment on column public.alert_rule.enabled is '是否啟用';
+comment on column public.alert_rule.last_triggered_time is '最後一次觸發的時間';
+
+create index if not exists "alert_rule-security_code-idx"
+    on public.alert_rule (security_code);
+
+-- 將舊的 trace 高低標轉換成提醒規則
+insert into public.alert_rule (name, security_code, condition)
+select '低於最低價', stock_symbol, jsonb_build_object('type', 'price', 'op', '<=', 'value', floor)
+from public.trace
+where floor > 0;
+
+insert into public.alert_rule (name, security_code, condition)
+select '超過最高價', stock_symbol, jsonb_build_object('type', 'price', 'op', '>=', 'value', ceiling)
+from public.trace
+where ceiling > 0;
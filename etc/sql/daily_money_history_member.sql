@@ -0,0 +1,38 @@
+create table if not exists public.daily_money_history_member
+(
+    date                       date                     default CURRENT_DATE                            not null,
+    member_id                  bigint                   default 0                                       not null,
+    market_value               numeric(18, 4)           default 0                                       not null,
+    cost                       numeric(18, 4)           default 0                                       not null,
+    unrealized_profit_and_loss numeric(18, 4)           default 0                                       not null,
+    dividend_income            numeric(18, 4)           default 0                                       not null,
+    created_time               timestamp with time zone default ('now'::text)::timestamp with time zone not null,
+    updated_time               timestamp with time zone default ('now'::text)::timestamp with time zone not null,
+    primary key (date, member_id)
+);
+
+comment on table public.daily_money_history_member is '每日各會員的市值記錄';
+comment on column public.daily_money_history_member.date is '資料屬於那一天';
+comment on column public.daily_money_history_member.member_id is '會員編號 member.id';
+comment on column public.daily_money_history_member.market_value is '市值';
+comment on column public.daily_money_history_member.cost is '持有成本，與 stock_ownership_details.holding_cost 相同為負數';
+comment on column public.daily_money_history_member.unrealized_profit_and_loss is '未實現損益(市值 + 持有成本)';
+comment on column public.daily_money_history_member.dividend_income is '庫存累積的股利(元)';
+
+create index if not exists "daily_money_history_member-member_id-idx"
+    on public.daily_money_history_member (member_id);
+
+-- 將舊的 eddie、unice 欄位轉換成各會員的市值記錄
+insert into public.daily_money_history_member (date, member_id, market_value)
+select date, 1, eddie
+from public.daily_money_history
+on conflict (date, member_id) do nothing;
+
+insert into public.daily_money_history_member (date, member_id, market_value)
+select date, 2, unice
+from public.daily_money_history
+on conflict (date, member_id) do nothing;
+
+alter table public.daily_money_history
+    drop column if exists eddie,
+    drop column if exists unice;
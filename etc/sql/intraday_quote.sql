@@ -0,0 +1,29 @@
+create table if not exists public.intraday_quote
+(
+    security_code varchar(24)              default ''::character varying                   not null,
+    date          date                     default CURRENT_DATE                            not null,
+    bar_time      timestamp with time zone                                                 not null,
+    opening_price numeric(18, 4)           default 0                                       not null,
+    highest_price numeric(18, 4)           default 0                                       not null,
+    lowest_price  numeric(18, 4)           default 0                                       not null,
+    closing_price numeric(18, 4)           default 0                                       not null,
+    samples       integer                  default 0                                       not null,
+    volume        bigint                   default 0                                       not null,
+    created_time  timestamp with time zone default ('now'::text)::timestamp with time zone not null,
+    updated_time  timestamp with time zone default ('now'::text)::timestamp with time zone not null,
+    primary key (security_code, bar_time)
+);
+
+comment on table public.intraday_quote is '盤中一分鐘K線';
+comment on column public.intraday_quote.security_code is '股票代號';
+comment on column public.intraday_quote.date is '交易日';
+comment on column public.intraday_quote.bar_time is '該分鐘K線的起始時間';
+comment on column public.intraday_quote.opening_price is '該分鐘第一筆取樣的價格';
+comment on column public.intraday_quote.highest_price is '該分鐘取樣的最高價';
+comment on column public.intraday_quote.lowest_price is '該分鐘取樣的最低價';
+comment on column public.intraday_quote.closing_price is '該分鐘最後一筆取樣的價格';
+comment on column public.intraday_quote.samples is '該分鐘取樣的次數';
+comment on column public.intraday_quote.volume is '該分鐘的成交股數，以取樣時的當日累計成交股數扣除之前K線的成交股數';
+
+create index if not exists "intraday_quote-date-security_code-idx"
+    on public.intraday_quote (date, security_code);
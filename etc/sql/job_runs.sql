@@ -0,0 +1,37 @@
+create table if not exists public.job_runs
+(
+    serial         bigserial
+        primary key,
+    job_name       varchar(64)              default ''::character varying                   not null,
+    trigger        varchar(16)              default ''::character varying                   not null,
+    scheduled_time timestamp with time zone,
+    parameters     jsonb                    default '{}'::jsonb                             not null,
+    status         varchar(16)              default 'running'::character varying            not null,
+    error          text                     default ''::text                                not null,
+    started_time   timestamp with time zone default ('now'::text)::timestamp with time zone not null,
+    finished_time  timestamp with time zone,
+    instance       varchar(128)             default ''::character varying                   not null,
+    deferred_time  timestamp with time zone,
+    created_time   timestamp with time zone default ('now'::text)::timestamp with time zone not null,
+    updated_time   timestamp with time zone default ('now'::text)::timestamp with time zone not null
+);
+
+comment on table public.job_runs is '排程工作的執行記錄';
+comment on column public.job_runs.job_name is '工作名稱';
+comment on column public.job_runs.trigger is '觸發方式 schedule:排程 catch_up:啟動時補執行 manual:手動';
+comment on column public.job_runs.scheduled_time is '對應的排程時間，手動觸發時為空值';
+comment on column public.job_runs.parameters is '執行參數，例如 {"date":"2024-01-02"}';
+comment on column public.job_runs.status is '狀態 deferred(順延等待中) running succeeded failed cancelled interrupted(服務中止時仍在執行)';
+comment on column public.job_runs.error is '失敗的原因';
+comment on column public.job_runs.started_time is '開始執行的時間';
+comment on column public.job_runs.finished_time is '結束執行的時間';
+comment on column public.job_runs.instance is '執行的實例，與排程租約(scheduler:lease:工作名稱)的持有者相同';
+comment on column public.job_runs.deferred_time is '順延執行的時間，狀態為 deferred 時有值';
+
+-- 同一個排程時間只會有一筆記錄，用來確保排程與補執行不會重複
+create unique index if not exists "job_runs-job_name-scheduled_time-uidx"
+    on public.job_runs (job_name, scheduled_time)
+    where scheduled_time is not null;
+
+create index if not exists "job_runs-started_time-idx"
+    on public.job_runs (started_time);
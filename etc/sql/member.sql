@@ -0,0 +1,19 @@
+create table if not exists public.member
+(
+    id           bigint                                                                   not null
+        primary key,
+    name         varchar(64)              default ''::character varying                   not null,
+    enabled      boolean                  default true                                    not null,
+    created_time timestamp with time zone default ('now'::text)::timestamp with time zone not null,
+    updated_time timestamp with time zone default ('now'::text)::timestamp with time zone not null
+);
+
+comment on table public.member is '持股的會員(帳戶)';
+comment on column public.member.id is '會員編號，對應 stock_ownership_details.member_id';
+comment on column public.member.name is '通知時顯示的名稱';
+comment on column public.member.enabled is '是否列入每日市值通知';
+
+insert into public.member (id, name)
+values (1, 'Eddie'),
+       (2, 'Unice')
+on conflict (id) do nothing;
@@ -0,0 +1,24 @@
+create table if not exists public.price_adjustment_factor
+(
+    security_code          varchar(24)              default ''::character varying                   not null,
+    date                   date                                                                     not null,
+    previous_closing_price numeric(18, 4)           default 0                                       not null,
+    cash_dividend          numeric(18, 4)           default 0                                       not null,
+    stock_dividend         numeric(18, 4)           default 0                                       not null,
+    reference_price        numeric(18, 4)           default 0                                       not null,
+    factor                 numeric(24, 12)          default 1                                       not null,
+    cumulative_factor      numeric(24, 12)          default 1                                       not null,
+    created_time           timestamp with time zone default ('now'::text)::timestamp with time zone not null,
+    updated_time           timestamp with time zone default ('now'::text)::timestamp with time zone not null,
+    primary key (security_code, date)
+);
+
+comment on table public.price_adjustment_factor is '除權息還原股價的調整因子';
+comment on column public.price_adjustment_factor.security_code is '股票代號';
+comment on column public.price_adjustment_factor.date is '除權息日';
+comment on column public.price_adjustment_factor.previous_closing_price is '除權息前一個交易日的收盤價';
+comment on column public.price_adjustment_factor.cash_dividend is '每股現金股利(元)';
+comment on column public.price_adjustment_factor.stock_dividend is '每股股票股利(元)';
+comment on column public.price_adjustment_factor.reference_price is '除權息參考價 = (前一日收盤價 - 現金股利) / (1 + 股票股利 / 10)';
+comment on column public.price_adjustment_factor.factor is '當次除權息的調整因子 = 除權息參考價 / 前一日收盤價';
+comment on column public.price_adjustment_factor.cumulative_factor is '本次及之後所有除權息調整因子的乘積，早於本次除權息日(且晚於上一次除權息日)的股價乘上此值即為還原股價';
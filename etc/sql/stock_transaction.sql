@@ -0,0 +1,47 @@
+create table if not exists public.stock_transaction
+(
+    serial        bigserial
+        primary key,
+    member_id     bigint                   default 0                                       not null,
+    security_code varchar(24)              default ''::character varying                   not null,
+    trade_date    date                     default CURRENT_DATE                            not null,
+    kind          varchar(16)              default 'buy'::character varying                not null,
+    quantity      bigint                   default 0                                       not null,
+    price         numeric(18, 4)           default 0                                       not null,
+    fee           numeric(18, 4)           default 0                                       not null,
+    tax           numeric(18, 4)           default 0                                       not null,
+    applied_until date,
+    opening_dividends_cash        numeric(18, 4) default 0 not null,
+    opening_dividends_stock       numeric(18, 4) default 0 not null,
+    opening_dividends_stock_money numeric(18, 4) default 0 not null,
+    created_time  timestamp with time zone default ('now'::text)::timestamp with time zone not null,
+    updated_time  timestamp with time zone default ('now'::text)::timestamp with time zone not null
+);
+
+comment on table public.stock_transaction is '股票交易明細，持股名細(stock_ownership_details)由此重建';
+comment on column public.stock_transaction.member_id is '會員編號 member.id';
+comment on column public.stock_transaction.security_code is '股票代號';
+comment on column public.stock_transaction.trade_date is '交易日(除權息、分割為生效日)';
+comment on column public.stock_transaction.kind is 'buy 買進、sell 賣出、split 分割、cash_dividend 現金股利、stock_dividend 股票股利';
+comment on column public.stock_transaction.quantity is '買進、賣出的股數';
+comment on column public.stock_transaction.price is '買進、賣出為每股價格；分割為每股換發的股數；現金股利為每股現金股利(元)；股票股利為每股股票股利(元)，以面額 10 元換算股數';
+comment on column public.stock_transaction.fee is '手續費(元)';
+comment on column public.stock_transaction.tax is '交易稅(元)';
+comment on column public.stock_transaction.applied_until is '由舊持股轉入的買進交易，此日(含)之前的分割與除權息已反映在股數與累積股利內';
+comment on column public.stock_transaction.opening_dividends_cash is '由舊持股轉入時已累積的現金股利(元)';
+comment on column public.stock_transaction.opening_dividends_stock is '由舊持股轉入時已累積的股票股利(股)';
+comment on column public.stock_transaction.opening_dividends_stock_money is '由舊持股轉入時已累積的股票股利(元)';
+
+create index if not exists "stock_transaction-member_id-security_code-idx"
+    on public.stock_transaction (member_id, security_code, trade_date);
+
+create unique index if not exists "stock_transaction-dividend-idx"
+    on public.stock_transaction (member_id, security_code, kind, trade_date)
+    where kind in ('cash_dividend', 'stock_dividend');
+
+-- 持股名細對應的買進交易，沒有對應的持股會在重建前轉為買進交易
+alter table public.stock_ownership_details
+    add if not exists transaction_serial bigint;
+comment on column public.stock_ownership_details.transaction_serial is '對應的買進交易 stock_transaction.serial';
+create unique index if not exists "stock_ownership_details-transaction_serial-idx"
+    on public.stock_ownership_details (transaction_serial);
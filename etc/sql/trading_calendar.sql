@@ -0,0 +1,14 @@
+create table if not exists public.trading_calendar
+(
+    date         date
+        primary key,
+    why          varchar(128)             default ''::character varying                   not null,
+    trading      boolean                  default false                                   not null,
+    created_time timestamp with time zone default ('now'::text)::timestamp with time zone not null,
+    updated_time timestamp with time zone default ('now'::text)::timestamp with time zone not null
+);
+
+comment on table public.trading_calendar is '證交所公告的休市日與交易日，每年向證交所查詢一次';
+comment on column public.trading_calendar.date is '日期';
+comment on column public.trading_calendar.why is '說明，例如 農曆春節、國曆新年開始交易';
+comment on column public.trading_calendar.trading is '是否為交易日，false 為休市日 true 為開始交易日或補行交易日';
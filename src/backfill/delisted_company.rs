@@ -1,16 +1,9 @@
 use anyhow::Result;
-use chrono::Local;
 
-use crate::{
-    cache::SHARE, crawler::twse, database::table::stock, logging, util::datetime::Weekend,
-};
+use crate::{cache::SHARE, crawler::twse, database::table::stock, logging};
 
 /// 更新資料庫中終止上市的公司
 pub async fn execute() -> Result<()> {
-    if Local::now().is_weekend() {
-        return Ok(());
-    }
-
     let delisted = twse::suspend_listing::visit().await?;
     let mut items_to_update = Vec::new();
 
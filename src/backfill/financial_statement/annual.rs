@@ -1,5 +1,4 @@
 use anyhow::Result;
-use chrono::Local;
 use futures::future;
 
 use crate::{
@@ -7,15 +6,11 @@ use crate::{
     crawler::wespai,
     database::table::{financial_statement, stock},
     logging, nosql,
-    util::{self, datetime::Weekend},
+    util,
 };
 
 /// 更新台股年報
 pub async fn execute() -> Result<()> {
-    if Local::now().is_weekend() {
-        return Ok(());
-    }
-
     let cache_key = "financial_statement:annual";
     let is_jump = nosql::redis::CLIENT.get_bool(cache_key).await?;
     if is_jump {
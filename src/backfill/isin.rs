@@ -1,20 +1,20 @@
 use std::fmt::Write;
 
 use anyhow::{anyhow, Result};
-use chrono::Local;
 use rust_decimal::prelude::ToPrimitive;
 
 use crate::{
-    bot, cache::SHARE, crawler::twse, database::table, declare::StockExchangeMarket, logging, rpc,
-    rpc::stock, util::datetime::Weekend,
+    bot::{self, notifier::NotifyEvent},
+    cache::SHARE,
+    crawler::twse,
+    database::table,
+    declare::StockExchangeMarket,
+    logging, rpc,
+    rpc::stock,
 };
 
 /// 更新資料庫新上市股票的或更新其交易所的市場編號、股票的產業分類、名稱等欄位
 pub async fn execute() -> Result<()> {
-    if Local::now().is_weekend() {
-        return Ok(());
-    }
-
     let tasks: Vec<_> = StockExchangeMarket::iterator()
         .map(process_market)
         .collect();
@@ -56,7 +56,7 @@ async fn process_market(mode: StockExchangeMarket) -> Result<()> {
         }
     }
 
-    bot::telegram::send(&to_bot_msg).await;
+    bot::notifier::send(NotifyEvent::StockInfo, &to_bot_msg).await;
 
     Ok(())
 }
@@ -1,17 +1,12 @@
 use anyhow::Result;
-use chrono::Local;
 
 use crate::{
     backfill::net_asset_value_per_share::update, cache::SHARE, crawler::tpex, database::table,
-    logging, util::datetime::Weekend,
+    logging,
 };
 
 /// 更新興櫃股票的每股淨值
 pub async fn execute() -> Result<()> {
-    if Local::now().is_weekend() {
-        return Ok(());
-    }
-
     let result = tpex::net_asset_value_per_share::visit().await?;
 
     for item in result {
@@ -4,17 +4,11 @@ use chrono::{DateTime, FixedOffset, Local};
 use crate::{
     cache::SHARE, crawler::twse,
     database::table::stock::extension::qualified_foreign_institutional_investor::QualifiedForeignInstitutionalInvestor,
-    logging, util::datetime::Weekend,
+    logging,
 };
 
 pub async fn execute() -> Result<()> {
-    let now = Local::now();
-
-    if now.is_weekend() {
-        return Ok(());
-    }
-
-    tokio::try_join!(listed(now.fixed_offset()), otc())?;
+    tokio::try_join!(listed(Local::now().fixed_offset()), otc())?;
 
     Ok(())
 }
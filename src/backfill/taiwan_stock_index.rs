@@ -2,7 +2,13 @@ use anyhow::Result;
 use chrono::Local;
 
 use crate::util::map::Keyable;
-use crate::{bot, cache::SHARE, crawler::twse, database::table, logging};
+use crate::{
+    bot::{self, notifier::NotifyEvent},
+    cache::SHARE,
+    crawler::twse,
+    database::table,
+    logging,
+};
 
 /// 調用  twse API 取得台股加權指數
 pub async fn execute() -> Result<()> {
@@ -44,7 +50,7 @@ pub async fn execute() -> Result<()> {
                         index.date, index.index, index.change
                     );
 
-                    bot::telegram::send(&msg).await;
+                    bot::notifier::send(NotifyEvent::Index, &msg).await;
 
                     SHARE.set_stock_index(key, index).await;
                 }
@@ -0,0 +1,91 @@
+use rust_decimal::{Decimal, RoundingStrategy};
+use rust_decimal_macros::dec;
+
+/// 台股交易成本
+///
+/// 手續費為成交金額 × 0.1425% × 折扣，不足最低手續費時以最低手續費計算；
+/// 證券交易稅只在賣出時收取，一般股票為 0.3%，ETF 為 0.1%。
+/// 手續費與交易稅皆無條件捨去至整數元。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fee {
+    /// 手續費率
+    pub commission_rate: Decimal,
+    /// 手續費折扣(1 表示不打折，0.6 表示六折)
+    pub commission_discount: Decimal,
+    /// 最低手續費(元)
+    pub minimum_commission: Decimal,
+    /// 證券交易稅率
+    pub tax_rate: Decimal,
+}
+
+impl Fee {
+    pub fn new() -> Self {
+        Fee {
+            commission_rate: dec!(0.001425),
+            commission_discount: Decimal::ONE,
+            minimum_commission: dec!(20),
+            tax_rate: dec!(0.003),
+        }
+    }
+
+    /// ETF 的交易成本(證交稅 0.1%)
+    pub fn etf() -> Self {
+        Fee {
+            tax_rate: dec!(0.001),
+            ..Self::new()
+        }
+    }
+
+    /// 計算成交金額的手續費
+    pub fn commission(&self, amount: Decimal) -> Decimal {
+        if amount <= Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+
+        let commission = (amount * self.commission_rate * self.commission_discount)
+            .round_dp_with_strategy(0, RoundingStrategy::ToZero);
+
+        commission.max(self.minimum_commission)
+    }
+
+    /// 計算賣出成交金額的證券交易稅
+    pub fn tax(&self, amount: Decimal) -> Decimal {
+        if amount <= Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+
+        (amount * self.tax_rate).round_dp_with_strategy(0, RoundingStrategy::ToZero)
+    }
+}
+
+impl Default for Fee {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commission() {
+        let fee = Fee::new();
+        assert_eq!(fee.commission(dec!(600000)), dec!(855));
+        assert_eq!(fee.commission(dec!(1000)), dec!(20));
+        assert_eq!(fee.commission(Decimal::ZERO), Decimal::ZERO);
+
+        let discount = Fee {
+            commission_discount: dec!(0.6),
+            ..Fee::new()
+        };
+        assert_eq!(discount.commission(dec!(600000)), dec!(513));
+    }
+
+    #[test]
+    fn test_tax() {
+        assert_eq!(Fee::new().tax(dec!(650000)), dec!(1950));
+        assert_eq!(Fee::etf().tax(dec!(650000)), dec!(650));
+        assert_eq!(Fee::new().tax(dec!(333)), Decimal::ZERO);
+    }
+}
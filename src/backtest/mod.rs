@@ -0,0 +1,489 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use rust_decimal::{prelude::FromPrimitive, Decimal, RoundingStrategy};
+use rust_decimal_macros::dec;
+
+use crate::{
+    backtest::{
+        fee::Fee,
+        report::{EquityPoint, Report, Side, Trade},
+        strategy::{Signal, Strategy},
+    },
+    calculation::adjusted_price::DividendEvent,
+    database::table::{
+        daily_quote::{self, DailyQuote},
+        dividend::Dividend,
+        estimate::Estimate,
+        stock,
+    },
+};
+
+/// 台股交易成本
+pub mod fee;
+/// 回測績效報告
+pub mod report;
+/// 回測策略
+pub mod strategy;
+
+/// 回測時每個交易日的資料
+#[derive(Debug, Clone, Default)]
+pub struct Bar {
+    /// 當日收盤行情(含均線)
+    pub quote: DailyQuote,
+    /// 當日(或最近一次)估算的便宜價，零表示沒有估價資料
+    pub cheap: Decimal,
+    /// 當日(或最近一次)估算的合理價
+    pub fair: Decimal,
+    /// 當日(或最近一次)估算的昂貴價
+    pub expensive: Decimal,
+}
+
+/// 回測的參數
+#[derive(Debug, Clone)]
+pub struct Setting {
+    /// 初始資金
+    pub initial_capital: Decimal,
+    /// 交易成本
+    pub fee: Fee,
+    /// 每次買進的股數需為此數的倍數(整股 1000，零股 1)
+    pub lot_size: i64,
+    /// 年化無風險利率，用於計算夏普值
+    pub risk_free_rate: f64,
+}
+
+impl Setting {
+    /// 依股票類型選擇交易成本，ETF 的證交稅為 0.1%
+    pub fn new(security_code: &str) -> Self {
+        Setting {
+            fee: if stock::is_etf(security_code) {
+                Fee::etf()
+            } else {
+                Fee::new()
+            },
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for Setting {
+    fn default() -> Self {
+        Setting {
+            initial_capital: dec!(1000000),
+            fee: Fee::new(),
+            lot_size: 1000,
+            risk_free_rate: 0.0,
+        }
+    }
+}
+
+/// 回測期間的帳戶狀態
+#[derive(Debug, Clone, Default)]
+pub struct Position {
+    /// 可用現金
+    pub cash: Decimal,
+    /// 持有股數
+    pub shares: i64,
+    /// 目前持股的買進成本(含手續費)
+    pub cost: Decimal,
+    /// 目前持股期間領取的現金股利
+    pub dividend: Decimal,
+}
+
+impl Position {
+    pub fn new(cash: Decimal) -> Self {
+        Position {
+            cash,
+            ..Default::default()
+        }
+    }
+
+    /// 以指定價格計算的帳戶淨值
+    pub fn equity(&self, price: Decimal) -> Decimal {
+        self.cash + price * Decimal::from(self.shares)
+    }
+}
+
+/// 從資料庫取出指定股票的歷史行情、估價與股利後執行回測
+pub async fn execute(
+    security_code: &str,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    strategy: &mut dyn Strategy,
+    setting: &Setting,
+) -> Result<Report> {
+    let quotes =
+        daily_quote::fetch_daily_quotes_by_security_code(security_code, start_date, end_date)
+            .await?;
+    let estimates = Estimate::fetch_by_security_code(security_code, start_date, end_date).await?;
+    let dividends = Dividend::fetch_by_security_code(security_code).await?;
+
+    let bars = build_bars(quotes, &estimates);
+    let events = DividendEvent::from_dividends(&dividends);
+
+    Ok(run(security_code, &bars, &events, strategy, setting))
+}
+
+/// 將每日行情與估價合併，估價缺漏的交易日沿用最近一次的估價
+pub fn build_bars(quotes: Vec<DailyQuote>, estimates: &[Estimate]) -> Vec<Bar> {
+    let mut estimates = estimates.iter().peekable();
+    let mut band = (Decimal::ZERO, Decimal::ZERO, Decimal::ZERO);
+
+    quotes
+        .into_iter()
+        .map(|quote| {
+            while let Some(e) = estimates.next_if(|e| e.date <= quote.date) {
+                band = (
+                    Decimal::from_f64(e.cheap).unwrap_or_default(),
+                    Decimal::from_f64(e.fair).unwrap_or_default(),
+                    Decimal::from_f64(e.expensive).unwrap_or_default(),
+                );
+            }
+
+            Bar {
+                quote,
+                cheap: band.0,
+                fair: band.1,
+                expensive: band.2,
+            }
+        })
+        .collect()
+}
+
+/// 依序以每個交易日的資料執行策略
+///
+/// 策略於收盤後產生的訊號以下一個交易日的開盤價成交(開盤價為零時以收盤價成交)，
+/// 除權息日前一日收盤仍持有的股票可參與除權息，現金股利於除息日直接入帳。
+pub fn run(
+    security_code: &str,
+    bars: &[Bar],
+    events: &[DividendEvent],
+    strategy: &mut dyn Strategy,
+    setting: &Setting,
+) -> Report {
+    let mut report = Report {
+        strategy: strategy.name(),
+        security_code: security_code.to_string(),
+        initial_capital: setting.initial_capital,
+        final_equity: setting.initial_capital,
+        dividend_income: Decimal::ZERO,
+        stock_dividend_shares: 0,
+        fee_paid: Decimal::ZERO,
+        cagr: 0.0,
+        max_drawdown: 0.0,
+        sharpe_ratio: 0.0,
+        equity_curve: Vec::with_capacity(bars.len()),
+        trades: Vec::new(),
+    };
+    let mut position = Position::new(setting.initial_capital);
+    let mut signal = Signal::Hold;
+    let mut last_price = Decimal::ZERO;
+    let mut previous_date: Option<NaiveDate> = None;
+    let mut events = events.iter().peekable();
+
+    for bar in bars {
+        let date = bar.quote.date;
+
+        // 除權息
+        while let Some(event) = events.next_if(|e| e.date <= date) {
+            if previous_date.is_some_and(|d| event.date > d) && position.shares > 0 {
+                apply_dividend(&mut position, &mut report, event);
+            }
+        }
+
+        let price = if bar.quote.opening_price > Decimal::ZERO {
+            bar.quote.opening_price
+        } else {
+            bar.quote.closing_price
+        };
+
+        if price > Decimal::ZERO {
+            match signal {
+                Signal::Buy => buy(&mut position, &mut report, setting, date, price),
+                Signal::Sell => sell(&mut position, &mut report, setting, date, price),
+                Signal::Hold => {}
+            }
+        }
+
+        if bar.quote.closing_price > Decimal::ZERO {
+            last_price = bar.quote.closing_price;
+        }
+
+        report.equity_curve.push(EquityPoint {
+            date,
+            equity: position.equity(last_price),
+        });
+
+        signal = strategy.on_bar(bar, &position);
+        previous_date = Some(date);
+    }
+
+    report.final_equity = position.equity(last_price);
+    report.calculate_metrics(setting.risk_free_rate);
+
+    report
+}
+
+fn apply_dividend(position: &mut Position, report: &mut Report, event: &DividendEvent) {
+    let shares = Decimal::from(position.shares);
+
+    let cash = (shares * event.cash_dividend).round_dp_with_strategy(0, RoundingStrategy::ToZero);
+    position.cash += cash;
+    position.dividend += cash;
+    report.dividend_income += cash;
+
+    // 股票股利以面額 10 元換算，不足一股的部分捨去
+    let stock = (shares * event.stock_dividend / dec!(10))
+        .round_dp_with_strategy(0, RoundingStrategy::ToZero)
+        .try_into()
+        .unwrap_or(0i64);
+    position.shares += stock;
+    report.stock_dividend_shares += stock;
+}
+
+fn buy(
+    position: &mut Position,
+    report: &mut Report,
+    setting: &Setting,
+    date: NaiveDate,
+    price: Decimal,
+) {
+    let lot_size = setting.lot_size.max(1);
+    let cost_per_share = price * (Decimal::ONE + setting.fee.commission_rate);
+    let affordable: i64 = (position.cash / cost_per_share)
+        .round_dp_with_strategy(0, RoundingStrategy::ToZero)
+        .try_into()
+        .unwrap_or(0);
+    let mut shares = affordable / lot_size * lot_size;
+
+    // 最低手續費可能讓資金不足，逐次減少一個交易單位
+    while shares > 0 {
+        let amount = price * Decimal::from(shares);
+        let commission = setting.fee.commission(amount);
+        if amount + commission <= position.cash {
+            position.cash -= amount + commission;
+            position.shares += shares;
+            position.cost += amount + commission;
+            report.fee_paid += commission;
+            report.trades.push(Trade {
+                date,
+                side: Side::Buy,
+                price,
+                shares,
+                amount,
+                commission,
+                tax: Decimal::ZERO,
+                profit: Decimal::ZERO,
+            });
+            return;
+        }
+        shares -= lot_size;
+    }
+}
+
+fn sell(
+    position: &mut Position,
+    report: &mut Report,
+    setting: &Setting,
+    date: NaiveDate,
+    price: Decimal,
+) {
+    if position.shares == 0 {
+        return;
+    }
+
+    let shares = position.shares;
+    let amount = price * Decimal::from(shares);
+    let commission = setting.fee.commission(amount);
+    let tax = setting.fee.tax(amount);
+    let proceeds = amount - commission - tax;
+
+    report.fee_paid += commission + tax;
+    report.trades.push(Trade {
+        date,
+        side: Side::Sell,
+        price,
+        shares,
+        amount,
+        commission,
+        tax,
+        profit: proceeds - position.cost + position.dividend,
+    });
+
+    position.cash += proceeds;
+    position.shares = 0;
+    position.cost = Decimal::ZERO;
+    position.dividend = Decimal::ZERO;
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Datelike, Local, TimeDelta};
+
+    use crate::{
+        backtest::strategy::EstimateBand,
+        cache::SHARE,
+        logging,
+    };
+
+    use super::*;
+
+    fn bar(date: NaiveDate, price: Decimal, cheap: Decimal, expensive: Decimal) -> Bar {
+        let mut bar = Bar::default();
+        bar.quote.date = date;
+        bar.quote.opening_price = price;
+        bar.quote.closing_price = price;
+        bar.cheap = cheap;
+        bar.expensive = expensive;
+        bar
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, 7, day).unwrap()
+    }
+
+    #[test]
+    fn test_build_bars() {
+        let mut quotes = Vec::new();
+        for day in 3..=6 {
+            let mut quote = DailyQuote::new("2330".to_string());
+            quote.date = date(day);
+            quotes.push(quote);
+        }
+        let mut e1 = Estimate::new("2330".to_string(), date(4));
+        e1.cheap = 100.0;
+        e1.expensive = 150.0;
+        let mut e2 = Estimate::new("2330".to_string(), date(6));
+        e2.cheap = 110.0;
+
+        let bars = build_bars(quotes, &[e1, e2]);
+
+        assert_eq!(bars[0].cheap, Decimal::ZERO);
+        assert_eq!(bars[1].cheap, dec!(100));
+        assert_eq!(bars[2].expensive, dec!(150));
+        assert_eq!(bars[3].cheap, dec!(110));
+    }
+
+    #[test]
+    fn test_run() {
+        let bars = vec![
+            bar(date(3), dec!(90), dec!(100), dec!(150)),
+            bar(date(4), dec!(95), dec!(100), dec!(150)),
+            bar(date(5), dec!(120), dec!(100), dec!(150)),
+            bar(date(6), dec!(160), dec!(100), dec!(150)),
+            bar(date(7), dec!(170), dec!(100), dec!(150)),
+        ];
+        let events = vec![DividendEvent {
+            date: date(5),
+            cash_dividend: dec!(2),
+            stock_dividend: dec!(1),
+        }];
+        let setting = Setting {
+            initial_capital: dec!(100000),
+            ..Default::default()
+        };
+
+        let report = run("2330", &bars, &events, &mut EstimateBand, &setting);
+
+        // 7/3 收盤低於便宜價，7/4 以 95 買進 1000 股，手續費 135
+        let buy = &report.trades[0];
+        assert_eq!(buy.side, Side::Buy);
+        assert_eq!(buy.date, date(4));
+        assert_eq!(buy.shares, 1000);
+        assert_eq!(buy.commission, dec!(135));
+
+        // 7/5 除權息：現金股利 2000 元，配股 100 股
+        assert_eq!(report.dividend_income, dec!(2000));
+        assert_eq!(report.stock_dividend_shares, 100);
+
+        // 7/6 收盤高於昂貴價，7/7 以 170 賣出 1100 股
+        let sell = &report.trades[1];
+        assert_eq!(sell.side, Side::Sell);
+        assert_eq!(sell.date, date(7));
+        assert_eq!(sell.shares, 1100);
+        assert_eq!(sell.commission, dec!(266));
+        assert_eq!(sell.tax, dec!(561));
+        assert_eq!(sell.profit, dec!(187000) - dec!(827) - dec!(95135) + dec!(2000));
+
+        assert_eq!(report.equity_curve.len(), 5);
+        assert_eq!(
+            report.final_equity,
+            dec!(100000) - dec!(95135) + dec!(2000) + dec!(187000) - dec!(827)
+        );
+        assert_eq!(report.fee_paid, dec!(135) + dec!(827));
+        assert!(report.cagr > 0.0);
+        // 7/4 買進的手續費讓淨值略低於初始資金
+        assert!(report.max_drawdown > 0.0);
+    }
+
+    #[test]
+    fn test_run_etf() {
+        let bars = vec![
+            bar(date(3), dec!(90), dec!(100), dec!(150)),
+            bar(date(4), dec!(95), dec!(100), dec!(150)),
+            bar(date(5), dec!(160), dec!(100), dec!(150)),
+            bar(date(6), dec!(170), dec!(100), dec!(150)),
+        ];
+        let setting = Setting {
+            initial_capital: dec!(100000),
+            ..Setting::new("0050")
+        };
+
+        let report = run("0050", &bars, &[], &mut EstimateBand, &setting);
+
+        assert_eq!(setting.fee, Fee::etf());
+        assert_eq!(Setting::new("2330").fee, Fee::new());
+
+        // 7/6 以 170 賣出 1000 股，ETF 證交稅 0.1%
+        let sell = &report.trades[1];
+        assert_eq!(sell.shares, 1000);
+        assert_eq!(sell.commission, dec!(242));
+        assert_eq!(sell.tax, dec!(170));
+        assert_eq!(report.fee_paid, dec!(135) + dec!(242) + dec!(170));
+    }
+
+    #[test]
+    fn test_run_without_signal() {
+        let bars = vec![
+            bar(date(3), dec!(120), dec!(100), dec!(150)),
+            bar(date(4), dec!(130), dec!(100), dec!(150)),
+        ];
+
+        let report = run("2330", &bars, &[], &mut EstimateBand, &Setting::default());
+
+        assert!(report.trades.is_empty());
+        assert_eq!(report.final_equity, report.initial_capital);
+        assert_eq!(report.cagr, 0.0);
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_execute() {
+        dotenv::dotenv().ok();
+        SHARE.load().await;
+        logging::debug_file_async("開始 backtest::execute".to_string());
+
+        let end_date = Local::now().date_naive();
+        let start_date = end_date
+            .with_year(end_date.year() - 10)
+            .unwrap_or(end_date - TimeDelta::try_days(3650).unwrap());
+
+        match execute(
+            "2330",
+            start_date,
+            end_date,
+            &mut EstimateBand,
+            &Setting::new("2330"),
+        )
+        .await
+        {
+            Ok(report) => {
+                logging::debug_file_async(format!("{}\n{:#?}", report, report.trades));
+            }
+            Err(why) => {
+                logging::debug_file_async(format!("Failed to backtest::execute because {:?}", why));
+            }
+        }
+
+        logging::debug_file_async("結束 backtest::execute".to_string());
+    }
+}
@@ -0,0 +1,201 @@
+use std::fmt;
+
+use chrono::NaiveDate;
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use strum_macros::Display;
+
+/// 一年的交易日數(用於年化夏普值)
+const TRADING_DAYS_PER_YEAR: f64 = 252.0;
+
+/// 買賣方向
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Display)]
+pub enum Side {
+    #[strum(serialize = "買進")]
+    Buy,
+    #[strum(serialize = "賣出")]
+    Sell,
+}
+
+/// 成交明細
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trade {
+    /// 成交日期
+    pub date: NaiveDate,
+    pub side: Side,
+    /// 成交價
+    pub price: Decimal,
+    /// 成交股數
+    pub shares: i64,
+    /// 成交金額
+    pub amount: Decimal,
+    /// 手續費
+    pub commission: Decimal,
+    /// 證券交易稅
+    pub tax: Decimal,
+    /// 賣出時的已實現損益(含手續費、交易稅與持有期間領取的現金股利)
+    pub profit: Decimal,
+}
+
+/// 每日的帳戶淨值
+#[derive(Debug, Clone, PartialEq)]
+pub struct EquityPoint {
+    pub date: NaiveDate,
+    /// 現金 + 持股市值
+    pub equity: Decimal,
+}
+
+/// 回測績效報告
+#[derive(Debug, Clone)]
+pub struct Report {
+    /// 策略名稱
+    pub strategy: String,
+    /// 股票代號
+    pub security_code: String,
+    /// 初始資金
+    pub initial_capital: Decimal,
+    /// 期末淨值
+    pub final_equity: Decimal,
+    /// 領取的現金股利
+    pub dividend_income: Decimal,
+    /// 配股取得的股數
+    pub stock_dividend_shares: i64,
+    /// 支付的手續費與交易稅
+    pub fee_paid: Decimal,
+    /// 年化報酬率
+    pub cagr: f64,
+    /// 最大回撤(0.2 表示 20%)
+    pub max_drawdown: f64,
+    /// 年化夏普值
+    pub sharpe_ratio: f64,
+    /// 每日淨值曲線
+    pub equity_curve: Vec<EquityPoint>,
+    /// 成交明細
+    pub trades: Vec<Trade>,
+}
+
+impl Report {
+    /// 由淨值曲線計算年化報酬率、最大回撤與夏普值
+    pub fn calculate_metrics(&mut self, risk_free_rate: f64) {
+        let equities: Vec<f64> = self
+            .equity_curve
+            .iter()
+            .map(|point| point.equity.to_f64().unwrap_or_default())
+            .collect();
+
+        let days = match (self.equity_curve.first(), self.equity_curve.last()) {
+            (Some(first), Some(last)) => (last.date - first.date).num_days(),
+            _ => 0,
+        };
+
+        self.cagr = cagr(
+            self.initial_capital.to_f64().unwrap_or_default(),
+            self.final_equity.to_f64().unwrap_or_default(),
+            days,
+        );
+        self.max_drawdown = max_drawdown(&equities);
+        self.sharpe_ratio = sharpe_ratio(&equities, risk_free_rate);
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (start, end) = match (self.equity_curve.first(), self.equity_curve.last()) {
+            (Some(first), Some(last)) => (first.date.to_string(), last.date.to_string()),
+            _ => ("-".to_string(), "-".to_string()),
+        };
+
+        write!(
+            f,
+            "{} {} 回測 {} ~ {}\n初始資金:{} 期末淨值:{}\n年化報酬率:{:.2}% 最大回撤:{:.2}% 夏普值:{:.2}\n現金股利:{} 配股:{}股 交易成本:{} 成交筆數:{}",
+            self.security_code,
+            self.strategy,
+            start,
+            end,
+            self.initial_capital.round_dp(0),
+            self.final_equity.round_dp(0),
+            self.cagr * 100.0,
+            self.max_drawdown * 100.0,
+            self.sharpe_ratio,
+            self.dividend_income.round_dp(0),
+            self.stock_dividend_shares,
+            self.fee_paid.round_dp(0),
+            self.trades.len()
+        )
+    }
+}
+
+/// 年化報酬率 = (期末淨值 / 初始資金)^(365.25 / 天數) - 1
+pub fn cagr(initial: f64, last: f64, days: i64) -> f64 {
+    if initial <= 0.0 || last <= 0.0 || days <= 0 {
+        return 0.0;
+    }
+
+    (last / initial).powf(365.25 / days as f64) - 1.0
+}
+
+/// 淨值自前高滑落的最大比例
+pub fn max_drawdown(equities: &[f64]) -> f64 {
+    let mut peak = f64::MIN;
+    let mut drawdown: f64 = 0.0;
+
+    for &equity in equities {
+        peak = peak.max(equity);
+        if peak > 0.0 {
+            drawdown = drawdown.max((peak - equity) / peak);
+        }
+    }
+
+    drawdown
+}
+
+/// 以日報酬計算的年化夏普值，`risk_free_rate` 為年化無風險利率
+pub fn sharpe_ratio(equities: &[f64], risk_free_rate: f64) -> f64 {
+    let daily_risk_free = risk_free_rate / TRADING_DAYS_PER_YEAR;
+    let returns: Vec<f64> = equities
+        .windows(2)
+        .filter(|w| w[0] > 0.0)
+        .map(|w| w[1] / w[0] - 1.0 - daily_risk_free)
+        .collect();
+
+    if returns.len() < 2 {
+        return 0.0;
+    }
+
+    let n = returns.len() as f64;
+    let mean = returns.iter().sum::<f64>() / n;
+    let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    let std_dev = variance.sqrt();
+
+    if std_dev == 0.0 {
+        return 0.0;
+    }
+
+    mean / std_dev * TRADING_DAYS_PER_YEAR.sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cagr() {
+        assert!((cagr(100.0, 121.0, 730) - 0.1).abs() < 0.001);
+        assert_eq!(cagr(100.0, 121.0, 0), 0.0);
+        assert_eq!(cagr(0.0, 121.0, 365), 0.0);
+    }
+
+    #[test]
+    fn test_max_drawdown() {
+        assert_eq!(max_drawdown(&[100.0, 120.0, 90.0, 130.0, 117.0]), 0.25);
+        assert_eq!(max_drawdown(&[100.0, 110.0, 120.0]), 0.0);
+        assert_eq!(max_drawdown(&[]), 0.0);
+    }
+
+    #[test]
+    fn test_sharpe_ratio() {
+        assert_eq!(sharpe_ratio(&[100.0, 101.0], 0.0), 0.0);
+        assert_eq!(sharpe_ratio(&[100.0, 100.0, 100.0], 0.0), 0.0);
+        assert!(sharpe_ratio(&[100.0, 101.0, 103.0, 102.0, 105.0], 0.0) > 0.0);
+        assert!(sharpe_ratio(&[100.0, 99.0, 97.0, 98.0, 95.0], 0.0) < 0.0);
+    }
+}
@@ -0,0 +1,164 @@
+use rust_decimal::Decimal;
+
+use crate::{
+    backtest::{Bar, Position},
+    declare::MovingAverage,
+};
+
+/// 策略產生的交易訊號
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Signal {
+    /// 全數資金買進
+    Buy,
+    /// 全數持股賣出
+    Sell,
+    /// 不動作
+    Hold,
+}
+
+/// 回測策略
+///
+/// 每個交易日收盤後會以當日的資料呼叫 `on_bar`，回傳的訊號於下一個交易日開盤時成交，
+/// 策略可以在自身的欄位保存前一日的狀態(例如均線是否已交叉)。
+pub trait Strategy {
+    /// 策略名稱
+    fn name(&self) -> String;
+
+    /// 依當日收盤資料與目前的持股決定下一個交易日的動作
+    fn on_bar(&mut self, bar: &Bar, position: &Position) -> Signal;
+}
+
+/// 收盤價低於便宜價時買進，高於昂貴價時賣出
+#[derive(Debug, Default)]
+pub struct EstimateBand;
+
+impl Strategy for EstimateBand {
+    fn name(&self) -> String {
+        "便宜價買進、昂貴價賣出".to_string()
+    }
+
+    fn on_bar(&mut self, bar: &Bar, position: &Position) -> Signal {
+        let closing_price = bar.quote.closing_price;
+        if closing_price <= Decimal::ZERO {
+            return Signal::Hold;
+        }
+
+        if position.shares == 0 {
+            if bar.cheap > Decimal::ZERO && closing_price <= bar.cheap {
+                return Signal::Buy;
+            }
+        } else if bar.expensive > Decimal::ZERO && closing_price >= bar.expensive {
+            return Signal::Sell;
+        }
+
+        Signal::Hold
+    }
+}
+
+/// 短期均線向上穿越長期均線時買進，向下穿越時賣出
+#[derive(Debug)]
+pub struct MovingAverageCross {
+    short: MovingAverage,
+    long: MovingAverage,
+    /// 前一個交易日短期均線是否在長期均線之上
+    previous_above: Option<bool>,
+}
+
+impl MovingAverageCross {
+    pub fn new(short: MovingAverage, long: MovingAverage) -> Self {
+        MovingAverageCross {
+            short,
+            long,
+            previous_above: None,
+        }
+    }
+}
+
+impl Strategy for MovingAverageCross {
+    fn name(&self) -> String {
+        format!("{}/{} 均線交叉", self.short, self.long)
+    }
+
+    fn on_bar(&mut self, bar: &Bar, position: &Position) -> Signal {
+        let short = bar.quote.moving_average(self.short);
+        let long = bar.quote.moving_average(self.long);
+        // 均線資料不足時(值為零)不判斷
+        if short <= Decimal::ZERO || long <= Decimal::ZERO {
+            return Signal::Hold;
+        }
+
+        let above = short > long;
+        let previous_above = self.previous_above.replace(above);
+
+        match previous_above {
+            Some(false) if above && position.shares == 0 => Signal::Buy,
+            Some(true) if !above && position.shares > 0 => Signal::Sell,
+            _ => Signal::Hold,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    fn bar(closing_price: Decimal, cheap: Decimal, expensive: Decimal) -> Bar {
+        let mut bar = Bar::default();
+        bar.quote.closing_price = closing_price;
+        bar.cheap = cheap;
+        bar.expensive = expensive;
+        bar
+    }
+
+    #[test]
+    fn test_estimate_band() {
+        let mut strategy = EstimateBand;
+        let empty = Position::new(dec!(100000));
+        let mut holding = Position::new(dec!(100000));
+        holding.shares = 1000;
+
+        assert_eq!(
+            strategy.on_bar(&bar(dec!(90), dec!(100), dec!(150)), &empty),
+            Signal::Buy
+        );
+        assert_eq!(
+            strategy.on_bar(&bar(dec!(120), dec!(100), dec!(150)), &empty),
+            Signal::Hold
+        );
+        assert_eq!(
+            strategy.on_bar(&bar(dec!(90), dec!(100), dec!(150)), &holding),
+            Signal::Hold
+        );
+        assert_eq!(
+            strategy.on_bar(&bar(dec!(155), dec!(100), dec!(150)), &holding),
+            Signal::Sell
+        );
+        // 沒有估價資料時不交易
+        assert_eq!(
+            strategy.on_bar(&bar(dec!(90), Decimal::ZERO, Decimal::ZERO), &empty),
+            Signal::Hold
+        );
+    }
+
+    #[test]
+    fn test_moving_average_cross() {
+        let mut strategy = MovingAverageCross::new(MovingAverage::Ma5, MovingAverage::Ma20);
+        let empty = Position::new(dec!(100000));
+        let mut holding = Position::new(dec!(100000));
+        holding.shares = 1000;
+
+        let mut below = Bar::default();
+        below.quote.moving_average_5 = dec!(95);
+        below.quote.moving_average_20 = dec!(100);
+        let mut above = Bar::default();
+        above.quote.moving_average_5 = dec!(105);
+        above.quote.moving_average_20 = dec!(100);
+
+        assert_eq!(strategy.on_bar(&below, &empty), Signal::Hold);
+        assert_eq!(strategy.on_bar(&above, &empty), Signal::Buy);
+        assert_eq!(strategy.on_bar(&above, &holding), Signal::Hold);
+        assert_eq!(strategy.on_bar(&below, &holding), Signal::Sell);
+    }
+}
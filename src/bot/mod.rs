@@ -1 +1,2 @@
+pub mod notifier;
 pub mod telegram;
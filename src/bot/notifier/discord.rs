@@ -0,0 +1,50 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Serialize;
+
+use crate::{
+    bot::notifier::{self, Notifier, NotifyEvent},
+    util::http,
+};
+
+/// Discord 單則訊息的字數上限
+const MAX_CONTENT_LENGTH: usize = 2000;
+
+/// 以 Discord webhook 發送訊息
+pub struct Discord {
+    url: String,
+}
+
+impl Discord {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ExecuteWebhookRequest<'a> {
+    content: &'a str,
+}
+
+#[async_trait]
+impl Notifier for Discord {
+    async fn notify(&self, _event: NotifyEvent, message: &str) -> Result<()> {
+        // 超過字數上限時分成多則發送
+        let chars: Vec<char> = message.chars().collect();
+        for chunk in chars.chunks(MAX_CONTENT_LENGTH) {
+            let content: String = chunk.iter().collect();
+            let res = http::post_response_use_json(
+                &self.url,
+                None,
+                &ExecuteWebhookRequest { content: &content },
+            )
+            .await?;
+
+            notifier::ensure_success(res).await?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,318 @@
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Local;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader},
+    net::TcpStream,
+    time::timeout,
+};
+
+use crate::{
+    bot::notifier::{Notifier, NotifyEvent},
+    config::{Smtp, SmtpSecurity},
+};
+
+/// 每個 SMTP 指令等待回應的秒數
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 以 SMTP 寄送電子郵件
+pub struct Email {
+    smtp: Smtp,
+}
+
+impl Email {
+    pub fn new(smtp: Smtp) -> Self {
+        Self { smtp }
+    }
+
+    async fn send_mail(&self, subject: &str, body: &str) -> Result<()> {
+        // AUTH PLAIN 只是 base64 編碼，未加密的連線上送出等同公開帳號密碼
+        if self.smtp.security == SmtpSecurity::None && !self.smtp.username.is_empty() {
+            return Err(anyhow!(
+                "Refused to authenticate with {} without TLS, set security to start_tls or tls",
+                self.smtp.host
+            ));
+        }
+
+        let address = format!("{}:{}", self.smtp.host, self.smtp.port);
+        let stream = timeout(COMMAND_TIMEOUT, TcpStream::connect(&address))
+            .await
+            .context(format!("Connect to {} timed out", address))??;
+
+        match self.smtp.security {
+            SmtpSecurity::None => {
+                let mut session = Session::new(stream);
+                session.greet().await?;
+                self.transact(session, subject, body).await
+            }
+            SmtpSecurity::Tls => {
+                let mut session = Session::new(self.tls(stream).await?);
+                session.greet().await?;
+                self.transact(session, subject, body).await
+            }
+            SmtpSecurity::StartTls => {
+                let mut session = Session::new(stream);
+                session.greet().await?;
+                session.command("STARTTLS", &[220]).await?;
+                let stream = session.reader.into_inner();
+                let mut session = Session::new(self.tls(stream).await?);
+                session.ehlo().await?;
+                self.transact(session, subject, body).await
+            }
+        }
+    }
+
+    async fn tls(&self, stream: TcpStream) -> Result<tokio_native_tls::TlsStream<TcpStream>> {
+        let connector = tokio_native_tls::TlsConnector::from(
+            tokio_native_tls::native_tls::TlsConnector::new()?,
+        );
+        connector
+            .connect(&self.smtp.host, stream)
+            .await
+            .context(format!("Failed to TLS handshake with {}", self.smtp.host))
+    }
+
+    async fn transact<S>(&self, mut session: Session<S>, subject: &str, body: &str) -> Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        if !self.smtp.username.is_empty() {
+            let credentials =
+                STANDARD.encode(format!("\0{}\0{}", self.smtp.username, self.smtp.password));
+            session
+                .command(&format!("AUTH PLAIN {}", credentials), &[235])
+                .await?;
+        }
+
+        session
+            .command(&format!("MAIL FROM:<{}>", self.smtp.from), &[250])
+            .await?;
+        for to in &self.smtp.to {
+            session
+                .command(&format!("RCPT TO:<{}>", to), &[250, 251])
+                .await?;
+        }
+        session.command("DATA", &[354]).await?;
+        session
+            .command(&format!("{}\r\n.", self.message(subject, body)), &[250])
+            .await?;
+        // 信件已送出，QUIT 失敗不影響結果
+        let _ = session.command("QUIT", &[221]).await;
+
+        Ok(())
+    }
+
+    /// 組出 MIME 信件，標題與內容皆以 base64 編碼，內容不會有需要 dot-stuffing 的行
+    fn message(&self, subject: &str, body: &str) -> String {
+        let encoded_body = STANDARD
+            .encode(body.replace('\n', "\r\n").replace("\r\r\n", "\r\n"))
+            .as_bytes()
+            .chunks(76)
+            .map(|line| String::from_utf8_lossy(line).to_string())
+            .collect::<Vec<_>>()
+            .join("\r\n");
+
+        format!(
+            "From: {from}\r\nTo: {to}\r\nSubject: =?UTF-8?B?{subject}?=\r\nDate: {date}\r\nMIME-Version: 1.0\r\nContent-Type: text/plain; charset=UTF-8\r\nContent-Transfer-Encoding: base64\r\n\r\n{body}",
+            from = self.smtp.from,
+            to = self.smtp.to.join(", "),
+            subject = STANDARD.encode(subject),
+            date = Local::now().to_rfc2822(),
+            body = encoded_body
+        )
+    }
+}
+
+#[async_trait]
+impl Notifier for Email {
+    async fn notify(&self, event: NotifyEvent, message: &str) -> Result<()> {
+        if self.smtp.to.is_empty() {
+            return Err(anyhow!("No recipient for {}", self.smtp.host));
+        }
+
+        let subject = format!("StockCrawler {}", event);
+        self.send_mail(&subject, message).await
+    }
+}
+
+/// SMTP 的對話
+struct Session<S> {
+    reader: BufReader<S>,
+}
+
+impl<S> Session<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    fn new(stream: S) -> Self {
+        Self {
+            reader: BufReader::new(stream),
+        }
+    }
+
+    /// 讀取伺服器的歡迎訊息後送出 EHLO
+    async fn greet(&mut self) -> Result<()> {
+        self.reply(&[220]).await?;
+        self.ehlo().await
+    }
+
+    async fn ehlo(&mut self) -> Result<()> {
+        self.command("EHLO stock-crawler", &[250]).await?;
+        Ok(())
+    }
+
+    async fn command(&mut self, line: &str, expected: &[u16]) -> Result<String> {
+        let stream = self.reader.get_mut();
+        stream.write_all(line.as_bytes()).await?;
+        stream.write_all(b"\r\n").await?;
+        stream.flush().await?;
+
+        self.reply(expected).await.map_err(|why| {
+            // 避免將帳號密碼寫入日誌
+            let verb = line.split_whitespace().next().unwrap_or_default();
+            anyhow!("SMTP {} failed because {:?}", verb, why)
+        })
+    }
+
+    /// 讀取回應(可能有多行，例如 250-...)並確認狀態碼
+    async fn reply(&mut self, expected: &[u16]) -> Result<String> {
+        let mut reply = String::new();
+        loop {
+            let mut line = String::new();
+            let read = timeout(COMMAND_TIMEOUT, self.reader.read_line(&mut line))
+                .await
+                .context("SMTP reply timed out")??;
+            if read == 0 {
+                return Err(anyhow!("SMTP connection closed: {}", reply));
+            }
+
+            reply.push_str(&line);
+            // 最後一行的狀態碼之後是空白，其餘為 -
+            if line.len() < 4 || line.as_bytes()[3] != b'-' {
+                break;
+            }
+        }
+
+        let code = reply
+            .get(..3)
+            .and_then(|code| code.parse::<u16>().ok())
+            .ok_or_else(|| anyhow!("Invalid SMTP reply: {}", reply))?;
+        if !expected.contains(&code) {
+            return Err(anyhow!("Unexpected SMTP reply: {}", reply.trim_end()));
+        }
+
+        Ok(reply)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    /// 模擬 SMTP 伺服器，記錄收到的指令與信件內容
+    async fn fake_smtp_server() -> (u16, Arc<Mutex<Vec<String>>>) {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let log = Arc::clone(&received);
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut reader = BufReader::new(stream);
+            reader
+                .get_mut()
+                .write_all(b"220 localhost ESMTP\r\n")
+                .await
+                .unwrap();
+            let mut in_data = false;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).await.unwrap() == 0 {
+                    break;
+                }
+                let line = line.trim_end().to_string();
+                log.lock().unwrap().push(line.clone());
+
+                let reply: &[u8] = if in_data {
+                    if line != "." {
+                        continue;
+                    }
+                    in_data = false;
+                    b"250 queued\r\n"
+                } else if line.starts_with("EHLO") {
+                    b"250-localhost\r\n250 AUTH PLAIN\r\n"
+                } else if line.starts_with("AUTH") {
+                    b"235 ok\r\n"
+                } else if line == "DATA" {
+                    in_data = true;
+                    b"354 go ahead\r\n"
+                } else if line == "QUIT" {
+                    b"221 bye\r\n"
+                } else {
+                    b"250 ok\r\n"
+                };
+                reader.get_mut().write_all(reply).await.unwrap();
+            }
+        });
+
+        (port, received)
+    }
+
+    #[tokio::test]
+    async fn test_notify() {
+        let (port, received) = fake_smtp_server().await;
+        let email = Email::new(Smtp {
+            host: "127.0.0.1".to_string(),
+            port,
+            security: SmtpSecurity::None,
+            username: String::new(),
+            password: String::new(),
+            from: "bot@example.com".to_string(),
+            to: vec!["a@example.com".to_string(), "b@example.com".to_string()],
+        });
+
+        email
+            .notify(NotifyEvent::ExDividend, "2330 除息\n2412 除息")
+            .await
+            .unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received[0], "EHLO stock-crawler");
+        assert_eq!(received[1], "MAIL FROM:<bot@example.com>");
+        assert_eq!(received[2], "RCPT TO:<a@example.com>");
+        assert_eq!(received[3], "RCPT TO:<b@example.com>");
+        assert!(received.contains(&format!(
+            "Subject: =?UTF-8?B?{}?=",
+            STANDARD.encode("StockCrawler ex_dividend")
+        )));
+        assert!(received.contains(&STANDARD.encode("2330 除息\r\n2412 除息")));
+        assert_eq!(received.last().unwrap(), "QUIT");
+    }
+
+    #[tokio::test]
+    async fn test_refuse_auth_without_tls() {
+        let (port, received) = fake_smtp_server().await;
+        let email = Email::new(Smtp {
+            host: "127.0.0.1".to_string(),
+            port,
+            security: SmtpSecurity::None,
+            username: "user".to_string(),
+            password: "password".to_string(),
+            from: "bot@example.com".to_string(),
+            to: vec!["a@example.com".to_string()],
+        });
+
+        assert!(email
+            .notify(NotifyEvent::ExDividend, "2330 除息")
+            .await
+            .is_err());
+        assert!(received.lock().unwrap().is_empty());
+    }
+}
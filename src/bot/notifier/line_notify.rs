@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+use serde::Deserialize;
+
+use crate::{
+    bot::notifier::{Notifier, NotifyEvent},
+    util::http,
+};
+
+const DEFAULT_URL: &str = "https://notify-api.line.me/api/notify";
+
+/// 以 LINE Notify 或相容的 HTTP 服務發送訊息
+pub struct LineNotify {
+    url: String,
+    token: String,
+}
+
+impl LineNotify {
+    /// url 空白時使用 LINE Notify 的網址
+    pub fn new(url: &str, token: &str) -> Self {
+        let url = if url.is_empty() { DEFAULT_URL } else { url };
+
+        Self {
+            url: url.to_string(),
+            token: token.to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct NotifyResponse {
+    status: i32,
+    #[serde(default)]
+    message: String,
+}
+
+#[async_trait]
+impl Notifier for LineNotify {
+    async fn notify(&self, _event: NotifyEvent, message: &str) -> Result<()> {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", self.token))?,
+        );
+        let params = HashMap::from([("message", message)]);
+        let body = http::post(&self.url, Some(headers), Some(params)).await?;
+        let res: NotifyResponse = serde_json::from_str(&body)
+            .map_err(|why| anyhow!("Failed to parse response({}) because {:?}", body, why))?;
+
+        if res.status != 200 {
+            return Err(anyhow!("Unexpected status {}: {}", res.status, res.message));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::future::join_all;
+use once_cell::sync::Lazy;
+use reqwest::Response;
+use strum_macros::{AsRefStr, Display};
+
+use crate::{
+    config::{Notify, NotifyChannel, SETTINGS},
+    logging,
+};
+
+/// Discord webhook
+pub mod discord;
+/// SMTP 電子郵件
+pub mod email;
+/// LINE Notify
+pub mod line_notify;
+/// Slack incoming webhook
+pub mod slack;
+/// Telegram
+pub mod telegram;
+/// 通用的 JSON webhook
+pub mod webhook;
+
+/// 依設定建立的通知管道
+static CHANNELS: Lazy<Channels> = Lazy::new(|| Channels::new(&SETTINGS.notify));
+
+/// 通知的事件，名稱對應 app.json 內 notify.routes 的 key
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Display, AsRefStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum NotifyEvent {
+    /// 除權息
+    ExDividend,
+    /// 股利發放
+    PayableDate,
+    /// 公開申購
+    Public,
+    /// 收盤後的市值變化
+    MoneyChange,
+    /// 股價提醒規則
+    Alert,
+    /// 大盤指數
+    Index,
+    /// 股票基本資料異動
+    StockInfo,
+    /// 服務啟動
+    Startup,
+    /// 抓取數據異常
+    Error,
+}
+
+/// 通知管道
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    async fn notify(&self, event: NotifyEvent, message: &str) -> Result<()>;
+}
+
+struct Channels {
+    notifiers: HashMap<String, Box<dyn Notifier>>,
+    routes: HashMap<String, Vec<String>>,
+}
+
+impl Channels {
+    fn new(config: &Notify) -> Self {
+        let mut notifiers: HashMap<String, Box<dyn Notifier>> = config
+            .channels
+            .iter()
+            .map(|(name, channel)| (name.to_string(), build(channel)))
+            .collect();
+
+        // 未設定任何管道時維持只通知 Telegram
+        if notifiers.is_empty() {
+            notifiers.insert(
+                "telegram".to_string(),
+                Box::new(telegram::Telegram::new(Vec::new())),
+            );
+        }
+
+        Self {
+            notifiers,
+            routes: config.routes.clone(),
+        }
+    }
+
+    /// 取得事件要通知的管道名稱
+    fn route(&self, event: NotifyEvent) -> Vec<&str> {
+        match self
+            .routes
+            .get(event.as_ref())
+            .or_else(|| self.routes.get("default"))
+        {
+            Some(names) => names.iter().map(String::as_str).collect(),
+            None => self.notifiers.keys().map(String::as_str).collect(),
+        }
+    }
+
+    /// 同時通知事件路由內所有的管道，回傳各管道的結果，單一管道失敗不影響其他管道
+    async fn deliver(&self, event: NotifyEvent, message: &str) -> Vec<(String, Result<()>)> {
+        let futures = self.route(event).into_iter().map(|name| async move {
+            let result = match self.notifiers.get(name) {
+                Some(notifier) => notifier.notify(event, message).await,
+                None => Err(anyhow!("Notify channel({}) is not configured", name)),
+            };
+            (name.to_string(), result)
+        });
+
+        join_all(futures).await
+    }
+}
+
+fn build(channel: &NotifyChannel) -> Box<dyn Notifier> {
+    match channel {
+        NotifyChannel::Telegram { chat_ids } => Box::new(telegram::Telegram::new(chat_ids.clone())),
+        NotifyChannel::Discord { url } => Box::new(discord::Discord::new(url)),
+        NotifyChannel::Slack { url } => Box::new(slack::Slack::new(url)),
+        NotifyChannel::LineNotify { url, token } => {
+            Box::new(line_notify::LineNotify::new(url, token))
+        }
+        NotifyChannel::Email(smtp) => Box::new(email::Email::new(smtp.clone())),
+        NotifyChannel::Webhook { url, headers } => Box::new(webhook::Webhook::new(url, headers)),
+    }
+}
+
+/// 依事件的路由發送訊息到各通知管道，失敗的管道會記錄於日誌
+pub async fn send(event: NotifyEvent, message: &str) {
+    if message.is_empty() {
+        return;
+    }
+
+    for (name, result) in CHANNELS.deliver(event, message).await {
+        if let Err(why) = result {
+            logging::error_file_async(format!(
+                "Failed to notify {} via {} because {:?}",
+                event, name, why
+            ));
+        }
+    }
+}
+
+/// 確認 HTTP 回應的狀態碼為成功，否則回傳含有回應內容的錯誤
+async fn ensure_success(response: Response) -> Result<()> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+
+    let body = response.text().await.unwrap_or_default();
+    Err(anyhow!("Unexpected status {}: {}", status, body))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::config::Smtp;
+
+    use super::*;
+
+    struct Recorder {
+        fail: bool,
+        received: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl Notifier for Recorder {
+        async fn notify(&self, event: NotifyEvent, message: &str) -> Result<()> {
+            if self.fail {
+                return Err(anyhow!("channel is down"));
+            }
+
+            self.received
+                .lock()
+                .unwrap()
+                .push(format!("{}:{}", event, message));
+            Ok(())
+        }
+    }
+
+    fn fake_channels(routes: &[(&str, &[&str])]) -> (Channels, Arc<Mutex<Vec<String>>>) {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let mut notifiers: HashMap<String, Box<dyn Notifier>> = HashMap::new();
+        for (name, fail) in [("ok", false), ("down", true), ("other", false)] {
+            notifiers.insert(
+                name.to_string(),
+                Box::new(Recorder {
+                    fail,
+                    received: Arc::clone(&received),
+                }),
+            );
+        }
+
+        let routes = routes
+            .iter()
+            .map(|(event, names)| {
+                (
+                    event.to_string(),
+                    names.iter().map(|name| name.to_string()).collect(),
+                )
+            })
+            .collect();
+
+        (Channels { notifiers, routes }, received)
+    }
+
+    #[test]
+    fn test_event_name() {
+        assert_eq!(NotifyEvent::ExDividend.as_ref(), "ex_dividend");
+        assert_eq!(NotifyEvent::MoneyChange.to_string(), "money_change");
+    }
+
+    #[test]
+    fn test_route() {
+        let (channels, _) = fake_channels(&[("alert", &["ok", "down"]), ("default", &["other"])]);
+
+        let mut names = channels.route(NotifyEvent::Alert);
+        names.sort();
+        assert_eq!(names, vec!["down", "ok"]);
+        assert_eq!(channels.route(NotifyEvent::Public), vec!["other"]);
+
+        let (channels, _) = fake_channels(&[]);
+        assert_eq!(channels.route(NotifyEvent::Public).len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_deliver_isolates_failures() {
+        let (channels, received) = fake_channels(&[("alert", &["down", "ok", "missing"])]);
+
+        let results = channels.deliver(NotifyEvent::Alert, "2330 跌破 550").await;
+
+        assert_eq!(results.len(), 3);
+        assert!(results
+            .iter()
+            .all(|(name, result)| result.is_ok() == (name == "ok")));
+        assert_eq!(*received.lock().unwrap(), vec!["alert:2330 跌破 550"]);
+    }
+
+    #[test]
+    fn test_notify_config() {
+        let notify: Notify = serde_json::from_str(
+            r#"{
+  "channels": {
+    "team": {"type": "discord", "url": "https://discord.com/api/webhooks/1/a"},
+    "mail": {"type": "email", "host": "smtp.example.com", "port": 465, "security": "tls", "from": "bot@example.com", "to": ["eddie@example.com"]},
+    "line": {"type": "line_notify", "token": "token"}
+  },
+  "routes": {"alert": ["team", "mail"]}
+}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            notify.channels["mail"],
+            NotifyChannel::Email(Smtp {
+                host: "smtp.example.com".to_string(),
+                port: 465,
+                security: crate::config::SmtpSecurity::Tls,
+                from: "bot@example.com".to_string(),
+                to: vec!["eddie@example.com".to_string()],
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            notify.channels["line"],
+            NotifyChannel::LineNotify {
+                url: String::new(),
+                token: "token".to_string()
+            }
+        );
+        assert_eq!(notify.routes["alert"], vec!["team", "mail"]);
+    }
+}
@@ -0,0 +1,40 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Serialize;
+
+use crate::{
+    bot::notifier::{self, Notifier, NotifyEvent},
+    util::http,
+};
+
+/// 以 Slack incoming webhook 發送訊息
+pub struct Slack {
+    url: String,
+}
+
+impl Slack {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct IncomingWebhookRequest<'a> {
+    text: &'a str,
+}
+
+#[async_trait]
+impl Notifier for Slack {
+    async fn notify(&self, _event: NotifyEvent, message: &str) -> Result<()> {
+        let res = http::post_response_use_json(
+            &self.url,
+            None,
+            &IncomingWebhookRequest { text: message },
+        )
+        .await?;
+
+        notifier::ensure_success(res).await
+    }
+}
@@ -0,0 +1,33 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::bot::{
+    self,
+    notifier::{Notifier, NotifyEvent},
+};
+
+/// 以 Telegram 機器人發送訊息
+pub struct Telegram {
+    /// 要通知的聊天室，未設定時通知 bot.telegram.allowed
+    chat_ids: Vec<i64>,
+}
+
+impl Telegram {
+    pub fn new(chat_ids: Vec<i64>) -> Self {
+        Self { chat_ids }
+    }
+}
+
+#[async_trait]
+impl Notifier for Telegram {
+    async fn notify(&self, _event: NotifyEvent, message: &str) -> Result<()> {
+        let client = bot::telegram::get_client()?;
+        let report = if self.chat_ids.is_empty() {
+            client.send(message).await
+        } else {
+            client.send_to(&self.chat_ids, message).await
+        };
+
+        report.into_result()
+    }
+}
@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde::Serialize;
+
+use crate::{
+    bot::notifier::{self, Notifier, NotifyEvent},
+    logging,
+    util::http,
+};
+
+/// 以 JSON 格式 POST 到自訂的網址
+pub struct Webhook {
+    url: String,
+    headers: HeaderMap,
+}
+
+impl Webhook {
+    pub fn new(url: &str, headers: &HashMap<String, String>) -> Self {
+        let mut header_map = HeaderMap::with_capacity(headers.len());
+        for (name, value) in headers {
+            match (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                (Ok(name), Ok(value)) => {
+                    header_map.insert(name, value);
+                }
+                _ => logging::error_file_async(format!(
+                    "Failed to parse webhook header {}: {}",
+                    name, value
+                )),
+            }
+        }
+
+        Self {
+            url: url.to_string(),
+            headers: header_map,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct WebhookRequest<'a> {
+    event: &'a str,
+    message: &'a str,
+}
+
+#[async_trait]
+impl Notifier for Webhook {
+    async fn notify(&self, event: NotifyEvent, message: &str) -> Result<()> {
+        let payload = WebhookRequest {
+            event: event.as_ref(),
+            message,
+        };
+        let res =
+            http::post_response_use_json(&self.url, Some(self.headers.clone()), &payload).await?;
+
+        notifier::ensure_success(res).await
+    }
+}
@@ -0,0 +1,341 @@
+use anyhow::{anyhow, Result};
+use chrono::{Datelike, Local, TimeDelta};
+use rust_decimal::Decimal;
+
+use crate::{
+    cache::SHARE,
+    calculation::portfolio,
+    calendar::CALENDAR,
+    crawler,
+    database::table::{alert_rule::AlertRule, dividend::Dividend, estimate::Estimate},
+    event::trace::alert_rule::condition::{Comparison, Condition},
+};
+
+/// 指令的用法說明
+pub const USAGE: &str = "可用的指令:
+/quote 2330 目前報價
+/estimate 2330 便宜、合理、昂貴價
+/dividend 2330 近年股利
+/trace add 2330 550 650 低於 550 或超過 650 時提醒
+/portfolio 目前庫存
+/holiday 今年剩餘的休市日";
+
+/// 聊天室可下的指令
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// 目前報價
+    Quote(String),
+    /// 最近一次估算的便宜、合理、昂貴價
+    Estimate(String),
+    /// 近年股利
+    Dividend(String),
+    /// 新增股價高低標提醒，零表示不設定
+    TraceAdd {
+        security_code: String,
+        floor: Decimal,
+        ceiling: Decimal,
+    },
+    /// 目前庫存
+    Portfolio,
+    /// 今年剩餘的休市日
+    Holiday,
+    /// 指令說明
+    Help,
+}
+
+impl Command {
+    /// 解析訊息文字，例如 `/quote 2330`、`/trace@my_bot add 2330 550 650`
+    pub fn parse(text: &str) -> Result<Command> {
+        let mut args = text.split_whitespace();
+        let name = args
+            .next()
+            .and_then(|name| name.strip_prefix('/'))
+            .ok_or_else(|| anyhow!("不是指令:{}", text))?;
+        // 群組內的指令會帶上機器人的名稱，例如 /quote@my_bot
+        let name = name.split('@').next().unwrap_or_default();
+        let args: Vec<&str> = args.collect();
+
+        match (name, args.as_slice()) {
+            ("quote", [security_code]) => Ok(Command::Quote(security_code.to_string())),
+            ("estimate", [security_code]) => Ok(Command::Estimate(security_code.to_string())),
+            ("dividend", [security_code]) => Ok(Command::Dividend(security_code.to_string())),
+            ("trace", ["add", security_code, floor, ceiling]) => {
+                let floor = parse_price(floor)?;
+                let ceiling = parse_price(ceiling)?;
+                if floor.is_zero() && ceiling.is_zero() {
+                    return Err(anyhow!("最低價與最高價不可同時為零"));
+                }
+                if !ceiling.is_zero() && floor >= ceiling {
+                    return Err(anyhow!("最低價({})必須小於最高價({})", floor, ceiling));
+                }
+
+                Ok(Command::TraceAdd {
+                    security_code: security_code.to_string(),
+                    floor,
+                    ceiling,
+                })
+            }
+            ("portfolio", []) => Ok(Command::Portfolio),
+            ("holiday", []) => Ok(Command::Holiday),
+            ("start" | "help", _) => Ok(Command::Help),
+            _ => Err(anyhow!("無法識別的指令:{}", text)),
+        }
+    }
+
+    /// 執行指令並回傳要回覆的文字
+    pub async fn execute(&self) -> Result<String> {
+        match self {
+            Command::Quote(security_code) => quote(security_code).await,
+            Command::Estimate(security_code) => estimate(security_code).await,
+            Command::Dividend(security_code) => dividend(security_code).await,
+            Command::TraceAdd {
+                security_code,
+                floor,
+                ceiling,
+            } => trace_add(security_code, *floor, *ceiling).await,
+            Command::Portfolio => portfolio().await,
+            Command::Holiday => holiday().await,
+            Command::Help => Ok(USAGE.to_string()),
+        }
+    }
+}
+
+fn parse_price(value: &str) -> Result<Decimal> {
+    match value.parse::<Decimal>() {
+        Ok(price) if price >= Decimal::ZERO => Ok(price),
+        _ => Err(anyhow!("價格格式錯誤:{}", value)),
+    }
+}
+
+async fn stock_name(security_code: &str) -> String {
+    SHARE
+        .get_stock(security_code)
+        .await
+        .map_or_else(String::new, |stock| stock.name)
+}
+
+async fn quote(security_code: &str) -> Result<String> {
+    let quotes = crawler::fetch_stock_quotes_from_remote_site(security_code).await?;
+
+    Ok(format!(
+        "{name}({code}) 目前報價:{price} 漲跌:{change} 漲幅:{change_range}%",
+        name = stock_name(security_code).await,
+        code = security_code,
+        price = quotes.price,
+        change = quotes.change,
+        change_range = quotes.change_range
+    ))
+}
+
+async fn estimate(security_code: &str) -> Result<String> {
+    let today = Local::now().date_naive();
+    let start = today - TimeDelta::try_days(30).unwrap();
+    let estimates = Estimate::fetch_by_security_code(security_code, start, today).await?;
+
+    Ok(match estimates.last() {
+        None => format!("查無 {} 最近的估價", security_code),
+        Some(e) => format!(
+            "{name}({code}) {date} 收盤價:{closing_price} 便宜價:{cheap} 合理價:{fair} 昂貴價:{expensive}",
+            name = stock_name(security_code).await,
+            code = security_code,
+            date = e.date,
+            closing_price = e.closing_price,
+            cheap = e.cheap,
+            fair = e.fair,
+            expensive = e.expensive
+        ),
+    })
+}
+
+async fn dividend(security_code: &str) -> Result<String> {
+    let dividends = Dividend::fetch_by_security_code(security_code).await?;
+    if dividends.is_empty() {
+        return Ok(format!("查無 {} 的股利", security_code));
+    }
+
+    let mut lines = vec![format!(
+        "{}({}) 近年股利:",
+        stock_name(security_code).await,
+        security_code
+    )];
+    lines.extend(dividends.iter().rev().take(8).map(|d| {
+        format!(
+            "{year}{quarter} 現金:{cash} 股票:{stock} 合計:{sum} 除息日:{ex_date}",
+            year = d.year,
+            quarter = d.quarter,
+            cash = d.cash_dividend.normalize(),
+            stock = d.stock_dividend.normalize(),
+            sum = d.sum.normalize(),
+            ex_date = d.ex_dividend_date1
+        )
+    }));
+
+    Ok(lines.join("\n"))
+}
+
+/// 與舊的 trace 高低標相同，分別新增低於最低價與超過最高價兩條提醒規則
+async fn trace_add(security_code: &str, floor: Decimal, ceiling: Decimal) -> Result<String> {
+    if !SHARE.stock_contains_key(security_code) {
+        return Ok(format!("查無股票代號 {}", security_code));
+    }
+
+    let rules = [
+        ("低於最低價", Comparison::LessOrEqual, floor),
+        ("超過最高價", Comparison::GreaterOrEqual, ceiling),
+    ];
+    let mut added = Vec::with_capacity(rules.len());
+
+    for (name, op, value) in rules {
+        if value.is_zero() {
+            continue;
+        }
+
+        let condition = Condition::Price { op, value };
+        let mut rule = AlertRule {
+            name: name.to_string(),
+            security_code: security_code.to_string(),
+            condition: serde_json::to_string(&condition)?,
+            cooldown_minutes: 300,
+            enabled: true,
+            ..Default::default()
+        };
+        rule.insert().await?;
+        added.push(format!("#{} {}", rule.serial, condition.describe()));
+    }
+
+    Ok(format!(
+        "已新增 {}({}) 的提醒:\n{}",
+        stock_name(security_code).await,
+        security_code,
+        added.join("\n")
+    ))
+}
+
+async fn portfolio() -> Result<String> {
+    let portfolio = portfolio::current().await?;
+    if portfolio.holdings.is_empty() {
+        return Ok("目前沒有庫存".to_string());
+    }
+
+    let mut lines: Vec<String> = portfolio
+        .holdings
+        .iter()
+        .map(|holding| {
+            format!(
+                "{name}({code}) {share_quantity}股 收盤價:{closing_price} 市值:{market_value} 損益:{profit}",
+                name = holding.name,
+                code = holding.security_code,
+                share_quantity = holding.share_quantity,
+                closing_price = holding.closing_price.normalize(),
+                market_value = holding.market_value.round_dp(0),
+                profit = holding.profit.round_dp(0)
+            )
+        })
+        .collect();
+
+    lines.push(format!(
+        "合計 市值:{} 損益:{}",
+        portfolio.total_value.round_dp(0),
+        portfolio.total_profit.round_dp(0)
+    ));
+
+    Ok(lines.join("\n"))
+}
+
+async fn holiday() -> Result<String> {
+    let today = Local::now().date_naive();
+    let lines: Vec<String> = CALENDAR
+        .holidays(today.year())
+        .await?
+        .into_iter()
+        .filter(|h| h.date >= today)
+        .map(|h| format!("{} {}", h.date, h.why))
+        .collect();
+
+    if lines.is_empty() {
+        return Ok(format!("{} 年已無休市日", today.year()));
+    }
+
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use crate::logging;
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Command::parse("/quote 2330").unwrap(),
+            Command::Quote("2330".to_string())
+        );
+        assert_eq!(
+            Command::parse("/estimate@stock_bot  2330").unwrap(),
+            Command::Estimate("2330".to_string())
+        );
+        assert_eq!(
+            Command::parse("/dividend 2330").unwrap(),
+            Command::Dividend("2330".to_string())
+        );
+        assert_eq!(
+            Command::parse("/trace add 2330 550 650").unwrap(),
+            Command::TraceAdd {
+                security_code: "2330".to_string(),
+                floor: dec!(550),
+                ceiling: dec!(650),
+            }
+        );
+        assert_eq!(
+            Command::parse("/trace add 2330 0 650.5").unwrap(),
+            Command::TraceAdd {
+                security_code: "2330".to_string(),
+                floor: dec!(0),
+                ceiling: dec!(650.5),
+            }
+        );
+        assert_eq!(Command::parse("/portfolio").unwrap(), Command::Portfolio);
+        assert_eq!(Command::parse("/holiday").unwrap(), Command::Holiday);
+        assert_eq!(Command::parse("/start").unwrap(), Command::Help);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(Command::parse("2330").is_err());
+        assert!(Command::parse("/quote").is_err());
+        assert!(Command::parse("/unknown 2330").is_err());
+        assert!(Command::parse("/trace add 2330 650 550").is_err());
+        assert!(Command::parse("/trace add 2330 0 0").is_err());
+        assert!(Command::parse("/trace add 2330 abc 650").is_err());
+        assert!(Command::parse("/trace add 2330 -1 650").is_err());
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_execute() {
+        dotenv::dotenv().ok();
+        SHARE.load().await;
+        logging::debug_file_async("開始 Command::execute".to_string());
+
+        for text in [
+            "/quote 2330",
+            "/estimate 2330",
+            "/dividend 2330",
+            "/portfolio",
+            "/holiday",
+        ] {
+            match Command::parse(text).unwrap().execute().await {
+                Ok(reply) => logging::debug_file_async(format!("{}:\n{}", text, reply)),
+                Err(why) => logging::debug_file_async(format!(
+                    "Failed to Command::execute({}) because {:?}",
+                    text, why
+                )),
+            }
+        }
+
+        logging::debug_file_async("結束 Command::execute".to_string());
+    }
+}
@@ -0,0 +1,175 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::config::TelegramParseMode;
+
+/// Telegram 單則訊息的字數上限
+pub const MAX_MESSAGE_LENGTH: usize = 4096;
+
+/// 訊息內的連結，格式為 [文字](網址)
+static LINK: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\[([^\[\]\n]+)\]\((https?://[^\s()]+)\)").unwrap());
+
+/// MarkdownV2 需要跳脫的字元
+const MARKDOWN_V2_SPECIAL_CHARACTERS: &[char] = &[
+    '\\', '_', '*', '[', ']', '(', ')', '~', '`', '>', '#', '+', '-', '=', '|', '{', '}', '.', '!',
+];
+
+/// sendMessage 的 parse_mode 參數值
+pub fn parse_mode_value(mode: TelegramParseMode) -> &'static str {
+    match mode {
+        TelegramParseMode::Html => "HTML",
+        TelegramParseMode::MarkdownV2 => "MarkdownV2",
+    }
+}
+
+/// 跳脫文字內的特殊字元，使其在指定的格式下顯示為原本的文字
+pub fn escape(text: &str, mode: TelegramParseMode) -> String {
+    let mut escaped = String::with_capacity(text.len() + text.len() / 8);
+    for c in text.chars() {
+        match mode {
+            TelegramParseMode::Html => match c {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                _ => escaped.push(c),
+            },
+            TelegramParseMode::MarkdownV2 => {
+                if MARKDOWN_V2_SPECIAL_CHARACTERS.contains(&c) {
+                    escaped.push('\\');
+                }
+                escaped.push(c);
+            }
+        }
+    }
+
+    escaped
+}
+
+/// 產生指定格式的連結
+pub fn link(label: &str, url: &str, mode: TelegramParseMode) -> String {
+    match mode {
+        TelegramParseMode::Html => format!(
+            r#"<a href="{}">{}</a>"#,
+            escape(url, mode),
+            escape(label, mode)
+        ),
+        // 網址內只需要跳脫 ) 與 \
+        TelegramParseMode::MarkdownV2 => format!(
+            "[{}]({})",
+            escape(label, mode),
+            url.replace('\\', "\\\\").replace(')', "\\)")
+        ),
+    }
+}
+
+/// 將訊息內的 [文字](網址) 轉為指定格式的連結，其餘文字皆跳脫
+pub fn render(text: &str, mode: TelegramParseMode) -> String {
+    let mut rendered = String::with_capacity(text.len() * 2);
+    let mut last = 0;
+    for caps in LINK.captures_iter(text) {
+        let whole = caps.get(0).unwrap();
+        rendered.push_str(&escape(&text[last..whole.start()], mode));
+        rendered.push_str(&link(&caps[1], &caps[2], mode));
+        last = whole.end();
+    }
+    rendered.push_str(&escape(&text[last..], mode));
+
+    rendered
+}
+
+/// 依行將訊息切成不超過 limit 個字元的多則訊息，單行超過上限時才會從行內切開
+pub fn split(text: &str, limit: usize) -> Vec<String> {
+    let mut chunks: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut current_length = 0;
+
+    for line in text.split_inclusive('\n') {
+        let line_length = line.chars().count();
+        if current_length + line_length > limit && !current.is_empty() {
+            chunks.push(std::mem::take(&mut current));
+            current_length = 0;
+        }
+
+        if line_length > limit {
+            let chars: Vec<char> = line.chars().collect();
+            let mut pieces = chars.chunks(limit).peekable();
+            while let Some(piece) = pieces.next() {
+                if pieces.peek().is_some() {
+                    chunks.push(piece.iter().collect());
+                } else {
+                    current = piece.iter().collect();
+                    current_length = piece.len();
+                }
+            }
+            continue;
+        }
+
+        current.push_str(line);
+        current_length += line_length;
+    }
+
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+
+    chunks
+        .into_iter()
+        .map(|chunk| chunk.trim_end_matches('\n').to_string())
+        .filter(|chunk| !chunk.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            escape("A&B <b> \"q\"", TelegramParseMode::Html),
+            "A&amp;B &lt;b&gt; &quot;q&quot;"
+        );
+        assert_eq!(
+            escape("2330_台積電 5.5% (+1) [x]!", TelegramParseMode::MarkdownV2),
+            "2330\\_台積電 5\\.5% \\(\\+1\\) \\[x\\]\\!"
+        );
+        assert_eq!(escape("a\\b", TelegramParseMode::MarkdownV2), "a\\\\b");
+    }
+
+    #[test]
+    fn test_render() {
+        let text = "    [2330](https://tw.stock.yahoo.com/quote/2330) 台積電 現金︰4.5元";
+        assert_eq!(
+            render(text, TelegramParseMode::Html),
+            r#"    <a href="https://tw.stock.yahoo.com/quote/2330">2330</a> 台積電 現金︰4.5元"#
+        );
+        assert_eq!(
+            render(text, TelegramParseMode::MarkdownV2),
+            "    [2330](https://tw.stock.yahoo.com/quote/2330) 台積電 現金︰4\\.5元"
+        );
+        assert_eq!(
+            render("[a_b](not a url) <x>", TelegramParseMode::Html),
+            "[a_b](not a url) &lt;x&gt;"
+        );
+    }
+
+    #[test]
+    fn test_split() {
+        assert_eq!(split("abc\ndef\n", 10), vec!["abc\ndef"]);
+        assert_eq!(split("abc\ndef\nghi", 8), vec!["abc\ndef", "ghi"]);
+        assert_eq!(split("abcdefghij\nk", 4), vec!["abcd", "efgh", "ij\nk"]);
+        assert_eq!(split("台積電台積電", 3), vec!["台積電", "台積電"]);
+        assert!(split("", 10).is_empty());
+
+        let line = format!("{}\n", "x".repeat(100));
+        let text = line.repeat(100);
+        let chunks = split(&text, MAX_MESSAGE_LENGTH);
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks
+            .iter()
+            .all(|chunk| chunk.chars().count() <= MAX_MESSAGE_LENGTH));
+        assert_eq!(chunks.concat().matches('x').count(), 100 * 100);
+    }
+}
@@ -0,0 +1,430 @@
+use std::{
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+use futures::future::join_all;
+use once_cell::sync::Lazy;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    config::{TelegramParseMode, SETTINGS},
+    util::http,
+};
+
+/// 聊天室的指令
+pub mod command;
+/// 訊息格式的跳脫與分段
+pub mod format;
+/// 以長輪詢或 webhook 接收指令
+pub mod receiver;
+
+const DEFAULT_API_URL: &str = "https://api.telegram.org";
+/// 收到 429 Too Many Requests 時最多重試的次數
+const MAX_RATE_LIMIT_RETRIES: usize = 3;
+/// 依 retry_after 等待的秒數上限
+const MAX_RETRY_AFTER_SECONDS: u64 = 60;
+
+static TELEGRAM: Lazy<Arc<OnceLock<Telegram>>> = Lazy::new(|| Arc::new(OnceLock::new()));
+
+pub struct Telegram {
+    /// https://api.telegram.org/bot{token}
+    bot_url: String,
+    parse_mode: TelegramParseMode,
+}
+
+impl Telegram {
+    pub fn new() -> Self {
+        let mut telegram =
+            Self::with_api_url(&SETTINGS.bot.telegram.api_url, &SETTINGS.bot.telegram.token);
+        telegram.parse_mode = SETTINGS.bot.telegram.parse_mode;
+        telegram
+    }
+
+    /// 指定 Bot API 的網址，空白時使用官方的網址
+    pub fn with_api_url(api_url: &str, token: &str) -> Self {
+        let api_url = match api_url.trim_end_matches('/') {
+            "" => DEFAULT_API_URL,
+            url => url,
+        };
+
+        Self {
+            bot_url: format!("{}/bot{}", api_url, token),
+            parse_mode: TelegramParseMode::default(),
+        }
+    }
+
+    /// 發送訊息給 bot.telegram.allowed 內所有的聊天室
+    pub async fn send(&self, message: &str) -> DeliveryReport {
+        let chat_ids: Vec<i64> = SETTINGS.bot.telegram.allowed.keys().copied().collect();
+        self.send_to(&chat_ids, message).await
+    }
+
+    /// 發送訊息給指定的聊天室並回傳每個聊天室的發送結果
+    ///
+    /// 訊息內的 [文字](網址) 會轉成連結，其餘文字皆會跳脫；超過 4096 字時依行分成多則發送
+    pub async fn send_to(&self, chat_ids: &[i64], message: &str) -> DeliveryReport {
+        let chunks: Vec<String> = format::split(message, format::MAX_MESSAGE_LENGTH)
+            .iter()
+            .map(|chunk| format::render(chunk, self.parse_mode))
+            .collect();
+        let deliveries = join_all(chat_ids.iter().map(|id| self.deliver(*id, &chunks))).await;
+
+        DeliveryReport { deliveries }
+    }
+
+    /// 回覆訊息給指定的聊天室
+    pub async fn reply(&self, chat_id: i64, text: &str) -> Result<()> {
+        self.send_to(&[chat_id], text).await.into_result()
+    }
+
+    /// 依序發送分段後的訊息，任一段失敗就停止發送給該聊天室
+    async fn deliver(&self, chat_id: i64, chunks: &[String]) -> Delivery {
+        let mut delivery = Delivery {
+            chat_id,
+            message_ids: Vec::with_capacity(chunks.len()),
+            error: None,
+        };
+
+        for chunk in chunks {
+            let payload = SendMessageRequest {
+                chat_id,
+                text: chunk,
+                parse_mode: Some(format::parse_mode_value(self.parse_mode)),
+            };
+
+            match self.call::<_, Message>("sendMessage", &payload).await {
+                Ok(message) => delivery.message_ids.push(message.message_id),
+                Err(why) => {
+                    delivery.error = Some(format!("{:?}", why));
+                    break;
+                }
+            }
+        }
+
+        delivery
+    }
+
+    /// 以長輪詢取得 offset 之後的更新，timeout 為 Telegram 等待新訊息的秒數，
+    /// 共用的 http client 逾時太短，需以逾時大於 timeout 的 client 發送
+    pub async fn get_updates(
+        &self,
+        client: &reqwest::Client,
+        offset: i64,
+        timeout: u64,
+    ) -> Result<Vec<Update>> {
+        let payload = GetUpdatesRequest {
+            offset,
+            timeout,
+            allowed_updates: &["message"],
+        };
+
+        self.call_with::<_, Vec<Update>>(Some(client), "getUpdates", &payload)
+            .await
+    }
+
+    /// 設定 webhook，Telegram 會將更新 POST 到指定的網址
+    pub async fn set_webhook(&self, url: &str, secret_token: &str) -> Result<bool> {
+        let payload = SetWebhookRequest {
+            url,
+            secret_token: (!secret_token.is_empty()).then_some(secret_token),
+            allowed_updates: &["message"],
+        };
+
+        self.call::<_, bool>("setWebhook", &payload).await
+    }
+
+    /// 移除 webhook，設定 webhook 時無法使用 getUpdates
+    pub async fn delete_webhook(&self) -> Result<bool> {
+        self.call::<_, bool>("deleteWebhook", &http::Empty {}).await
+    }
+
+    /// 呼叫 Bot API，遇到 429 時依 retry_after 等待後重試
+    async fn call<REQ, RES>(&self, method: &str, payload: &REQ) -> Result<RES>
+    where
+        REQ: Serialize,
+        RES: DeserializeOwned,
+    {
+        self.call_with(None, method, payload).await
+    }
+
+    /// 同 call，client 為 None 時使用共用的 http client
+    async fn call_with<REQ, RES>(
+        &self,
+        client: Option<&reqwest::Client>,
+        method: &str,
+        payload: &REQ,
+    ) -> Result<RES>
+    where
+        REQ: Serialize,
+        RES: DeserializeOwned,
+    {
+        let url = format!("{}/{}", self.bot_url, method);
+        let mut retries = 0;
+
+        loop {
+            let res = match client {
+                Some(client) => post_json::<REQ, ApiResponse<RES>>(client, &url, payload).await,
+                None => {
+                    http::post_use_json::<REQ, ApiResponse<RES>>(&url, None, Some(payload)).await
+                }
+            }
+            .map_err(|why| anyhow!("Failed to {} because: {:?}", method, why))?;
+
+            if let (true, Some(result)) = (res.ok, res.result) {
+                return Ok(result);
+            }
+
+            let retry_after = res.parameters.and_then(|p| p.retry_after);
+            match retry_after {
+                Some(seconds)
+                    if res.error_code == Some(429) && retries < MAX_RATE_LIMIT_RETRIES =>
+                {
+                    retries += 1;
+                    tokio::time::sleep(Duration::from_secs(seconds.min(MAX_RETRY_AFTER_SECONDS)))
+                        .await;
+                }
+                _ => {
+                    return Err(anyhow!(
+                        "Failed to {} because: {}({})",
+                        method,
+                        res.description.unwrap_or_default(),
+                        res.error_code.unwrap_or_default()
+                    ))
+                }
+            }
+        }
+    }
+}
+
+/// 以指定的 client 發送 JSON 並解析回應，Bot API 失敗時也會回傳 JSON 因此不檢查狀態碼
+async fn post_json<REQ, RES>(client: &reqwest::Client, url: &str, payload: &REQ) -> Result<RES>
+where
+    REQ: Serialize,
+    RES: DeserializeOwned,
+{
+    Ok(client
+        .post(url)
+        .json(payload)
+        .send()
+        .await?
+        .json::<RES>()
+        .await?)
+}
+
+impl Default for Telegram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub(crate) fn get_client() -> Result<&'static Telegram> {
+    Ok(TELEGRAM.get_or_init(Telegram::new))
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ApiResponse<T> {
+    pub ok: bool,
+    pub result: Option<T>,
+    pub error_code: Option<i32>,
+    pub description: Option<String>,
+    pub parameters: Option<ResponseParameters>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ResponseParameters {
+    /// 被限流時需等待的秒數
+    pub retry_after: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Message {
+    pub message_id: i64,
+    pub chat: Option<Chat>,
+    pub text: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Chat {
+    pub id: i64,
+}
+
+/// getUpdates 或 webhook 收到的更新
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Update {
+    pub update_id: i64,
+    pub message: Option<Message>,
+}
+
+#[derive(Serialize)]
+pub struct SendMessageRequest<'a> {
+    pub chat_id: i64,
+    pub text: &'a str,
+    #[serde(rename = "parse_mode", skip_serializing_if = "Option::is_none")]
+    pub parse_mode: Option<&'a str>,
+}
+
+/// 單一聊天室的發送結果
+#[derive(Debug)]
+pub struct Delivery {
+    pub chat_id: i64,
+    /// 已送出的訊息編號，訊息分段時會有多筆
+    pub message_ids: Vec<i64>,
+    pub error: Option<String>,
+}
+
+/// 每個聊天室的發送結果
+#[derive(Debug)]
+pub struct DeliveryReport {
+    pub deliveries: Vec<Delivery>,
+}
+
+impl DeliveryReport {
+    /// 沒有任何收件者或有任一聊天室發送失敗時回傳錯誤
+    pub fn into_result(self) -> Result<()> {
+        if self.deliveries.is_empty() {
+            return Err(anyhow!("No telegram recipient"));
+        }
+
+        let failures: Vec<String> = self
+            .deliveries
+            .iter()
+            .filter_map(|d| {
+                d.error
+                    .as_ref()
+                    .map(|why| format!("{}: {}", d.chat_id, why))
+            })
+            .collect();
+        if failures.is_empty() {
+            return Ok(());
+        }
+
+        Err(anyhow!(
+            "Failed to send message to {} of {} telegram chats\n{}",
+            failures.len(),
+            self.deliveries.len(),
+            failures.join("\n")
+        ))
+    }
+}
+
+#[derive(Serialize)]
+struct GetUpdatesRequest<'a> {
+    offset: i64,
+    timeout: u64,
+    allowed_updates: &'a [&'a str],
+}
+
+#[derive(Serialize)]
+struct SetWebhookRequest<'a> {
+    url: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    secret_token: Option<&'a str>,
+    allowed_updates: &'a [&'a str],
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use axum::{extract::State, routing::post, Json, Router};
+    use serde_json::{json, Value};
+    use tokio::{net::TcpListener, time};
+
+    use crate::{cache::SHARE, logging};
+
+    use super::*;
+
+    /// 模擬 sendMessage，聊天室 1 第一次會被限流，聊天室 2 不存在
+    async fn fake_send_message_api() -> (String, Arc<Mutex<Vec<Value>>>) {
+        let sent: Arc<Mutex<Vec<Value>>> = Arc::new(Mutex::new(Vec::new()));
+        let app = Router::new()
+            .route(
+                "/bottoken/sendMessage",
+                post(
+                    |State(sent): State<Arc<Mutex<Vec<Value>>>>, Json(payload): Json<Value>| async move {
+                        let mut sent = sent.lock().unwrap();
+                        sent.push(payload.clone());
+                        if payload["chat_id"] == 2 {
+                            return Json(json!({"ok": false, "error_code": 400, "description": "Bad Request: chat not found"}));
+                        }
+                        if sent.len() == 1 {
+                            return Json(json!({"ok": false, "error_code": 429, "description": "Too Many Requests: retry after 1", "parameters": {"retry_after": 1}}));
+                        }
+                        Json(json!({"ok": true, "result": {"message_id": sent.len()}}))
+                    },
+                ),
+            )
+            .with_state(Arc::clone(&sent));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (format!("http://{}", addr), sent)
+    }
+
+    #[tokio::test]
+    async fn test_send_to() {
+        let (api_url, sent) = fake_send_message_api().await;
+        let telegram = Telegram::with_api_url(&api_url, "token");
+        let line = format!(
+            "[2330](https://tw.stock.yahoo.com/quote/2330) A&B {}\n",
+            "x".repeat(90)
+        );
+        let message = line.repeat(50);
+
+        let report = telegram.send_to(&[1], &message).await;
+
+        assert_eq!(report.deliveries.len(), 1);
+        assert!(report.deliveries[0].error.is_none());
+        // 第一次被限流後重試，訊息超過 4096 字分成兩則
+        assert_eq!(report.deliveries[0].message_ids.len(), 2);
+        {
+            let sent = sent.lock().unwrap();
+            assert_eq!(sent.len(), 3);
+            assert_eq!(sent[0], sent[1]);
+            assert_eq!(sent[1]["parse_mode"], "HTML");
+            assert!(sent[1]["text"].as_str().unwrap().starts_with(
+                r#"<a href="https://tw.stock.yahoo.com/quote/2330">2330</a> A&amp;B"#
+            ));
+        }
+        assert!(report.into_result().is_ok());
+
+        let report = telegram.send_to(&[1, 2], "hello").await;
+        let failed: Vec<i64> = report
+            .deliveries
+            .iter()
+            .filter(|d| d.error.is_some())
+            .map(|d| d.chat_id)
+            .collect();
+        assert_eq!(failed, vec![2]);
+        assert!(report.into_result().is_err());
+        assert!(telegram.send_to(&[], "hello").await.into_result().is_err());
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_send_message() {
+        dotenv::dotenv().ok();
+        SHARE.load().await;
+        logging::debug_file_async("開始 test_send_message".to_string());
+        let msg = format!(
+            "test_send_message Rust OSArch: {}{}",
+            env::consts::OS,
+            env::consts::ARCH
+        );
+        get_client()
+            .expect("REASON")
+            .send(&msg)
+            .await
+            .into_result()
+            .expect("TODO: panic message");
+        // let _ = send_to_allowed(&msg).await;
+
+        logging::debug_file_async("結束 test_send_message".to_string());
+        time::sleep(Duration::from_secs(1)).await;
+    }
+}
@@ -0,0 +1,344 @@
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use anyhow::{anyhow, Context, Result};
+use axum::{extract::State, http::HeaderMap, http::StatusCode, routing::post, Json, Router};
+use tokio::net::TcpListener;
+
+use crate::{
+    bot::telegram::{
+        command::{Command, USAGE},
+        Telegram, Update,
+    },
+    config::{TelegramReceiveMode, SETTINGS},
+    logging,
+    rpc::server::auth,
+};
+
+/// Telegram 等待新訊息的秒數
+const LONG_POLL_TIMEOUT_SECONDS: u64 = 30;
+/// 長輪詢的 http client 逾時，需大於 Telegram 等待新訊息的時間
+const LONG_POLL_CLIENT_TIMEOUT: Duration = Duration::from_secs(LONG_POLL_TIMEOUT_SECONDS + 10);
+/// webhook 的路徑，對外的網址需轉發到此路徑
+const WEBHOOK_PATH: &str = "/telegram/webhook";
+const SECRET_TOKEN_HEADER: &str = "X-Telegram-Bot-Api-Secret-Token";
+
+/// 接收聊天室的指令並回覆，只處理允許的聊天室
+pub struct Receiver {
+    telegram: Telegram,
+    allowed: HashSet<i64>,
+    /// 長輪詢專用的 http client
+    client: reqwest::Client,
+}
+
+impl Receiver {
+    pub fn new(telegram: Telegram, allowed: HashSet<i64>) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(5))
+            .timeout(LONG_POLL_CLIENT_TIMEOUT)
+            .build()
+            .context("Failed to create telegram long polling client")?;
+
+        Ok(Self {
+            telegram,
+            allowed,
+            client,
+        })
+    }
+
+    /// 取得一次 offset 之後的更新並逐一處理，回傳下一次要使用的 offset
+    pub async fn poll(&self, offset: i64) -> Result<i64> {
+        let mut next_offset = offset;
+        for update in self
+            .telegram
+            .get_updates(&self.client, offset, LONG_POLL_TIMEOUT_SECONDS)
+            .await?
+        {
+            next_offset = next_offset.max(update.update_id + 1);
+            self.dispatch(update).await;
+        }
+
+        Ok(next_offset)
+    }
+
+    /// 處理一則更新，非允許的聊天室或非指令的訊息會略過
+    pub async fn dispatch(&self, update: Update) {
+        let (chat_id, text) = match update.message {
+            Some(message) => match (message.chat, message.text) {
+                (Some(chat), Some(text)) => (chat.id, text),
+                _ => return,
+            },
+            None => return,
+        };
+
+        if !self.allowed.contains(&chat_id) {
+            logging::warn_file_async(format!(
+                "Ignore telegram message from chat({}) that is not allowed: {}",
+                chat_id, text
+            ));
+            return;
+        }
+
+        if !text.starts_with('/') {
+            return;
+        }
+
+        let reply = match Command::parse(&text) {
+            Ok(command) => match command.execute().await {
+                Ok(reply) => reply,
+                Err(why) => {
+                    logging::error_file_async(format!(
+                        "Failed to execute telegram command({}) because {:?}",
+                        text, why
+                    ));
+                    format!("執行 {} 失敗", text)
+                }
+            },
+            Err(why) => format!("{}\n{}", why, USAGE),
+        };
+
+        if let Err(why) = self.telegram.reply(chat_id, &reply).await {
+            logging::error_file_async(format!(
+                "Failed to reply telegram chat({}) because {:?}",
+                chat_id, why
+            ));
+        }
+    }
+}
+
+/// 依設定以長輪詢或 webhook 開始接收指令
+pub async fn start() -> Result<()> {
+    let config = &SETTINGS.bot.telegram;
+    let receiver = Arc::new(Receiver::new(
+        Telegram::new(),
+        config.allowed.keys().copied().collect(),
+    )?);
+
+    match config.receive_mode {
+        TelegramReceiveMode::Disabled => Ok(()),
+        TelegramReceiveMode::Polling => {
+            receiver.telegram.delete_webhook().await?;
+            tokio::spawn(run_polling(receiver));
+            Ok(())
+        }
+        TelegramReceiveMode::Webhook => {
+            check_webhook_config(
+                &config.webhook_url,
+                &config.webhook_listen,
+                &config.webhook_secret,
+            )?;
+
+            let listener = TcpListener::bind(&config.webhook_listen)
+                .await
+                .context(format!("Failed to bind {}", config.webhook_listen))?;
+            receiver
+                .telegram
+                .set_webhook(&config.webhook_url, &config.webhook_secret)
+                .await?;
+            let app = webhook_router(receiver, config.webhook_secret.to_string());
+            tokio::spawn(async move {
+                if let Err(why) = axum::serve(listener, app).await {
+                    logging::error_file_async(format!(
+                        "Failed to serve telegram webhook because {:?}",
+                        why
+                    ));
+                }
+            });
+            Ok(())
+        }
+    }
+}
+
+/// webhook 對外公開，沒有 secret 時任何人都能偽造更新，因此三者皆為必填
+fn check_webhook_config(url: &str, listen: &str, secret: &str) -> Result<()> {
+    if url.is_empty() || listen.is_empty() || secret.is_empty() {
+        return Err(anyhow!(
+            "webhook_url, webhook_listen and webhook_secret are required for telegram webhook"
+        ));
+    }
+
+    Ok(())
+}
+
+async fn run_polling(receiver: Arc<Receiver>) {
+    let mut offset = 0;
+    loop {
+        match receiver.poll(offset).await {
+            Ok(next_offset) => offset = next_offset,
+            Err(why) => {
+                logging::error_file_async(format!(
+                    "Failed to poll telegram updates because {:?}",
+                    why
+                ));
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+        }
+    }
+}
+
+struct WebhookState {
+    receiver: Arc<Receiver>,
+    secret: String,
+}
+
+fn webhook_router(receiver: Arc<Receiver>, secret: String) -> Router {
+    Router::new()
+        .route(WEBHOOK_PATH, post(webhook))
+        .with_state(Arc::new(WebhookState { receiver, secret }))
+}
+
+async fn webhook(
+    State(state): State<Arc<WebhookState>>,
+    headers: HeaderMap,
+    Json(update): Json<Update>,
+) -> StatusCode {
+    let token = headers
+        .get(SECRET_TOKEN_HEADER)
+        .map(|value| value.as_bytes())
+        .unwrap_or_default();
+    if state.secret.is_empty() || !auth::constant_time_eq(token, state.secret.as_bytes()) {
+        return StatusCode::UNAUTHORIZED;
+    }
+
+    // 先回應 Telegram，避免執行指令太久導致重送
+    let receiver = Arc::clone(&state.receiver);
+    tokio::spawn(async move { receiver.dispatch(update).await });
+
+    StatusCode::OK
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use axum::extract::Path;
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    /// 模擬 Telegram Bot API，getUpdates 回傳固定的更新並記錄 sendMessage 的內容
+    async fn fake_telegram_api() -> (String, Arc<Mutex<Vec<Value>>>) {
+        let sent: Arc<Mutex<Vec<Value>>> = Arc::new(Mutex::new(Vec::new()));
+        let app = Router::new()
+            .route(
+                "/:bot/:method",
+                post(
+                    |State(sent): State<Arc<Mutex<Vec<Value>>>>,
+                     Path((_bot, method)): Path<(String, String)>,
+                     Json(payload): Json<Value>| async move {
+                        match method.as_str() {
+                            "getUpdates" => Json(json!({
+                                "ok": true,
+                                "result": [
+                                    {"update_id": 10, "message": {"message_id": 1, "chat": {"id": 1}, "text": "/help"}},
+                                    {"update_id": 11, "message": {"message_id": 2, "chat": {"id": 2}, "text": "/portfolio"}},
+                                    {"update_id": 12, "message": {"message_id": 3, "chat": {"id": 1}, "text": "/quote"}},
+                                    {"update_id": 13, "message": {"message_id": 4, "chat": {"id": 1}, "text": "hello"}}
+                                ]
+                            })),
+                            "sendMessage" => {
+                                sent.lock().unwrap().push(payload);
+                                Json(json!({"ok": true, "result": {"message_id": 100}}))
+                            }
+                            _ => Json(json!({"ok": true, "result": true})),
+                        }
+                    },
+                ),
+            )
+            .with_state(Arc::clone(&sent));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (format!("http://{}", addr), sent)
+    }
+
+    #[tokio::test]
+    async fn test_poll() {
+        let (api_url, sent) = fake_telegram_api().await;
+        let receiver = Receiver::new(
+            Telegram::with_api_url(&api_url, "token"),
+            HashSet::from([1]),
+        )
+        .unwrap();
+
+        let offset = receiver.poll(0).await.unwrap();
+
+        assert_eq!(offset, 14);
+        let sent = sent.lock().unwrap();
+        // 非允許的聊天室與非指令的訊息不回覆
+        assert_eq!(sent.len(), 2);
+        assert!(sent.iter().all(|payload| payload["chat_id"] == 1));
+        assert_eq!(sent[0]["text"], USAGE);
+        assert!(sent[1]["text"].as_str().unwrap().ends_with(USAGE));
+        assert_eq!(sent[1]["parse_mode"], "HTML");
+    }
+
+    #[tokio::test]
+    async fn test_poll_longer_than_shared_timeout() {
+        // 共用的 http client 逾時為 3 秒，Telegram 在等待新訊息時會超過它才回應
+        let app = Router::new().route(
+            "/:bot/getUpdates",
+            post(|| async {
+                tokio::time::sleep(Duration::from_secs(4)).await;
+                Json(json!({"ok": true, "result": []}))
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let receiver = Receiver::new(
+            Telegram::with_api_url(&api_url, "token"),
+            HashSet::from([1]),
+        )
+        .unwrap();
+
+        assert_eq!(receiver.poll(5).await.unwrap(), 5);
+    }
+
+    #[test]
+    fn test_check_webhook_config() {
+        let url = "https://example.com/telegram/webhook";
+        assert!(check_webhook_config(url, "127.0.0.1:8443", "secret").is_ok());
+        assert!(check_webhook_config(url, "127.0.0.1:8443", "").is_err());
+        assert!(check_webhook_config("", "127.0.0.1:8443", "secret").is_err());
+        assert!(check_webhook_config(url, "", "secret").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_webhook_secret() {
+        let (api_url, sent) = fake_telegram_api().await;
+        let receiver = Arc::new(
+            Receiver::new(
+                Telegram::with_api_url(&api_url, "token"),
+                HashSet::from([1]),
+            )
+            .unwrap(),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}{}", listener.local_addr().unwrap(), WEBHOOK_PATH);
+        let app = webhook_router(receiver, "secret".to_string());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let update = json!({"update_id": 1, "message": {"message_id": 1, "chat": {"id": 1}, "text": "/help"}});
+        let client = reqwest::Client::new();
+        let res = client.post(&url).json(&update).send().await.unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+        let res = client
+            .post(&url)
+            .header(SECRET_TOKEN_HEADER, "secret")
+            .json(&update)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+        for _ in 0..50 {
+            if !sent.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(sent.lock().unwrap().len(), 1);
+    }
+}
@@ -13,7 +13,7 @@ use crate::{
     },
     declare::{self, Industry},
     logging,
-    util::map::Keyable,
+    util::{map::Keyable, metrics},
 };
 
 pub static SHARE: Lazy<Share> = Lazy::new(Default::default);
@@ -322,6 +322,21 @@ impl Share {
     }
 
     /// 將目前的IP放入快取資料內
+    /// 各個快取目前的筆數
+    pub fn sizes(&self) -> Vec<(&'static str, usize)> {
+        fn len<K, V>(cache: &RwLock<HashMap<K, V>>) -> usize {
+            cache.read().map(|cache| cache.len()).unwrap_or_default()
+        }
+
+        vec![
+            ("indices", len(&self.indices)),
+            ("stocks", len(&self.stocks)),
+            ("last_revenues", len(&self.last_revenues)),
+            ("last_trading_day_quotes", len(&self.last_trading_day_quotes)),
+            ("quote_history_records", len(&self.quote_history_records)),
+        ]
+    }
+
     pub fn set_current_ip(&self, ip: String) {
         if let Ok(mut current_ip) = self.current_ip.write() {
             *current_ip = ip;
@@ -485,17 +500,21 @@ impl TtlCacheInner for Ttl {
     }
 
     fn daily_quote_contains_key(&self, key: &str) -> bool {
-        match self.daily_quote.read() {
+        let hit = match self.daily_quote.read() {
             Ok(ttl) => ttl.contains_key(key),
             Err(_) => false,
-        }
+        };
+        metrics::record_ttl_lookup("daily_quote", hit);
+        hit
     }
 
     fn daily_quote_get(&self, key: &str) -> Option<String> {
-        match self.daily_quote.read() {
+        let value = match self.daily_quote.read() {
             Ok(ttl) => ttl.get(key).map(|value| value.to_string()),
             Err(_) => None,
-        }
+        };
+        metrics::record_ttl_lookup("daily_quote", value.is_some());
+        value
     }
 
     fn daily_quote_set(&self, key: String, val: String, duration: Duration) -> Option<String> {
@@ -506,17 +525,21 @@ impl TtlCacheInner for Ttl {
     }
 
     fn trace_quote_contains_key(&self, key: &str) -> bool {
-        match self.trace_quote_notify.read() {
+        let hit = match self.trace_quote_notify.read() {
             Ok(ttl) => ttl.contains_key(key),
             Err(_) => false,
-        }
+        };
+        metrics::record_ttl_lookup("trace_quote_notify", hit);
+        hit
     }
 
     fn trace_quote_get(&self, key: &str) -> Option<Decimal> {
-        match self.trace_quote_notify.read() {
+        let value = match self.trace_quote_notify.read() {
             Ok(ttl) => ttl.get(key).copied(),
             Err(_) => None,
-        }
+        };
+        metrics::record_ttl_lookup("trace_quote_notify", value.is_some());
+        value
     }
     fn trace_quote_set(&self, key: String, val: Decimal, duration: Duration) -> Option<Decimal> {
         match self.trace_quote_notify.write() {
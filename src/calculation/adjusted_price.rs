@@ -0,0 +1,767 @@
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use anyhow::{bail, Result};
+use chrono::{Local, NaiveDate, TimeDelta};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use crate::{
+    cache::SHARE,
+    database::table::{
+        adjusted_quote_history_record::AdjustedQuoteHistoryRecord,
+        daily_quote::{self, DailyQuote},
+        dividend::Dividend,
+        price_adjustment_factor::PriceAdjustmentFactor,
+    },
+    declare::MovingAverage,
+    logging,
+    util::datetime::EARLIEST_DATE,
+};
+
+/// 計算均線與年度高低價時往前多取的天數(與 `DailyQuote::fill_moving_average` 相同)
+const LOOKBACK_DAYS: i64 = 400;
+/// 年度高低價、均價的計算筆數(與 `DailyQuote::fill_moving_average` 相同)
+const DAYS_IN_YEAR: usize = 240;
+
+/// 股價序列的種類
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum PriceSeries {
+    /// 原始股價
+    #[default]
+    Raw,
+    /// 除權息還原股價
+    Adjusted,
+}
+
+impl fmt::Display for PriceSeries {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PriceSeries::Raw => write!(f, "raw"),
+            PriceSeries::Adjusted => write!(f, "adjusted"),
+        }
+    }
+}
+
+/// 格式與 Display 相同，空字串為原始股價
+impl FromStr for PriceSeries {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "" | "raw" => Ok(PriceSeries::Raw),
+            "adjusted" => Ok(PriceSeries::Adjusted),
+            _ => bail!("Unknown price series '{}'", s),
+        }
+    }
+}
+
+/// 除權息事件
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DividendEvent {
+    /// 除權息日
+    pub date: NaiveDate,
+    /// 每股現金股利(元)
+    pub cash_dividend: Decimal,
+    /// 每股股票股利(元)，以面額 10 元換算配股數
+    pub stock_dividend: Decimal,
+}
+
+impl DividendEvent {
+    /// 將股利發放記錄轉換成依日期排序的除權息事件
+    ///
+    /// 現金股利以除息日(ex_dividend_date1)、股票股利以除權日(ex_dividend_date2)為準，
+    /// 年度合計的記錄其日期為 `-` 因此不會被重複計算。
+    pub fn from_dividends(dividends: &[Dividend]) -> Vec<DividendEvent> {
+        let mut events: BTreeMap<NaiveDate, DividendEvent> = BTreeMap::new();
+
+        for d in dividends {
+            if d.cash_dividend > Decimal::ZERO {
+                if let Ok(date) = NaiveDate::parse_from_str(&d.ex_dividend_date1, "%Y-%m-%d") {
+                    events
+                        .entry(date)
+                        .or_insert_with(|| DividendEvent::new(date))
+                        .cash_dividend += d.cash_dividend;
+                }
+            }
+
+            if d.stock_dividend > Decimal::ZERO {
+                if let Ok(date) = NaiveDate::parse_from_str(&d.ex_dividend_date2, "%Y-%m-%d") {
+                    events
+                        .entry(date)
+                        .or_insert_with(|| DividendEvent::new(date))
+                        .stock_dividend += d.stock_dividend;
+                }
+            }
+        }
+
+        events.into_values().collect()
+    }
+
+    fn new(date: NaiveDate) -> Self {
+        DividendEvent {
+            date,
+            ..Default::default()
+        }
+    }
+}
+
+/// 重新計算指定股票的還原股價調整因子
+pub async fn execute(security_codes: Vec<String>) {
+    logging::info_file_async("計算還原股價調整因子開始".to_string());
+
+    for security_code in security_codes {
+        if let Err(why) = calculate(&security_code).await {
+            logging::error_file_async(format!(
+                "Failed to calculate adjusted price factor({}) because {:?}",
+                security_code, why
+            ));
+        }
+    }
+
+    logging::info_file_async("計算還原股價調整因子結束".to_string());
+}
+
+/// 重新計算所有未下市股票的還原股價調整因子
+pub async fn execute_all() -> Result<()> {
+    let security_codes: Vec<String> = match SHARE.stocks.read() {
+        Ok(stocks) => stocks
+            .values()
+            .filter(|stock| !stock.suspend_listing)
+            .map(|stock| stock.stock_symbol.to_string())
+            .collect(),
+        Err(why) => {
+            return Err(anyhow::anyhow!(
+                "Failed to read stocks cache because {:?}",
+                why
+            ))
+        }
+    };
+
+    execute(security_codes).await;
+
+    Ok(())
+}
+
+/// 依股利發放記錄與歷史收盤價計算指定股票的調整因子並寫回資料庫
+pub async fn calculate(security_code: &str) -> Result<Vec<PriceAdjustmentFactor>> {
+    let today = Local::now().date_naive();
+    let dividends = Dividend::fetch_by_security_code(security_code).await?;
+    // 尚未到除權息日的事件沒有參考價，不列入計算
+    let events: Vec<DividendEvent> = DividendEvent::from_dividends(&dividends)
+        .into_iter()
+        .filter(|event| event.date <= today)
+        .collect();
+
+    let factors = match (events.first(), events.last()) {
+        (Some(first), Some(last)) => {
+            let quotes = daily_quote::fetch_daily_quotes_by_security_code(
+                security_code,
+                first.date - TimeDelta::try_days(30).unwrap(),
+                last.date,
+            )
+            .await?;
+            calculate_factors(security_code, &quotes, &events)
+        }
+        _ => Vec::new(),
+    };
+
+    PriceAdjustmentFactor::replace(security_code, &factors).await?;
+    update_adjusted_quote_history_record(security_code, &factors).await?;
+
+    Ok(factors)
+}
+
+/// 從上次計算到的日期之後繼續以還原股價計算歷史最高、最低價並寫回資料庫，
+/// 原始股價的歷史高低價(quote_history_record)仍由每日收盤更新
+async fn update_adjusted_quote_history_record(
+    security_code: &str,
+    factors: &[PriceAdjustmentFactor],
+) -> Result<()> {
+    let record = AdjustedQuoteHistoryRecord::fetch(security_code).await?;
+    let start_date = record.as_ref().map_or(EARLIEST_DATE, |record| {
+        record.processed_date + TimeDelta::days(1)
+    });
+    let quotes = daily_quote::fetch_daily_quotes_by_security_code(
+        security_code,
+        start_date,
+        Local::now().date_naive(),
+    )
+    .await?;
+
+    if let Some(record) = accumulate_extremes(security_code, record, &quotes, factors) {
+        record.upsert().await?;
+    }
+
+    Ok(())
+}
+
+/// 將已記錄的高低價換算成目前的還原股價後，與 quotes(需晚於 processed_date)的還原股價比較，
+/// 沒有記錄也沒有成交時回傳 None
+///
+/// processed_date 之後新增的除權息只會讓更早的股價再乘上調整因子，
+/// 所以已記錄的高低價乘上 processed_date 新舊累積調整因子的比值即為目前的還原股價。
+fn accumulate_extremes(
+    security_code: &str,
+    record: Option<AdjustedQuoteHistoryRecord>,
+    quotes: &[DailyQuote],
+    factors: &[PriceAdjustmentFactor],
+) -> Option<AdjustedQuoteHistoryRecord> {
+    let mut record = match record {
+        Some(mut record) => {
+            let factor = cumulative_factor_on(record.processed_date, factors);
+            if record.processed_factor > Decimal::ZERO && factor != record.processed_factor {
+                let scale = factor / record.processed_factor;
+                record.maximum_price = (record.maximum_price * scale).round_dp(4);
+                record.minimum_price = (record.minimum_price * scale).round_dp(4);
+            }
+            record.processed_factor = factor;
+            Some(record)
+        }
+        None => None,
+    };
+
+    if let Some(((maximum_price, maximum_date), (minimum_price, minimum_date))) =
+        price_extremes(quotes, factors)
+    {
+        let record = record.get_or_insert_with(|| AdjustedQuoteHistoryRecord {
+            security_code: security_code.to_string(),
+            maximum_price,
+            maximum_price_date_on: maximum_date,
+            minimum_price,
+            minimum_price_date_on: minimum_date,
+            ..Default::default()
+        });
+        if maximum_price > record.maximum_price {
+            record.maximum_price = maximum_price;
+            record.maximum_price_date_on = maximum_date;
+        }
+        if minimum_price < record.minimum_price {
+            record.minimum_price = minimum_price;
+            record.minimum_price_date_on = minimum_date;
+        }
+    }
+
+    if let (Some(record), Some(last)) = (record.as_mut(), quotes.last()) {
+        record.processed_date = last.date;
+        record.processed_factor = cumulative_factor_on(last.date, factors);
+    }
+
+    record
+}
+
+/// 以還原股價計算的最高價與最低價及其日期，沒有成交的日子不列入計算
+fn price_extremes(
+    quotes: &[DailyQuote],
+    factors: &[PriceAdjustmentFactor],
+) -> Option<((Decimal, NaiveDate), (Decimal, NaiveDate))> {
+    let mut maximum: Option<(Decimal, NaiveDate)> = None;
+    let mut minimum: Option<(Decimal, NaiveDate)> = None;
+
+    for quote in quotes {
+        if quote.highest_price <= Decimal::ZERO || quote.lowest_price <= Decimal::ZERO {
+            continue;
+        }
+
+        let factor = cumulative_factor_on(quote.date, factors);
+        let highest = (quote.highest_price * factor).round_dp(4);
+        let lowest = (quote.lowest_price * factor).round_dp(4);
+        if maximum.is_none_or(|(price, _)| highest > price) {
+            maximum = Some((highest, quote.date));
+        }
+        if minimum.is_none_or(|(price, _)| lowest < price) {
+            minimum = Some((lowest, quote.date));
+        }
+    }
+
+    maximum.zip(minimum)
+}
+
+/// 取得指定股票在日期區間內的每日收盤數據，`series` 為還原股價時價格與均線皆以還原後的股價計算
+pub async fn fetch_daily_quotes(
+    security_code: &str,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    series: PriceSeries,
+) -> Result<Vec<DailyQuote>> {
+    if series == PriceSeries::Raw {
+        return daily_quote::fetch_daily_quotes_by_security_code(
+            security_code,
+            start_date,
+            end_date,
+        )
+        .await;
+    }
+
+    let mut quotes = daily_quote::fetch_daily_quotes_by_security_code(
+        security_code,
+        start_date - TimeDelta::try_days(LOOKBACK_DAYS).unwrap(),
+        end_date,
+    )
+    .await?;
+    let factors = PriceAdjustmentFactor::fetch(security_code).await?;
+
+    adjust(&mut quotes, &factors);
+    quotes.retain(|quote| quote.date >= start_date);
+
+    Ok(quotes)
+}
+
+/// 分頁取得指定股票在日期區間內的每日收盤數據(依日期由舊到新排序)，`series` 的用途與 `fetch_daily_quotes` 相同
+pub async fn fetch_daily_quotes_page(
+    security_code: &str,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    offset: i64,
+    limit: i64,
+    series: PriceSeries,
+) -> Result<Vec<DailyQuote>> {
+    let quotes = daily_quote::fetch_daily_quotes_page_by_security_code(
+        security_code,
+        start_date,
+        end_date,
+        offset,
+        limit,
+    )
+    .await?;
+
+    // 以分頁的日期區間重新取得還原股價，均線需要往前的股價所以不能只調整這一頁
+    match (series, quotes.first(), quotes.last()) {
+        (PriceSeries::Adjusted, Some(first), Some(last)) => {
+            fetch_daily_quotes(security_code, first.date, last.date, series).await
+        }
+        _ => Ok(quotes),
+    }
+}
+
+/// 取得指定股票在 `after` 之後(不含)的下一頁每日收盤數據(依日期由舊到新排序)，
+/// 以上一頁最後的日期作為 `after` 逐頁讀取，`series` 的用途與 `fetch_daily_quotes` 相同
+pub async fn fetch_daily_quotes_after(
+    security_code: &str,
+    after: NaiveDate,
+    end_date: NaiveDate,
+    limit: i64,
+    series: PriceSeries,
+) -> Result<Vec<DailyQuote>> {
+    let quotes = daily_quote::fetch_daily_quotes_after_by_security_code(
+        security_code,
+        after,
+        end_date,
+        limit,
+    )
+    .await?;
+
+    match (series, quotes.first(), quotes.last()) {
+        (PriceSeries::Adjusted, Some(first), Some(last)) => {
+            fetch_daily_quotes(security_code, first.date, last.date, series).await
+        }
+        _ => Ok(quotes),
+    }
+}
+
+/// 計算每次除權息的調整因子
+///
+/// 除權息參考價 = (前一日收盤價 - 現金股利) / (1 + 股票股利 / 10)，
+/// 調整因子 = 除權息參考價 / 前一日收盤價，
+/// 累積調整因子為本次及之後所有調整因子的乘積(後復權，最新的股價維持不變)。
+/// `quotes` 與 `events` 皆需依日期由舊到新排序。
+pub fn calculate_factors(
+    security_code: &str,
+    quotes: &[DailyQuote],
+    events: &[DividendEvent],
+) -> Vec<PriceAdjustmentFactor> {
+    let mut factors: Vec<PriceAdjustmentFactor> = Vec::with_capacity(events.len());
+
+    for event in events {
+        let index = quotes.partition_point(|quote| quote.date < event.date);
+        let previous = match quotes[..index]
+            .iter()
+            .rev()
+            .find(|quote| quote.closing_price > Decimal::ZERO)
+        {
+            Some(quote) => quote,
+            None => continue,
+        };
+
+        let previous_closing_price = previous.closing_price;
+        let reference_price = (previous_closing_price - event.cash_dividend)
+            / (Decimal::ONE + event.stock_dividend / dec!(10));
+        if reference_price <= Decimal::ZERO {
+            continue;
+        }
+
+        factors.push(PriceAdjustmentFactor {
+            security_code: security_code.to_string(),
+            date: event.date,
+            previous_closing_price,
+            cash_dividend: event.cash_dividend,
+            stock_dividend: event.stock_dividend,
+            reference_price: reference_price.round_dp(4),
+            factor: (reference_price / previous_closing_price).round_dp(12),
+            cumulative_factor: Decimal::ONE,
+        });
+    }
+
+    let mut cumulative = Decimal::ONE;
+    for factor in factors.iter_mut().rev() {
+        cumulative = (cumulative * factor.factor).round_dp(12);
+        factor.cumulative_factor = cumulative;
+    }
+
+    factors
+}
+
+/// 取得指定日期的股價需乘上的累積調整因子
+pub fn cumulative_factor_on(date: NaiveDate, factors: &[PriceAdjustmentFactor]) -> Decimal {
+    factors
+        .iter()
+        .find(|factor| factor.date > date)
+        .map_or(Decimal::ONE, |factor| factor.cumulative_factor)
+}
+
+/// 將原始股價轉換成還原股價，並以還原後的股價重新計算均線與年度高低價
+///
+/// `quotes` 需依日期由舊到新排序，前 240 筆以內的均線會因資料不足而為零。
+pub fn adjust(quotes: &mut [DailyQuote], factors: &[PriceAdjustmentFactor]) {
+    for quote in quotes.iter_mut() {
+        let factor = cumulative_factor_on(quote.date, factors);
+        if factor == Decimal::ONE {
+            continue;
+        }
+
+        quote.opening_price = (quote.opening_price * factor).round_dp(4);
+        quote.highest_price = (quote.highest_price * factor).round_dp(4);
+        quote.lowest_price = (quote.lowest_price * factor).round_dp(4);
+        quote.closing_price = (quote.closing_price * factor).round_dp(4);
+        quote.change = (quote.change * factor).round_dp(4);
+    }
+
+    for index in 0..quotes.len() {
+        let count = index + 1;
+        for ma in MovingAverage::iterator() {
+            let days = ma.days() as usize;
+            let value = if count >= days {
+                let sum: Decimal = quotes[count - days..count]
+                    .iter()
+                    .map(|quote| quote.closing_price)
+                    .sum();
+                (sum / Decimal::from(days)).round_dp(2)
+            } else {
+                Decimal::ZERO
+            };
+
+            set_moving_average(&mut quotes[index], ma, value);
+        }
+
+        let window = &quotes[count.saturating_sub(DAYS_IN_YEAR)..count];
+        let highest = window
+            .iter()
+            .max_by(|a, b| a.highest_price.cmp(&b.highest_price))
+            .map(|quote| (quote.highest_price, quote.date));
+        let lowest = window
+            .iter()
+            .min_by(|a, b| a.lowest_price.cmp(&b.lowest_price))
+            .map(|quote| (quote.lowest_price, quote.date));
+        let average = window.iter().map(|quote| quote.closing_price).sum::<Decimal>()
+            / Decimal::from(window.len());
+
+        let quote = &mut quotes[index];
+        if let Some((price, date)) = highest {
+            quote.maximum_price_in_year = price.round_dp(2);
+            quote.maximum_price_in_year_date_on = date;
+        }
+        if let Some((price, date)) = lowest {
+            quote.minimum_price_in_year = price.round_dp(2);
+            quote.minimum_price_in_year_date_on = date;
+        }
+        quote.average_price_in_year = average.round_dp(2);
+    }
+}
+
+fn set_moving_average(quote: &mut DailyQuote, ma: MovingAverage, value: Decimal) {
+    match ma {
+        MovingAverage::Ma5 => quote.moving_average_5 = value,
+        MovingAverage::Ma10 => quote.moving_average_10 = value,
+        MovingAverage::Ma20 => quote.moving_average_20 = value,
+        MovingAverage::Ma60 => quote.moving_average_60 = value,
+        MovingAverage::Ma120 => quote.moving_average_120 = value,
+        MovingAverage::Ma240 => quote.moving_average_240 = value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logging;
+
+    use super::*;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, month, day).unwrap()
+    }
+
+    fn quote(date: NaiveDate, price: Decimal) -> DailyQuote {
+        let mut quote = DailyQuote::new("2330".to_string());
+        quote.date = date;
+        quote.opening_price = price;
+        quote.highest_price = price;
+        quote.lowest_price = price;
+        quote.closing_price = price;
+        quote
+    }
+
+    #[test]
+    fn test_from_dividends() {
+        let mut annual = Dividend::new();
+        annual.cash_dividend = dec!(3);
+        annual.ex_dividend_date1 = "-".to_string();
+        let mut q1 = Dividend::new();
+        q1.cash_dividend = dec!(1.5);
+        q1.stock_dividend = dec!(0.5);
+        q1.ex_dividend_date1 = "2023-07-10".to_string();
+        q1.ex_dividend_date2 = "2023-07-10".to_string();
+        let mut q2 = Dividend::new();
+        q2.cash_dividend = dec!(1.5);
+        q2.ex_dividend_date1 = "2023-10-10".to_string();
+        q2.ex_dividend_date2 = "尚未公布".to_string();
+
+        let events = DividendEvent::from_dividends(&[q2, annual, q1]);
+
+        assert_eq!(
+            events,
+            vec![
+                DividendEvent {
+                    date: date(7, 10),
+                    cash_dividend: dec!(1.5),
+                    stock_dividend: dec!(0.5),
+                },
+                DividendEvent {
+                    date: date(10, 10),
+                    cash_dividend: dec!(1.5),
+                    stock_dividend: Decimal::ZERO,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_calculate_factors() {
+        let quotes = vec![
+            quote(date(7, 6), dec!(100)),
+            quote(date(7, 7), dec!(105)),
+            quote(date(7, 10), dec!(102)),
+            quote(date(10, 6), dec!(80)),
+            quote(date(10, 11), dec!(76)),
+        ];
+        let events = vec![
+            DividendEvent {
+                date: date(7, 10),
+                cash_dividend: dec!(5),
+                stock_dividend: Decimal::ZERO,
+            },
+            DividendEvent {
+                date: date(10, 10),
+                cash_dividend: Decimal::ZERO,
+                stock_dividend: dec!(2.5),
+            },
+        ];
+
+        let factors = calculate_factors("2330", &quotes, &events);
+
+        assert_eq!(factors.len(), 2);
+        assert_eq!(factors[0].previous_closing_price, dec!(105));
+        assert_eq!(factors[0].reference_price, dec!(100));
+        assert_eq!(factors[0].factor, dec!(0.952380952381));
+        // 除權日當天沒有行情時以前一個有收盤價的交易日計算
+        assert_eq!(factors[1].previous_closing_price, dec!(80));
+        assert_eq!(factors[1].reference_price, dec!(64));
+        assert_eq!(factors[1].factor, dec!(0.8));
+        assert_eq!(factors[1].cumulative_factor, dec!(0.8));
+        assert_eq!(factors[0].cumulative_factor, dec!(0.761904761905));
+    }
+
+    #[test]
+    fn test_calculate_factors_without_previous_quote() {
+        let quotes = vec![quote(date(7, 10), dec!(100))];
+        let events = vec![DividendEvent {
+            date: date(7, 10),
+            cash_dividend: dec!(5),
+            stock_dividend: Decimal::ZERO,
+        }];
+
+        assert!(calculate_factors("2330", &quotes, &events).is_empty());
+    }
+
+    #[test]
+    fn test_cumulative_factor_on() {
+        let factors = vec![
+            PriceAdjustmentFactor {
+                date: date(7, 10),
+                cumulative_factor: dec!(0.5),
+                ..Default::default()
+            },
+            PriceAdjustmentFactor {
+                date: date(10, 10),
+                cumulative_factor: dec!(0.8),
+                ..Default::default()
+            },
+        ];
+
+        assert_eq!(cumulative_factor_on(date(7, 7), &factors), dec!(0.5));
+        assert_eq!(cumulative_factor_on(date(7, 10), &factors), dec!(0.8));
+        assert_eq!(cumulative_factor_on(date(10, 10), &factors), Decimal::ONE);
+    }
+
+    #[test]
+    fn test_adjust() {
+        let mut quotes: Vec<DailyQuote> = (1..=5)
+            .map(|day| quote(date(7, day), dec!(100)))
+            .collect();
+        // 7/4 除息 50 元，除息前的股價還原後應與除息後的股價相同
+        quotes[3].closing_price = dec!(50);
+        quotes[4].closing_price = dec!(50);
+        quotes[3].highest_price = dec!(50);
+        quotes[4].highest_price = dec!(50);
+        quotes[3].lowest_price = dec!(50);
+        quotes[4].lowest_price = dec!(50);
+        let factors = vec![PriceAdjustmentFactor {
+            date: date(7, 4),
+            factor: dec!(0.5),
+            cumulative_factor: dec!(0.5),
+            ..Default::default()
+        }];
+
+        adjust(&mut quotes, &factors);
+
+        assert!(quotes.iter().all(|q| q.closing_price == dec!(50)));
+        assert_eq!(quotes[0].opening_price, dec!(50));
+        assert_eq!(quotes[4].opening_price, dec!(100));
+        assert_eq!(quotes[3].moving_average_5, Decimal::ZERO);
+        assert_eq!(quotes[4].moving_average_5, dec!(50));
+        assert_eq!(quotes[4].maximum_price_in_year, dec!(50));
+        assert_eq!(quotes[4].minimum_price_in_year, dec!(50));
+        assert_eq!(quotes[4].average_price_in_year, dec!(50));
+    }
+
+    #[test]
+    fn test_price_extremes() {
+        let mut quotes = vec![
+            quote(date(7, 3), dec!(120)),
+            quote(date(7, 4), dec!(55)),
+            quote(date(7, 5), Decimal::ZERO),
+            quote(date(7, 6), dec!(45)),
+        ];
+        quotes[1].highest_price = dec!(70);
+        let factors = vec![PriceAdjustmentFactor {
+            date: date(7, 4),
+            factor: dec!(0.5),
+            cumulative_factor: dec!(0.5),
+            ..Default::default()
+        }];
+
+        // 7/3 的原始最高價 120 還原後為 60，低於 7/4 的 70
+        assert_eq!(
+            price_extremes(&quotes, &factors),
+            Some(((dec!(70), date(7, 4)), (dec!(45), date(7, 6))))
+        );
+        assert_eq!(
+            price_extremes(&quotes, &[]),
+            Some(((dec!(120), date(7, 3)), (dec!(45), date(7, 6))))
+        );
+        assert_eq!(price_extremes(&[], &factors), None);
+    }
+
+    #[test]
+    fn test_accumulate_extremes() {
+        let factors = vec![PriceAdjustmentFactor {
+            date: date(7, 4),
+            factor: dec!(0.5),
+            cumulative_factor: dec!(0.5),
+            ..Default::default()
+        }];
+        let quotes = vec![quote(date(7, 3), dec!(120)), quote(date(7, 4), dec!(55))];
+
+        let first = accumulate_extremes("2330", None, &quotes, &factors).unwrap();
+        assert_eq!(first.maximum_price, dec!(60));
+        assert_eq!(first.minimum_price, dec!(55));
+        assert_eq!(first.processed_date, date(7, 4));
+        assert_eq!(first.processed_factor, Decimal::ONE);
+
+        // 之後除息 50%，已記錄的高低價一併還原後再與新的股價比較
+        let factors = vec![
+            PriceAdjustmentFactor {
+                date: date(7, 4),
+                factor: dec!(0.5),
+                cumulative_factor: dec!(0.25),
+                ..Default::default()
+            },
+            PriceAdjustmentFactor {
+                date: date(7, 6),
+                factor: dec!(0.5),
+                cumulative_factor: dec!(0.5),
+                ..Default::default()
+            },
+        ];
+        let quotes = vec![quote(date(7, 5), dec!(40)), quote(date(7, 6), dec!(25))];
+        let second = accumulate_extremes("2330", Some(first), &quotes, &factors).unwrap();
+        assert_eq!(second.maximum_price, dec!(30));
+        assert_eq!(second.maximum_price_date_on, date(7, 3));
+        assert_eq!(second.minimum_price, dec!(20));
+        assert_eq!(second.minimum_price_date_on, date(7, 5));
+        assert_eq!(second.processed_date, date(7, 6));
+        assert_eq!(second.processed_factor, Decimal::ONE);
+
+        // 沒有新的收盤數據時維持原本的記錄
+        let third = accumulate_extremes("2330", Some(second.clone()), &[], &factors);
+        assert_eq!(third, Some(second));
+        assert_eq!(accumulate_extremes("2330", None, &[], &factors), None);
+    }
+
+    #[test]
+    fn test_price_series_from_str() {
+        for series in [PriceSeries::Raw, PriceSeries::Adjusted] {
+            assert_eq!(series.to_string().parse::<PriceSeries>().unwrap(), series);
+        }
+        assert_eq!("".parse::<PriceSeries>().unwrap(), PriceSeries::Raw);
+        assert!("forward".parse::<PriceSeries>().is_err());
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_calculate() {
+        dotenv::dotenv().ok();
+        logging::debug_file_async("開始 adjusted_price::calculate".to_string());
+
+        match calculate("2330").await {
+            Ok(factors) => {
+                logging::debug_file_async(format!("adjusted_price::calculate:{:#?}", factors))
+            }
+            Err(why) => logging::debug_file_async(format!(
+                "Failed to adjusted_price::calculate because {:?}",
+                why
+            )),
+        }
+
+        logging::debug_file_async("結束 adjusted_price::calculate".to_string());
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_fetch_daily_quotes() {
+        dotenv::dotenv().ok();
+        logging::debug_file_async("開始 adjusted_price::fetch_daily_quotes".to_string());
+        let end_date = Local::now().date_naive();
+        let start_date = end_date - TimeDelta::try_days(365).unwrap();
+
+        match fetch_daily_quotes("2330", start_date, end_date, PriceSeries::Adjusted).await {
+            Ok(quotes) => logging::debug_file_async(format!(
+                "adjusted_price::fetch_daily_quotes:{:#?}",
+                quotes.last()
+            )),
+            Err(why) => logging::debug_file_async(format!(
+                "Failed to adjusted_price::fetch_daily_quotes because {:?}",
+                why
+            )),
+        }
+
+        logging::debug_file_async("結束 adjusted_price::fetch_daily_quotes".to_string());
+    }
+}
@@ -1,8 +1,11 @@
+use std::collections::BTreeSet;
+
 use anyhow::{anyhow, Result};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 
 use crate::{
+    calculation::ledger,
     database::{
         self,
         table::{
@@ -17,6 +20,14 @@ use crate::{
 pub async fn execute(year: i32, security_codes: Option<Vec<String>>) {
     logging::info_file_async("計算指定年份領取的股利開始".to_string());
 
+    // 累積股利與成本改由交易明細重建
+    if let Err(why) = rebuild_from_ledger(security_codes.clone()).await {
+        logging::error_file_async(format!(
+            "Failed to rebuild stock_ownership_details from ledger because {:?}",
+            why
+        ));
+    }
+
     match stock_ownership_details::StockOwnershipDetail::fetch(security_codes).await {
         Ok(inventories) => {
             if !inventories.is_empty() {
@@ -42,9 +53,49 @@ pub async fn execute(year: i32, security_codes: Option<Vec<String>>) {
     logging::info_file_async("計算指定年份領取的股利結束".to_string());
 }
 
+/// 補上庫存股票的除權息交易後，由交易明細重建持股的股數、成本與累積股利，
+/// 尚未轉為交易明細的舊持股維持原本的計算，需先執行 stock_crawler adopt 轉入
+async fn rebuild_from_ledger(security_codes: Option<Vec<String>>) -> Result<()> {
+    let (adopted, legacy): (Vec<_>, Vec<_>) =
+        stock_ownership_details::StockOwnershipDetail::fetch(security_codes)
+            .await?
+            .into_iter()
+            .partition(|sod| sod.transaction_serial.is_some());
+    if !legacy.is_empty() {
+        logging::warn_file_async(format!(
+            "{} 筆持股尚未轉為交易明細，請執行 stock_crawler adopt",
+            legacy.len()
+        ));
+    }
+
+    let holdings: BTreeSet<(i64, String)> = adopted
+        .into_iter()
+        .map(|sod| (sod.member_id, sod.security_code))
+        .collect();
+
+    for (member_id, security_code) in holdings {
+        if let Err(why) = ledger::record_dividends(member_id, &security_code).await {
+            logging::error_file_async(format!(
+                "Failed to record_dividends({},{}) because {:?}",
+                member_id, security_code, why
+            ));
+            continue;
+        }
+
+        if let Err(why) = ledger::rebuild(member_id, &security_code).await {
+            logging::error_file_async(format!(
+                "Failed to rebuild({},{}) because {:?}",
+                member_id, security_code, why
+            ));
+        }
+    }
+
+    Ok(())
+}
+
 /// 計算股票於該年度可以領取的股利
 async fn calculate_dividend(
-    mut sod: stock_ownership_details::StockOwnershipDetail,
+    sod: stock_ownership_details::StockOwnershipDetail,
     year: i32,
 ) -> Result<()> {
     //計算股票於該年度可以領取的股利
@@ -117,35 +168,6 @@ async fn calculate_dividend(
                 why
             ));
         }
-
-        // 計算指定股票其累積的領取股利
-        let cumulate_dividend = match drd.fetch_cumulate_dividend(&mut tx_option).await {
-            Ok(cd) => cd,
-            Err(why) => {
-                if let Some(tx) = tx_option {
-                    tx.rollback().await?;
-                }
-                return Err(anyhow!(
-                    "Failed to execute calculate_cumulate_dividend because {:?}",
-                    why
-                ));
-            }
-        };
-
-        sod.cumulate_dividends_cash = cumulate_dividend.cash;
-        sod.cumulate_dividends_stock_money = cumulate_dividend.stock_money;
-        sod.cumulate_dividends_stock = cumulate_dividend.stock;
-        sod.cumulate_dividends_total = cumulate_dividend.total;
-
-        if let Err(why) = sod.update_cumulate_dividends(&mut tx_option).await {
-            if let Some(tx) = tx_option {
-                tx.rollback().await?;
-            }
-            return Err(anyhow!(
-                "Failed to execute update_cumulate_dividends because {:?}",
-                why
-            ));
-        }
     }
 
     if let Some(tx) = tx_option {
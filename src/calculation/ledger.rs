@@ -0,0 +1,527 @@
+use anyhow::{anyhow, Result};
+use chrono::{Local, NaiveDate};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use rust_decimal_macros::dec;
+
+use crate::{
+    calculation::adjusted_price::DividendEvent,
+    database::{
+        self,
+        table::{
+            dividend::Dividend,
+            stock_ownership_details::StockOwnershipDetail,
+            stock_transaction::{StockTransaction, TransactionKind},
+        },
+    },
+    logging,
+};
+
+/// 股票面額，用來將每股股票股利(元)換算成配股數
+const PAR_VALUE: Decimal = dec!(10);
+
+/// 一筆買進交易所形成的持股
+#[derive(Debug, Clone, PartialEq)]
+struct Lot {
+    transaction_serial: i64,
+    date: NaiveDate,
+    quantity: i64,
+    /// 持有成本(含手續費)，正數
+    cost: Decimal,
+    dividends_cash: Decimal,
+    /// 股票股利(股)
+    dividends_stock: Decimal,
+    dividends_stock_money: Decimal,
+    is_sold: bool,
+    /// 由舊持股轉入的持股，此日(含)之前的分割與除權息已反映在股數與累積股利內
+    applied_until: Option<NaiveDate>,
+}
+
+impl Lot {
+    /// 是否參與 date 生效的分割與除權息
+    fn accrues(&self, date: NaiveDate) -> bool {
+        !self.is_sold && self.applied_until.is_none_or(|until| date > until)
+    }
+
+    /// 部分賣出後依剩餘股數等比例調整成本與累積股利
+    fn shrink(&mut self, remaining: i64) {
+        let ratio = Decimal::from(remaining) / Decimal::from(self.quantity);
+        self.cost *= ratio;
+        self.dividends_cash *= ratio;
+        self.dividends_stock *= ratio;
+        self.dividends_stock_money *= ratio;
+        self.quantity = remaining;
+    }
+}
+
+/// 由交易明細推算出的單一會員單一股票的持股與損益
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Position {
+    pub member_id: i64,
+    pub security_code: String,
+    lots: Vec<Lot>,
+    /// 平均成本法下的持有成本
+    average_cost: Decimal,
+    /// 先進先出法的已實現損益
+    pub realized_fifo: Decimal,
+    /// 平均成本法的已實現損益
+    pub realized_average: Decimal,
+    /// 累積領取的股利(元)，含已賣出的持股
+    pub dividend_income: Decimal,
+}
+
+impl Position {
+    /// 依交易日依序套用交易，同一天先處理除權息與分割再處理買賣，除權息日買進的股票不參與配息
+    pub fn from_transactions(
+        member_id: i64,
+        security_code: &str,
+        transactions: &[StockTransaction],
+    ) -> Result<Position> {
+        let mut sorted: Vec<&StockTransaction> = transactions.iter().collect();
+        sorted.sort_by_key(|t| (t.trade_date, priority(t.kind), t.serial));
+
+        let mut position = Position {
+            member_id,
+            security_code: security_code.to_string(),
+            ..Default::default()
+        };
+        for transaction in sorted {
+            position.apply(transaction)?;
+        }
+
+        Ok(position)
+    }
+
+    /// 目前持有的股數
+    pub fn quantity(&self) -> i64 {
+        self.holding().map(|lot| lot.quantity).sum()
+    }
+
+    /// 先進先出法下目前持有的成本
+    pub fn cost(&self) -> Decimal {
+        self.holding().map(|lot| lot.cost).sum()
+    }
+
+    /// 轉換成持股名細，每筆買進交易一筆，已全部賣出的標記為已賣出
+    pub fn ownership_details(&self) -> Vec<StockOwnershipDetail> {
+        self.lots
+            .iter()
+            .map(|lot| {
+                let quantity = Decimal::from(lot.quantity);
+                let mut sod = StockOwnershipDetail::new();
+                sod.transaction_serial = Some(lot.transaction_serial);
+                sod.member_id = self.member_id;
+                sod.security_code = self.security_code.to_string();
+                sod.date = lot.date;
+                sod.share_quantity = lot.quantity;
+                sod.holding_cost = -lot.cost.round_dp(4);
+                if lot.quantity > 0 {
+                    sod.share_price_average = (lot.cost / quantity).round_dp(4);
+                    sod.current_cost_per_share =
+                        ((lot.cost - lot.dividends_cash) / quantity).round_dp(4);
+                }
+                sod.is_sold = lot.is_sold;
+                sod.cumulate_dividends_cash = lot.dividends_cash.round_dp(4);
+                sod.cumulate_dividends_stock = lot.dividends_stock.round_dp(4);
+                sod.cumulate_dividends_stock_money = lot.dividends_stock_money.round_dp(4);
+                sod.cumulate_dividends_total =
+                    (lot.dividends_cash + lot.dividends_stock_money).round_dp(4);
+                sod
+            })
+            .collect()
+    }
+
+    fn holding(&self) -> impl Iterator<Item = &Lot> {
+        self.lots.iter().filter(|lot| !lot.is_sold)
+    }
+
+    fn apply(&mut self, t: &StockTransaction) -> Result<()> {
+        match t.kind {
+            TransactionKind::Buy => {
+                if t.quantity <= 0 {
+                    return Err(anyhow!("Invalid buy quantity in transaction {:?}", t));
+                }
+
+                let cost = Decimal::from(t.quantity) * t.price + t.fee + t.tax;
+                self.average_cost += cost;
+                self.dividend_income += t.opening_dividends_cash + t.opening_dividends_stock_money;
+                self.lots.push(Lot {
+                    transaction_serial: t.serial,
+                    date: t.trade_date,
+                    quantity: t.quantity,
+                    cost,
+                    dividends_cash: t.opening_dividends_cash,
+                    dividends_stock: t.opening_dividends_stock,
+                    dividends_stock_money: t.opening_dividends_stock_money,
+                    is_sold: false,
+                    applied_until: t.applied_until,
+                });
+            }
+            TransactionKind::Sell => self.sell(t)?,
+            TransactionKind::Split => {
+                if t.price <= Decimal::ZERO {
+                    return Err(anyhow!("Invalid split ratio in transaction {:?}", t));
+                }
+
+                // 換發後不足一股的部分捨去
+                for lot in self.lots.iter_mut().filter(|lot| lot.accrues(t.trade_date)) {
+                    lot.quantity = (Decimal::from(lot.quantity) * t.price)
+                        .floor()
+                        .to_i64()
+                        .unwrap_or_default();
+                }
+            }
+            TransactionKind::CashDividend => {
+                let mut income = Decimal::ZERO;
+                for lot in self.lots.iter_mut().filter(|lot| lot.accrues(t.trade_date)) {
+                    let cash = Decimal::from(lot.quantity) * t.price;
+                    lot.dividends_cash += cash;
+                    income += cash;
+                }
+                self.dividend_income += income;
+            }
+            TransactionKind::StockDividend => {
+                let held: i64 = self
+                    .lots
+                    .iter()
+                    .filter(|lot| lot.accrues(t.trade_date))
+                    .map(|lot| lot.quantity)
+                    .sum();
+                if held == 0 {
+                    return Ok(());
+                }
+
+                // 配股數以全部持股計算後捨去零股，再依各筆持股的股數分配，餘數給最後一筆
+                let shares = (Decimal::from(held) * t.price / PAR_VALUE)
+                    .floor()
+                    .to_i64()
+                    .unwrap_or_default();
+                let mut allocated = 0;
+                let last = self.lots.iter().rposition(|lot| lot.accrues(t.trade_date));
+                for (i, lot) in self.lots.iter_mut().enumerate() {
+                    if !lot.accrues(t.trade_date) {
+                        continue;
+                    }
+
+                    let lot_shares = if Some(i) == last {
+                        shares - allocated
+                    } else {
+                        lot.quantity * shares / held
+                    };
+                    allocated += lot_shares;
+                    let money = Decimal::from(lot.quantity) * t.price;
+                    lot.quantity += lot_shares;
+                    lot.dividends_stock += Decimal::from(lot_shares);
+                    lot.dividends_stock_money += money;
+                    self.dividend_income += money;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn sell(&mut self, t: &StockTransaction) -> Result<()> {
+        let held = self.quantity();
+        if t.quantity <= 0 || t.quantity > held {
+            return Err(anyhow!(
+                "Sell quantity({}) exceeds holding({}) in transaction {:?}",
+                t.quantity,
+                held,
+                t
+            ));
+        }
+
+        let proceeds = Decimal::from(t.quantity) * t.price - t.fee - t.tax;
+
+        let average_sold_cost = self.average_cost * Decimal::from(t.quantity) / Decimal::from(held);
+        self.average_cost -= average_sold_cost;
+        self.realized_average += proceeds - average_sold_cost;
+
+        let mut remaining = t.quantity;
+        let mut fifo_sold_cost = Decimal::ZERO;
+        for lot in self.lots.iter_mut().filter(|lot| !lot.is_sold) {
+            if remaining == 0 {
+                break;
+            }
+
+            if lot.quantity <= remaining {
+                remaining -= lot.quantity;
+                fifo_sold_cost += lot.cost;
+                lot.is_sold = true;
+            } else {
+                let before = lot.cost;
+                lot.shrink(lot.quantity - remaining);
+                fifo_sold_cost += before - lot.cost;
+                remaining = 0;
+            }
+        }
+        self.realized_fifo += proceeds - fifo_sold_cost;
+
+        Ok(())
+    }
+}
+
+/// 同一天交易的處理順序
+fn priority(kind: TransactionKind) -> u8 {
+    match kind {
+        TransactionKind::Split => 0,
+        TransactionKind::CashDividend | TransactionKind::StockDividend => 1,
+        TransactionKind::Buy => 2,
+        TransactionKind::Sell => 3,
+    }
+}
+
+/// 依股利發放記錄補上持股期間的除權息交易
+pub async fn record_dividends(member_id: i64, security_code: &str) -> Result<()> {
+    let transactions = StockTransaction::fetch(member_id, security_code).await?;
+    let first_date = match transactions.iter().map(|t| t.trade_date).min() {
+        Some(date) => date,
+        None => return Ok(()),
+    };
+
+    let today = Local::now().date_naive();
+    let dividends = Dividend::fetch_by_security_code(security_code).await?;
+    let mut tx = database::get_tx().await.ok();
+    for event in DividendEvent::from_dividends(&dividends)
+        .into_iter()
+        .filter(|event| event.date > first_date && event.date <= today)
+    {
+        for (kind, price) in [
+            (TransactionKind::CashDividend, event.cash_dividend),
+            (TransactionKind::StockDividend, event.stock_dividend),
+        ] {
+            if price <= Decimal::ZERO {
+                continue;
+            }
+
+            let mut transaction = StockTransaction::new(member_id, security_code, event.date, kind);
+            transaction.price = price;
+            if let Err(why) = transaction.upsert_dividend(&mut tx).await {
+                if let Some(tx) = tx {
+                    tx.rollback().await?;
+                }
+                return Err(why);
+            }
+        }
+    }
+
+    if let Some(tx) = tx {
+        tx.commit().await?;
+    }
+
+    Ok(())
+}
+
+/// 由交易明細重建指定會員與股票的持股名細，結果只與交易明細有關
+pub async fn rebuild(member_id: i64, security_code: &str) -> Result<Position> {
+    let transactions = StockTransaction::fetch(member_id, security_code).await?;
+    let position = Position::from_transactions(member_id, security_code, &transactions)?;
+    let mut details = position.ownership_details();
+    let transaction_serials: Vec<i64> = details
+        .iter()
+        .filter_map(|sod| sod.transaction_serial)
+        .collect();
+
+    let mut tx = database::get_tx().await.ok();
+    for sod in details.iter_mut() {
+        if let Err(why) = sod.upsert_by_transaction_serial(&mut tx).await {
+            if let Some(tx) = tx {
+                tx.rollback().await?;
+            }
+            return Err(why);
+        }
+    }
+
+    if let Err(why) = StockOwnershipDetail::delete_stale_transactions(
+        member_id,
+        security_code,
+        &transaction_serials,
+        &mut tx,
+    )
+    .await
+    {
+        if let Some(tx) = tx {
+            tx.rollback().await?;
+        }
+        return Err(why);
+    }
+
+    if let Some(tx) = tx {
+        tx.commit().await?;
+    }
+
+    logging::info_file_async(format!(
+        "rebuild {} of member({}): quantity {} cost {} realized(fifo) {} realized(average) {} dividend {}",
+        security_code,
+        member_id,
+        position.quantity(),
+        position.cost().round_dp(2),
+        position.realized_fifo.round_dp(2),
+        position.realized_average.round_dp(2),
+        position.dividend_income.round_dp(2)
+    ));
+
+    Ok(position)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logging;
+
+    use super::*;
+
+    fn transaction(
+        serial: i64,
+        date: &str,
+        kind: TransactionKind,
+        quantity: i64,
+        price: Decimal,
+    ) -> StockTransaction {
+        let mut t = StockTransaction::new(
+            1,
+            "2330",
+            NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap(),
+            kind,
+        );
+        t.serial = serial;
+        t.quantity = quantity;
+        t.price = price;
+        t
+    }
+
+    fn with_fee(mut t: StockTransaction, fee: Decimal) -> StockTransaction {
+        t.fee = fee;
+        t
+    }
+
+    #[test]
+    fn test_fifo_and_average_realized() {
+        let transactions = vec![
+            with_fee(
+                transaction(1, "2024-01-02", TransactionKind::Buy, 1000, dec!(100)),
+                dec!(100),
+            ),
+            with_fee(
+                transaction(2, "2024-02-01", TransactionKind::Buy, 1000, dec!(120)),
+                dec!(100),
+            ),
+            with_fee(
+                transaction(3, "2024-03-01", TransactionKind::Sell, 1500, dec!(130)),
+                dec!(200),
+            ),
+        ];
+
+        let position = Position::from_transactions(1, "2330", &transactions).unwrap();
+
+        assert_eq!(position.quantity(), 500);
+        // 先進先出：賣出第一筆全部(100100)與第二筆一半(60050)
+        assert_eq!(position.realized_fifo, dec!(194800) - dec!(160150));
+        assert_eq!(position.cost(), dec!(60050));
+        // 平均成本：220200 的四分之三
+        assert_eq!(position.realized_average, dec!(194800) - dec!(165150));
+
+        let details = position.ownership_details();
+        assert_eq!(details.len(), 2);
+        assert!(details[0].is_sold);
+        assert!(!details[1].is_sold);
+        assert_eq!(details[1].share_quantity, 500);
+        assert_eq!(details[1].holding_cost, dec!(-60050));
+        assert_eq!(details[1].transaction_serial, Some(2));
+    }
+
+    #[test]
+    fn test_dividends() {
+        let transactions = vec![
+            transaction(1, "2024-01-02", TransactionKind::Buy, 1000, dec!(50)),
+            transaction(2, "2024-01-03", TransactionKind::Buy, 500, dec!(60)),
+            transaction(3, "2024-07-01", TransactionKind::CashDividend, 0, dec!(2)),
+            transaction(4, "2024-07-01", TransactionKind::StockDividend, 0, dec!(1)),
+            // 除權息日買進不參與配息
+            transaction(5, "2024-07-01", TransactionKind::Buy, 100, dec!(55)),
+        ];
+
+        let position = Position::from_transactions(1, "2330", &transactions).unwrap();
+        let details = position.ownership_details();
+
+        // 1500 股配股 150 股，依股數分配
+        assert_eq!(position.quantity(), 1750);
+        assert_eq!(details[0].share_quantity, 1100);
+        assert_eq!(details[1].share_quantity, 550);
+        assert_eq!(details[2].share_quantity, 100);
+        assert_eq!(details[0].cumulate_dividends_cash, dec!(2000));
+        assert_eq!(details[0].cumulate_dividends_stock, dec!(100));
+        assert_eq!(details[0].cumulate_dividends_total, dec!(3000));
+        assert_eq!(details[2].cumulate_dividends_total, dec!(0));
+        // (50000 - 2000) / 1100
+        assert_eq!(details[0].current_cost_per_share, dec!(43.6364));
+        assert_eq!(position.dividend_income, dec!(4500));
+    }
+
+    #[test]
+    fn test_adopted_lot() {
+        let mut adopted = transaction(1, "2023-01-02", TransactionKind::Buy, 1100, dec!(50));
+        adopted.applied_until = NaiveDate::from_ymd_opt(2024, 7, 1);
+        adopted.opening_dividends_cash = dec!(2000);
+        adopted.opening_dividends_stock = dec!(100);
+        adopted.opening_dividends_stock_money = dec!(1000);
+        let transactions = vec![
+            adopted,
+            transaction(2, "2024-01-03", TransactionKind::Buy, 500, dec!(60)),
+            // 轉入前已反映在舊持股的股數與累積股利內
+            transaction(3, "2023-07-01", TransactionKind::StockDividend, 0, dec!(1)),
+            transaction(4, "2024-07-01", TransactionKind::CashDividend, 0, dec!(2)),
+            transaction(5, "2025-07-01", TransactionKind::CashDividend, 0, dec!(3)),
+        ];
+
+        let position = Position::from_transactions(1, "2330", &transactions).unwrap();
+        let details = position.ownership_details();
+
+        assert_eq!(position.quantity(), 1600);
+        assert_eq!(details[0].share_quantity, 1100);
+        assert_eq!(details[0].cumulate_dividends_cash, dec!(2000) + dec!(3300));
+        assert_eq!(details[0].cumulate_dividends_stock, dec!(100));
+        assert_eq!(details[1].cumulate_dividends_cash, dec!(1000) + dec!(1500));
+        // 2000 + 1000 + 1000 + 3300 + 1500
+        assert_eq!(position.dividend_income, dec!(8800));
+    }
+
+    #[test]
+    fn test_split_and_oversell() {
+        let transactions = vec![
+            transaction(1, "2024-01-02", TransactionKind::Buy, 1000, dec!(100)),
+            transaction(2, "2024-06-01", TransactionKind::Split, 0, dec!(0.25)),
+        ];
+        let position = Position::from_transactions(1, "2330", &transactions).unwrap();
+        assert_eq!(position.quantity(), 250);
+        assert_eq!(
+            position.ownership_details()[0].share_price_average,
+            dec!(400)
+        );
+
+        let mut transactions = transactions;
+        transactions.push(transaction(
+            3,
+            "2024-06-02",
+            TransactionKind::Sell,
+            251,
+            dec!(400),
+        ));
+        assert!(Position::from_transactions(1, "2330", &transactions).is_err());
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_rebuild() {
+        dotenv::dotenv().ok();
+        logging::debug_file_async("開始 ledger::rebuild".to_string());
+        match rebuild(1, "2330").await {
+            Ok(position) => {
+                logging::debug_file_async(format!("position: {:#?}", position));
+            }
+            Err(why) => {
+                logging::debug_file_async(format!("Failed to ledger::rebuild because {:?}", why));
+            }
+        }
+        logging::debug_file_async("結束 ledger::rebuild".to_string());
+    }
+}
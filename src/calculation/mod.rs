@@ -1,8 +1,14 @@
+/// 除權息還原股價
+pub mod adjusted_price;
 /// 股票每日行情
 pub mod daily_quotes;
 /// 計算股票股息收入
 pub mod dividend_record;
 /// 估算便宜、合理、昂貴價
 pub mod estimated_price;
+/// 依交易明細計算持股、已實現損益與成本
+pub mod ledger;
 /// 計算每日市值
 pub mod money_history;
+/// 目前庫存的市值與損益
+pub mod portfolio;
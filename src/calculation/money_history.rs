@@ -8,6 +8,7 @@ use crate::{
             daily_money_history::DailyMoneyHistory,
             daily_money_history_detail::DailyMoneyHistoryDetail,
             daily_money_history_detail_more::DailyMoneyHistoryDetailMore,
+            daily_money_history_member::DailyMoneyHistoryMember,
             daily_stock_price_stats::DailyStockPriceStats
         }
     }
@@ -24,6 +25,20 @@ pub async fn calculate_money_history(date: NaiveDate) -> Result<()> {
         return Err(anyhow!("{:?}", why));
     }
 
+    if let Err(why) = DailyMoneyHistoryMember::delete(date, &mut tx_option).await {
+        if let Some(tx) = tx_option {
+            tx.rollback().await?;
+        }
+        return Err(anyhow!("{:?}", why));
+    }
+
+    if let Err(why) = DailyMoneyHistoryMember::upsert(date, &mut tx_option).await {
+        if let Some(tx) = tx_option {
+            tx.rollback().await?;
+        }
+        return Err(anyhow!("{:?}", why));
+    }
+
     if let Err(why) = DailyMoneyHistoryDetail::delete(date, &mut tx_option).await {
         if let Some(tx) = tx_option {
             tx.rollback().await?;
@@ -0,0 +1,75 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::{cache::SHARE, database::table::stock_ownership_details::StockOwnershipDetail};
+
+/// 單一股票合計所有會員後的庫存
+#[derive(Debug, Clone, Serialize)]
+pub struct Holding {
+    pub security_code: String,
+    pub name: String,
+    pub share_quantity: i64,
+    /// 持有成本，負數(支出)
+    pub holding_cost: Decimal,
+    /// 最近的收盤價，沒有報價時為零
+    pub closing_price: Decimal,
+    pub market_value: Decimal,
+    pub profit: Decimal,
+}
+
+/// 目前的庫存與市值
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Portfolio {
+    pub holdings: Vec<Holding>,
+    pub total_cost: Decimal,
+    pub total_value: Decimal,
+    pub total_profit: Decimal,
+}
+
+/// 依股票代號合計所有會員的庫存，並以快取內最近的收盤價計算市值與損益
+pub async fn current() -> Result<Portfolio> {
+    let mut quantities: BTreeMap<String, (i64, Decimal)> = BTreeMap::new();
+    for detail in StockOwnershipDetail::fetch(None).await? {
+        let holding = quantities.entry(detail.security_code).or_default();
+        holding.0 += detail.share_quantity;
+        holding.1 += detail.holding_cost;
+    }
+
+    let mut portfolio = Portfolio {
+        holdings: Vec::with_capacity(quantities.len()),
+        ..Default::default()
+    };
+
+    for (security_code, (share_quantity, holding_cost)) in quantities {
+        let closing_price = SHARE
+            .get_stock_last_price(&security_code)
+            .await
+            .map_or(Decimal::ZERO, |quote| quote.closing_price);
+        let name = SHARE
+            .get_stock(&security_code)
+            .await
+            .map_or_else(String::new, |stock| stock.name);
+        let market_value = closing_price * Decimal::from(share_quantity);
+        // 成本為負數(支出)，市值加上成本即為損益
+        let profit = market_value + holding_cost;
+
+        portfolio.total_cost += holding_cost;
+        portfolio.total_value += market_value;
+        portfolio.holdings.push(Holding {
+            security_code,
+            name,
+            share_quantity,
+            holding_cost,
+            closing_price,
+            market_value,
+            profit,
+        });
+    }
+
+    portfolio.total_profit = portfolio.total_value + portfolio.total_cost;
+
+    Ok(portfolio)
+}
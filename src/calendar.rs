@@ -0,0 +1,433 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    str::FromStr,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Result};
+use chrono::{Datelike, Local, NaiveDate, TimeDelta, Weekday};
+use once_cell::sync::Lazy;
+
+use crate::{
+    crawler::twse::{self, holiday_schedule::HolidaySchedule},
+    database::table::trading_calendar::CalendarDate,
+    logging,
+};
+
+/// 往後找交易日的上限，避免休市日資料異常時無窮迴圈
+const MAX_SEARCH_DAYS: i64 = 60;
+
+/// 證交所尚未公告或查詢失敗的年度，在這段時間內以週一至週五為交易日而不再重新查詢
+const EMPTY_YEAR_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// 今年(含)以後的年度可能臨時增加休市日(例如颱風假)，每隔這段時間重新向證交所查詢
+const CURRENT_YEAR_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// 台股的交易日曆
+pub static CALENDAR: Lazy<TradingCalendar> = Lazy::new(Default::default);
+
+/// 依證交所公告的休市日與開始交易日判斷是否為交易日，
+/// 資料庫內沒有的年度向證交所查詢後存入資料庫，載入後保留在記憶體，
+/// 今年(含)以後的年度每天重新向證交所查詢一次
+#[derive(Default)]
+pub struct TradingCalendar {
+    years: RwLock<HashMap<i32, Year>>,
+}
+
+/// 記憶體內一個年度的資料，expires_at 為 None 表示永久保留
+struct Year {
+    dates: Arc<Vec<CalendarDate>>,
+    expires_at: Option<Instant>,
+}
+
+impl From<HolidaySchedule> for CalendarDate {
+    fn from(schedule: HolidaySchedule) -> Self {
+        CalendarDate::new(schedule.date, schedule.why, schedule.trading)
+    }
+}
+
+impl TradingCalendar {
+    /// 取得指定年度公告的休市日與開始交易日
+    async fn dates(&self, year: i32) -> Result<Arc<Vec<CalendarDate>>> {
+        let cached = self.years.read().ok().and_then(|years| {
+            years
+                .get(&year)
+                .map(|y| (Arc::clone(&y.dates), y.expires_at))
+        });
+        if let Some((dates, expires_at)) = &cached {
+            if expires_at.is_none_or(|at| at > Instant::now()) {
+                return Ok(Arc::clone(dates));
+            }
+        }
+
+        // 記憶體內的資料到期時直接向證交所查詢最新的公告，尚未載入的年度先從資料庫載入
+        let mut dates = match cached {
+            Some(_) => Vec::new(),
+            None => CalendarDate::fetch_by_year(year)
+                .await
+                .unwrap_or_else(|why| {
+                    logging::error_file_async(format!("{:?}", why));
+                    Vec::new()
+                }),
+        };
+
+        if dates.is_empty() {
+            dates = match twse::holiday_schedule::visit_all(year).await {
+                Ok(schedules) => schedules.into_iter().map(CalendarDate::from).collect(),
+                Err(why) => {
+                    logging::error_file_async(format!(
+                        "Failed to visit twse::holiday_schedule({}) because {:?}",
+                        year, why
+                    ));
+                    Vec::new()
+                }
+            };
+
+            if !dates.is_empty() {
+                if let Err(why) = CalendarDate::replace_year(year, &dates).await {
+                    logging::error_file_async(format!("{:?}", why));
+                }
+            }
+        }
+
+        // 重新查詢失敗時沿用記憶體內的資料
+        if dates.is_empty() {
+            if let Some((cached, _)) = cached {
+                dates = cached.to_vec();
+            }
+        }
+
+        let expires_at =
+            ttl(year, Local::now().year(), dates.is_empty()).map(|ttl| Instant::now() + ttl);
+        let dates = Arc::new(dates);
+        if let Ok(mut years) = self.years.write() {
+            years.insert(
+                year,
+                Year {
+                    dates: Arc::clone(&dates),
+                    expires_at,
+                },
+            );
+        }
+
+        Ok(dates)
+    }
+
+    /// 是否為交易日，未公告的日期以週一至週五為交易日
+    pub async fn is_trading_day(&self, date: NaiveDate) -> Result<bool> {
+        Ok(is_trading_day(&self.dates(date.year()).await?, date))
+    }
+
+    /// 取得指定年度的休市日
+    pub async fn holidays(&self, year: i32) -> Result<Vec<CalendarDate>> {
+        Ok(self
+            .dates(year)
+            .await?
+            .iter()
+            .filter(|date| !date.trading)
+            .cloned()
+            .collect())
+    }
+
+    /// 取得 date 當天(含)之後最近的交易日
+    pub async fn trading_day_on_or_after(&self, date: NaiveDate) -> Result<NaiveDate> {
+        let mut current = date;
+        for _ in 0..MAX_SEARCH_DAYS {
+            if self.is_trading_day(current).await? {
+                return Ok(current);
+            }
+            current += TimeDelta::days(1);
+        }
+
+        bail!("Failed to find the trading day on or after {}", date)
+    }
+
+    /// 取得 date 之後(不含)的第 n 個交易日，n 為零時為 date 當天(含)之後最近的交易日
+    pub async fn trading_day_after(&self, date: NaiveDate, n: u32) -> Result<NaiveDate> {
+        let mut current = self.trading_day_on_or_after(date).await?;
+        let mut remaining = n;
+        if current > date {
+            remaining = remaining.saturating_sub(1);
+        }
+        for _ in 0..remaining {
+            current = self
+                .trading_day_on_or_after(current + TimeDelta::days(1))
+                .await?;
+        }
+
+        Ok(current)
+    }
+}
+
+/// 記憶體內年度資料的保留時間，None 表示永久保留
+fn ttl(year: i32, current_year: i32, empty: bool) -> Option<Duration> {
+    if empty {
+        Some(EMPTY_YEAR_TTL)
+    } else if year >= current_year {
+        Some(CURRENT_YEAR_TTL)
+    } else {
+        None
+    }
+}
+
+fn is_trading_day(dates: &[CalendarDate], date: NaiveDate) -> bool {
+    match dates.iter().find(|d| d.date == date) {
+        Some(d) => d.trading,
+        None => is_weekday(date),
+    }
+}
+
+fn is_weekday(date: NaiveDate) -> bool {
+    !matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+/// 排程工作依交易日決定是否執行的規則
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TradingDayRule {
+    /// 不考慮交易日，每次排程觸發都執行
+    #[default]
+    Always,
+    /// 只在交易日執行
+    TradingDay,
+    /// 排程日不是交易日時順延到下一個交易日執行
+    NextTradingDay,
+    /// 在排程日之後(不含)的第 n 個交易日執行，例如資料在 T+n 日才公布的工作
+    TradingDaysAfter(u32),
+}
+
+impl TradingDayRule {
+    /// 依排程日取得實際執行的日期，不需執行時回傳 None
+    pub async fn run_date(&self, date: NaiveDate) -> Result<Option<NaiveDate>> {
+        match self {
+            TradingDayRule::Always => Ok(Some(date)),
+            TradingDayRule::TradingDay => Ok(CALENDAR.is_trading_day(date).await?.then_some(date)),
+            TradingDayRule::NextTradingDay => {
+                CALENDAR.trading_day_on_or_after(date).await.map(Some)
+            }
+            TradingDayRule::TradingDaysAfter(n) => {
+                CALENDAR.trading_day_after(date, *n).await.map(Some)
+            }
+        }
+    }
+
+    /// 無法取得交易日曆時改以週一至週五為交易日取得實際執行的日期
+    pub fn run_date_by_weekday(&self, date: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            TradingDayRule::Always => Some(date),
+            TradingDayRule::TradingDay => is_weekday(date).then_some(date),
+            TradingDayRule::NextTradingDay => date.iter_days().find(|date| is_weekday(*date)),
+            TradingDayRule::TradingDaysAfter(0) => {
+                TradingDayRule::NextTradingDay.run_date_by_weekday(date)
+            }
+            TradingDayRule::TradingDaysAfter(n) => date
+                .iter_days()
+                .skip(1)
+                .filter(|date| is_weekday(*date))
+                .nth(*n as usize - 1),
+        }
+    }
+}
+
+impl fmt::Display for TradingDayRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TradingDayRule::Always => write!(f, "always"),
+            TradingDayRule::TradingDay => write!(f, "trading_day"),
+            TradingDayRule::NextTradingDay => write!(f, "next_trading_day"),
+            TradingDayRule::TradingDaysAfter(n) => write!(f, "trading_days_after:{}", n),
+        }
+    }
+}
+
+/// 格式與 Display 相同，例如 trading_day、trading_days_after:2
+impl FromStr for TradingDayRule {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if let Some(n) = s.strip_prefix("trading_days_after:") {
+            return match n.parse::<u32>() {
+                Ok(n) => Ok(TradingDayRule::TradingDaysAfter(n)),
+                Err(_) => bail!("Invalid number of trading days in '{}'", s),
+            };
+        }
+
+        match s {
+            "always" => Ok(TradingDayRule::Always),
+            "trading_day" => Ok(TradingDayRule::TradingDay),
+            "next_trading_day" => Ok(TradingDayRule::NextTradingDay),
+            _ => bail!("Unknown trading day rule '{}'", s),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    /// 2024 年農曆春節前後的休市日，02-15 為春節後開始交易日
+    fn calendar() -> TradingCalendar {
+        let dates = vec![
+            CalendarDate::new(date(2024, 2, 8), "農曆除夕前一日".to_string(), false),
+            CalendarDate::new(date(2024, 2, 9), "農曆除夕".to_string(), false),
+            CalendarDate::new(date(2024, 2, 12), "農曆春節".to_string(), false),
+            CalendarDate::new(date(2024, 2, 13), "農曆春節".to_string(), false),
+            CalendarDate::new(date(2024, 2, 14), "農曆春節".to_string(), false),
+            CalendarDate::new(date(2024, 2, 15), "農曆春節後開始交易".to_string(), true),
+            CalendarDate::new(date(2024, 3, 2), "補行交易日".to_string(), true),
+        ];
+        let calendar = TradingCalendar::default();
+        calendar.years.write().unwrap().insert(
+            2024,
+            Year {
+                dates: Arc::new(dates),
+                expires_at: None,
+            },
+        );
+        calendar
+    }
+
+    #[tokio::test]
+    async fn test_is_trading_day() {
+        let calendar = calendar();
+        assert!(calendar.is_trading_day(date(2024, 2, 7)).await.unwrap());
+        assert!(!calendar.is_trading_day(date(2024, 2, 8)).await.unwrap());
+        assert!(!calendar.is_trading_day(date(2024, 2, 10)).await.unwrap());
+        assert!(calendar.is_trading_day(date(2024, 2, 15)).await.unwrap());
+        assert!(calendar.is_trading_day(date(2024, 3, 2)).await.unwrap());
+        assert!(!calendar.is_trading_day(date(2024, 3, 3)).await.unwrap());
+        assert_eq!(calendar.holidays(2024).await.unwrap().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_trading_day_on_or_after() {
+        let calendar = calendar();
+        assert_eq!(
+            calendar
+                .trading_day_on_or_after(date(2024, 2, 7))
+                .await
+                .unwrap(),
+            date(2024, 2, 7)
+        );
+        assert_eq!(
+            calendar
+                .trading_day_on_or_after(date(2024, 2, 8))
+                .await
+                .unwrap(),
+            date(2024, 2, 15)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_trading_day_after() {
+        let calendar = calendar();
+        // 02-07 之後第一個交易日為春節後的 02-15
+        assert_eq!(
+            calendar
+                .trading_day_after(date(2024, 2, 7), 1)
+                .await
+                .unwrap(),
+            date(2024, 2, 15)
+        );
+        assert_eq!(
+            calendar
+                .trading_day_after(date(2024, 2, 7), 2)
+                .await
+                .unwrap(),
+            date(2024, 2, 16)
+        );
+        // 排程日不是交易日時，順延後的交易日算第一個
+        assert_eq!(
+            calendar
+                .trading_day_after(date(2024, 2, 10), 1)
+                .await
+                .unwrap(),
+            date(2024, 2, 15)
+        );
+        assert_eq!(
+            calendar
+                .trading_day_after(date(2024, 2, 10), 0)
+                .await
+                .unwrap(),
+            date(2024, 2, 15)
+        );
+        assert_eq!(
+            calendar
+                .trading_day_after(date(2024, 2, 7), 0)
+                .await
+                .unwrap(),
+            date(2024, 2, 7)
+        );
+    }
+
+    #[test]
+    fn test_ttl() {
+        assert_eq!(ttl(2024, 2024, true), Some(EMPTY_YEAR_TTL));
+        assert_eq!(ttl(2024, 2024, false), Some(CURRENT_YEAR_TTL));
+        assert_eq!(ttl(2025, 2024, false), Some(CURRENT_YEAR_TTL));
+        assert_eq!(ttl(2023, 2024, false), None);
+    }
+
+    #[tokio::test]
+    async fn test_empty_year_is_cached() {
+        let calendar = calendar();
+        calendar.years.write().unwrap().insert(
+            2023,
+            Year {
+                dates: Arc::new(Vec::new()),
+                expires_at: Some(Instant::now() + EMPTY_YEAR_TTL),
+            },
+        );
+        // 未過期的空年度不會再查詢，以週一至週五為交易日
+        assert!(calendar.is_trading_day(date(2023, 1, 2)).await.unwrap());
+        assert!(!calendar.is_trading_day(date(2023, 1, 1)).await.unwrap());
+    }
+
+    #[test]
+    fn test_run_date_by_weekday() {
+        let saturday = date(2024, 3, 2);
+        assert_eq!(
+            TradingDayRule::Always.run_date_by_weekday(saturday),
+            Some(saturday)
+        );
+        assert_eq!(
+            TradingDayRule::TradingDay.run_date_by_weekday(saturday),
+            None
+        );
+        assert_eq!(
+            TradingDayRule::NextTradingDay.run_date_by_weekday(saturday),
+            Some(date(2024, 3, 4))
+        );
+        assert_eq!(
+            TradingDayRule::TradingDaysAfter(0).run_date_by_weekday(saturday),
+            Some(date(2024, 3, 4))
+        );
+        assert_eq!(
+            TradingDayRule::TradingDaysAfter(2).run_date_by_weekday(saturday),
+            Some(date(2024, 3, 5))
+        );
+        assert_eq!(
+            TradingDayRule::TradingDaysAfter(1).run_date_by_weekday(date(2024, 3, 1)),
+            Some(date(2024, 3, 4))
+        );
+    }
+
+    #[test]
+    fn test_trading_day_rule_from_str() {
+        for rule in [
+            TradingDayRule::Always,
+            TradingDayRule::TradingDay,
+            TradingDayRule::NextTradingDay,
+            TradingDayRule::TradingDaysAfter(2),
+        ] {
+            assert_eq!(rule.to_string().parse::<TradingDayRule>().unwrap(), rule);
+        }
+        assert!("weekday".parse::<TradingDayRule>().is_err());
+        assert!("trading_days_after:-1".parse::<TradingDayRule>().is_err());
+    }
+}
@@ -0,0 +1,319 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{Local, NaiveDate};
+use rust_decimal::Decimal;
+
+use crate::{
+    backtest::{
+        self,
+        strategy::{EstimateBand, MovingAverageCross, Strategy},
+        Setting,
+    },
+    database::table::stock_transaction::StockTransaction,
+    declare::MovingAverage,
+    importer,
+    util::datetime::EARLIEST_DATE,
+};
+
+pub const USAGE: &str = "usage:
+  stock_crawler import --member <id> [--broker <statement|confirmation>] [--dry-run] <file>
+  stock_crawler adopt
+  stock_crawler backtest [--start <YYYY-MM-DD>] [--end <YYYY-MM-DD>] [--strategy <estimate|ma:5,20>] [--capital <amount>] <symbol>";
+
+/// 回測使用的策略
+#[derive(Debug, PartialEq)]
+pub enum BacktestStrategy {
+    /// 便宜價買進、昂貴價賣出
+    EstimateBand,
+    /// 短期與長期均線交叉
+    MovingAverageCross(MovingAverage, MovingAverage),
+}
+
+impl BacktestStrategy {
+    /// 解析 estimate 或 ma:<短期天數>,<長期天數>，例如 ma:5,20
+    fn parse(value: &str) -> Result<Self> {
+        if value == "estimate" {
+            return Ok(BacktestStrategy::EstimateBand);
+        }
+
+        let days = value
+            .strip_prefix("ma:")
+            .and_then(|days| days.split_once(','))
+            .and_then(|(short, long)| {
+                let short = MovingAverage::from_days(short.parse().ok()?)?;
+                let long = MovingAverage::from_days(long.parse().ok()?)?;
+                Some((short, long))
+            });
+        match days {
+            Some((short, long)) if short.days() < long.days() => {
+                Ok(BacktestStrategy::MovingAverageCross(short, long))
+            }
+            _ => Err(anyhow!("Invalid strategy '{}'\n{}", value, USAGE)),
+        }
+    }
+
+    fn build(&self) -> Box<dyn Strategy> {
+        match self {
+            BacktestStrategy::EstimateBand => Box::new(EstimateBand),
+            BacktestStrategy::MovingAverageCross(short, long) => {
+                Box::new(MovingAverageCross::new(*short, *long))
+            }
+        }
+    }
+}
+
+/// 命令列的子命令
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    /// 匯入券商對帳單
+    Import {
+        member_id: i64,
+        broker: Option<String>,
+        dry_run: bool,
+        path: String,
+    },
+    /// 將舊的持股名細轉為買進交易，導入交易明細時執行一次
+    Adopt,
+    /// 以歷史行情回測策略並輸出績效報告
+    Backtest {
+        security_code: String,
+        start_date: Option<NaiveDate>,
+        end_date: Option<NaiveDate>,
+        strategy: BacktestStrategy,
+        capital: Option<Decimal>,
+    },
+}
+
+impl Command {
+    /// 解析程式名稱之後的參數，沒有參數時回傳 None 表示以服務模式執行
+    pub fn parse(args: &[String]) -> Result<Option<Command>> {
+        let (name, rest) = match args.split_first() {
+            Some(split) => split,
+            None => return Ok(None),
+        };
+
+        match name.as_str() {
+            "import" => {
+                let mut member_id = None;
+                let mut broker = None;
+                let mut dry_run = false;
+                let mut path = None;
+                let mut iter = rest.iter();
+                while let Some(arg) = iter.next() {
+                    match arg.as_str() {
+                        "--member" => {
+                            let value = iter.next().ok_or_else(|| anyhow!(USAGE))?;
+                            member_id = Some(
+                                value
+                                    .parse::<i64>()
+                                    .context(format!("Invalid member id '{}'", value))?,
+                            );
+                        }
+                        "--broker" => {
+                            broker = Some(iter.next().ok_or_else(|| anyhow!(USAGE))?.to_string())
+                        }
+                        "--dry-run" => dry_run = true,
+                        _ if path.is_none() && !arg.starts_with("--") => {
+                            path = Some(arg.to_string())
+                        }
+                        _ => return Err(anyhow!("Unexpected argument '{}'\n{}", arg, USAGE)),
+                    }
+                }
+
+                match (member_id, path) {
+                    (Some(member_id), Some(path)) => Ok(Some(Command::Import {
+                        member_id,
+                        broker,
+                        dry_run,
+                        path,
+                    })),
+                    _ => Err(anyhow!(USAGE)),
+                }
+            }
+            "adopt" => match rest.first() {
+                None => Ok(Some(Command::Adopt)),
+                Some(arg) => Err(anyhow!("Unexpected argument '{}'\n{}", arg, USAGE)),
+            },
+            "backtest" => {
+                let mut security_code = None;
+                let mut start_date = None;
+                let mut end_date = None;
+                let mut strategy = BacktestStrategy::EstimateBand;
+                let mut capital = None;
+                let mut iter = rest.iter();
+                while let Some(arg) = iter.next() {
+                    match arg.as_str() {
+                        "--start" => {
+                            start_date =
+                                Some(parse_date(iter.next().ok_or_else(|| anyhow!(USAGE))?)?)
+                        }
+                        "--end" => {
+                            end_date = Some(parse_date(iter.next().ok_or_else(|| anyhow!(USAGE))?)?)
+                        }
+                        "--strategy" => {
+                            strategy =
+                                BacktestStrategy::parse(iter.next().ok_or_else(|| anyhow!(USAGE))?)?
+                        }
+                        "--capital" => {
+                            let value = iter.next().ok_or_else(|| anyhow!(USAGE))?;
+                            capital = Some(
+                                value
+                                    .parse::<Decimal>()
+                                    .ok()
+                                    .filter(|capital| *capital > Decimal::ZERO)
+                                    .ok_or_else(|| anyhow!("Invalid capital '{}'", value))?,
+                            );
+                        }
+                        _ if security_code.is_none() && !arg.starts_with("--") => {
+                            security_code = Some(arg.to_string())
+                        }
+                        _ => return Err(anyhow!("Unexpected argument '{}'\n{}", arg, USAGE)),
+                    }
+                }
+
+                match security_code {
+                    Some(security_code) => Ok(Some(Command::Backtest {
+                        security_code,
+                        start_date,
+                        end_date,
+                        strategy,
+                        capital,
+                    })),
+                    None => Err(anyhow!(USAGE)),
+                }
+            }
+            _ => Err(anyhow!("Unknown command '{}'\n{}", name, USAGE)),
+        }
+    }
+
+    pub async fn execute(self) -> Result<()> {
+        match self {
+            Command::Import {
+                member_id,
+                broker,
+                dry_run,
+                path,
+            } => {
+                let data = tokio::fs::read(&path)
+                    .await
+                    .context(format!("Failed to read {}", path))?;
+                let report =
+                    importer::execute(member_id, &data, broker.as_deref(), dry_run).await?;
+                print!("{}", report);
+                Ok(())
+            }
+            Command::Adopt => {
+                let result = StockTransaction::adopt_ownership_details(&mut None).await?;
+                println!("adopted {} lots", result.rows_affected());
+                Ok(())
+            }
+            Command::Backtest {
+                security_code,
+                start_date,
+                end_date,
+                strategy,
+                capital,
+            } => {
+                let mut setting = Setting::new(&security_code);
+                if let Some(capital) = capital {
+                    setting.initial_capital = capital;
+                }
+                let report = backtest::execute(
+                    &security_code,
+                    start_date.unwrap_or(EARLIEST_DATE),
+                    end_date.unwrap_or_else(|| Local::now().date_naive()),
+                    strategy.build().as_mut(),
+                    &setting,
+                )
+                .await?;
+
+                println!("{}", report);
+                for trade in &report.trades {
+                    println!(
+                        "{} {} {}股 @{} 損益:{}",
+                        trade.date,
+                        trade.side,
+                        trade.shares,
+                        trade.price,
+                        trade.profit.round_dp(0)
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").context(format!("Invalid date '{}'", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Command::parse(&[]).unwrap(), None);
+        assert_eq!(
+            Command::parse(&args("import --member 2 --dry-run trades.csv")).unwrap(),
+            Some(Command::Import {
+                member_id: 2,
+                broker: None,
+                dry_run: true,
+                path: "trades.csv".to_string(),
+            })
+        );
+        assert_eq!(
+            Command::parse(&args("import trades.csv --broker statement --member 1")).unwrap(),
+            Some(Command::Import {
+                member_id: 1,
+                broker: Some("statement".to_string()),
+                dry_run: false,
+                path: "trades.csv".to_string(),
+            })
+        );
+        assert!(Command::parse(&args("import trades.csv")).is_err());
+        assert!(Command::parse(&args("import --member x trades.csv")).is_err());
+        assert_eq!(
+            Command::parse(&args("adopt")).unwrap(),
+            Some(Command::Adopt)
+        );
+        assert!(Command::parse(&args("adopt now")).is_err());
+        assert_eq!(
+            Command::parse(&args("backtest 2330")).unwrap(),
+            Some(Command::Backtest {
+                security_code: "2330".to_string(),
+                start_date: None,
+                end_date: None,
+                strategy: BacktestStrategy::EstimateBand,
+                capital: None,
+            })
+        );
+        assert_eq!(
+            Command::parse(&args(
+                "backtest --start 2020-01-02 --strategy ma:20,60 --capital 500000 0050"
+            ))
+            .unwrap(),
+            Some(Command::Backtest {
+                security_code: "0050".to_string(),
+                start_date: NaiveDate::from_ymd_opt(2020, 1, 2),
+                end_date: None,
+                strategy: BacktestStrategy::MovingAverageCross(
+                    MovingAverage::Ma20,
+                    MovingAverage::Ma60
+                ),
+                capital: Some(Decimal::from(500000)),
+            })
+        );
+        assert!(Command::parse(&args("backtest")).is_err());
+        assert!(Command::parse(&args("backtest --start 2020/01/02 2330")).is_err());
+        assert!(Command::parse(&args("backtest --strategy ma:20,5 2330")).is_err());
+        assert!(Command::parse(&args("backtest --strategy ma:7,20 2330")).is_err());
+        assert!(Command::parse(&args("backtest --capital -1 2330")).is_err());
+        assert!(Command::parse(&args("export")).is_err());
+    }
+}
@@ -4,6 +4,7 @@ use anyhow::Result;
 use config::{Config as config_config, File as config_file, FileFormat};
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use strum_macros::EnumString;
 
 use crate::logging;
 
@@ -19,17 +20,283 @@ pub struct App {
     pub rpc: Rpc,
     pub nosql: NoSQL,
     pub system: System,
+    #[serde(default)]
+    pub intraday: Intraday,
+    #[serde(default)]
+    pub notify: Notify,
+    #[serde(default)]
+    pub logging: Logging,
+    #[serde(default)]
+    pub scheduler: Scheduler,
 }
 
 const SYSTEM_GRPC_USE_PORT: &str = "SYSTEM_GRPC_USE_PORT";
 const SYSTEM_SSL_CERT_FILE: &str = "SYSTEM_SSL_CERT_FILE";
 const SYSTEM_SSL_KEY_FILE: &str = "SYSTEM_SSL_KEY_FILE";
+const SYSTEM_GRPC_AUTH: &str = "SYSTEM_GRPC_AUTH";
+const SYSTEM_HTTP_USE_PORT: &str = "SYSTEM_HTTP_USE_PORT";
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone)]
 pub struct System {
     pub grpc_use_port: i32,
     pub ssl_cert_file: String,
     pub ssl_key_file: String,
+    /// gRPC 用戶端的驗證，未設定任何 api key 與 client CA 時只允許 Read 權限的方法
+    #[serde(default)]
+    pub grpc_auth: GrpcAuth,
+    /// HTTP/JSON API 與 /metrics 的埠號，0 時不啟動，驗證沿用 grpc_auth 的 api key，
+    /// 只監聽 127.0.0.1 且不加密，對外需經由反向代理提供 TLS，
+    /// grpc_auth 只設定 mTLS 而沒有 api key 時不啟動
+    #[serde(default)]
+    pub http_use_port: i32,
+}
+
+/// gRPC 用戶端的驗證方式與權限
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+pub struct GrpcAuth {
+    /// 以 metadata 的 x-api-key 或 authorization: Bearer 帶入的靜態金鑰
+    #[serde(default)]
+    pub api_keys: Vec<GrpcApiKey>,
+    /// 驗證用戶端憑證的 CA 檔案，設定後啟用 mTLS
+    #[serde(default)]
+    pub client_ca_file: String,
+    /// 通過 mTLS 驗證的用戶端擁有的權限
+    #[serde(default)]
+    pub client_scopes: Vec<GrpcScope>,
+}
+
+impl GrpcAuth {
+    /// 是否需要驗證用戶端
+    pub fn is_enabled(&self) -> bool {
+        !self.api_keys.is_empty() || !self.client_ca_file.is_empty()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GrpcApiKey {
+    /// 用於日誌辨識金鑰的名稱
+    pub name: String,
+    pub key: String,
+    pub scopes: Vec<GrpcScope>,
+}
+
+/// gRPC 方法的權限範圍
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GrpcScope {
+    /// 查詢報價與市場數據
+    Read,
+    /// 更新股票資料、匯入對帳單
+    Write,
+    /// 排程與快取等維運操作
+    Control,
+}
+
+const INTRADAY_WATCHLIST: &str = "INTRADAY_WATCHLIST";
+const INTRADAY_SAMPLE_SECONDS: &str = "INTRADAY_SAMPLE_SECONDS";
+
+/// 盤中報價記錄
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct Intraday {
+    /// 要記錄盤中一分鐘K線的股票代號
+    #[serde(default)]
+    pub watchlist: Vec<String>,
+    /// 每隔幾秒取樣一次報價，零表示使用預設值
+    #[serde(default)]
+    pub sample_seconds: u64,
+}
+
+const LOGGING: &str = "LOGGING";
+
+/// 日誌的輸出格式與各模組的等級
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct Logging {
+    #[serde(default)]
+    pub format: LogFormat,
+    /// 未在 modules 內設定的模組，低於此等級的日誌不輸出
+    #[serde(default)]
+    pub level: LogLevel,
+    /// key 為模組路徑，例如 crawler::goodinfo，包含其下所有的子模組，以最長的路徑為準
+    #[serde(default)]
+    pub modules: HashMap<String, LogLevel>,
+    /// 同時輸出到 stdout，給容器部署使用
+    #[serde(default)]
+    pub stdout: bool,
+    /// 保留幾天內的日誌，零表示使用預設值 7 天
+    #[serde(default)]
+    pub max_age_days: u32,
+    /// 同一種日誌所有檔案合計的上限(MB)，超過時從最舊的檔案開始刪除，零表示不限制
+    #[serde(default)]
+    pub max_total_size_mb: u64,
+    /// 同一種日誌最多保留的檔案數，零表示不限制
+    #[serde(default)]
+    pub max_files: usize,
+    /// 單一檔案超過此大小(MB)時換到下一個檔案，零表示只依日期換檔
+    #[serde(default)]
+    pub max_file_size_mb: u64,
+    /// 以 gzip 壓縮已關閉的檔案
+    #[serde(default)]
+    pub compress: bool,
+}
+
+/// 日誌的輸出格式
+#[derive(Serialize, Deserialize, Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    /// 時間加上訊息
+    #[default]
+    Text,
+    /// 每行一個 JSON 物件
+    Json,
+}
+
+/// 日誌等級，由低到高
+#[derive(
+    Serialize, Deserialize, Default, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum LogLevel {
+    #[default]
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+const SCHEDULER: &str = "SCHEDULER";
+
+/// 排程工作的設定
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct Scheduler {
+    /// 未設定時區的工作使用的時區，例如 Asia/Taipei，空白表示 Asia/Taipei
+    #[serde(default)]
+    pub timezone: String,
+    /// key 為工作名稱，未設定的工作使用程式內的預設值，不存在的工作名稱會在啟動時報錯
+    #[serde(default)]
+    pub jobs: HashMap<String, JobSchedule>,
+    /// 多個實例同時運作時，以 Redis 的租約讓每個排程工作只在一個實例執行
+    #[serde(default)]
+    pub lease: SchedulerLease,
+}
+
+/// 排程工作在多個實例間的租約
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct SchedulerLease {
+    /// 只有單一實例時不需啟用
+    #[serde(default)]
+    pub enabled: bool,
+    /// 租約的有效秒數，持有的實例停止續約超過此時間後由其他實例接手，零表示使用預設值 30 秒
+    #[serde(default)]
+    pub ttl_seconds: u64,
+}
+
+/// 單一排程工作的設定
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JobSchedule {
+    /// cron 表示式(秒 分 時 日 月 星期)，以 timezone 的時間計算，空白表示使用程式內的預設值
+    #[serde(default)]
+    pub cron: String,
+    /// 空白表示使用 scheduler.timezone
+    #[serde(default)]
+    pub timezone: String,
+    /// false 時不由排程觸發也不補執行，仍可手動執行
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 同時執行的上限，零表示使用預設值 1
+    #[serde(default)]
+    pub concurrency: usize,
+    /// 依交易日決定是否執行的規則，always、trading_day、next_trading_day 或
+    /// trading_days_after:N(排程日之後第 N 個交易日)，空白表示 always
+    #[serde(default)]
+    pub trading_day: String,
+}
+
+impl Default for JobSchedule {
+    fn default() -> Self {
+        JobSchedule {
+            cron: String::new(),
+            timezone: String::new(),
+            enabled: true,
+            concurrency: 0,
+            trading_day: String::new(),
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+const NOTIFY: &str = "NOTIFY";
+
+/// 通知管道與各事件的路由
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct Notify {
+    /// 通知管道，key 為管道名稱，未設定任何管道時通知 bot.telegram.allowed
+    #[serde(default)]
+    pub channels: HashMap<String, NotifyChannel>,
+    /// key 為事件名稱，value 為要通知的管道名稱，未設定的事件使用 default 的路由，
+    /// 連 default 都未設定時通知所有管道
+    #[serde(default)]
+    pub routes: HashMap<String, Vec<String>>,
+}
+
+/// 通知管道的設定
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotifyChannel {
+    /// Telegram，chat_ids 未設定時通知 bot.telegram.allowed
+    Telegram {
+        #[serde(default)]
+        chat_ids: Vec<i64>,
+    },
+    /// Discord webhook
+    Discord { url: String },
+    /// Slack incoming webhook
+    Slack { url: String },
+    /// LINE Notify 或相容的 HTTP 服務，url 未設定時使用 LINE Notify 的網址
+    LineNotify {
+        #[serde(default)]
+        url: String,
+        token: String,
+    },
+    /// SMTP 電子郵件
+    Email(Smtp),
+    /// 通用的 JSON webhook，POST {"event":"...","message":"..."}
+    Webhook {
+        url: String,
+        #[serde(default)]
+        headers: HashMap<String, String>,
+    },
+}
+
+/// SMTP 連線的加密方式
+#[derive(Serialize, Deserialize, Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+    /// 不加密，僅適用於本機不需驗證的郵件伺服器，設定了帳號時拒絕寄送
+    None,
+    /// 以明文連線後使用 STARTTLS 升級(通常為 587 port)
+    #[default]
+    StartTls,
+    /// 直接以 TLS 連線(通常為 465 port)
+    Tls,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+pub struct Smtp {
+    pub host: String,
+    pub port: u16,
+    #[serde(default)]
+    pub security: SmtpSecurity,
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub password: String,
+    /// 寄件者
+    pub from: String,
+    /// 收件者
+    pub to: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone)]
@@ -113,6 +380,12 @@ pub struct Bot {
 
 const TELEGRAM_TOKEN: &str = "TELEGRAM_TOKEN";
 const TELEGRAM_ALLOWED: &str = "TELEGRAM_ALLOWED";
+const TELEGRAM_API_URL: &str = "TELEGRAM_API_URL";
+const TELEGRAM_RECEIVE_MODE: &str = "TELEGRAM_RECEIVE_MODE";
+const TELEGRAM_WEBHOOK_URL: &str = "TELEGRAM_WEBHOOK_URL";
+const TELEGRAM_WEBHOOK_LISTEN: &str = "TELEGRAM_WEBHOOK_LISTEN";
+const TELEGRAM_WEBHOOK_SECRET: &str = "TELEGRAM_WEBHOOK_SECRET";
+const TELEGRAM_PARSE_MODE: &str = "TELEGRAM_PARSE_MODE";
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone)]
 pub struct Telegram {
@@ -120,6 +393,48 @@ pub struct Telegram {
     pub allowed: HashMap<i64, String>,
     #[serde(default)]
     pub token: String,
+    /// Bot API 的網址，空白時使用 https://api.telegram.org
+    #[serde(default)]
+    pub api_url: String,
+    /// 接收指令的方式
+    #[serde(default)]
+    pub receive_mode: TelegramReceiveMode,
+    /// 設定給 Telegram 的 webhook 公開網址
+    #[serde(default)]
+    pub webhook_url: String,
+    /// webhook 本機監聽的位址，例如 0.0.0.0:8443
+    #[serde(default)]
+    pub webhook_listen: String,
+    /// 驗證 webhook 請求的密鑰(X-Telegram-Bot-Api-Secret-Token)，webhook 模式必填
+    #[serde(default)]
+    pub webhook_secret: String,
+    /// 發送訊息使用的格式
+    #[serde(default)]
+    pub parse_mode: TelegramParseMode,
+}
+
+/// Telegram 訊息的格式
+#[derive(Serialize, Deserialize, EnumString, Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum TelegramParseMode {
+    #[default]
+    Html,
+    MarkdownV2,
+}
+
+/// Telegram 接收指令的方式
+#[derive(Serialize, Deserialize, EnumString, Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum TelegramReceiveMode {
+    /// 不接收指令
+    #[default]
+    Disabled,
+    /// 以 getUpdates 長輪詢
+    Polling,
+    /// 由 Telegram 呼叫 webhook
+    Webhook,
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone)]
@@ -248,6 +563,18 @@ impl App {
                 telegram: Telegram {
                     allowed: allowed_list,
                     token: env::var(TELEGRAM_TOKEN).expect(TELEGRAM_TOKEN),
+                    api_url: env::var(TELEGRAM_API_URL).unwrap_or_default(),
+                    receive_mode: env::var(TELEGRAM_RECEIVE_MODE)
+                        .ok()
+                        .and_then(|mode| TelegramReceiveMode::from_str(&mode).ok())
+                        .unwrap_or_default(),
+                    webhook_url: env::var(TELEGRAM_WEBHOOK_URL).unwrap_or_default(),
+                    webhook_listen: env::var(TELEGRAM_WEBHOOK_LISTEN).unwrap_or_default(),
+                    webhook_secret: env::var(TELEGRAM_WEBHOOK_SECRET).unwrap_or_default(),
+                    parse_mode: env::var(TELEGRAM_PARSE_MODE)
+                        .ok()
+                        .and_then(|mode| TelegramParseMode::from_str(&mode).ok())
+                        .unwrap_or_default(),
                 },
             },
 
@@ -276,6 +603,14 @@ impl App {
                     .unwrap_or(0),
                 ssl_cert_file: env::var(SYSTEM_SSL_CERT_FILE).expect(SYSTEM_SSL_CERT_FILE),
                 ssl_key_file: env::var(SYSTEM_SSL_KEY_FILE).expect(SYSTEM_SSL_KEY_FILE),
+                grpc_auth: env::var(SYSTEM_GRPC_AUTH)
+                    .ok()
+                    .and_then(|auth| serde_json::from_str::<GrpcAuth>(&auth).ok())
+                    .unwrap_or_default(),
+                http_use_port: env::var(SYSTEM_HTTP_USE_PORT)
+                    .unwrap_or_else(|_| "0".to_string())
+                    .parse::<i32>()
+                    .unwrap_or(0),
             },
             dyny: Dynu {
                 username: env::var(DYNU_USERNAME).expect(DYNU_USERNAME),
@@ -286,6 +621,28 @@ impl App {
                 password: env::var(NOIP_USERNAME).expect(NOIP_USERNAME),
                 hostnames: noip_hostnames_list,
             },
+            intraday: Intraday {
+                watchlist: env::var(INTRADAY_WATCHLIST)
+                    .ok()
+                    .and_then(|watchlist| serde_json::from_str::<Vec<String>>(&watchlist).ok())
+                    .unwrap_or_default(),
+                sample_seconds: env::var(INTRADAY_SAMPLE_SECONDS)
+                    .ok()
+                    .and_then(|seconds| seconds.parse::<u64>().ok())
+                    .unwrap_or(0),
+            },
+            notify: env::var(NOTIFY)
+                .ok()
+                .and_then(|notify| serde_json::from_str::<Notify>(&notify).ok())
+                .unwrap_or_default(),
+            logging: env::var(LOGGING)
+                .ok()
+                .and_then(|log_config| serde_json::from_str::<Logging>(&log_config).ok())
+                .unwrap_or_default(),
+            scheduler: env::var(SCHEDULER)
+                .ok()
+                .and_then(|scheduler| serde_json::from_str::<Scheduler>(&scheduler).ok())
+                .unwrap_or_default(),
         }
     }
 
@@ -325,6 +682,66 @@ impl App {
             }
         }
 
+        if let Ok(watchlist) = env::var(INTRADAY_WATCHLIST) {
+            match serde_json::from_str::<Vec<String>>(&watchlist) {
+                Ok(result) => {
+                    self.intraday.watchlist = result;
+                }
+                Err(why) => {
+                    logging::error_file_async(format!(
+                        "Failed to serde_json because: {:?} \r\n {}",
+                        why, &watchlist
+                    ));
+                }
+            }
+        }
+
+        if let Ok(seconds) = env::var(INTRADAY_SAMPLE_SECONDS) {
+            self.intraday.sample_seconds = seconds.parse::<u64>().unwrap_or(0);
+        }
+
+        if let Ok(log_config) = env::var(LOGGING) {
+            match serde_json::from_str::<Logging>(&log_config) {
+                Ok(result) => {
+                    self.logging = result;
+                }
+                Err(why) => {
+                    logging::error_file_async(format!(
+                        "Failed to serde_json because: {:?} \r\n {}",
+                        why, &log_config
+                    ));
+                }
+            }
+        }
+
+        if let Ok(scheduler) = env::var(SCHEDULER) {
+            match serde_json::from_str::<Scheduler>(&scheduler) {
+                Ok(result) => {
+                    self.scheduler = result;
+                }
+                Err(why) => {
+                    logging::error_file_async(format!(
+                        "Failed to serde_json because: {:?} \r\n {}",
+                        why, &scheduler
+                    ));
+                }
+            }
+        }
+
+        if let Ok(notify) = env::var(NOTIFY) {
+            match serde_json::from_str::<Notify>(&notify) {
+                Ok(result) => {
+                    self.notify = result;
+                }
+                Err(why) => {
+                    logging::error_file_async(format!(
+                        "Failed to serde_json because: {:?} \r\n {}",
+                        why, &notify
+                    ));
+                }
+            }
+        }
+
         if let Ok(cert_file) = env::var(SYSTEM_SSL_CERT_FILE) {
             self.system.ssl_cert_file = cert_file;
         }
@@ -332,6 +749,26 @@ impl App {
             self.system.ssl_key_file = key_file;
         }
 
+        if let Ok(auth) = env::var(SYSTEM_GRPC_AUTH) {
+            match serde_json::from_str::<GrpcAuth>(&auth) {
+                Ok(result) => {
+                    self.system.grpc_auth = result;
+                }
+                Err(why) => {
+                    logging::error_file_async(format!(
+                        "Failed to serde_json because: {:?} \r\n {}",
+                        why, &auth
+                    ));
+                }
+            }
+        }
+
+        if let Ok(port) = env::var(SYSTEM_HTTP_USE_PORT) {
+            if let Ok(port) = port.parse::<i32>() {
+                self.system.http_use_port = port;
+            }
+        }
+
         if let Ok(target) = env::var(GO_GRPC_TARGET) {
             self.rpc.go_service.target = target;
         }
@@ -386,6 +823,46 @@ impl App {
             self.bot.telegram.token = token
         }
 
+        if let Ok(api_url) = env::var(TELEGRAM_API_URL) {
+            self.bot.telegram.api_url = api_url
+        }
+
+        if let Ok(mode) = env::var(TELEGRAM_RECEIVE_MODE) {
+            match TelegramReceiveMode::from_str(&mode) {
+                Ok(mode) => self.bot.telegram.receive_mode = mode,
+                Err(why) => {
+                    logging::error_file_async(format!(
+                        "Failed to parse {} because: {:?} \r\n {}",
+                        TELEGRAM_RECEIVE_MODE, why, &mode
+                    ));
+                }
+            }
+        }
+
+        if let Ok(url) = env::var(TELEGRAM_WEBHOOK_URL) {
+            self.bot.telegram.webhook_url = url
+        }
+
+        if let Ok(listen) = env::var(TELEGRAM_WEBHOOK_LISTEN) {
+            self.bot.telegram.webhook_listen = listen
+        }
+
+        if let Ok(secret) = env::var(TELEGRAM_WEBHOOK_SECRET) {
+            self.bot.telegram.webhook_secret = secret
+        }
+
+        if let Ok(mode) = env::var(TELEGRAM_PARSE_MODE) {
+            match TelegramParseMode::from_str(&mode) {
+                Ok(mode) => self.bot.telegram.parse_mode = mode,
+                Err(why) => {
+                    logging::error_file_async(format!(
+                        "Failed to parse {} because: {:?} \r\n {}",
+                        TELEGRAM_PARSE_MODE, why, &mode
+                    ));
+                }
+            }
+        }
+
         if let Ok(addr) = env::var(REDIS_ADDR) {
             self.nosql.redis.addr = addr
         }
@@ -464,4 +941,23 @@ mod tests {
         }
         tokio::time::sleep(time::Duration::from_secs(1)).await;
     }
+
+    #[test]
+    fn test_notify_from_config_file() {
+        let app: App = config::Config::builder()
+            .add_source(config::File::from(config_path()).format(FileFormat::Json))
+            .build()
+            .and_then(|cfg| cfg.try_deserialize())
+            .unwrap();
+
+        assert_eq!(
+            app.notify.channels["telegram"],
+            NotifyChannel::Telegram { chat_ids: vec![] }
+        );
+        assert_eq!(app.notify.routes["default"], vec!["telegram"]);
+        assert_eq!(app.bot.telegram.receive_mode, TelegramReceiveMode::Disabled);
+        assert!(!app.system.grpc_auth.is_enabled());
+        assert_eq!(app.logging.format, LogFormat::Text);
+        assert_eq!(app.logging.level, LogLevel::Debug);
+    }
 }
@@ -76,6 +76,7 @@ impl StockInfo for CMoney {
             price,
             change,
             change_range,
+            volume: None,
         })
     }
 }
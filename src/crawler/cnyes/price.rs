@@ -20,6 +20,9 @@ struct QuotesResponse {
     pub change: f64,
     #[serde(rename = "56")]
     pub change_range: f64,
+    /// 當日累計成交量(張)
+    #[serde(rename = "800001", default)]
+    pub volume: Option<i64>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -64,6 +67,7 @@ impl StockInfo for CnYes {
             price: r.current_price,
             change: r.change,
             change_range: r.change_range,
+            volume: r.volume.map(|lots| lots * 1000),
         })
     }
 }
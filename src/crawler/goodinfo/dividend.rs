@@ -1,9 +1,9 @@
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use hashbrown::HashMap;
 use regex::Regex;
 use reqwest::header::{HeaderMap, COOKIE};
 use rust_decimal::Decimal;
-use scraper::{Html, Selector};
+use scraper::Html;
 use serde::{Deserialize, Serialize};
 use urlencoding::encode;
 
@@ -145,19 +145,18 @@ pub async fn visit(stock_symbol: &str) -> Result<HashMap<i32, Vec<GoodInfoDivide
     let text = http::post(&url, Some(headers), None).await?;
 
     if text.contains("您的瀏覽量異常") {
-        return Err(anyhow!("{} 瀏覽量異常", url));
+        return Err(element::parse_failure(&url, format!("{} 瀏覽量異常", url)));
     }
 
     if text.contains("初始化中") {
-        return Err(anyhow!("{} 初始化中", url));
+        return Err(element::parse_failure(&url, format!("{} 初始化中", url)));
     }
 
     let document = Html::parse_document(text.as_str());
-    let selector = Selector::parse("#tblDetail > tbody > tr")
-        .map_err(|why| anyhow!("Failed to Selector::parse because: {:?}", why))?;
     let mut last_year: i32 = 0;
-    let result: Result<Vec<GoodInfoDividend>, _> = document
-        .select(&selector)
+    let rows = element::select(&document, &url, "#tblDetail > tbody > tr")?;
+    let result: Result<Vec<GoodInfoDividend>, _> = rows
+        .into_iter()
         .filter_map(|element| {
             let tds: Vec<&str> = element.text().collect();
 
@@ -0,0 +1,276 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Local};
+use once_cell::sync::Lazy;
+use rand::Rng;
+
+use crate::{logging, util::metrics};
+
+/// 成功率與耗時的移動平均中最新一次所佔的比重
+const SMOOTHING: f64 = 0.2;
+
+/// 尚未成功過的站點預估的耗時
+const DEFAULT_LATENCY: Duration = Duration::from_secs(1);
+
+/// 連續失敗幾次後暫停使用站點
+const FAILURE_THRESHOLD: u32 = 3;
+
+/// 第一次暫停使用的時間，之後每次試用失敗加倍
+const BREAK_DURATION: Duration = Duration::from_secs(60);
+
+/// 暫停使用的時間上限
+const MAX_BREAK_DURATION: Duration = Duration::from_secs(30 * 60);
+
+/// 每個站點保留最近幾次的錯誤
+const RECENT_ERRORS: usize = 5;
+
+/// 所有報價站點的健康狀態
+pub static SITES: Lazy<SiteHealth> = Lazy::new(Default::default);
+
+/// 單一站點的健康狀態
+#[derive(Debug)]
+struct Health {
+    /// 成功率的移動平均，介於 0 到 1
+    success_rate: f64,
+    /// 請求耗時的移動平均，失敗的請求也計入
+    latency: Duration,
+    /// 連續失敗的次數
+    consecutive_failures: u32,
+    /// 連續暫停使用的次數，用來計算下次暫停的時間
+    trips: u32,
+    /// 暫停使用到此時間為止，時間到後只讓一個請求試用
+    open_until: Option<Instant>,
+    /// 最近的錯誤，由舊到新
+    recent_errors: VecDeque<(DateTime<Local>, String)>,
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Health {
+            success_rate: 1.0,
+            latency: DEFAULT_LATENCY,
+            consecutive_failures: 0,
+            trips: 0,
+            open_until: None,
+            recent_errors: VecDeque::new(),
+        }
+    }
+}
+
+impl Health {
+    /// 選擇站點的權重，成功率越高、耗時越短的站點越容易被選到
+    fn weight(&self) -> f64 {
+        self.success_rate.max(0.05).powi(2) / self.latency.as_secs_f64().max(0.05)
+    }
+}
+
+/// 記錄每個報價站點的成功率、耗時與最近的錯誤，連續失敗的站點會暫停使用一段時間，
+/// 時間到後讓一個請求試用，成功就恢復使用，失敗就加倍暫停的時間
+#[derive(Default)]
+pub struct SiteHealth {
+    sites: Mutex<HashMap<&'static str, Health>>,
+}
+
+impl SiteHealth {
+    /// 依健康狀態決定嘗試站點的順序，回傳 names 的索引，
+    /// 暫停的時間已到的站點排在最前面試用，暫停使用中的站點不會被選到，
+    /// 全部都暫停時依恢復時間的先後全部嘗試
+    pub fn order(&self, names: &[&'static str]) -> Vec<usize> {
+        let mut rng = rand::rng();
+        self.order_with(names, Instant::now(), || rng.random::<f64>())
+    }
+
+    fn order_with(
+        &self,
+        names: &[&'static str],
+        now: Instant,
+        mut random: impl FnMut() -> f64,
+    ) -> Vec<usize> {
+        let mut sites = match self.sites.lock() {
+            Ok(sites) => sites,
+            Err(_) => return (0..names.len()).collect(),
+        };
+
+        // 依權重抽樣不放回，每個站點以 u^(1/w) 排序
+        let mut probes: Vec<usize> = Vec::new();
+        let mut available: Vec<(f64, usize)> = Vec::new();
+        for (index, name) in names.iter().enumerate() {
+            let health = sites.entry(name).or_default();
+            match health.open_until {
+                Some(until) if until > now => continue,
+                // 暫停的時間已到，排在最前面確保試用會送出，
+                // 並延後恢復時間讓其他請求在試用結束前不會選到
+                Some(_) => {
+                    health.open_until = Some(now + break_duration(health.trips));
+                    probes.push(index);
+                    continue;
+                }
+                None => {}
+            }
+            let key = random().max(f64::MIN_POSITIVE).powf(1.0 / health.weight());
+            available.push((key, index));
+        }
+
+        if probes.is_empty() && available.is_empty() {
+            let mut all: Vec<usize> = (0..names.len()).collect();
+            all.sort_by_key(|index| sites.get(names[*index]).and_then(|h| h.open_until));
+            return all;
+        }
+
+        available.sort_by(|a, b| b.0.total_cmp(&a.0));
+        probes
+            .into_iter()
+            .chain(available.into_iter().map(|(_, index)| index))
+            .collect()
+    }
+
+    /// 記錄一次請求的結果
+    pub fn record(&self, name: &'static str, elapsed: Duration, error: Option<&anyhow::Error>) {
+        let mut sites = match self.sites.lock() {
+            Ok(sites) => sites,
+            Err(_) => return,
+        };
+        let health = sites.entry(name).or_default();
+        health.latency = health.latency.mul_f64(1.0 - SMOOTHING) + elapsed.mul_f64(SMOOTHING);
+
+        match error {
+            None => {
+                if health.open_until.is_some() {
+                    logging::info_file_async(format!("站點 {} 已恢復，重新使用", name));
+                }
+                health.success_rate += (1.0 - health.success_rate) * SMOOTHING;
+                health.consecutive_failures = 0;
+                health.trips = 0;
+                health.open_until = None;
+            }
+            Some(why) => {
+                health.success_rate -= health.success_rate * SMOOTHING;
+                health.consecutive_failures += 1;
+                if health.recent_errors.len() >= RECENT_ERRORS {
+                    health.recent_errors.pop_front();
+                }
+                health
+                    .recent_errors
+                    .push_back((Local::now(), why.to_string()));
+
+                // 試用失敗或連續失敗達到門檻時暫停使用
+                if health.open_until.is_some() || health.consecutive_failures >= FAILURE_THRESHOLD {
+                    let duration = break_duration(health.trips);
+                    health.trips += 1;
+                    health.open_until = Some(Instant::now() + duration);
+                    logging::warn_file_async(format!(
+                        "站點 {} 連續失敗 {} 次，暫停使用 {} 秒，最近的錯誤: {:?}",
+                        name,
+                        health.consecutive_failures,
+                        duration.as_secs(),
+                        health.recent_errors.back().map(|(_, why)| why)
+                    ));
+                }
+            }
+        }
+
+        metrics::record_site_health(name, health.success_rate, health.open_until.is_some());
+    }
+
+    #[cfg(test)]
+    pub(super) fn consecutive_failures(&self, name: &str) -> u32 {
+        self.sites
+            .lock()
+            .unwrap()
+            .get(name)
+            .map_or(0, |health| health.consecutive_failures)
+    }
+}
+
+/// 第 trips + 1 次暫停使用的時間
+fn break_duration(trips: u32) -> Duration {
+    BREAK_DURATION
+        .saturating_mul(2u32.saturating_pow(trips))
+        .min(MAX_BREAK_DURATION)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+
+    use super::*;
+
+    const NAMES: &[&str] = &["fast", "slow", "broken"];
+
+    fn fail(sites: &SiteHealth, name: &'static str) {
+        sites.record(name, Duration::from_secs(3), Some(&anyhow!("timeout")));
+    }
+
+    #[test]
+    fn test_weighted_order() {
+        let sites = SiteHealth::default();
+        sites.record("fast", Duration::from_millis(100), None);
+        sites.record("slow", Duration::from_secs(5), None);
+        fail(&sites, "broken");
+
+        // 相同的亂數下權重高的站點排在前面
+        assert_eq!(
+            sites.order_with(NAMES, Instant::now(), || 0.5),
+            vec![0, 1, 2]
+        );
+        assert_eq!(sites.order(NAMES).len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker() {
+        let sites = SiteHealth::default();
+        for _ in 0..FAILURE_THRESHOLD {
+            fail(&sites, "broken");
+        }
+        let now = Instant::now();
+        assert_eq!(sites.order_with(NAMES, now, || 0.5), vec![0, 1]);
+
+        // 暫停的時間到後只讓一個請求試用，試用的站點排在最前面，試用失敗時暫停的時間加倍
+        let later = now + BREAK_DURATION + Duration::from_secs(1);
+        assert_eq!(sites.order_with(NAMES, later, || 0.5), vec![2, 0, 1]);
+        assert_eq!(sites.order_with(NAMES, later, || 0.5), vec![0, 1]);
+        fail(&sites, "broken");
+        assert_eq!(sites.sites.lock().unwrap()["broken"].trips, 2);
+
+        // 試用成功後恢復使用
+        sites.record("broken", Duration::from_millis(100), None);
+        assert_eq!(sites.order_with(NAMES, later, || 0.5).len(), 3);
+    }
+
+    #[test]
+    fn test_failure_latency() {
+        let sites = SiteHealth::default();
+        sites.record("slow", Duration::from_millis(100), None);
+        let latency = sites.sites.lock().unwrap()["slow"].latency;
+
+        // 失敗請求的耗時也計入平均耗時
+        fail(&sites, "slow");
+        assert!(sites.sites.lock().unwrap()["slow"].latency > latency);
+    }
+
+    #[test]
+    fn test_all_open() {
+        let sites = SiteHealth::default();
+        let now = Instant::now();
+        for (name, minutes) in [("slow", 1), ("fast", 2), ("broken", 3)] {
+            fail(&sites, name);
+            let mut all = sites.sites.lock().unwrap();
+            all.get_mut(name).unwrap().open_until = Some(now + Duration::from_secs(minutes * 60));
+        }
+
+        // 全部暫停使用時依恢復時間的先後嘗試
+        assert_eq!(sites.order_with(NAMES, now, || 0.5), vec![1, 0, 2]);
+    }
+
+    #[test]
+    fn test_break_duration() {
+        assert_eq!(break_duration(0), BREAK_DURATION);
+        assert_eq!(break_duration(1), BREAK_DURATION * 2);
+        assert_eq!(break_duration(10), MAX_BREAK_DURATION);
+        assert_eq!(break_duration(u32::MAX), MAX_BREAK_DURATION);
+    }
+}
@@ -78,6 +78,7 @@ impl StockInfo for HiStock {
             price,
             change,
             change_range,
+            volume: None,
         })
     }
 }
@@ -1,9 +1,9 @@
 use std::collections::HashMap;
 
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use async_trait::async_trait;
 use rust_decimal::Decimal;
-use scraper::{Html, Selector};
+use scraper::Html;
 
 use crate::{
     util::{
@@ -33,17 +33,17 @@ impl StockInfo for PcHome {
         params.insert("is_check", "1");
         let text = util::http::post(&url, None, Some(params)).await?;
         let document = Html::parse_document(&text);
-        let selector = Selector::parse(SELECTOR)
-            .map_err(|why| anyhow!("Failed to Selector::parse because: {:?}", why))?;
-
-        if let Some(element) = document.select(&selector).next() {
-            let price = element::parse_to_decimal(&element, "span.data_close");
+        if let Some(element) = element::select(&document, &url, SELECTOR)?.first() {
+            let price = element::parse_to_decimal(element, "span.data_close");
             if price > Decimal::ZERO {
                 return Ok(price);
             }
         }
 
-        Err(anyhow!("Price element not found from pchome"))
+        Err(element::parse_failure(
+            &url,
+            "Price element not found from pchome".to_string(),
+        ))
     }
 
     async fn get_stock_quotes(stock_symbol: &str) -> Result<declare::StockQuotes> {
@@ -91,6 +91,7 @@ impl StockInfo for PcHome {
             price,
             change,
             change_range,
+            volume: None,
         })
     }
 }
@@ -1,17 +1,18 @@
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
 
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
+use futures::future::BoxFuture;
 use rust_decimal::Decimal;
 
 use crate::{
     crawler::{
-        cmoney::CMoney, megatime::PcHome, nstock::NStock,
+        cmoney::CMoney, cnyes::CnYes, histock::HiStock, megatime::PcHome, nstock::NStock,
         yahoo::Yahoo,
     },
-    declare,
+    declare, logging,
+    util::http,
 };
-use crate::crawler::cnyes::CnYes;
 
 pub mod afraid;
 /// 臺灣銀行
@@ -26,6 +27,8 @@ pub mod dynu;
 pub mod fbs;
 /// 股市資訊網
 pub mod goodinfo;
+/// 報價站點的健康狀態與選擇
+pub mod health;
 /// 嗨投資
 pub mod histock;
 pub mod ipify;
@@ -62,88 +65,142 @@ pub trait StockInfo {
     async fn get_stock_quotes(stock_symbol: &str) -> Result<declare::StockQuotes>;
 }
 
-/// 標記採集站點的遊標，每採集一次遊標就會+1，分別對應6個站點，每個站點都輪過一次時就會歸零從頭開始
-static INDEX: AtomicUsize = AtomicUsize::new(0);
-
-fn get_and_increment_index(max: usize) -> usize {
-   /* let old_val = INDEX.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |val| {
-        if val >= max - 1 {
-            Some(0)
-        } else {
-            Some(val + 1)
-        }
-    });
-
-    old_val.unwrap_or(0)*/
-    INDEX.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |val| {
-        Some((val + 1) % max)
-    }).unwrap_or(0)
-}
+/// 報價站點的查詢函式
+type Fetch<'a, T> = fn(&'a str) -> BoxFuture<'a, Result<T>>;
 
 /// 取得股票的目前的報價
 pub async fn fetch_stock_price_from_remote_site(stock_symbol: &str) -> Result<Decimal> {
-    let sites = [
-        Yahoo::get_stock_price,
-        NStock::get_stock_price,
-        PcHome::get_stock_price,
-        CMoney::get_stock_price,
-        //HiStock::get_stock_price,
-        CnYes::get_stock_price,
+    let sites: [(&'static str, Fetch<'_, Decimal>); 6] = [
+        ("yahoo", Yahoo::get_stock_price),
+        ("nstock", NStock::get_stock_price),
+        ("pchome", PcHome::get_stock_price),
+        ("cmoney", CMoney::get_stock_price),
+        ("histock", HiStock::get_stock_price),
+        ("cnyes", CnYes::get_stock_price),
     ];
-    let site_len = sites.len();
-
-    for _ in 0..site_len {
-        //let current = get_and_increment_index(site_len);
-        // let index = current % site_len;
-        let current_site = get_and_increment_index(site_len);
-        //println!("current:{} current_site:{}", current, current_site);
-        if let Ok(price) = sites[current_site](stock_symbol).await {
-            return Ok(price.normalize())
-        };
-    }
 
-    Err(anyhow!(
-        "Failed to fetch stock price({}) from all sites",
-        stock_symbol
-    ))
+    match fetch_from_sites(stock_symbol, &sites).await {
+        Some(price) => Ok(price.normalize()),
+        None => Err(anyhow!(
+            "Failed to fetch stock price({}) from all sites",
+            stock_symbol
+        )),
+    }
 }
 
 /// 取得股票目前的報價含漲跌、漲幅
 pub async fn fetch_stock_quotes_from_remote_site(
     stock_symbol: &str,
 ) -> Result<declare::StockQuotes> {
-    let sites = [
-        Yahoo::get_stock_quotes,
-        NStock::get_stock_quotes,
-        PcHome::get_stock_quotes,
-        CMoney::get_stock_quotes,
-        //HiStock::get_stock_quotes,
-        CnYes::get_stock_quotes,
+    let sites: [(&'static str, Fetch<'_, declare::StockQuotes>); 6] = [
+        ("yahoo", Yahoo::get_stock_quotes),
+        ("nstock", NStock::get_stock_quotes),
+        ("pchome", PcHome::get_stock_quotes),
+        ("cmoney", CMoney::get_stock_quotes),
+        ("histock", HiStock::get_stock_quotes),
+        ("cnyes", CnYes::get_stock_quotes),
     ];
-    let site_len = sites.len();
 
-    for _ in 0..site_len {
-        //let index = INDEX.fetch_add(1, Ordering::SeqCst) % site_len;
-        let current_site = get_and_increment_index(site_len);
-        let r = sites[current_site](stock_symbol).await;
+    fetch_from_sites(stock_symbol, &sites)
+        .await
+        .ok_or_else(|| {
+            anyhow!(
+                "Failed to fetch stock quotes({}) from all sites",
+                stock_symbol
+            )
+        })
+}
 
-        if r.is_ok() {
-            return r;
+/// 依站點的健康狀態決定嘗試的順序，回傳第一個成功的結果並記錄每個站點的結果
+async fn fetch_from_sites<'a, T>(
+    stock_symbol: &'a str,
+    sites: &[(&'static str, Fetch<'a, T>)],
+) -> Option<T> {
+    let names: Vec<&'static str> = sites.iter().map(|(name, _)| *name).collect();
+    let order = health::SITES.order(&names);
+
+    fetch_in_order(&health::SITES, stock_symbol, sites, order).await
+}
+
+/// 連線、狀態碼與回應格式的錯誤立即計入站點的健康狀態，
+/// 其他錯誤(例如查無資料)可能是股票代號本身的問題，等到有其他站點查到時才計入，
+/// 所有站點都查不到時不計入
+async fn fetch_in_order<'a, T>(
+    sites_health: &health::SiteHealth,
+    stock_symbol: &'a str,
+    sites: &[(&'static str, Fetch<'a, T>)],
+    order: Vec<usize>,
+) -> Option<T> {
+    let mut deferred = Vec::new();
+
+    for index in order {
+        let (name, fetch) = sites[index];
+        let start = Instant::now();
+        match logging::with_symbol(stock_symbol, fetch(stock_symbol)).await {
+            Ok(result) => {
+                for (name, elapsed, why) in deferred {
+                    sites_health.record(name, elapsed, Some(&why));
+                }
+                sites_health.record(name, start.elapsed(), None);
+                return Some(result);
+            }
+            Err(why) if http::is_site_error(&why) => {
+                sites_health.record(name, start.elapsed(), Some(&why))
+            }
+            Err(why) => deferred.push((name, start.elapsed(), why)),
         }
     }
 
-    Err(anyhow!(
-        "Failed to fetch stock quotes({}) from all sites",
-        stock_symbol
-    ))
+    None
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::logging;
+    use crate::util::http::SiteError;
 
     use super::*;
 
+    fn found(_: &str) -> BoxFuture<'_, Result<i32>> {
+        Box::pin(async { Ok(1) })
+    }
+
+    fn not_found(stock_symbol: &str) -> BoxFuture<'_, Result<i32>> {
+        Box::pin(async move { Err(anyhow!("{} data is empty", stock_symbol)) })
+    }
+
+    fn unavailable(_: &str) -> BoxFuture<'_, Result<i32>> {
+        Box::pin(async { Err(SiteError::Transport("connection refused".to_string()).into()) })
+    }
+
+    #[tokio::test]
+    async fn test_fetch_unknown_symbol() {
+        let sites_health = health::SiteHealth::default();
+        let sites: [(&'static str, Fetch<'_, i32>); 2] =
+            [("empty", not_found), ("down", unavailable)];
+
+        // 所有站點都查不到時只計入站點本身的錯誤
+        assert_eq!(
+            fetch_in_order(&sites_health, "0000", &sites, vec![0, 1]).await,
+            None
+        );
+        assert_eq!(sites_health.consecutive_failures("empty"), 0);
+        assert_eq!(sites_health.consecutive_failures("down"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_found_elsewhere() {
+        let sites_health = health::SiteHealth::default();
+        let sites: [(&'static str, Fetch<'_, i32>); 2] = [("empty", not_found), ("found", found)];
+
+        // 其他站點查得到時，查不到的站點計入失敗
+        assert_eq!(
+            fetch_in_order(&sites_health, "2330", &sites, vec![0, 1]).await,
+            Some(1)
+        );
+        assert_eq!(sites_health.consecutive_failures("empty"), 1);
+        assert_eq!(sites_health.consecutive_failures("found"), 0);
+    }
+
     #[tokio::test]
     async fn test_fetch_stock_price_from_remote_site() {
         dotenv::dotenv().ok();
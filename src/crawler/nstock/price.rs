@@ -73,6 +73,7 @@ impl StockInfo for NStock {
             price,
             change,
             change_range,
+            volume: None,
         })
     }
 }
@@ -1,10 +1,10 @@
 use std::{future::Future, pin::Pin};
 
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use async_trait::async_trait;
 use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
 use rust_decimal::Decimal;
-use scraper::{ElementRef, Html, Selector};
+use scraper::{ElementRef, Html};
 
 use crate::crawler::{bigdatacloud, myip};
 use crate::{
@@ -60,11 +60,11 @@ pub(super) async fn fetch_annual_profits(
 ) -> Result<Vec<AnnualProfit>> {
     let text = util::http::get(url, None).await?;
     let document = Html::parse_document(&text);
-    let selector = Selector::parse("#oMainTable > tbody > tr:nth-child(n+4)")
-        .map_err(|why| anyhow!("Failed to Selector::parse because: {:?}", why))?;
+    let rows =
+        util::http::element::select(&document, url, "#oMainTable > tbody > tr:nth-child(n+4)")?;
     let mut result: Vec<AnnualProfit> = Vec::with_capacity(24);
 
-    for node in document.select(&selector) {
+    for node in rows {
         if let Some(ap) = parse_annual_profit(node, stock_symbol) {
             result.push(ap);
         }
@@ -2,7 +2,11 @@ use anyhow::Result;
 use chrono::{Local, NaiveDate};
 use serde::{Deserialize, Serialize};
 
-use crate::{bot, crawler::twse, util};
+use crate::{
+    bot::{self, notifier::NotifyEvent},
+    crawler::twse,
+    util,
+};
 
 #[derive(Serialize, Deserialize)]
 struct HolidayScheduleResponse {
@@ -18,9 +22,21 @@ struct HolidayScheduleResponse {
 pub struct HolidaySchedule {
     pub date: NaiveDate,
     pub why: String,
+    /// 開始交易日或補行交易日為 true，休市日為 false
+    pub trading: bool,
 }
 
+/// 取得指定年度的休市日
 pub async fn visit(year: i32) -> Result<Vec<HolidaySchedule>> {
+    Ok(visit_all(year)
+        .await?
+        .into_iter()
+        .filter(|schedule| !schedule.trading)
+        .collect())
+}
+
+/// 取得指定年度的休市日與開始交易日
+pub async fn visit_all(year: i32) -> Result<Vec<HolidaySchedule>> {
     let now = Local::now();
     let url = format!(
         "https://www.{host}/rwd/zh/holidaySchedule/holidaySchedule?date={year}&response=json&_={time}",
@@ -43,15 +59,12 @@ pub async fn visit(year: i32) -> Result<Vec<HolidaySchedule>> {
         return Ok(result);
     }
 
-    for date_info in res
-        .data
-        .iter()
-        .filter(|d| d.len() >= 3 && !d[2].contains("開始交易"))
-    {
+    for date_info in res.data.iter().filter(|d| d.len() >= 3) {
         if let Ok(d) = NaiveDate::parse_from_str(&date_info[0], "%Y-%m-%d") {
             result.push(HolidaySchedule {
                 date: d,
-                why: date_info[1].to_string()
+                why: date_info[1].to_string(),
+                trading: date_info[2].contains("開始交易"),
             });
         }
     }
@@ -60,7 +73,7 @@ pub async fn visit(year: i32) -> Result<Vec<HolidaySchedule>> {
 }
 
 async fn report_error(message: &str) {
-    bot::telegram::send(message).await;
+    bot::notifier::send(NotifyEvent::Error, message).await;
 }
 
 #[cfg(test)]
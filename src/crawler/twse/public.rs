@@ -3,7 +3,12 @@ use chrono::{Datelike, Local, NaiveDate, TimeDelta};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
-use crate::{bot, crawler::twse, util, util::map::Keyable};
+use crate::{
+    bot::{self, notifier::NotifyEvent},
+    crawler::twse,
+    util,
+    util::map::Keyable,
+};
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
 struct PublicFormResponse {
@@ -73,7 +78,7 @@ pub async fn visit() -> Result<Vec<Public>> {
     let stat = match res.stat {
         None => {
             let to_bot_msg = "Public.res.Stat is None";
-            bot::telegram::send(to_bot_msg).await;
+            bot::notifier::send(NotifyEvent::Error, to_bot_msg).await;
             return Ok(result);
         }
         Some(stat) => stat.to_uppercase(),
@@ -81,7 +86,7 @@ pub async fn visit() -> Result<Vec<Public>> {
 
     if stat != "OK" {
         let to_bot_msg = "Public.res.Stat is not ok";
-        bot::telegram::send(to_bot_msg).await;
+        bot::notifier::send(NotifyEvent::Error, to_bot_msg).await;
         return Ok(result);
     }
 
@@ -2,7 +2,7 @@ use anyhow::{anyhow, Result};
 use regex::Regex;
 use reqwest::header::HeaderMap;
 use rust_decimal::Decimal;
-use scraper::{Html, Selector};
+use scraper::Html;
 use serde::{Deserialize, Serialize};
 
 use crate::{crawler::wespai::HOST, util::http, util::http::element};
@@ -68,21 +68,18 @@ pub async fn visit() -> Result<Vec<Profit>> {
 
     let text = http::get(&url, Some(headers)).await?;
     let document = Html::parse_document(text.as_str());
-    let selector = match Selector::parse("body > h1 > a") {
-        Ok(selector) => selector,
-        Err(why) => {
-            return Err(anyhow!("Failed to Selector::parse because: {:?}", why));
-        }
-    };
-    let year = match document.select(&selector).next() {
+    let year = match element::select(&document, &url, "body > h1 > a")?.first() {
         None => {
             return Err(anyhow!("Failed to select .next()"));
         }
-        Some(year) => year,
+        Some(year) => *year,
     };
     let year = match year.text().next() {
         None => {
-            return Err(anyhow!("Failed to parse year raw({:?})", year));
+            return Err(element::parse_failure(
+                &url,
+                format!("Failed to parse year raw({:?})", year),
+            ));
         }
         Some(year) => year,
     };
@@ -95,18 +92,15 @@ pub async fn visit() -> Result<Vec<Profit>> {
     }
 
     if profit_year == 0 {
-        return Err(anyhow!("profit_year is zero"));
+        return Err(element::parse_failure(
+            &url,
+            "profit_year is zero".to_string(),
+        ));
     }
 
-    let selector = match Selector::parse("#example > tbody > tr") {
-        Ok(selector) => selector,
-        Err(why) => {
-            return Err(anyhow!("Failed to Selector::parse because: {:?}", why));
-        }
-    };
     let mut profits = Vec::with_capacity(2048);
 
-    for element in document.select(&selector) {
+    for element in element::select(&document, &url, "#example > tbody > tr")? {
         //let tds: Vec<&str> = element.text().collect();
         //println!("tds:{:#?}",tds);
         let security_code = match element::parse_value(&element, "td:nth-child(1)") {
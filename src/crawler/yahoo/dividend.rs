@@ -1,8 +1,8 @@
 use std::collections::HashMap;
 
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use regex::Regex;
-use scraper::{Html, Selector};
+use scraper::Html;
 
 use crate::{crawler::yahoo::HOST, util::http};
 
@@ -83,19 +83,16 @@ pub async fn visit(stock_symbol: &str) -> Result<YahooDividend> {
     let text = http::get(&url, None).await?;
     let document = Html::parse_document(&text);
     //#main-2-QuoteDividend-Proxy > div > section.Mb\(\$m-module\).Mb\(\$mobile-m-module\)--mobile > div.Pos\(r\).Ov\(h\) > div.table-body.Pos\(r\).Bxz\(bb\).W\(100\%\).Ovx\(s\).Ovy\(h\) > div > div > ul > li:nth-child(1) > div
-    let selector = match Selector::parse(
+    let elements = http::element::select(
+        &document,
+        &url,
         "#main-2-QuoteDividend-Proxy > div > section > div > div > div > div > ul > li",
-    ) {
-        Ok(selector) => selector,
-        Err(why) => {
-            return Err(anyhow!("Failed to Selector::parse because: {:?}", why));
-        }
-    };
+    )?;
 
     let re = Regex::new(r"(\d+)(Q\d|H\d)?")?;
     let mut e = YahooDividend::new(stock_symbol.to_string());
 
-    for element in document.select(&selector) {
+    for element in elements {
         let dividend_period = http::element::parse_value(&element, "div > div.Fxg\\(1\\).Fxs\\(1\\).Fxb\\(0\\%\\).Ta\\(end\\).Mend\\(0\\)\\:lc.Mend\\(12px\\).W\\(88px\\).Miw\\(88px\\)");
 
         if dividend_period.is_none() {
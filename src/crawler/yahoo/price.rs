@@ -50,15 +50,15 @@ impl StockInfo for Yahoo {
         //#main-0-QuoteHeader-Proxy > div > div.D\(f\).Jc\(sb\).Ai\(fe\) > div.D\(f\).Fld\(c\).Ai\(fs\) > div > span.Fz\(20px\).Fw\(b\).Lh\(1\.2\).Mend\(4px\).D\(f\).Ai\(c\).C\(\$c-trend-up\) > span
         //Negative
 
-        let is_negative = util::http::element::get_one_element(
-            util::http::element::GetOneElementText {
+        let is_negative = util::http::element::find_one_element(
+            &util::http::element::GetOneElementText {
                 stock_symbol,
                 document: document.clone(),
                 selector: r"#main-0-QuoteHeader-Proxy > div > div > div > div > span.Fz\(20px\).Fw\(b\).Lh\(1\.2\).Mend\(4px\).D\(f\).Ai\(c\).C\(\$c-trend-down\)",
                 element: "span",
                 url,
             },
-        ).is_ok();
+        ).is_some();
         let price = util::http::element::get_one_element(util::http::element::GetOneElementText {
             stock_symbol,
             document: document.clone(),
@@ -97,6 +97,7 @@ impl StockInfo for Yahoo {
             price,
             change,
             change_range,
+            volume: None,
         })
     }
 }
@@ -1,7 +1,7 @@
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use regex::Regex;
 use rust_decimal::Decimal;
-use scraper::{Html, Selector};
+use scraper::Html;
 use serde::{Deserialize, Serialize};
 
 use crate::{crawler::yahoo::HOST, util, util::http::element};
@@ -60,17 +60,15 @@ pub async fn visit(stock_symbol: &str) -> Result<Profile> {
     let url = format!("https://{}/quote/{}/profile", HOST, stock_symbol);
     let text = util::http::get(&url, None).await?;
     let document = Html::parse_document(text.as_str());
-    let selector = match Selector::parse("#main-2-QuoteProfile-Proxy > div > section:nth-child(3)")
-    {
-        Ok(selector) => selector,
-        Err(why) => {
-            return Err(anyhow!("Failed to Selector::parse because: {:?}", why));
-        }
-    };
+    let elements = element::select(
+        &document,
+        &url,
+        "#main-2-QuoteProfile-Proxy > div > section:nth-child(3)",
+    )?;
     let mut e = Profile::new(stock_symbol.to_string());
     let css_base = "div.table-grid.Mb\\(20px\\).row-fit-half > div:nth-child";
 
-    for element in document.select(&selector) {
+    for element in elements {
         let year_and_quarter = element::parse_value(&element, "div:nth-child(2).D\\(f\\)");
         if let Some(year_and_quarter_text) = year_and_quarter {
             let reg_quarter = Regex::new(r"(?i)q\d")?;
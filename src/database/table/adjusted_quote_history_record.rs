@@ -0,0 +1,100 @@
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use sqlx::postgres::PgQueryResult;
+
+use crate::database;
+
+/// 以除權息還原股價計算的歷史最高、最低價
+#[derive(sqlx::FromRow, Debug, Clone, Default, PartialEq)]
+pub struct AdjustedQuoteHistoryRecord {
+    /// 股票代號
+    pub security_code: String,
+    /// 還原股價的歷史最高價
+    pub maximum_price: Decimal,
+    /// 還原股價的歷史最高價出現在哪一天
+    pub maximum_price_date_on: NaiveDate,
+    /// 還原股價的歷史最低價
+    pub minimum_price: Decimal,
+    /// 還原股價的歷史最低價出現在哪一天
+    pub minimum_price_date_on: NaiveDate,
+    /// 已計算到哪一天的收盤數據
+    pub processed_date: NaiveDate,
+    /// 計算時 processed_date 的累積調整因子
+    pub processed_factor: Decimal,
+}
+
+impl AdjustedQuoteHistoryRecord {
+    /// 取得指定股票以還原股價計算的歷史最高、最低價
+    pub async fn fetch(security_code: &str) -> Result<Option<AdjustedQuoteHistoryRecord>> {
+        sqlx::query_as::<_, AdjustedQuoteHistoryRecord>(
+            r#"
+SELECT
+    security_code,
+    maximum_price,
+    maximum_price_date_on,
+    minimum_price,
+    minimum_price_date_on,
+    processed_date,
+    processed_factor
+FROM
+    adjusted_quote_history_record
+WHERE
+    security_code = $1
+"#,
+        )
+        .bind(security_code)
+        .fetch_optional(database::get_connection())
+        .await
+        .context(format!(
+            "Failed to AdjustedQuoteHistoryRecord::fetch({}) from database",
+            security_code
+        ))
+    }
+
+    pub async fn upsert(&self) -> Result<PgQueryResult> {
+        let sql = r#"
+INSERT INTO
+    adjusted_quote_history_record (
+        security_code,
+        maximum_price,
+        maximum_price_date_on,
+        minimum_price,
+        minimum_price_date_on,
+        processed_date,
+        processed_factor,
+        created_time,
+        updated_time
+    )
+VALUES
+    (
+      $1, $2, $3, $4, $5, $6, $7, now(), now()
+    )
+ON CONFLICT
+    (security_code)
+DO UPDATE
+SET
+    maximum_price = EXCLUDED.maximum_price,
+    maximum_price_date_on = EXCLUDED.maximum_price_date_on,
+    minimum_price = EXCLUDED.minimum_price,
+    minimum_price_date_on = EXCLUDED.minimum_price_date_on,
+    processed_date = EXCLUDED.processed_date,
+    processed_factor = EXCLUDED.processed_factor,
+    updated_time = now()
+"#;
+        sqlx::query(sql)
+            .bind(&self.security_code)
+            .bind(self.maximum_price)
+            .bind(self.maximum_price_date_on)
+            .bind(self.minimum_price)
+            .bind(self.minimum_price_date_on)
+            .bind(self.processed_date)
+            .bind(self.processed_factor)
+            .execute(database::get_connection())
+            .await
+            .context(format!(
+                "Failed to AdjustedQuoteHistoryRecord::upsert({:?}) from database",
+                self
+            ))
+    }
+}
@@ -0,0 +1,154 @@
+use anyhow::{Context, Result};
+use chrono::{NaiveDate, TimeDelta};
+use rust_decimal::Decimal;
+
+use crate::database;
+
+/// 評估提醒規則所需的數據，以指定日期(含)之前最後一個交易日為準
+#[derive(sqlx::FromRow, Debug, Clone, Default)]
+pub struct AlertSnapshot {
+    pub security_code: String,
+    /// 最後一個交易日
+    pub date: NaiveDate,
+    pub closing_price: Decimal,
+    pub change_range: Decimal,
+    pub trading_volume: Decimal,
+    pub price_to_book_ratio: Decimal,
+    pub moving_average_5: Decimal,
+    pub moving_average_10: Decimal,
+    pub moving_average_20: Decimal,
+    pub moving_average_60: Decimal,
+    pub moving_average_120: Decimal,
+    pub moving_average_240: Decimal,
+    /// 前一個交易日的收盤價
+    pub previous_closing_price: Decimal,
+    pub previous_moving_average_5: Decimal,
+    pub previous_moving_average_10: Decimal,
+    pub previous_moving_average_20: Decimal,
+    pub previous_moving_average_60: Decimal,
+    pub previous_moving_average_120: Decimal,
+    pub previous_moving_average_240: Decimal,
+    /// 最後一個交易日之前 20 個交易日的平均成交股數
+    pub average_volume: Decimal,
+    /// 最近一次估算的便宜價
+    pub cheap: Decimal,
+    /// 最近一次估算的合理價
+    pub fair: Decimal,
+    /// 最近一次估算的昂貴價
+    pub expensive: Decimal,
+    /// 殖利率排行所用的年度合計股利
+    pub dividend: Decimal,
+}
+
+/// 取得指定股票在指定日期(含)之前最後一個交易日的提醒規則評估數據
+pub async fn fetch(security_codes: &[String], date: NaiveDate) -> Result<Vec<AlertSnapshot>> {
+    let sql = r#"
+WITH ranked AS (
+    SELECT
+        "SecurityCode" AS security_code,
+        "Date" AS date,
+        "ClosingPrice" AS closing_price,
+        "ChangeRange" AS change_range,
+        "TradingVolume" AS trading_volume,
+        "price-to-book_ratio" AS price_to_book_ratio,
+        "MovingAverage5" AS moving_average_5,
+        "MovingAverage10" AS moving_average_10,
+        "MovingAverage20" AS moving_average_20,
+        "MovingAverage60" AS moving_average_60,
+        "MovingAverage120" AS moving_average_120,
+        "MovingAverage240" AS moving_average_240,
+        ROW_NUMBER() OVER (PARTITION BY "SecurityCode" ORDER BY "Date" DESC) AS row_number
+    FROM "DailyQuotes"
+    WHERE "SecurityCode" = ANY($1) AND "Date" <= $2 AND "Date" >= $3
+),
+volume AS (
+    SELECT security_code, AVG(trading_volume) AS average_volume
+    FROM ranked
+    WHERE row_number BETWEEN 2 AND 21
+    GROUP BY security_code
+),
+latest_estimate AS (
+    SELECT DISTINCT ON (security_code) security_code, cheap, fair, expensive
+    FROM estimate
+    WHERE security_code = ANY($1) AND date <= $2
+    ORDER BY security_code, date DESC
+),
+latest_yield AS (
+    SELECT DISTINCT ON (yr.security_code) yr.security_code, d."sum" AS dividend
+    FROM yield_rank AS yr
+    INNER JOIN dividend AS d ON d.serial = yr.dividend_serial
+    WHERE yr.security_code = ANY($1) AND yr.date <= $2
+    ORDER BY yr.security_code, yr.date DESC
+)
+SELECT
+    l.security_code,
+    l.date,
+    l.closing_price,
+    l.change_range,
+    l.trading_volume,
+    l.price_to_book_ratio,
+    l.moving_average_5,
+    l.moving_average_10,
+    l.moving_average_20,
+    l.moving_average_60,
+    l.moving_average_120,
+    l.moving_average_240,
+    COALESCE(p.closing_price, 0) AS previous_closing_price,
+    COALESCE(p.moving_average_5, 0) AS previous_moving_average_5,
+    COALESCE(p.moving_average_10, 0) AS previous_moving_average_10,
+    COALESCE(p.moving_average_20, 0) AS previous_moving_average_20,
+    COALESCE(p.moving_average_60, 0) AS previous_moving_average_60,
+    COALESCE(p.moving_average_120, 0) AS previous_moving_average_120,
+    COALESCE(p.moving_average_240, 0) AS previous_moving_average_240,
+    COALESCE(v.average_volume, 0) AS average_volume,
+    COALESCE(e.cheap, 0) AS cheap,
+    COALESCE(e.fair, 0) AS fair,
+    COALESCE(e.expensive, 0) AS expensive,
+    COALESCE(y.dividend, 0) AS dividend
+FROM ranked AS l
+LEFT JOIN ranked AS p ON p.security_code = l.security_code AND p.row_number = 2
+LEFT JOIN volume AS v ON v.security_code = l.security_code
+LEFT JOIN latest_estimate AS e ON e.security_code = l.security_code
+LEFT JOIN latest_yield AS y ON y.security_code = l.security_code
+WHERE l.row_number = 1;
+"#;
+    sqlx::query_as::<_, AlertSnapshot>(sql)
+        .bind(security_codes)
+        .bind(date)
+        .bind(date - TimeDelta::try_days(60).unwrap())
+        .fetch_all(database::get_connection())
+        .await
+        .context(format!(
+            "Failed to alert_snapshot::fetch({:?},{}) from database",
+            security_codes, date
+        ))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Local;
+
+    use crate::logging;
+
+    use super::*;
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_fetch() {
+        dotenv::dotenv().ok();
+        logging::debug_file_async("開始 alert_snapshot::fetch".to_string());
+        let security_codes = vec!["2330".to_string(), "2412".to_string()];
+
+        match fetch(&security_codes, Local::now().date_naive()).await {
+            Ok(snapshots) => {
+                logging::debug_file_async(format!("alert_snapshot::fetch:{:#?}", snapshots))
+            }
+            Err(why) => logging::debug_file_async(format!(
+                "Failed to alert_snapshot::fetch because {:?}",
+                why
+            )),
+        }
+
+        logging::debug_file_async("結束 alert_snapshot::fetch".to_string());
+    }
+}
@@ -0,0 +1,2 @@
+/// 評估提醒規則所需的行情、估價與股利數據
+pub mod alert_snapshot;
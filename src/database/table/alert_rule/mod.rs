@@ -0,0 +1,140 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, TimeDelta};
+use sqlx::postgres::PgQueryResult;
+
+use crate::database;
+
+pub(crate) mod extension;
+
+/// 股價提醒規則
+#[derive(sqlx::FromRow, Debug, Clone, Default)]
+pub struct AlertRule {
+    pub serial: i64,
+    /// 規則名稱
+    pub name: String,
+    /// 股票代號
+    pub security_code: String,
+    /// 觸發條件(JSON)
+    pub condition: String,
+    /// 觸發後幾分鐘內不再重複提醒
+    pub cooldown_minutes: i32,
+    pub enabled: bool,
+    /// 最後一次觸發的時間
+    pub last_triggered_time: Option<DateTime<Local>>,
+}
+
+impl AlertRule {
+    /// 取得所有啟用中的提醒規則
+    pub async fn fetch_enabled() -> Result<Vec<AlertRule>> {
+        let sql = r#"
+SELECT
+    serial,
+    name,
+    security_code,
+    condition::text AS condition,
+    cooldown_minutes,
+    enabled,
+    last_triggered_time
+FROM alert_rule
+WHERE enabled = true
+ORDER BY serial;
+"#;
+        sqlx::query_as::<_, AlertRule>(sql)
+            .fetch_all(database::get_connection())
+            .await
+            .context("Failed to AlertRule::fetch_enabled() from database")
+    }
+
+    /// 新增提醒規則並回傳序號
+    pub async fn insert(&mut self) -> Result<i64> {
+        let sql = r#"
+INSERT INTO alert_rule (name, security_code, condition, cooldown_minutes, enabled)
+VALUES ($1, $2, $3::jsonb, $4, $5)
+RETURNING serial;
+"#;
+        let (serial,): (i64,) = sqlx::query_as(sql)
+            .bind(&self.name)
+            .bind(&self.security_code)
+            .bind(&self.condition)
+            .bind(self.cooldown_minutes)
+            .bind(self.enabled)
+            .fetch_one(database::get_connection())
+            .await
+            .context(format!(
+                "Failed to AlertRule::insert({:?}) from database",
+                self
+            ))?;
+        self.serial = serial;
+
+        Ok(serial)
+    }
+
+    /// 記錄規則最後一次觸發的時間
+    pub async fn update_last_triggered_time(
+        &mut self,
+        time: DateTime<Local>,
+    ) -> Result<PgQueryResult> {
+        self.last_triggered_time = Some(time);
+        sqlx::query(
+            "UPDATE alert_rule SET last_triggered_time = $2, updated_time = now() WHERE serial = $1;",
+        )
+        .bind(self.serial)
+        .bind(time)
+        .execute(database::get_connection())
+        .await
+        .context(format!(
+            "Failed to AlertRule::update_last_triggered_time({}) from database",
+            self.serial
+        ))
+    }
+
+    /// 是否仍在冷卻時間內
+    pub fn is_cooling_down(&self, now: DateTime<Local>) -> bool {
+        match self.last_triggered_time {
+            Some(last) => now - last < TimeDelta::minutes(self.cooldown_minutes as i64),
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logging;
+
+    use super::*;
+
+    #[test]
+    fn test_is_cooling_down() {
+        let now = Local::now();
+        let mut rule = AlertRule {
+            cooldown_minutes: 300,
+            ..Default::default()
+        };
+        assert!(!rule.is_cooling_down(now));
+
+        rule.last_triggered_time = Some(now - TimeDelta::minutes(299));
+        assert!(rule.is_cooling_down(now));
+
+        rule.last_triggered_time = Some(now - TimeDelta::minutes(300));
+        assert!(!rule.is_cooling_down(now));
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_fetch_enabled() {
+        dotenv::dotenv().ok();
+        logging::debug_file_async("開始 AlertRule::fetch_enabled".to_string());
+
+        match AlertRule::fetch_enabled().await {
+            Ok(rules) => {
+                logging::debug_file_async(format!("AlertRule::fetch_enabled:{:#?}", rules))
+            }
+            Err(why) => logging::debug_file_async(format!(
+                "Failed to AlertRule::fetch_enabled because {:?}",
+                why
+            )),
+        }
+
+        logging::debug_file_async("結束 AlertRule::fetch_enabled".to_string());
+    }
+}
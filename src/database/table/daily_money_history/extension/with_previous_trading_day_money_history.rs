@@ -9,13 +9,9 @@ pub struct DailyMoneyHistoryWithPreviousTradingDayMoneyHistory {
     pub date: NaiveDate,
     pub created_at: DateTime<Local>,
     pub updated_at: DateTime<Local>,
-    pub unice: Decimal,
-    pub eddie: Decimal,
     pub sum: Decimal,
 
     pub previous_date: NaiveDate,
-    pub previous_unice: Decimal,
-    pub previous_eddie: Decimal,
     pub previous_sum: Decimal,
 }
 
@@ -24,7 +20,7 @@ impl DailyMoneyHistoryWithPreviousTradingDayMoneyHistory {
         date: NaiveDate,
     ) -> Result<DailyMoneyHistoryWithPreviousTradingDayMoneyHistory> {
         let sql = "
-select date, sum, eddie, unice, created_time as created_at, updated_time as updated_at
+select date, sum, created_time as created_at, updated_time as updated_at
 from daily_money_history
 where date <= $1
 order by date desc
@@ -40,23 +36,15 @@ limit 2;"
             date,
             created_at: Default::default(),
             updated_at: Default::default(),
-            unice: Default::default(),
-            eddie: Default::default(),
             sum: Default::default(),
             previous_date: Default::default(),
-            previous_unice: Default::default(),
-            previous_eddie: Default::default(),
             previous_sum: Default::default(),
         };
 
         for r in result {
             if r.date == date {
-                dmhwptdmh.unice = r.unice;
-                dmhwptdmh.eddie = r.eddie;
                 dmhwptdmh.sum = r.sum;
             } else {
-                dmhwptdmh.previous_unice = r.unice;
-                dmhwptdmh.previous_eddie = r.eddie;
                 dmhwptdmh.previous_sum = r.sum;
                 dmhwptdmh.previous_date = r.date;
                 break;
@@ -13,8 +13,6 @@ pub struct DailyMoneyHistory {
     pub date: NaiveDate,
     pub created_at: DateTime<Local>,
     pub updated_at: DateTime<Local>,
-    pub unice: Decimal,
-    pub eddie: Decimal,
     pub sum: Decimal,
 }
 
@@ -36,47 +34,18 @@ impl DailyMoneyHistory {
         date: NaiveDate,
         tx: &mut Option<Transaction<'_, Postgres>>,
     ) -> Result<PgQueryResult> {
-        let sql = format!(
-            r#"
-WITH ownership_details AS (
-	SELECT security_code, share_quantity, member_id
-	FROM stock_ownership_details
-	WHERE is_sold = false and date <= $1
-),
- daily_quotes AS (
-	SELECT "SecurityCode", "ClosingPrice"
-	FROM "DailyQuotes"
-	WHERE "Date" = $1 AND "SecurityCode" in (select security_code FROM ownership_details)
-),
-total AS (
-	SELECT '{0}' AS "date", SUM(od.share_quantity * dq."ClosingPrice") AS "sum"
-	FROM ownership_details od
-	INNER JOIN daily_quotes dq ON od.security_code = dq."SecurityCode"
-),
-eddie AS (
-	SELECT '{0}' AS "date", SUM(od.share_quantity * dq."ClosingPrice") AS "sum"
-	FROM ownership_details od
-	INNER JOIN daily_quotes dq ON od.security_code = dq."SecurityCode"
-	WHERE od.member_id = 1
-)
-INSERT INTO daily_money_history (date, sum, eddie, unice)
-SELECT
-	TO_DATE(total."date",'YYYY-MM-DD') AS "date",
-	"total"."sum" AS sum,
-	"eddie"."sum" AS eddie,
-	"total"."sum" - "eddie"."sum" AS unice
-FROM total
-INNER JOIN eddie ON total."date" = eddie."date"
+        let sql = r#"
+INSERT INTO daily_money_history (date, sum)
+SELECT $1, COALESCE(SUM(od.share_quantity * dq."ClosingPrice"), 0)
+FROM stock_ownership_details od
+INNER JOIN "DailyQuotes" dq ON od.security_code = dq."SecurityCode" AND dq."Date" = $1
+WHERE od.is_sold = false AND od.date <= $1
 ON CONFLICT (date) DO UPDATE SET
 	sum = EXCLUDED.sum,
-	eddie = EXCLUDED.eddie,
-	unice = EXCLUDED.unice,
 	updated_time = now();
-"#,
-            date
-        );
+"#;
 
-        let query = sqlx::query(&sql).bind(date);
+        let query = sqlx::query(sql).bind(date);
         let result = match tx {
             None => query.execute(database::get_connection()).await,
             Some(t) => query.execute(&mut **t).await,
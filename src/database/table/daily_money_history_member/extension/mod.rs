@@ -0,0 +1 @@
+pub mod with_previous_trading_day_money_history;
@@ -0,0 +1,78 @@
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+
+use crate::database;
+
+/// 會員當日與前一個交易日的市值記錄
+#[derive(sqlx::FromRow, Default, Debug)]
+pub struct DailyMoneyHistoryMemberWithPreviousTradingDayMoneyHistory {
+    pub name: String,
+    pub market_value: Decimal,
+    pub cost: Decimal,
+    pub unrealized_profit_and_loss: Decimal,
+    pub dividend_income: Decimal,
+    pub previous_market_value: Decimal,
+}
+
+impl DailyMoneyHistoryMemberWithPreviousTradingDayMoneyHistory {
+    /// 取得指定日期啟用中會員的市值，以及各會員前一個交易日的市值
+    pub async fn fetch(date: NaiveDate) -> Result<Vec<Self>> {
+        let sql = r#"
+WITH previous AS (
+    SELECT MAX(date) AS date
+    FROM daily_money_history_member
+    WHERE date < $1
+)
+SELECT
+    COALESCE(m.name, cur.member_id::text) AS name,
+    cur.market_value,
+    cur.cost,
+    cur.unrealized_profit_and_loss,
+    cur.dividend_income,
+    COALESCE(prev.market_value, 0) AS previous_market_value
+FROM daily_money_history_member cur
+LEFT JOIN member m ON m.id = cur.member_id
+LEFT JOIN daily_money_history_member prev
+    ON prev.member_id = cur.member_id AND prev.date = (SELECT date FROM previous)
+WHERE cur.date = $1 AND COALESCE(m.enabled, true)
+ORDER BY cur.member_id;
+"#;
+        sqlx::query_as::<_, Self>(sql)
+            .bind(date)
+            .fetch_all(database::get_connection())
+            .await
+            .context(format!(
+                "Failed to fetch({}) daily_money_history_member from database",
+                date
+            ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Local;
+
+    use crate::logging;
+
+    use super::*;
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_fetch() {
+        dotenv::dotenv().ok();
+        logging::debug_file_async("開始 fetch".to_string());
+        let d = Local::now().date_naive();
+        match DailyMoneyHistoryMemberWithPreviousTradingDayMoneyHistory::fetch(d).await {
+            Ok(members) => {
+                dbg!(&members);
+                logging::debug_file_async(format!("members: {:?}", members));
+            }
+            Err(why) => {
+                logging::debug_file_async(format!("Failed to fetch because {:?}", why));
+            }
+        }
+
+        logging::debug_file_async("結束 fetch".to_string());
+    }
+}
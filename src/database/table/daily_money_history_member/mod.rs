@@ -0,0 +1,116 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate};
+use rust_decimal::Decimal;
+use sqlx::{postgres::PgQueryResult, Postgres, Transaction};
+
+use crate::database;
+
+pub(crate) mod extension;
+
+/// 每日各會員的市值記錄
+#[derive(sqlx::FromRow, Debug)]
+pub struct DailyMoneyHistoryMember {
+    pub date: NaiveDate,
+    pub member_id: i64,
+    pub market_value: Decimal,
+    pub cost: Decimal,
+    pub unrealized_profit_and_loss: Decimal,
+    pub dividend_income: Decimal,
+    pub created_time: DateTime<Local>,
+    pub updated_time: DateTime<Local>,
+}
+
+impl DailyMoneyHistoryMember {
+    pub async fn delete(
+        date: NaiveDate,
+        tx: &mut Option<Transaction<'_, Postgres>>,
+    ) -> Result<PgQueryResult> {
+        let sql = "DELETE FROM daily_money_history_member WHERE date = $1;";
+        let query = sqlx::query(sql).bind(date);
+        let result = match tx {
+            None => query.execute(database::get_connection()).await,
+            Some(t) => query.execute(&mut **t).await,
+        };
+
+        result.context(format!(
+            "Failed to delete({}) daily_money_history_member from database",
+            &date
+        ))
+    }
+
+    /// 依持股明細計算指定日期每個會員的市值、成本、未實現損益與股利
+    pub async fn upsert(
+        date: NaiveDate,
+        tx: &mut Option<Transaction<'_, Postgres>>,
+    ) -> Result<PgQueryResult> {
+        let sql = r#"
+INSERT INTO daily_money_history_member (
+    date, member_id, market_value, cost, unrealized_profit_and_loss, dividend_income
+)
+SELECT
+    $1,
+    od.member_id,
+    SUM(od.share_quantity * dq."ClosingPrice"),
+    SUM(od.holding_cost),
+    SUM(od.share_quantity * dq."ClosingPrice" + od.holding_cost),
+    SUM(COALESCE(od.cumulate_dividends_total, 0))
+FROM stock_ownership_details od
+INNER JOIN "DailyQuotes" dq ON od.security_code = dq."SecurityCode" AND dq."Date" = $1
+WHERE od.is_sold = false AND od.date <= $1
+GROUP BY od.member_id
+ON CONFLICT (date, member_id) DO UPDATE SET
+    market_value = EXCLUDED.market_value,
+    cost = EXCLUDED.cost,
+    unrealized_profit_and_loss = EXCLUDED.unrealized_profit_and_loss,
+    dividend_income = EXCLUDED.dividend_income,
+    updated_time = now();
+"#;
+        let query = sqlx::query(sql).bind(date);
+        let result = match tx {
+            None => query.execute(database::get_connection()).await,
+            Some(t) => query.execute(&mut **t).await,
+        };
+
+        result.context(format!(
+            "Failed to upsert({}) daily_money_history_member from database",
+            &date
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logging;
+
+    use super::*;
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_upsert() {
+        dotenv::dotenv().ok();
+        logging::debug_file_async("開始 DailyMoneyHistoryMember::upsert".to_string());
+        let current_date = NaiveDate::parse_from_str("2023-08-30", "%Y-%m-%d").unwrap();
+        let mut tx = database::get_tx().await.ok();
+        match DailyMoneyHistoryMember::upsert(current_date, &mut tx).await {
+            Ok(r) => {
+                logging::debug_file_async(format!("DailyMoneyHistoryMember::upsert:{:#?}", r));
+                tx.unwrap()
+                    .commit()
+                    .await
+                    .expect("tx.unwrap().commit() is failed");
+            }
+            Err(why) => {
+                logging::debug_file_async(format!(
+                    "Failed to DailyMoneyHistoryMember::upsert because {:?}",
+                    why
+                ));
+                tx.unwrap()
+                    .rollback()
+                    .await
+                    .expect("tx.unwrap().rollback() is failed");
+            }
+        }
+
+        logging::debug_file_async("結束 DailyMoneyHistoryMember::upsert".to_string());
+    }
+}
@@ -3,7 +3,10 @@ use std::fmt::Write;
 use anyhow::{Context, Result};
 use chrono::{DateTime, Local, NaiveDate, TimeDelta};
 use rust_decimal::Decimal;
-use sqlx::{postgres::PgQueryResult, Row};
+use sqlx::{
+    postgres::{PgQueryResult, PgRow},
+    Row,
+};
 
 use crate::{
     database::{
@@ -11,7 +14,7 @@ use crate::{
         CopyIn,
         table::daily_quote::extension::MonthlyStockPriceSummary
     },
-    declare::StockExchange,
+    declare::{MovingAverage, StockExchange},
     util::{datetime, map::Keyable}
 };
 
@@ -105,6 +108,42 @@ pub const COPY_IN_QUERY: &str = r#"COPY "DailyQuotes"(
             month,
             day) FROM STDIN WITH (FORMAT CSV)"#;
 
+const TABLE_COLUMNS: &str = r#"
+    "Serial",
+    "Date",
+    "SecurityCode",
+    "TradingVolume",
+    "Transaction",
+    "TradeValue",
+    "OpeningPrice",
+    "HighestPrice",
+    "LowestPrice",
+    "ClosingPrice",
+    "ChangeRange",
+    "Change",
+    "LastBestBidPrice",
+    "LastBestBidVolume",
+    "LastBestAskPrice",
+    "LastBestAskVolume",
+    "PriceEarningRatio",
+    "RecordTime",
+    "CreateTime",
+    "MovingAverage5",
+    "MovingAverage10",
+    "MovingAverage20",
+    "MovingAverage60",
+    "MovingAverage120",
+    "MovingAverage240",
+    maximum_price_in_year,
+    minimum_price_in_year,
+    average_price_in_year,
+    maximum_price_in_year_date_on,
+    minimum_price_in_year_date_on,
+    "price-to-book_ratio",
+    year,
+    month,
+    day"#;
+
 impl CopyIn for DailyQuote {
     fn to_csv(&self) -> String {
         self.to_csv()
@@ -361,6 +400,57 @@ WHERE "Serial" = $1
             ))
     }
 
+    /// 取得指定的均線值
+    pub fn moving_average(&self, ma: MovingAverage) -> Decimal {
+        match ma {
+            MovingAverage::Ma5 => self.moving_average_5,
+            MovingAverage::Ma10 => self.moving_average_10,
+            MovingAverage::Ma20 => self.moving_average_20,
+            MovingAverage::Ma60 => self.moving_average_60,
+            MovingAverage::Ma120 => self.moving_average_120,
+            MovingAverage::Ma240 => self.moving_average_240,
+        }
+    }
+
+    fn row_to_entity(row: PgRow) -> Result<DailyQuote, sqlx::Error> {
+        Ok(DailyQuote {
+            maximum_price_in_year_date_on: row.get("maximum_price_in_year_date_on"),
+            minimum_price_in_year_date_on: row.get("minimum_price_in_year_date_on"),
+            date: row.get("Date"),
+            create_time: row.try_get("CreateTime")?,
+            record_time: row.try_get("RecordTime")?,
+            price_earning_ratio: row.get("PriceEarningRatio"),
+            moving_average_60: row.get("MovingAverage60"),
+            closing_price: row.get("ClosingPrice"),
+            change_range: row.get("ChangeRange"),
+            change: row.get("Change"),
+            last_best_bid_price: row.get("LastBestBidPrice"),
+            last_best_bid_volume: row.get("LastBestBidVolume"),
+            last_best_ask_price: row.get("LastBestAskPrice"),
+            last_best_ask_volume: row.get("LastBestAskVolume"),
+            moving_average_5: row.get("MovingAverage5"),
+            moving_average_10: row.get("MovingAverage10"),
+            moving_average_20: row.get("MovingAverage20"),
+            lowest_price: row.get("LowestPrice"),
+            moving_average_120: row.get("MovingAverage120"),
+            moving_average_240: row.get("MovingAverage240"),
+            maximum_price_in_year: row.get("maximum_price_in_year"),
+            minimum_price_in_year: row.get("minimum_price_in_year"),
+            average_price_in_year: row.get("average_price_in_year"),
+            highest_price: row.get("HighestPrice"),
+            opening_price: row.get("OpeningPrice"),
+            trading_volume: row.get("TradingVolume"),
+            trade_value: row.get("TradeValue"),
+            transaction: row.get("Transaction"),
+            price_to_book_ratio: row.get("price-to-book_ratio"),
+            security_code: row.get("SecurityCode"),
+            serial: row.get("Serial"),
+            year: row.get("year"),
+            month: row.get("month"),
+            day: row.get("day"),
+        })
+    }
+
     pub async fn copy_in_raw(quotes: &[Self]) -> Result<u64> {
         database::copy_in_raw(COPY_IN_QUERY, quotes).await
     }
@@ -582,91 +672,110 @@ pub async fn fetch_count_by_date(date: NaiveDate) -> Result<i64> {
 }
 
 pub async fn fetch_daily_quotes_by_date(date: NaiveDate) -> Result<Vec<DailyQuote>> {
-    let sql = r#"
-    SELECT
-        "Serial",
-        "Date",
-        "SecurityCode",
-        "TradingVolume",
-        "Transaction",
-        "TradeValue",
-        "OpeningPrice",
-        "HighestPrice",
-        "LowestPrice",
-        "ClosingPrice",
-        "ChangeRange",
-        "Change",
-        "LastBestBidPrice",
-        "LastBestBidVolume",
-        "LastBestAskPrice",
-        "LastBestAskVolume",
-        "PriceEarningRatio",
-        "RecordTime",
-        "CreateTime",
-        "MovingAverage5",
-        "MovingAverage10",
-        "MovingAverage20",
-        "MovingAverage60",
-        "MovingAverage120",
-        "MovingAverage240",
-        maximum_price_in_year,
-        minimum_price_in_year,
-        average_price_in_year,
-        maximum_price_in_year_date_on,
-        minimum_price_in_year_date_on,
-        "price-to-book_ratio",
-        year,
-        month,
-        day
-    FROM "DailyQuotes"
-    WHERE "Date" = $1"#;
-    sqlx::query(sql)
+    let sql = format!(
+        r#"
+SELECT {}
+FROM "DailyQuotes"
+WHERE "Date" = $1"#,
+        TABLE_COLUMNS
+    );
+    sqlx::query(&sql)
         .bind(date)
-        .try_map(|row: sqlx::postgres::PgRow| {
-            let dq = DailyQuote {
-                maximum_price_in_year_date_on: row.get("maximum_price_in_year_date_on"),
-                minimum_price_in_year_date_on: row.get("minimum_price_in_year_date_on"),
-                date: row.get("Date"),
-                create_time: row.try_get("CreateTime")?,
-                record_time: row.try_get("RecordTime")?,
-                price_earning_ratio: row.get("PriceEarningRatio"),
-                moving_average_60: row.get("MovingAverage60"),
-                closing_price: row.get("ClosingPrice"),
-                change_range: row.get("ChangeRange"),
-                change: row.get("Change"),
-                last_best_bid_price: row.get("LastBestBidPrice"),
-                last_best_bid_volume: row.get("LastBestBidVolume"),
-                last_best_ask_price: row.get("LastBestAskPrice"),
-                last_best_ask_volume: row.get("LastBestAskVolume"),
-                moving_average_5: row.get("MovingAverage5"),
-                moving_average_10: row.get("MovingAverage10"),
-                moving_average_20: row.get("MovingAverage20"),
-                lowest_price: row.get("LowestPrice"),
-                moving_average_120: row.get("MovingAverage120"),
-                moving_average_240: row.get("MovingAverage240"),
-                maximum_price_in_year: row.get("maximum_price_in_year"),
-                minimum_price_in_year: row.get("minimum_price_in_year"),
-                average_price_in_year: row.get("average_price_in_year"),
-                highest_price: row.get("HighestPrice"),
-                opening_price: row.get("OpeningPrice"),
-                trading_volume: row.get("TradingVolume"),
-                trade_value: row.get("TradeValue"),
-                transaction: row.get("Transaction"),
-                price_to_book_ratio: row.get("price-to-book_ratio"),
-                security_code: row.get("SecurityCode"),
-                serial: row.get("Serial"),
-                year: row.get("year"),
-                month: row.get("month"),
-                day: row.get("day"),
-            };
-
-            Ok(dq)
-        })
+        .try_map(DailyQuote::row_to_entity)
         .fetch_all(database::get_connection())
         .await
         .context("Failed to fetch_daily_quotes_by_date from database")
 }
 
+/// 取得指定股票在日期區間內的每日收盤數據(依日期由舊到新排序)
+pub async fn fetch_daily_quotes_by_security_code(
+    security_code: &str,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+) -> Result<Vec<DailyQuote>> {
+    let sql = format!(
+        r#"
+SELECT {}
+FROM "DailyQuotes"
+WHERE "SecurityCode" = $1 AND "Date" >= $2 AND "Date" <= $3
+ORDER BY "Date""#,
+        TABLE_COLUMNS
+    );
+    sqlx::query(&sql)
+        .bind(security_code)
+        .bind(start_date)
+        .bind(end_date)
+        .try_map(DailyQuote::row_to_entity)
+        .fetch_all(database::get_connection())
+        .await
+        .context(format!(
+            "Failed to fetch_daily_quotes_by_security_code({},{},{}) from database",
+            security_code, start_date, end_date
+        ))
+}
+
+/// 分頁取得指定股票在日期區間內的每日收盤數據(依日期由舊到新排序)
+pub async fn fetch_daily_quotes_page_by_security_code(
+    security_code: &str,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    offset: i64,
+    limit: i64,
+) -> Result<Vec<DailyQuote>> {
+    let sql = format!(
+        r#"
+SELECT {}
+FROM "DailyQuotes"
+WHERE "SecurityCode" = $1 AND "Date" >= $2 AND "Date" <= $3
+ORDER BY "Date"
+LIMIT $4 OFFSET $5"#,
+        TABLE_COLUMNS
+    );
+    sqlx::query(&sql)
+        .bind(security_code)
+        .bind(start_date)
+        .bind(end_date)
+        .bind(limit)
+        .bind(offset)
+        .try_map(DailyQuote::row_to_entity)
+        .fetch_all(database::get_connection())
+        .await
+        .context(format!(
+            "Failed to fetch_daily_quotes_page_by_security_code({},{},{},{},{}) from database",
+            security_code, start_date, end_date, offset, limit
+        ))
+}
+
+/// 以 ("SecurityCode","Date") 為鍵分頁取得指定股票在 `after` 之後(不含)到 `end_date` 的每日收盤數據(依日期由舊到新排序)
+pub async fn fetch_daily_quotes_after_by_security_code(
+    security_code: &str,
+    after: NaiveDate,
+    end_date: NaiveDate,
+    limit: i64,
+) -> Result<Vec<DailyQuote>> {
+    let sql = format!(
+        r#"
+SELECT {}
+FROM "DailyQuotes"
+WHERE ("SecurityCode", "Date") > ($1, $2) AND "SecurityCode" = $1 AND "Date" <= $3
+ORDER BY "SecurityCode", "Date"
+LIMIT $4"#,
+        TABLE_COLUMNS
+    );
+    sqlx::query(&sql)
+        .bind(security_code)
+        .bind(after)
+        .bind(end_date)
+        .bind(limit)
+        .try_map(DailyQuote::row_to_entity)
+        .fetch_all(database::get_connection())
+        .await
+        .context(format!(
+            "Failed to fetch_daily_quotes_after_by_security_code({},{},{},{}) from database",
+            security_code, after, end_date, limit
+        ))
+}
+
 #[cfg(test)]
 mod tests {
     use chrono::Datelike;
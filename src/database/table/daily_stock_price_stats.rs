@@ -37,6 +37,35 @@ pub struct DailyStockPriceStats {
 }
 
 impl DailyStockPriceStats {
+    /// 分頁取得日期區間內指定市場的每日股價統計(依日期由新到舊排序)，市場為 None 時取得所有市場
+    pub async fn fetch_page(
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        stock_exchange_market_id: Option<i32>,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<DailyStockPriceStats>> {
+        let sql = r#"
+SELECT *
+FROM daily_stock_price_stats
+WHERE date >= $1 AND date <= $2 AND ($3::int IS NULL OR stock_exchange_market_id = $3)
+ORDER BY date DESC, stock_exchange_market_id
+LIMIT $4 OFFSET $5
+"#;
+        sqlx::query_as::<_, DailyStockPriceStats>(sql)
+            .bind(start_date)
+            .bind(end_date)
+            .bind(stock_exchange_market_id)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(database::get_connection())
+            .await
+            .context(format!(
+                "Failed to DailyStockPriceStats::fetch_page({},{},{:?},{},{}) from database",
+                start_date, end_date, stock_exchange_market_id, offset, limit
+            ))
+    }
+
     pub async fn upsert(date: NaiveDate, tx: &mut Option<Transaction<'_, Postgres>>) -> Result<PgQueryResult> {
         let sql = r#"
 WITH cte AS (
@@ -407,6 +407,59 @@ WHERE year = $1 AND quarter IN ('Q1','Q2','Q3','Q4','H1','H2');
             ))
     }
 
+    /// 取得指定股票所有的股利發放記錄(依年度由舊到新排序)
+    pub async fn fetch_by_security_code(security_code: &str) -> Result<Vec<Dividend>> {
+        let sql = format!(
+            r#"
+SELECT {}
+FROM dividend
+WHERE security_code = $1
+ORDER BY year, quarter;
+"#,
+            TABLE_COLUMNS
+        );
+
+        sqlx::query(&sql)
+            .bind(security_code)
+            .try_map(Self::row_to_entity)
+            .fetch_all(database::get_connection())
+            .await
+            .context(format!(
+                "Failed to fetch_by_security_code({}) from database",
+                security_code
+            ))
+    }
+
+    /// 分頁取得指定股票的股利發放記錄(依年度由新到舊排序)
+    pub async fn fetch_page_by_security_code(
+        security_code: &str,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<Dividend>> {
+        let sql = format!(
+            r#"
+SELECT {}
+FROM dividend
+WHERE security_code = $1
+ORDER BY year DESC, quarter DESC
+LIMIT $2 OFFSET $3;
+"#,
+            TABLE_COLUMNS
+        );
+
+        sqlx::query(&sql)
+            .bind(security_code)
+            .bind(limit)
+            .bind(offset)
+            .try_map(Self::row_to_entity)
+            .fetch_all(database::get_connection())
+            .await
+            .context(format!(
+                "Failed to fetch_page_by_security_code({},{},{}) from database",
+                security_code, offset, limit
+            ))
+    }
+
     /// 取得尚未有指定年度配息的股票代號
     pub async fn fetch_no_dividends_for_year(year: i32) -> Result<Vec<String>> {
         let sql = r#"
@@ -3,9 +3,7 @@ use chrono::{DateTime, Local};
 use rust_decimal::Decimal;
 use sqlx::{Postgres, Transaction};
 
-use crate::database::{self, table::dividend_record_detail::extension::CumulateDividend};
-
-pub(crate) mod extension;
+use crate::database;
 
 #[derive(sqlx::Type, sqlx::FromRow, Debug, Copy)]
 /// 持股股息發放記錄表 原表名 dividend_record_detail
@@ -81,13 +79,6 @@ impl DividendRecordDetail {
         Ok(self.serial)
     }
 
-    /// 計算指定股票其累積的領取股利
-    pub async fn fetch_cumulate_dividend(
-        &self,
-        tx: &mut Option<Transaction<'_, Postgres>>,
-    ) -> Result<CumulateDividend> {
-        CumulateDividend::fetch_cumulate_dividend(self.stock_ownership_details_serial, tx).await
-    }
 }
 
 impl Default for DividendRecordDetail {
@@ -108,41 +99,3 @@ impl Clone for DividendRecordDetail {
         *self
     }
 }
-
-#[cfg(test)]
-mod tests {
-    use crate::logging;
-
-    use super::*;
-
-    #[tokio::test]
-    #[ignore]
-    async fn test_calculate_cumulate_dividend() {
-        dotenv::dotenv().ok();
-        logging::debug_file_async("開始 calculate_cumulate_dividend".to_string());
-        let drd = DividendRecordDetail::new(
-            27,
-            2022,
-            Decimal::ZERO,
-            Decimal::ZERO,
-            Decimal::ZERO,
-            Decimal::ZERO,
-        );
-        let mut tx_option: Option<Transaction<Postgres>> =
-            Some(database::get_connection().begin().await.unwrap());
-        match drd.fetch_cumulate_dividend(&mut tx_option).await {
-            Ok(cd) => {
-                logging::debug_file_async(format!("cd: {:?}", cd));
-            }
-            Err(why) => {
-                logging::debug_file_async(format!("Failed to execute because {:?}", why));
-            }
-        }
-
-        if let Some(tx) = tx_option {
-            tx.commit().await.unwrap();
-        }
-
-        logging::debug_file_async("結束 calculate_cumulate_dividend".to_string());
-    }
-}
@@ -1,6 +1,9 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use chrono::NaiveDate;
-use sqlx::postgres::PgQueryResult;
+use sqlx::{
+    postgres::{PgQueryResult, PgRow},
+    Row,
+};
 
 use crate::database;
 
@@ -488,6 +491,117 @@ ON CONFLICT (date, security_code) DO UPDATE SET
                 )
             })
     }
+
+    /// 取得指定股票在日期區間內每日估算的便宜、合理、昂貴價(依日期由舊到新排序)
+    pub async fn fetch_by_security_code(
+        security_code: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<Vec<Estimate>> {
+        let sql = r#"
+SELECT
+    date,
+    security_code,
+    closing_price::float8 AS closing_price,
+    percentage::float8 AS percentage,
+    cheap::float8 AS cheap,
+    fair::float8 AS fair,
+    expensive::float8 AS expensive
+FROM estimate
+WHERE security_code = $1 AND date >= $2 AND date <= $3
+ORDER BY date
+"#;
+        sqlx::query(sql)
+            .bind(security_code)
+            .bind(start_date)
+            .bind(end_date)
+            .try_map(|row: PgRow| {
+                let mut e = Estimate::new(row.try_get("security_code")?, row.try_get("date")?);
+                e.closing_price = row.try_get("closing_price")?;
+                e.percentage = row.try_get("percentage")?;
+                e.cheap = row.try_get("cheap")?;
+                e.fair = row.try_get("fair")?;
+                e.expensive = row.try_get("expensive")?;
+                Ok(e)
+            })
+            .fetch_all(database::get_connection())
+            .await
+            .context(format!(
+                "Failed to Estimate::fetch_by_security_code({},{},{}) from database",
+                security_code, start_date, end_date
+            ))
+    }
+
+    /// 分頁取得指定股票在日期區間內每日估算的各項便宜、合理、昂貴價(依日期由新到舊排序)
+    pub async fn fetch_page_by_security_code(
+        security_code: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<Estimate>> {
+        let sql = r#"
+SELECT
+    date,
+    security_code,
+    closing_price::float8 AS closing_price,
+    percentage::float8 AS percentage,
+    cheap::float8 AS cheap,
+    fair::float8 AS fair,
+    expensive::float8 AS expensive,
+    price_cheap::float8 AS price_cheap,
+    price_fair::float8 AS price_fair,
+    price_expensive::float8 AS price_expensive,
+    dividend_cheap::float8 AS dividend_cheap,
+    dividend_fair::float8 AS dividend_fair,
+    dividend_expensive::float8 AS dividend_expensive,
+    eps_cheap::float8 AS eps_cheap,
+    eps_fair::float8 AS eps_fair,
+    eps_expensive::float8 AS eps_expensive,
+    pbr_cheap::float8 AS pbr_cheap,
+    pbr_fair::float8 AS pbr_fair,
+    pbr_expensive::float8 AS pbr_expensive,
+    year_count
+FROM estimate
+WHERE security_code = $1 AND date >= $2 AND date <= $3
+ORDER BY date DESC
+LIMIT $4 OFFSET $5
+"#;
+        sqlx::query(sql)
+            .bind(security_code)
+            .bind(start_date)
+            .bind(end_date)
+            .bind(limit)
+            .bind(offset)
+            .try_map(|row: PgRow| {
+                let mut e = Estimate::new(row.try_get("security_code")?, row.try_get("date")?);
+                e.closing_price = row.try_get("closing_price")?;
+                e.percentage = row.try_get("percentage")?;
+                e.cheap = row.try_get("cheap")?;
+                e.fair = row.try_get("fair")?;
+                e.expensive = row.try_get("expensive")?;
+                e.price_cheap = row.try_get("price_cheap")?;
+                e.price_fair = row.try_get("price_fair")?;
+                e.price_expensive = row.try_get("price_expensive")?;
+                e.dividend_cheap = row.try_get("dividend_cheap")?;
+                e.dividend_fair = row.try_get("dividend_fair")?;
+                e.dividend_expensive = row.try_get("dividend_expensive")?;
+                e.eps_cheap = row.try_get("eps_cheap")?;
+                e.eps_fair = row.try_get("eps_fair")?;
+                e.eps_expensive = row.try_get("eps_expensive")?;
+                e.pbr_cheap = row.try_get("pbr_cheap")?;
+                e.pbr_fair = row.try_get("pbr_fair")?;
+                e.pbr_expensive = row.try_get("pbr_expensive")?;
+                e.year_count = row.try_get("year_count")?;
+                Ok(e)
+            })
+            .fetch_all(database::get_connection())
+            .await
+            .context(format!(
+                "Failed to Estimate::fetch_page_by_security_code({},{},{},{},{}) from database",
+                security_code, start_date, end_date, offset, limit
+            ))
+    }
 }
 
 #[cfg(test)]
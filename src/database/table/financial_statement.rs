@@ -1,4 +1,4 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use chrono::{DateTime, Local};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
@@ -240,32 +240,82 @@ WHERE "year" = $1 AND quarter= ''
 "#;
     let result = sqlx::query(sql)
         .bind(year)
-        .try_map(|row: PgRow| {
-            Ok(FinancialStatement {
-                updated_time: row.try_get("updated_time")?,
-                created_time: row.try_get("created_time")?,
-                quarter: row.try_get("quarter")?,
-                security_code: row.try_get("security_code")?,
-                gross_profit: row.try_get("gross_profit")?,
-                operating_profit_margin: row.try_get("operating_profit_margin")?,
-                pre_tax_income: row.try_get("pre-tax_income")?,
-                net_income: row.try_get("net_income")?,
-                net_asset_value_per_share: row.try_get("net_asset_value_per_share")?,
-                sales_per_share: row.try_get("sales_per_share")?,
-                earnings_per_share: row.try_get("earnings_per_share")?,
-                profit_before_tax: row.try_get("profit_before_tax")?,
-                return_on_equity: row.try_get("return_on_equity")?,
-                return_on_assets: row.try_get("return_on_assets")?,
-                serial: row.try_get("serial")?,
-                year: row.try_get("year")?,
-            })
-        })
+        .try_map(row_to_entity)
         .fetch_all(database::get_connection())
         .await?;
 
     Ok(result)
 }
 
+/// 分頁取得指定股票在年度區間內的財報(依年度、季度由新到舊排序)，annual 為 true 時只取年度財報，否則只取季度財報
+pub async fn fetch_page_by_security_code(
+    security_code: &str,
+    start_year: i32,
+    end_year: i32,
+    annual: bool,
+    offset: i64,
+    limit: i64,
+) -> Result<Vec<FinancialStatement>> {
+    let sql = r#"
+SELECT
+    serial,
+    security_code,
+    year,
+    quarter,
+    gross_profit,
+    operating_profit_margin,
+    "pre-tax_income",
+    net_income,
+    net_asset_value_per_share,
+    sales_per_share,
+    earnings_per_share,
+    profit_before_tax,
+    return_on_equity,
+    return_on_assets,
+    created_time,
+    updated_time
+FROM financial_statement
+WHERE security_code = $1 AND "year" >= $2 AND "year" <= $3 AND (quarter = '') = $4
+ORDER BY "year" DESC, quarter DESC
+LIMIT $5 OFFSET $6
+"#;
+    sqlx::query(sql)
+        .bind(security_code)
+        .bind(start_year)
+        .bind(end_year)
+        .bind(annual)
+        .bind(limit)
+        .bind(offset)
+        .try_map(row_to_entity)
+        .fetch_all(database::get_connection())
+        .await
+        .context(format!(
+            "Failed to fetch_page_by_security_code({},{},{},{},{},{}) from database",
+            security_code, start_year, end_year, annual, offset, limit
+        ))
+}
+
+fn row_to_entity(row: PgRow) -> Result<FinancialStatement, sqlx::Error> {
+    Ok(FinancialStatement {
+        updated_time: row.try_get("updated_time")?,
+        created_time: row.try_get("created_time")?,
+        quarter: row.try_get("quarter")?,
+        security_code: row.try_get("security_code")?,
+        gross_profit: row.try_get("gross_profit")?,
+        operating_profit_margin: row.try_get("operating_profit_margin")?,
+        pre_tax_income: row.try_get("pre-tax_income")?,
+        net_income: row.try_get("net_income")?,
+        net_asset_value_per_share: row.try_get("net_asset_value_per_share")?,
+        sales_per_share: row.try_get("sales_per_share")?,
+        earnings_per_share: row.try_get("earnings_per_share")?,
+        profit_before_tax: row.try_get("profit_before_tax")?,
+        return_on_equity: row.try_get("return_on_equity")?,
+        return_on_assets: row.try_get("return_on_assets")?,
+        serial: row.try_get("serial")?,
+        year: row.try_get("year")?,
+    })
+}
+
 /// 取得季度財報 ROE、ROA為零的數據
 pub async fn fetch_roe_or_roa_equal_to_zero(
     year: Option<i32>,
@@ -0,0 +1,305 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate, Timelike};
+use rust_decimal::Decimal;
+use sqlx::postgres::PgQueryResult;
+
+use crate::database;
+
+/// 盤中一分鐘K線
+#[derive(sqlx::FromRow, Debug, Clone, Default)]
+pub struct IntradayQuote {
+    pub security_code: String,
+    /// 交易日
+    pub date: NaiveDate,
+    /// 該分鐘K線的起始時間
+    pub bar_time: DateTime<Local>,
+    /// 開盤價
+    pub opening_price: Decimal,
+    /// 最高價
+    pub highest_price: Decimal,
+    /// 最低價
+    pub lowest_price: Decimal,
+    /// 收盤價
+    pub closing_price: Decimal,
+    /// 取樣次數
+    pub samples: i32,
+    /// 成交股數
+    pub volume: i64,
+}
+
+impl IntradayQuote {
+    /// 將一筆取樣的報價併入所屬分鐘的K線，該分鐘尚無資料時新增一筆
+    ///
+    /// `cumulative_volume` 為當日累計成交股數，扣除當日所有K線已記錄的成交股數後即為這次取樣新增的成交股數，
+    /// 站點未提供時不增加，留待下次有提供時一併計入。
+    pub async fn upsert_sample(
+        security_code: &str,
+        time: DateTime<Local>,
+        price: Decimal,
+        cumulative_volume: Option<i64>,
+    ) -> Result<PgQueryResult> {
+        let sql = r#"
+WITH recorded AS (
+    SELECT COALESCE(SUM(volume), 0)::bigint AS volume
+    FROM intraday_quote
+    WHERE security_code = $1 AND date = $2
+)
+INSERT INTO intraday_quote (
+    security_code, date, bar_time, opening_price, highest_price, lowest_price,
+    closing_price, samples, volume, created_time, updated_time)
+SELECT $1, $2, $3, $4, $4, $4, $4, 1, GREATEST($5 - recorded.volume, 0), now(), now()
+FROM recorded
+ON CONFLICT (security_code, bar_time) DO UPDATE SET
+    highest_price = GREATEST(intraday_quote.highest_price, EXCLUDED.highest_price),
+    lowest_price = LEAST(intraday_quote.lowest_price, EXCLUDED.lowest_price),
+    closing_price = EXCLUDED.closing_price,
+    samples = intraday_quote.samples + 1,
+    volume = intraday_quote.volume + EXCLUDED.volume,
+    updated_time = now();
+"#;
+        sqlx::query(sql)
+            .bind(security_code)
+            .bind(time.date_naive())
+            .bind(bar_time(time))
+            .bind(price)
+            .bind(cumulative_volume)
+            .execute(database::get_connection())
+            .await
+            .context(format!(
+                "Failed to IntradayQuote::upsert_sample({},{},{},{:?}) from database",
+                security_code, time, price, cumulative_volume
+            ))
+    }
+
+    /// 取得指定股票在指定交易日的一分鐘K線(依時間由早到晚排序)
+    pub async fn fetch(security_code: &str, date: NaiveDate) -> Result<Vec<IntradayQuote>> {
+        let sql = r#"
+SELECT
+    security_code,
+    date,
+    bar_time,
+    opening_price,
+    highest_price,
+    lowest_price,
+    closing_price,
+    samples,
+    volume
+FROM intraday_quote
+WHERE security_code = $1 AND date = $2
+ORDER BY bar_time;
+"#;
+        sqlx::query_as::<_, IntradayQuote>(sql)
+            .bind(security_code)
+            .bind(date)
+            .fetch_all(database::get_connection())
+            .await
+            .context(format!(
+                "Failed to IntradayQuote::fetch({},{}) from database",
+                security_code, date
+            ))
+    }
+}
+
+/// 盤中K線彙總成的日K與官方收盤數據
+#[derive(sqlx::FromRow, Debug, Clone, Default)]
+pub struct IntradayDailyRollup {
+    pub security_code: String,
+    pub date: NaiveDate,
+    /// 盤中K線彙總的開盤價
+    pub opening_price: Decimal,
+    /// 盤中K線彙總的最高價
+    pub highest_price: Decimal,
+    /// 盤中K線彙總的最低價
+    pub lowest_price: Decimal,
+    /// 盤中K線彙總的收盤價
+    pub closing_price: Decimal,
+    /// 一分鐘K線的筆數
+    pub bars: i64,
+    /// 取樣次數
+    pub samples: i64,
+    /// 盤中K線合計的成交股數
+    pub volume: i64,
+    /// 官方收盤數據的開盤價，尚未有收盤數據時為空
+    pub official_opening_price: Option<Decimal>,
+    /// 官方收盤數據的最高價
+    pub official_highest_price: Option<Decimal>,
+    /// 官方收盤數據的最低價
+    pub official_lowest_price: Option<Decimal>,
+    /// 官方收盤數據的收盤價
+    pub official_closing_price: Option<Decimal>,
+    /// 官方收盤數據的成交股數
+    pub official_trading_volume: Option<Decimal>,
+}
+
+impl IntradayDailyRollup {
+    /// 取得指定交易日所有股票的盤中K線彙總與官方收盤數據
+    pub async fn fetch(date: NaiveDate) -> Result<Vec<IntradayDailyRollup>> {
+        let sql = r#"
+WITH bars AS (
+    SELECT
+        security_code,
+        date,
+        (ARRAY_AGG(opening_price ORDER BY bar_time))[1] AS opening_price,
+        MAX(highest_price) AS highest_price,
+        MIN(lowest_price) AS lowest_price,
+        (ARRAY_AGG(closing_price ORDER BY bar_time DESC))[1] AS closing_price,
+        COUNT(*) AS bars,
+        SUM(samples)::bigint AS samples,
+        SUM(volume)::bigint AS volume
+    FROM intraday_quote
+    WHERE date = $1
+    GROUP BY security_code, date
+)
+SELECT
+    b.security_code,
+    b.date,
+    b.opening_price,
+    b.highest_price,
+    b.lowest_price,
+    b.closing_price,
+    b.bars,
+    b.samples,
+    b.volume,
+    dq."OpeningPrice" AS official_opening_price,
+    dq."HighestPrice" AS official_highest_price,
+    dq."LowestPrice" AS official_lowest_price,
+    dq."ClosingPrice" AS official_closing_price,
+    dq."TradingVolume" AS official_trading_volume
+FROM bars AS b
+LEFT JOIN "DailyQuotes" AS dq ON dq."SecurityCode" = b.security_code AND dq."Date" = b.date
+ORDER BY b.security_code;
+"#;
+        sqlx::query_as::<_, IntradayDailyRollup>(sql)
+            .bind(date)
+            .fetch_all(database::get_connection())
+            .await
+            .context(format!(
+                "Failed to IntradayDailyRollup::fetch({}) from database",
+                date
+            ))
+    }
+
+    /// 與官方收盤數據比對，回傳不一致的項目
+    ///
+    /// 取樣只能取得部分成交價，所以盤中最高、最低價只要落在官方的區間內即視為一致，
+    /// 收盤價則需與官方相同，盤中K線合計的成交股數不會超過官方的成交股數。
+    pub fn discrepancies(&self) -> Vec<String> {
+        let mut discrepancies = Vec::new();
+
+        let (Some(highest), Some(lowest), Some(closing)) = (
+            self.official_highest_price,
+            self.official_lowest_price,
+            self.official_closing_price,
+        ) else {
+            discrepancies.push("尚未有官方收盤數據".to_string());
+            return discrepancies;
+        };
+
+        if self.highest_price > highest {
+            discrepancies.push(format!(
+                "盤中最高價 {} 高於官方最高價 {}",
+                self.highest_price.normalize(),
+                highest.normalize()
+            ));
+        }
+
+        if self.lowest_price < lowest {
+            discrepancies.push(format!(
+                "盤中最低價 {} 低於官方最低價 {}",
+                self.lowest_price.normalize(),
+                lowest.normalize()
+            ));
+        }
+
+        if self.closing_price != closing {
+            discrepancies.push(format!(
+                "盤中收盤價 {} 與官方收盤價 {} 不同",
+                self.closing_price.normalize(),
+                closing.normalize()
+            ));
+        }
+
+        if let Some(trading_volume) = self.official_trading_volume {
+            if Decimal::from(self.volume) > trading_volume {
+                discrepancies.push(format!(
+                    "盤中成交股數 {} 高於官方成交股數 {}",
+                    self.volume,
+                    trading_volume.normalize()
+                ));
+            }
+        }
+
+        discrepancies
+    }
+}
+
+/// 取得時間所屬的一分鐘K線起始時間
+pub fn bar_time(time: DateTime<Local>) -> DateTime<Local> {
+    time.with_second(0)
+        .and_then(|t| t.with_nanosecond(0))
+        .unwrap_or(time)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    use crate::logging;
+
+    use super::*;
+
+    #[test]
+    fn test_bar_time() {
+        let time = Local.with_ymd_and_hms(2024, 3, 4, 9, 15, 42).unwrap();
+        assert_eq!(
+            bar_time(time),
+            Local.with_ymd_and_hms(2024, 3, 4, 9, 15, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_discrepancies() {
+        let mut rollup = IntradayDailyRollup {
+            highest_price: dec!(101),
+            lowest_price: dec!(98),
+            closing_price: dec!(100),
+            ..Default::default()
+        };
+        assert_eq!(rollup.discrepancies().len(), 1);
+
+        rollup.official_highest_price = Some(dec!(102));
+        rollup.official_lowest_price = Some(dec!(97.5));
+        rollup.official_closing_price = Some(dec!(100));
+        rollup.official_trading_volume = Some(dec!(5000));
+        rollup.volume = 4000;
+        assert!(rollup.discrepancies().is_empty());
+
+        rollup.volume = 6000;
+        assert_eq!(rollup.discrepancies().len(), 1);
+        rollup.volume = 4000;
+
+        rollup.official_highest_price = Some(dec!(100.5));
+        rollup.official_closing_price = Some(dec!(100.5));
+        assert_eq!(rollup.discrepancies().len(), 2);
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_fetch_daily_rollup() {
+        dotenv::dotenv().ok();
+        logging::debug_file_async("開始 IntradayDailyRollup::fetch".to_string());
+
+        match IntradayDailyRollup::fetch(Local::now().date_naive()).await {
+            Ok(rollups) => {
+                logging::debug_file_async(format!("IntradayDailyRollup::fetch:{:#?}", rollups))
+            }
+            Err(why) => logging::debug_file_async(format!(
+                "Failed to IntradayDailyRollup::fetch because {:?}",
+                why
+            )),
+        }
+
+        logging::debug_file_async("結束 IntradayDailyRollup::fetch".to_string());
+    }
+}
@@ -0,0 +1,256 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use sqlx::postgres::PgQueryResult;
+
+use crate::database;
+
+/// 排程工作的一次執行記錄
+#[derive(sqlx::FromRow, Debug, Clone, Default)]
+pub struct JobRun {
+    pub serial: i64,
+    /// 工作名稱
+    pub job_name: String,
+    /// 觸發方式 schedule、catch_up、manual
+    pub trigger: String,
+    /// 對應的排程時間，手動觸發時為 None
+    pub scheduled_time: Option<DateTime<Local>>,
+    /// 執行參數(JSON)
+    pub parameters: String,
+    /// 狀態 deferred、running、succeeded、failed、cancelled、interrupted
+    pub status: String,
+    /// 失敗的原因
+    pub error: String,
+    pub started_time: DateTime<Local>,
+    pub finished_time: Option<DateTime<Local>>,
+    /// 順延執行的時間，狀態為 deferred 時有值
+    pub deferred_time: Option<DateTime<Local>>,
+    /// 執行的實例，與排程租約的持有者相同
+    pub instance: String,
+}
+
+impl JobRun {
+    pub fn new(
+        job_name: &str,
+        trigger: &str,
+        scheduled_time: Option<DateTime<Local>>,
+        parameters: String,
+    ) -> Self {
+        JobRun {
+            job_name: job_name.to_string(),
+            trigger: trigger.to_string(),
+            scheduled_time,
+            parameters,
+            status: "running".to_string(),
+            started_time: Local::now(),
+            ..Default::default()
+        }
+    }
+
+    /// 新增執行記錄並回傳序號，同一個排程時間已有記錄時回傳 None，
+    /// 只有順延等待中或服務中止而被標記為 interrupted 的記錄可以重新取得
+    pub async fn claim(&mut self) -> Result<Option<i64>> {
+        let sql = r#"
+INSERT INTO job_runs (job_name, trigger, scheduled_time, parameters, status, started_time, instance)
+VALUES ($1, $2, $3, $4::jsonb, 'running', $5, $6)
+ON CONFLICT (job_name, scheduled_time) WHERE scheduled_time IS NOT NULL
+DO UPDATE SET
+    trigger = excluded.trigger,
+    instance = excluded.instance,
+    parameters = excluded.parameters,
+    status = 'running',
+    error = '',
+    started_time = excluded.started_time,
+    finished_time = NULL,
+    deferred_time = NULL,
+    updated_time = now()
+WHERE job_runs.status IN ('deferred', 'interrupted')
+RETURNING serial;
+"#;
+        let serial: Option<(i64,)> = sqlx::query_as(sql)
+            .bind(&self.job_name)
+            .bind(&self.trigger)
+            .bind(self.scheduled_time)
+            .bind(&self.parameters)
+            .bind(self.started_time)
+            .bind(&self.instance)
+            .fetch_optional(database::get_connection())
+            .await
+            .context(format!("Failed to JobRun::claim({:?}) from database", self))?;
+
+        let serial = serial.map(|(serial,)| serial);
+        self.serial = serial.unwrap_or_default();
+
+        Ok(serial)
+    }
+
+    /// 記錄排程順延到 deferred_time 才執行，同一個排程時間已有記錄時回傳 false
+    pub async fn defer(&self, deferred_time: DateTime<Local>) -> Result<bool> {
+        let sql = r#"
+INSERT INTO job_runs (job_name, trigger, scheduled_time, parameters, status, started_time, deferred_time, instance)
+VALUES ($1, $2, $3, $4::jsonb, 'deferred', $5, $6, $7)
+ON CONFLICT (job_name, scheduled_time) WHERE scheduled_time IS NOT NULL
+DO UPDATE SET
+    parameters = excluded.parameters,
+    instance = excluded.instance,
+    status = 'deferred',
+    deferred_time = excluded.deferred_time,
+    updated_time = now()
+WHERE job_runs.status = 'interrupted'
+RETURNING serial;
+"#;
+        let serial: Option<(i64,)> = sqlx::query_as(sql)
+            .bind(&self.job_name)
+            .bind(&self.trigger)
+            .bind(self.scheduled_time)
+            .bind(&self.parameters)
+            .bind(self.started_time)
+            .bind(deferred_time)
+            .bind(&self.instance)
+            .fetch_optional(database::get_connection())
+            .await
+            .context(format!("Failed to JobRun::defer({:?}) from database", self))?;
+
+        Ok(serial.is_some())
+    }
+
+    /// 取得所有順延等待中的記錄，重新啟動後要繼續等待執行
+    pub async fn fetch_deferred() -> Result<Vec<JobRun>> {
+        let sql = r#"
+SELECT
+    serial,
+    job_name,
+    trigger,
+    scheduled_time,
+    parameters::text AS parameters,
+    status,
+    error,
+    started_time,
+    finished_time,
+    deferred_time,
+    instance
+FROM job_runs
+WHERE status = 'deferred'
+ORDER BY deferred_time;
+"#;
+        sqlx::query_as::<_, JobRun>(sql)
+            .fetch_all(database::get_connection())
+            .await
+            .context("Failed to JobRun::fetch_deferred() from database")
+    }
+
+    /// 記錄執行的結果
+    pub async fn finish(serial: i64, status: &str, error: &str) -> Result<PgQueryResult> {
+        let sql = r#"
+UPDATE job_runs
+SET status = $2, error = $3, finished_time = now(), updated_time = now()
+WHERE serial = $1;
+"#;
+        sqlx::query(sql)
+            .bind(serial)
+            .bind(status)
+            .bind(error)
+            .execute(database::get_connection())
+            .await
+            .context(format!(
+                "Failed to JobRun::finish({}, {}) from database",
+                serial, status
+            ))
+    }
+
+    /// 將上次服務中止時仍在執行的記錄標記為 interrupted，讓啟動時可以補執行，
+    /// 只適用於單一實例，多個實例時需改用 interrupt_others
+    pub async fn mark_interrupted() -> Result<PgQueryResult> {
+        let sql = r#"
+UPDATE job_runs
+SET status = 'interrupted', finished_time = now(), updated_time = now()
+WHERE status = 'running';
+"#;
+        sqlx::query(sql)
+            .execute(database::get_connection())
+            .await
+            .context("Failed to JobRun::mark_interrupted() from database")
+    }
+
+    /// 將指定工作由其他實例執行中的記錄標記為 interrupted，在取得工作的租約時呼叫，
+    /// 此時前一個持有者的租約已過期，它執行中的記錄不會再有結果
+    pub async fn interrupt_others(job_name: &str, instance: &str) -> Result<PgQueryResult> {
+        let sql = r#"
+UPDATE job_runs
+SET status = 'interrupted', finished_time = now(), updated_time = now()
+WHERE job_name = $1 AND status = 'running' AND instance <> $2;
+"#;
+        sqlx::query(sql)
+            .bind(job_name)
+            .bind(instance)
+            .execute(database::get_connection())
+            .await
+            .context(format!(
+                "Failed to JobRun::interrupt_others({}, {}) from database",
+                job_name, instance
+            ))
+    }
+
+    /// 取得工作最近一次由排程觸發(含補執行)的記錄
+    pub async fn fetch_last_scheduled(job_name: &str) -> Result<Option<JobRun>> {
+        let sql = r#"
+SELECT
+    serial,
+    job_name,
+    trigger,
+    scheduled_time,
+    parameters::text AS parameters,
+    status,
+    error,
+    started_time,
+    finished_time,
+    deferred_time,
+    instance
+FROM job_runs
+WHERE job_name = $1 AND scheduled_time IS NOT NULL
+ORDER BY scheduled_time DESC
+LIMIT 1;
+"#;
+        sqlx::query_as::<_, JobRun>(sql)
+            .bind(job_name)
+            .fetch_optional(database::get_connection())
+            .await
+            .context(format!(
+                "Failed to JobRun::fetch_last_scheduled({}) from database",
+                job_name
+            ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logging;
+
+    use super::*;
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_claim_and_finish() {
+        dotenv::dotenv().ok();
+        let scheduled_time = Local::now();
+        let mut run = JobRun::new(
+            "test",
+            "schedule",
+            Some(scheduled_time),
+            r#"{"date":"2024-01-02"}"#.to_string(),
+        );
+
+        match run.claim().await {
+            Ok(Some(serial)) => {
+                let mut again = JobRun::new("test", "catch_up", Some(scheduled_time), "{}".into());
+                assert_eq!(again.claim().await.unwrap(), None);
+                JobRun::finish(serial, "succeeded", "").await.unwrap();
+                let last = JobRun::fetch_last_scheduled("test").await.unwrap();
+                logging::debug_file_async(format!("last: {:?}", last));
+            }
+            Ok(None) => panic!("the run should be claimed"),
+            Err(why) => {
+                logging::debug_file_async(format!("Failed to claim because {:?}", why));
+            }
+        }
+    }
+}
@@ -1,3 +1,7 @@
+/// 以除權息還原股價計算的歷史最高、最低價
+pub mod adjusted_quote_history_record;
+/// 股價提醒規則
+pub mod alert_rule;
 /// 每日股票報價數據
 pub mod daily_quote;
 /// 年度股利發放明細與總計
@@ -9,15 +13,25 @@ pub mod dividend_record_detail_more;
 /// 公司每季獲利能力
 pub mod financial_statement;
 pub mod index;
+/// 盤中一分鐘K線
+pub mod intraday_quote;
+/// 排程工作的執行記錄
+pub mod job_run;
 pub mod last_daily_quotes;
+/// 除權息還原股價的調整因子
+pub mod price_adjustment_factor;
 pub mod revenue;
 pub mod stock;
 mod stock_index;
 /// 持股名細
 pub mod stock_ownership_details;
+/// 股票交易明細
+pub mod stock_transaction;
 mod stock_word;
 // 股票交易所的市場
 pub mod stock_exchange_market;
+/// 證交所公告的休市日與交易日
+pub mod trading_calendar;
 
 pub mod config;
 /// 每日市值記錄各
@@ -26,12 +40,12 @@ pub mod daily_money_history;
 pub mod daily_money_history_detail;
 /// 每日市值記錄各檔股票的股數明細
 pub mod daily_money_history_detail_more;
+/// 每日市值記錄各會員的市值、成本與損益
+pub mod daily_money_history_member;
 /// 股票便宜、合理、昂貴價的估算
 pub mod estimate;
 /// 股票歷史最高、最低等數據
 pub mod quote_history_record;
-/// 追踪即時股價，當超過或低於設定的數值時發送TG訊息
-pub mod trace;
 /// 殖利率排行
 pub mod yield_rank;
 /// 每日股票價格估值統計
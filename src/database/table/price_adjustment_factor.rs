@@ -0,0 +1,136 @@
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use sqlx::{postgres::PgRow, Row};
+
+use crate::database;
+
+/// 除權息還原股價的調整因子
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PriceAdjustmentFactor {
+    /// 股票代號
+    pub security_code: String,
+    /// 除權息日
+    pub date: NaiveDate,
+    /// 除權息前一個交易日的收盤價
+    pub previous_closing_price: Decimal,
+    /// 每股現金股利(元)
+    pub cash_dividend: Decimal,
+    /// 每股股票股利(元)
+    pub stock_dividend: Decimal,
+    /// 除權息參考價
+    pub reference_price: Decimal,
+    /// 當次除權息的調整因子
+    pub factor: Decimal,
+    /// 本次及之後所有除權息調整因子的乘積
+    pub cumulative_factor: Decimal,
+}
+
+impl PriceAdjustmentFactor {
+    /// 取得指定股票的調整因子(依除權息日由舊到新排序)
+    pub async fn fetch(security_code: &str) -> Result<Vec<PriceAdjustmentFactor>> {
+        let sql = r#"
+SELECT
+    security_code,
+    date,
+    previous_closing_price,
+    cash_dividend,
+    stock_dividend,
+    reference_price,
+    factor,
+    cumulative_factor
+FROM price_adjustment_factor
+WHERE security_code = $1
+ORDER BY date;
+"#;
+        sqlx::query(sql)
+            .bind(security_code)
+            .try_map(|row: PgRow| {
+                Ok(PriceAdjustmentFactor {
+                    security_code: row.try_get("security_code")?,
+                    date: row.try_get("date")?,
+                    previous_closing_price: row.try_get("previous_closing_price")?,
+                    cash_dividend: row.try_get("cash_dividend")?,
+                    stock_dividend: row.try_get("stock_dividend")?,
+                    reference_price: row.try_get("reference_price")?,
+                    factor: row.try_get("factor")?,
+                    cumulative_factor: row.try_get("cumulative_factor")?,
+                })
+            })
+            .fetch_all(database::get_connection())
+            .await
+            .context(format!(
+                "Failed to PriceAdjustmentFactor::fetch({}) from database",
+                security_code
+            ))
+    }
+
+    /// 以新的計算結果取代指定股票全部的調整因子
+    pub async fn replace(security_code: &str, factors: &[PriceAdjustmentFactor]) -> Result<()> {
+        let mut tx = database::get_tx()
+            .await
+            .context("Failed to get_tx in price_adjustment_factor")?;
+
+        sqlx::query("DELETE FROM price_adjustment_factor WHERE security_code = $1;")
+            .bind(security_code)
+            .execute(&mut *tx)
+            .await
+            .context(format!(
+                "Failed to delete({}) price_adjustment_factor from database",
+                security_code
+            ))?;
+
+        let sql = r#"
+INSERT INTO price_adjustment_factor (
+    security_code, date, previous_closing_price, cash_dividend, stock_dividend,
+    reference_price, factor, cumulative_factor, created_time, updated_time)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, now(), now());
+"#;
+        for factor in factors {
+            sqlx::query(sql)
+                .bind(&factor.security_code)
+                .bind(factor.date)
+                .bind(factor.previous_closing_price)
+                .bind(factor.cash_dividend)
+                .bind(factor.stock_dividend)
+                .bind(factor.reference_price)
+                .bind(factor.factor)
+                .bind(factor.cumulative_factor)
+                .execute(&mut *tx)
+                .await
+                .context(format!(
+                    "Failed to insert({:?}) price_adjustment_factor from database",
+                    factor
+                ))?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logging;
+
+    use super::*;
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_fetch() {
+        dotenv::dotenv().ok();
+        logging::debug_file_async("開始 PriceAdjustmentFactor::fetch".to_string());
+        match PriceAdjustmentFactor::fetch("2330").await {
+            Ok(factors) => {
+                logging::debug_file_async(format!("PriceAdjustmentFactor::fetch:{:#?}", factors))
+            }
+            Err(why) => logging::debug_file_async(format!(
+                "Failed to PriceAdjustmentFactor::fetch because {:?}",
+                why
+            )),
+        }
+
+        logging::debug_file_async("結束 PriceAdjustmentFactor::fetch".to_string());
+    }
+}
@@ -58,6 +58,43 @@ FROM
         .context("Failed to QuoteHistoryRecord.fetch from database")
     }
 
+    /// 分頁取得指定股票歷史最高、最低等數據，未指定股票時取得所有股票(依股票代號排序)
+    pub async fn fetch_page(
+        security_codes: &[String],
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<QuoteHistoryRecord>> {
+        sqlx::query_as::<_, QuoteHistoryRecord>(
+            r#"
+SELECT
+    security_code,
+    maximum_price,
+    maximum_price_date_on,
+    minimum_price,
+    minimum_price_date_on,
+    "maximum_price-to-book_ratio" as maximum_price_to_book_ratio,
+    "maximum_price-to-book_ratio_date_on" as maximum_price_to_book_ratio_date_on,
+    "minimum_price-to-book_ratio" as minimum_price_to_book_ratio,
+    "minimum_price-to-book_ratio_date_on" as minimum_price_to_book_ratio_date_on
+FROM
+    quote_history_record
+WHERE
+    cardinality($1::varchar[]) = 0 OR security_code = ANY($1)
+ORDER BY security_code
+LIMIT $2 OFFSET $3
+"#,
+        )
+        .bind(security_codes)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(database::get_connection())
+        .await
+        .context(format!(
+            "Failed to QuoteHistoryRecord.fetch_page({:?},{},{}) from database",
+            security_codes, offset, limit
+        ))
+    }
+
     pub async fn upsert(&self) -> Result<PgQueryResult> {
         let sql = r#"
 INSERT INTO
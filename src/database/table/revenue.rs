@@ -116,6 +116,71 @@ SET
             .await
             .context(format!("Failed to upsert({:#?}) from database", self))
     }
+
+    /// 分頁取得指定股票在月份區間內的營收(依月份由新到舊排序)，月份格式為 yyyymm
+    pub async fn fetch_page_by_security_code(
+        security_code: &str,
+        start_month: i64,
+        end_month: i64,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<Revenue>> {
+        sqlx::query(
+            r#"
+select
+    "SecurityCode",
+    "Date",
+    "Monthly",
+    "LastMonth",
+    "LastYearThisMonth",
+    "MonthlyAccumulated",
+    "LastYearMonthlyAccumulated",
+    "ComparedWithLastMonth",
+    "ComparedWithLastYearSameMonth",
+    "AccumulatedComparedWithLastYear",
+    "CreateTime",
+    avg_price,
+    lowest_price,
+    highest_price
+from "Revenue"
+where
+    "SecurityCode" = $1 and "Date" >= $2 and "Date" <= $3
+order by "Date" desc
+limit $4 offset $5
+"#,
+        )
+        .bind(security_code)
+        .bind(start_month)
+        .bind(end_month)
+        .bind(limit)
+        .bind(offset)
+        .try_map(Revenue::row_to_entity)
+        .fetch_all(database::get_connection())
+        .await
+        .context(format!(
+            "Failed to Revenue::fetch_page_by_security_code({},{},{},{},{}) from database",
+            security_code, start_month, end_month, offset, limit
+        ))
+    }
+
+    fn row_to_entity(row: PgRow) -> Result<Revenue, sqlx::Error> {
+        Ok(Revenue {
+            date: row.try_get("Date")?,
+            security_code: row.try_get("SecurityCode")?,
+            monthly: row.try_get("Monthly")?,
+            last_month: row.try_get("LastMonth")?,
+            last_year_this_month: row.try_get("LastYearThisMonth")?,
+            monthly_accumulated: row.try_get("MonthlyAccumulated")?,
+            last_year_monthly_accumulated: row.try_get("LastYearMonthlyAccumulated")?,
+            compared_with_last_month: row.try_get("ComparedWithLastMonth")?,
+            compared_with_last_year_same_month: row.try_get("ComparedWithLastYearSameMonth")?,
+            accumulated_compared_with_last_year: row.try_get("AccumulatedComparedWithLastYear")?,
+            avg_price: row.try_get("avg_price")?,
+            lowest_price: row.try_get("lowest_price")?,
+            highest_price: row.try_get("highest_price")?,
+            create_time: row.try_get("CreateTime")?,
+        })
+    }
 }
 
 impl Default for Revenue {
@@ -261,38 +326,7 @@ order by "Serial" desc
     )
     .bind(last_month_int)
     .bind(two_month_ago_int)
-    .try_map(|row: PgRow| {
-        let date = row.try_get("Date")?;
-        let security_code = row.try_get("SecurityCode")?;
-        let monthly = row.try_get("Monthly")?;
-        let last_month = row.try_get("LastMonth")?;
-        let last_year_this_month = row.try_get("LastYearThisMonth")?;
-        let monthly_accumulated = row.try_get("MonthlyAccumulated")?;
-        let last_year_monthly_accumulated = row.try_get("LastYearMonthlyAccumulated")?;
-        let compared_with_last_month = row.try_get("ComparedWithLastMonth")?;
-        let compared_with_last_year_same_month = row.try_get("ComparedWithLastYearSameMonth")?;
-        let accumulated_compared_with_last_year = row.try_get("AccumulatedComparedWithLastYear")?;
-        let avg_price = row.try_get("avg_price")?;
-        let lowest_price = row.try_get("lowest_price")?;
-        let highest_price = row.try_get("highest_price")?;
-        let create_time = row.try_get("CreateTime")?;
-        Ok(Revenue {
-            date,
-            security_code,
-            monthly,
-            last_month,
-            last_year_this_month,
-            monthly_accumulated,
-            last_year_monthly_accumulated,
-            compared_with_last_month,
-            compared_with_last_year_same_month,
-            accumulated_compared_with_last_year,
-            avg_price,
-            lowest_price,
-            highest_price,
-            create_time,
-        })
-    })
+    .try_map(Revenue::row_to_entity)
     .fetch_all(database::get_connection())
     .await?;
 
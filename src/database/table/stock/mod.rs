@@ -62,6 +62,11 @@ impl Stock {
         is_preference_shares(&self.stock_symbol)
     }
 
+    /// 是否為 ETF
+    pub fn is_etf(&self) -> bool {
+        is_etf(&self.stock_symbol)
+    }
+
     /// 是否為臺灣存託憑證
     pub fn is_tdr(&self) -> bool {
         self.name.contains("-DR")
@@ -413,6 +418,11 @@ pub fn is_preference_shares(stock_symbol: &str) -> bool {
         .any(|c| c.is_ascii_uppercase() || c.is_ascii_lowercase())
 }
 
+/// 是否為 ETF(台股 ETF 的代號皆以 00 開頭)
+pub fn is_etf(stock_symbol: &str) -> bool {
+    stock_symbol.starts_with("00")
+}
+
 #[cfg(test)]
 mod tests {
     use crate::logging;
@@ -1,5 +1,5 @@
-use anyhow::Result;
-use chrono::{DateTime, Local};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate};
 use rust_decimal::Decimal;
 use sqlx::{postgres::PgQueryResult, Postgres, Transaction};
 
@@ -20,6 +20,8 @@ pub struct StockOwnershipDetail {
     pub share_price_average: Decimal,
     /// 買入成本
     pub holding_cost: Decimal,
+    /// 扣除已領現金股利後的每股成本
+    pub current_cost_per_share: Decimal,
     /// 是否賣出
     pub is_sold: bool,
     /// 累積現金股利(元)
@@ -30,6 +32,10 @@ pub struct StockOwnershipDetail {
     pub cumulate_dividends_stock_money: Decimal,
     /// 總計累積股利(元)
     pub cumulate_dividends_total: Decimal,
+    /// 交易日期
+    pub date: NaiveDate,
+    /// 對應的買進交易 stock_transaction.serial
+    pub transaction_serial: Option<i64>,
     pub created_time: DateTime<Local>,
 }
 
@@ -42,11 +48,14 @@ impl StockOwnershipDetail {
             share_quantity: Default::default(),
             share_price_average: Default::default(),
             holding_cost: Default::default(),
+            current_cost_per_share: Default::default(),
             is_sold: false,
             cumulate_dividends_cash: Default::default(),
             cumulate_dividends_stock: Default::default(),
             cumulate_dividends_stock_money: Default::default(),
             cumulate_dividends_total: Default::default(),
+            date: Default::default(),
+            transaction_serial: None,
             created_time: Default::default(),
         }
     }
@@ -62,11 +71,14 @@ SELECT
     holding_cost,
     created_time,
     share_price_average,
+    current_cost_per_share,
     is_sold,
     cumulate_dividends_cash,
     cumulate_dividends_stock,
     cumulate_dividends_stock_money,
-    cumulate_dividends_total
+    cumulate_dividends_total,
+    date,
+    transaction_serial
 FROM stock_ownership_details
 WHERE is_sold = false";
         let (sql, bind_params) = security_codes
@@ -93,33 +105,87 @@ WHERE is_sold = false";
         Ok(rows)
     }
 
-    /// 更新指定股票累積的股利
-    pub async fn update_cumulate_dividends(
-        &self,
+    /// 依對應的買進交易新增或更新持股，回傳持股的序號
+    pub async fn upsert_by_transaction_serial(
+        &mut self,
         tx: &mut Option<Transaction<'_, Postgres>>,
-    ) -> Result<PgQueryResult> {
+    ) -> Result<i64> {
         let sql = r#"
-UPDATE stock_ownership_details
-SET
-    cumulate_dividends_cash = $2,
-    cumulate_dividends_stock = $3,
-    cumulate_dividends_stock_money = $4,
-    cumulate_dividends_total = $5
-WHERE
-    serial = $1
+INSERT INTO stock_ownership_details (
+    transaction_serial, member_id, security_code, share_quantity, holding_cost,
+    share_price_average, current_cost_per_share, is_sold, cumulate_dividends_cash,
+    cumulate_dividends_stock, cumulate_dividends_stock_money, cumulate_dividends_total, date
+)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+ON CONFLICT (transaction_serial) DO UPDATE SET
+    member_id = EXCLUDED.member_id,
+    security_code = EXCLUDED.security_code,
+    share_quantity = EXCLUDED.share_quantity,
+    holding_cost = EXCLUDED.holding_cost,
+    share_price_average = EXCLUDED.share_price_average,
+    current_cost_per_share = EXCLUDED.current_cost_per_share,
+    is_sold = EXCLUDED.is_sold,
+    cumulate_dividends_cash = EXCLUDED.cumulate_dividends_cash,
+    cumulate_dividends_stock = EXCLUDED.cumulate_dividends_stock,
+    cumulate_dividends_stock_money = EXCLUDED.cumulate_dividends_stock_money,
+    cumulate_dividends_total = EXCLUDED.cumulate_dividends_total,
+    date = EXCLUDED.date
+RETURNING serial;
 "#;
-        let query = sqlx::query(sql)
-            .bind(self.serial)
+        let query = sqlx::query_as(sql)
+            .bind(self.transaction_serial)
+            .bind(self.member_id)
+            .bind(&self.security_code)
+            .bind(self.share_quantity)
+            .bind(self.holding_cost)
+            .bind(self.share_price_average)
+            .bind(self.current_cost_per_share)
+            .bind(self.is_sold)
             .bind(self.cumulate_dividends_cash)
             .bind(self.cumulate_dividends_stock)
             .bind(self.cumulate_dividends_stock_money)
-            .bind(self.cumulate_dividends_total);
+            .bind(self.cumulate_dividends_total)
+            .bind(self.date);
+        let row: (i64,) = match tx {
+            None => query.fetch_one(database::get_connection()).await,
+            Some(t) => query.fetch_one(&mut **t).await,
+        }
+        .context(format!(
+            "Failed to upsert_by_transaction_serial({:?}) from database",
+            self.transaction_serial
+        ))?;
+        self.serial = row.0;
+
+        Ok(self.serial)
+    }
+
+    /// 刪除指定會員與股票中，對應的買進交易已不存在的持股
+    pub async fn delete_stale_transactions(
+        member_id: i64,
+        security_code: &str,
+        transaction_serials: &[i64],
+        tx: &mut Option<Transaction<'_, Postgres>>,
+    ) -> Result<PgQueryResult> {
+        let sql = r#"
+DELETE FROM stock_ownership_details
+WHERE member_id = $1
+    AND security_code = $2
+    AND transaction_serial IS NOT NULL
+    AND NOT (transaction_serial = ANY($3));
+"#;
+        let query = sqlx::query(sql)
+            .bind(member_id)
+            .bind(security_code)
+            .bind(transaction_serials);
         let result = match tx {
-            None => query.execute(database::get_connection()).await?,
-            Some(t) => query.execute(&mut **t).await?,
+            None => query.execute(database::get_connection()).await,
+            Some(t) => query.execute(&mut **t).await,
         };
 
-        Ok(result)
+        result.context(format!(
+            "Failed to delete_stale_transactions({},{}) from database",
+            member_id, security_code
+        ))
     }
 }
 
@@ -138,11 +204,14 @@ impl Clone for StockOwnershipDetail {
             share_quantity: self.share_quantity,
             share_price_average: self.share_price_average,
             holding_cost: self.holding_cost,
+            current_cost_per_share: self.current_cost_per_share,
             is_sold: self.is_sold,
             cumulate_dividends_cash: self.cumulate_dividends_cash,
             cumulate_dividends_stock: self.cumulate_dividends_stock,
             cumulate_dividends_stock_money: self.cumulate_dividends_stock_money,
             cumulate_dividends_total: self.cumulate_dividends_total,
+            date: self.date,
+            transaction_serial: self.transaction_serial,
             created_time: self.created_time,
         }
     }
@@ -0,0 +1,264 @@
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use sqlx::{postgres::PgQueryResult, postgres::PgRow, Postgres, Row, Transaction};
+use strum_macros::{AsRefStr, Display, EnumString};
+
+use crate::database;
+
+/// 交易的種類
+#[derive(Debug, Copy, Clone, PartialEq, Eq, EnumString, Display, AsRefStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum TransactionKind {
+    /// 買進
+    Buy,
+    /// 賣出
+    Sell,
+    /// 分割(含減資換股)
+    Split,
+    /// 現金股利
+    CashDividend,
+    /// 股票股利
+    StockDividend,
+}
+
+/// 股票交易明細
+#[derive(Debug, Clone, PartialEq)]
+pub struct StockTransaction {
+    pub serial: i64,
+    /// 會員編號
+    pub member_id: i64,
+    /// 股票代號
+    pub security_code: String,
+    /// 交易日(除權息、分割為生效日)
+    pub trade_date: NaiveDate,
+    pub kind: TransactionKind,
+    /// 買進、賣出的股數
+    pub quantity: i64,
+    /// 買進、賣出為每股價格；分割為每股換發的股數；股利為每股股利(元)
+    pub price: Decimal,
+    /// 手續費(元)
+    pub fee: Decimal,
+    /// 交易稅(元)
+    pub tax: Decimal,
+    /// 由舊持股轉入的買進交易，此日(含)之前的分割與除權息已反映在股數與累積股利內
+    pub applied_until: Option<NaiveDate>,
+    /// 由舊持股轉入時已累積的現金股利(元)
+    pub opening_dividends_cash: Decimal,
+    /// 由舊持股轉入時已累積的股票股利(股)
+    pub opening_dividends_stock: Decimal,
+    /// 由舊持股轉入時已累積的股票股利(元)
+    pub opening_dividends_stock_money: Decimal,
+}
+
+impl StockTransaction {
+    pub fn new(
+        member_id: i64,
+        security_code: &str,
+        trade_date: NaiveDate,
+        kind: TransactionKind,
+    ) -> Self {
+        StockTransaction {
+            serial: 0,
+            member_id,
+            security_code: security_code.to_string(),
+            trade_date,
+            kind,
+            quantity: 0,
+            price: Decimal::ZERO,
+            fee: Decimal::ZERO,
+            tax: Decimal::ZERO,
+            applied_until: None,
+            opening_dividends_cash: Decimal::ZERO,
+            opening_dividends_stock: Decimal::ZERO,
+            opening_dividends_stock_money: Decimal::ZERO,
+        }
+    }
+
+    /// 取得指定會員與股票的全部交易(依交易日、序號排序)
+    pub async fn fetch(member_id: i64, security_code: &str) -> Result<Vec<StockTransaction>> {
+        let sql = r#"
+SELECT
+    serial, member_id, security_code, trade_date, kind, quantity, price, fee, tax,
+    applied_until, opening_dividends_cash, opening_dividends_stock, opening_dividends_stock_money
+FROM stock_transaction
+WHERE member_id = $1 AND security_code = $2
+ORDER BY trade_date, serial;
+"#;
+        sqlx::query(sql)
+            .bind(member_id)
+            .bind(security_code)
+            .try_map(Self::row_to_entity)
+            .fetch_all(database::get_connection())
+            .await
+            .context(format!(
+                "Failed to StockTransaction::fetch({},{}) from database",
+                member_id, security_code
+            ))
+    }
+
+    /// 新增一筆交易，回傳交易的序號
+    pub async fn insert(&mut self, tx: &mut Option<Transaction<'_, Postgres>>) -> Result<i64> {
+        let sql = r#"
+INSERT INTO stock_transaction (member_id, security_code, trade_date, kind, quantity, price, fee, tax)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+RETURNING serial;
+"#;
+        let query = sqlx::query_as(sql)
+            .bind(self.member_id)
+            .bind(&self.security_code)
+            .bind(self.trade_date)
+            .bind(self.kind.as_ref())
+            .bind(self.quantity)
+            .bind(self.price)
+            .bind(self.fee)
+            .bind(self.tax);
+        let row: (i64,) = match tx {
+            None => query.fetch_one(database::get_connection()).await,
+            Some(t) => query.fetch_one(&mut **t).await,
+        }
+        .context(format!(
+            "Failed to StockTransaction::insert({:?}) from database",
+            self
+        ))?;
+        self.serial = row.0;
+
+        Ok(self.serial)
+    }
+
+    /// 新增或更新除權息的交易，同一天同一種股利只會有一筆
+    pub async fn upsert_dividend(
+        &self,
+        tx: &mut Option<Transaction<'_, Postgres>>,
+    ) -> Result<PgQueryResult> {
+        let sql = r#"
+INSERT INTO stock_transaction (member_id, security_code, trade_date, kind, price)
+VALUES ($1, $2, $3, $4, $5)
+ON CONFLICT (member_id, security_code, kind, trade_date)
+    WHERE kind IN ('cash_dividend', 'stock_dividend')
+DO UPDATE SET
+    price = EXCLUDED.price,
+    updated_time = now();
+"#;
+        let query = sqlx::query(sql)
+            .bind(self.member_id)
+            .bind(&self.security_code)
+            .bind(self.trade_date)
+            .bind(self.kind.as_ref())
+            .bind(self.price);
+        let result = match tx {
+            None => query.execute(database::get_connection()).await,
+            Some(t) => query.execute(&mut **t).await,
+        };
+
+        result.context(format!(
+            "Failed to StockTransaction::upsert_dividend({:?}) from database",
+            self
+        ))
+    }
+
+    /// 將尚未有對應交易且未賣出的持股轉為買進交易，使持股可以由交易明細重建，
+    /// 持股的股數與累積股利已包含今天之前的分割與除權息，所以記錄在 applied_until 避免重建時重複計算，
+    /// 只需在導入交易明細時執行一次
+    pub async fn adopt_ownership_details(
+        tx: &mut Option<Transaction<'_, Postgres>>,
+    ) -> Result<PgQueryResult> {
+        let sql = r#"
+WITH lots AS (
+    SELECT
+        serial AS ownership_serial,
+        nextval(pg_get_serial_sequence('stock_transaction', 'serial')) AS serial,
+        member_id,
+        security_code,
+        date,
+        share_quantity,
+        share_price_average,
+        -holding_cost - share_quantity * share_price_average AS fee,
+        cumulate_dividends_cash,
+        cumulate_dividends_stock,
+        cumulate_dividends_stock_money
+    FROM stock_ownership_details
+    WHERE transaction_serial IS NULL AND is_sold = false
+),
+inserted AS (
+    INSERT INTO stock_transaction (
+        serial, member_id, security_code, trade_date, kind, quantity, price, fee, applied_until,
+        opening_dividends_cash, opening_dividends_stock, opening_dividends_stock_money
+    )
+    SELECT
+        serial, member_id, security_code, date, 'buy', share_quantity, share_price_average, fee,
+        CURRENT_DATE, cumulate_dividends_cash, cumulate_dividends_stock, cumulate_dividends_stock_money
+    FROM lots
+)
+UPDATE stock_ownership_details sod
+SET transaction_serial = lots.serial
+FROM lots
+WHERE sod.serial = lots.ownership_serial;
+"#;
+        let query = sqlx::query(sql);
+        let result = match tx {
+            None => query.execute(database::get_connection()).await,
+            Some(t) => query.execute(&mut **t).await,
+        };
+
+        result.context("Failed to StockTransaction::adopt_ownership_details from database")
+    }
+
+    fn row_to_entity(row: PgRow) -> Result<StockTransaction, sqlx::Error> {
+        let kind: String = row.try_get("kind")?;
+        Ok(StockTransaction {
+            serial: row.try_get("serial")?,
+            member_id: row.try_get("member_id")?,
+            security_code: row.try_get("security_code")?,
+            trade_date: row.try_get("trade_date")?,
+            kind: TransactionKind::from_str(&kind)
+                .map_err(|why| sqlx::Error::Decode(Box::new(why)))?,
+            quantity: row.try_get("quantity")?,
+            price: row.try_get("price")?,
+            fee: row.try_get("fee")?,
+            tax: row.try_get("tax")?,
+            applied_until: row.try_get("applied_until")?,
+            opening_dividends_cash: row.try_get("opening_dividends_cash")?,
+            opening_dividends_stock: row.try_get("opening_dividends_stock")?,
+            opening_dividends_stock_money: row.try_get("opening_dividends_stock_money")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logging;
+
+    use super::*;
+
+    #[test]
+    fn test_transaction_kind() {
+        assert_eq!(TransactionKind::CashDividend.as_ref(), "cash_dividend");
+        assert_eq!(
+            TransactionKind::from_str("stock_dividend").unwrap(),
+            TransactionKind::StockDividend
+        );
+        assert!(TransactionKind::from_str("transfer").is_err());
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_fetch() {
+        dotenv::dotenv().ok();
+        logging::debug_file_async("開始 StockTransaction::fetch".to_string());
+        match StockTransaction::fetch(1, "2330").await {
+            Ok(transactions) => {
+                logging::debug_file_async(format!("transactions: {:#?}", transactions));
+            }
+            Err(why) => {
+                logging::debug_file_async(format!(
+                    "Failed to StockTransaction::fetch because {:?}",
+                    why
+                ));
+            }
+        }
+        logging::debug_file_async("結束 StockTransaction::fetch".to_string());
+    }
+}
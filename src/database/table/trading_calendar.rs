@@ -0,0 +1,100 @@
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+
+use crate::database;
+
+/// 證交所公告的休市日或交易日
+#[derive(sqlx::FromRow, Debug, Clone, Default, PartialEq)]
+pub struct CalendarDate {
+    pub date: NaiveDate,
+    /// 說明，例如 農曆春節
+    pub why: String,
+    /// 是否為交易日，false 為休市日
+    pub trading: bool,
+}
+
+impl CalendarDate {
+    pub fn new(date: NaiveDate, why: String, trading: bool) -> Self {
+        CalendarDate { date, why, trading }
+    }
+
+    /// 取得指定年度的休市日與交易日
+    pub async fn fetch_by_year(year: i32) -> Result<Vec<CalendarDate>> {
+        let sql = r#"
+SELECT date, why, trading
+FROM trading_calendar
+WHERE date >= make_date($1, 1, 1) AND date < make_date($1 + 1, 1, 1)
+ORDER BY date;
+"#;
+        sqlx::query_as::<_, CalendarDate>(sql)
+            .bind(year)
+            .fetch_all(database::get_connection())
+            .await
+            .context(format!(
+                "Failed to CalendarDate::fetch_by_year({}) from database",
+                year
+            ))
+    }
+
+    /// 以新的資料取代指定年度的休市日與交易日
+    pub async fn replace_year(year: i32, dates: &[CalendarDate]) -> Result<()> {
+        let mut tx = database::get_tx()
+            .await
+            .context("Failed to get_tx in trading_calendar")?;
+
+        sqlx::query(
+            "DELETE FROM trading_calendar WHERE date >= make_date($1, 1, 1) AND date < make_date($1 + 1, 1, 1);",
+        )
+        .bind(year)
+        .execute(&mut *tx)
+        .await
+        .context(format!(
+            "Failed to CalendarDate::replace_year({}) from database",
+            year
+        ))?;
+
+        let sql = r#"
+INSERT INTO trading_calendar (date, why, trading)
+VALUES ($1, $2, $3)
+ON CONFLICT (date)
+DO UPDATE SET why = excluded.why, trading = excluded.trading, updated_time = now();
+"#;
+        for date in dates {
+            sqlx::query(sql)
+                .bind(date.date)
+                .bind(&date.why)
+                .bind(date.trading)
+                .execute(&mut *tx)
+                .await
+                .context(format!(
+                    "Failed to CalendarDate::replace_year({:?}) from database",
+                    date
+                ))?;
+        }
+
+        tx.commit()
+            .await
+            .context(format!("Failed to commit trading_calendar of {}", year))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Datelike, Local};
+
+    use crate::logging;
+
+    use super::*;
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_fetch_by_year() {
+        dotenv::dotenv().ok();
+        match CalendarDate::fetch_by_year(Local::now().year()).await {
+            Ok(dates) => logging::debug_file_async(format!("dates:{:#?}", dates)),
+            Err(why) => {
+                logging::debug_file_async(format!("Failed to fetch_by_year because {:?}", why))
+            }
+        }
+    }
+}
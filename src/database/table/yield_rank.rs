@@ -1,6 +1,9 @@
 use anyhow::{anyhow, Context, Result};
 use chrono::{Datelike, NaiveDate, TimeDelta};
-use sqlx::postgres::PgQueryResult;
+use sqlx::{
+    postgres::{PgQueryResult, PgRow},
+    Row,
+};
 
 use crate::database;
 
@@ -14,6 +17,51 @@ pub struct YieldRank {
 }
 
 impl YieldRank {
+    /// 取得最近一次計算殖利率排行的日期
+    pub async fn fetch_latest_date() -> Result<Option<NaiveDate>> {
+        sqlx::query_scalar("SELECT MAX(date) FROM yield_rank")
+            .fetch_one(database::get_connection())
+            .await
+            .context("Failed to YieldRank::fetch_latest_date from database")
+    }
+
+    /// 分頁取得指定日期的殖利率排行(依殖利率由高到低排序)
+    pub async fn fetch_page_by_date(date: NaiveDate, offset: i64, limit: i64) -> Result<Vec<YieldRank>> {
+        let sql = r#"
+SELECT
+    yr.security_code,
+    yr.daily_quotes_serial,
+    d."sum"::float8 AS dividend,
+    dq."ClosingPrice"::float8 AS closing_price,
+    yr.yield::float8 AS yield
+FROM yield_rank AS yr
+    INNER JOIN "DailyQuotes" AS dq ON dq."Serial" = yr.daily_quotes_serial
+    INNER JOIN dividend AS d ON d.serial = yr.dividend_serial
+WHERE yr.date = $1
+ORDER BY yr.yield DESC, yr.security_code
+LIMIT $2 OFFSET $3
+"#;
+        sqlx::query(sql)
+            .bind(date)
+            .bind(limit)
+            .bind(offset)
+            .try_map(|row: PgRow| {
+                Ok(YieldRank {
+                    security_code: row.try_get("security_code")?,
+                    daily_quotes_serial: row.try_get("daily_quotes_serial")?,
+                    dividend: row.try_get("dividend")?,
+                    closing_price: row.try_get("closing_price")?,
+                    r#yield: row.try_get("yield")?,
+                })
+            })
+            .fetch_all(database::get_connection())
+            .await
+            .context(format!(
+                "Failed to YieldRank::fetch_page_by_date({},{},{}) from database",
+                date, offset, limit
+            ))
+    }
+
     pub async fn upsert(date: NaiveDate) -> Result<PgQueryResult> {
         let mut tx = database::get_tx()
             .await
@@ -360,6 +360,54 @@ impl Industry {
     }
 }
 
+/// 均線
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone, Display, EnumString, Serialize, Deserialize)]
+pub enum MovingAverage {
+    /// 5日週線
+    #[strum(serialize = "MA5")]
+    Ma5 = 5,
+    /// 10日雙週線
+    #[strum(serialize = "MA10")]
+    Ma10 = 10,
+    /// 20日月線
+    #[strum(serialize = "MA20")]
+    Ma20 = 20,
+    /// 60日季線
+    #[strum(serialize = "MA60")]
+    Ma60 = 60,
+    /// 120日半年線
+    #[strum(serialize = "MA120")]
+    Ma120 = 120,
+    /// 240日年線
+    #[strum(serialize = "MA240")]
+    Ma240 = 240,
+}
+
+impl MovingAverage {
+    /// 返回均線的天數
+    pub fn days(&self) -> i32 {
+        *self as i32
+    }
+
+    /// 根據天數返回對應的均線
+    pub fn from_days(days: i32) -> Option<MovingAverage> {
+        Self::iterator().find(|ma| ma.days() == days)
+    }
+
+    pub fn iterator() -> impl Iterator<Item = Self> {
+        [
+            Self::Ma5,
+            Self::Ma10,
+            Self::Ma20,
+            Self::Ma60,
+            Self::Ma120,
+            Self::Ma240,
+        ]
+        .iter()
+        .copied()
+    }
+}
+
 /// 股票報價
 #[derive(Debug)]
 pub struct StockQuotes {
@@ -369,6 +417,8 @@ pub struct StockQuotes {
     pub change: f64,
     /// 漲跌百分比
     pub change_range: f64,
+    /// 當日累計成交股數，站點未提供時為 None
+    pub volume: Option<i64>,
 }
 
 /// 三天的秒數
@@ -498,6 +548,16 @@ mod tests {
         assert_eq!(StockExchangeMarket::Emerging.name(), "興櫃");
     }
 
+    #[test]
+    fn test_moving_average_days() {
+        assert_eq!(MovingAverage::Ma5.days(), 5);
+        assert_eq!(MovingAverage::Ma20.days(), 20);
+        assert_eq!(MovingAverage::Ma240.days(), 240);
+        assert_eq!(MovingAverage::from_days(60), Some(MovingAverage::Ma60));
+        assert_eq!(MovingAverage::from_days(7), None);
+        assert_eq!(MovingAverage::Ma120.to_string(), "MA120");
+    }
+
     #[test]
     fn test_serial() {
         assert_eq!(Quarter::Q1.serial(), 1);
@@ -1,22 +1,29 @@
 use anyhow::Result;
 use chrono::{Local, NaiveDate};
+use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 
 use crate::{
-    backfill, bot,
+    backfill,
+    bot::{self, notifier::NotifyEvent},
     cache::{TtlCacheInner, TTL},
     calculation,
     database::table::{
         daily_money_history::extension::with_previous_trading_day_money_history::DailyMoneyHistoryWithPreviousTradingDayMoneyHistory,
+        daily_money_history_member::extension::with_previous_trading_day_money_history::DailyMoneyHistoryMemberWithPreviousTradingDayMoneyHistory,
         daily_quote, last_daily_quotes, yield_rank::YieldRank,
     },
-    logging,
+    event, logging,
 };
 
 /// 台股收盤事件發生時要進行的事情
 pub async fn execute() -> Result<()> {
-    let current_date: NaiveDate = Local::now().date_naive();
-    let aggregate = aggregate(current_date);
+    execute_for(Local::now().date_naive()).await
+}
+
+/// 以指定日期執行收盤事件，用於重新執行失敗的收盤作業
+pub async fn execute_for(date: NaiveDate) -> Result<()> {
+    let aggregate = aggregate(date);
     let index = backfill::taiwan_stock_index::execute();
     let (res_aggregation, res_index) = tokio::join!(aggregate, index);
 
@@ -53,6 +60,14 @@ async fn aggregate(date: NaiveDate) -> Result<()> {
         lack_daily_quotes_count
     ));
 
+    // 盤中K線彙總與官方收盤數據比對
+    if let Err(why) = event::taiwan_stock::intraday_quote::reconcile(date).await {
+        logging::error_file_async(format!(
+            "Failed to intraday_quote::reconcile() because {:#?}",
+            why
+        ));
+    }
+
     // 計算均線
     calculation::daily_quotes::calculate_moving_average(date).await?;
     logging::info_file_async("計算均線結束".to_string());
@@ -69,6 +84,14 @@ async fn aggregate(date: NaiveDate) -> Result<()> {
     YieldRank::upsert(date).await?;
     logging::info_file_async("重建 yield_rank 表內的數據結束".to_string());
 
+    // 以收盤數據評估提醒規則
+    if let Err(why) = event::trace::alert_rule::evaluate_closing(date).await {
+        logging::error_file_async(format!(
+            "Failed to alert_rule::evaluate_closing() because {:#?}",
+            why
+        ));
+    }
+
     // 計算帳戶內市值
     calculation::money_history::calculate_money_history(date).await?;
     logging::info_file_async("計算帳戶內市值結束".to_string());
@@ -82,32 +105,55 @@ async fn aggregate(date: NaiveDate) -> Result<()> {
 
 async fn notify_money_change(date: NaiveDate) -> Result<()> {
     let mh = DailyMoneyHistoryWithPreviousTradingDayMoneyHistory::fetch(date).await?;
+    let members = DailyMoneyHistoryMemberWithPreviousTradingDayMoneyHistory::fetch(date).await?;
+    let msg = format_money_change(date, &mh, &members);
+
+    bot::notifier::send(NotifyEvent::MoneyChange, &msg).await;
 
-    // Percentage = ((a-b)/b)*100
-    let hundred = dec!(100);
-    let sum_diff = mh.sum - mh.previous_sum;
-    let sum_percentage = (sum_diff / mh.previous_sum) * hundred;
-    let eddie_diff = mh.eddie - mh.previous_eddie;
-    let eddie_percentage = (eddie_diff / mh.previous_eddie) * hundred;
-    let unice_diff = mh.unice - mh.previous_unice;
-    let unice_percentage = (unice_diff / mh.previous_unice) * hundred;
-    let msg = format!(
-        "{} 市值變化\n合計:{} {} ({}%)\nEddie:{} {} ({}%)\nUnice:{} {} ({}%)",
+    Ok(())
+}
+
+/// 組出合計與各會員的市值變化訊息
+fn format_money_change(
+    date: NaiveDate,
+    mh: &DailyMoneyHistoryWithPreviousTradingDayMoneyHistory,
+    members: &[DailyMoneyHistoryMemberWithPreviousTradingDayMoneyHistory],
+) -> String {
+    let mut msg = format!(
+        "{} 市值變化\n合計:{}",
         date,
-        mh.sum.round_dp(2),
-        sum_diff.round_dp(2),
-        sum_percentage.round_dp(2),
-        mh.eddie.round_dp(2),
-        eddie_diff.round_dp(2),
-        eddie_percentage.round_dp(2),
-        mh.unice.round_dp(2),
-        unice_diff.round_dp(2),
-        unice_percentage.round_dp(2),
+        format_change(mh.sum, mh.previous_sum)
     );
 
-    bot::telegram::send(&msg).await;
-        
-    Ok(())
+    for member in members {
+        msg.push_str(&format!(
+            "\n{}:{} 成本:{} 損益:{} 股利:{}",
+            member.name,
+            format_change(member.market_value, member.previous_market_value),
+            member.cost.abs().round_dp(2),
+            member.unrealized_profit_and_loss.round_dp(2),
+            member.dividend_income.round_dp(2),
+        ));
+    }
+
+    msg
+}
+
+/// 市值與前一次的差額及百分比，Percentage = ((a-b)/b)*100
+fn format_change(current: Decimal, previous: Decimal) -> String {
+    let diff = current - previous;
+    let percentage = if previous.is_zero() {
+        Decimal::ZERO
+    } else {
+        (diff / previous) * dec!(100)
+    };
+
+    format!(
+        "{} {} ({}%)",
+        current.round_dp(2),
+        diff.round_dp(2),
+        percentage.round_dp(2)
+    )
 }
 
 #[cfg(test)]
@@ -176,4 +222,38 @@ mod tests {
         );
 
     }
+
+    #[test]
+    fn test_format_money_change() {
+        let date = NaiveDate::from_ymd_opt(2024, 12, 23).unwrap();
+        let mh = DailyMoneyHistoryWithPreviousTradingDayMoneyHistory {
+            date,
+            sum: dec!(3300),
+            previous_sum: dec!(3000),
+            ..Default::default()
+        };
+        let members = vec![
+            DailyMoneyHistoryMemberWithPreviousTradingDayMoneyHistory {
+                name: "Eddie".to_string(),
+                market_value: dec!(2200),
+                cost: dec!(-2000),
+                unrealized_profit_and_loss: dec!(200),
+                dividend_income: dec!(35.5),
+                previous_market_value: dec!(2000),
+            },
+            DailyMoneyHistoryMemberWithPreviousTradingDayMoneyHistory {
+                name: "Ann".to_string(),
+                market_value: dec!(1100),
+                cost: dec!(-1000),
+                unrealized_profit_and_loss: dec!(100),
+                dividend_income: dec!(0),
+                previous_market_value: dec!(0),
+            },
+        ];
+
+        assert_eq!(
+            format_money_change(date, &mh, &members),
+            "2024-12-23 市值變化\n合計:3300 300 (10.00%)\nEddie:2200 200 (10.00%) 成本:2000 損益:200 股利:35.5\nAnn:1100 1100 (0%) 成本:1000 損益:100 股利:0"
+        );
+    }
 }
@@ -3,7 +3,11 @@ use std::fmt::Write;
 use anyhow::Result;
 use chrono::{Datelike, Local, NaiveDate};
 
-use crate::{bot, calculation, database::table::dividend};
+use crate::{
+    bot::{self, notifier::NotifyEvent},
+    calculation,
+    database::table::dividend,
+};
 
 /// 提醒本日為除權息的股票有那些
 pub async fn execute() -> Result<()> {
@@ -35,10 +39,12 @@ pub async fn execute() -> Result<()> {
         }
     }
 
+    //重新計算還原股價的調整因子
+    calculation::adjusted_price::execute(stock_symbols.clone()).await;
     //計算股利
     calculation::dividend_record::execute(today.year(), Some(stock_symbols)).await;
     //群內通知
-    bot::telegram::send(&msg).await;
+    bot::notifier::send(NotifyEvent::ExDividend, &msg).await;
     Ok(())
 }
 
@@ -0,0 +1,140 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{Local, NaiveDate};
+use futures::future;
+use rust_decimal::{prelude::FromPrimitive, Decimal};
+use tokio::{task, time};
+
+use crate::{
+    calendar::CALENDAR,
+    config::SETTINGS,
+    crawler,
+    database::table::intraday_quote::{IntradayDailyRollup, IntradayQuote},
+    declare, logging,
+};
+
+/// 未設定取樣間隔時的預設秒數
+const DEFAULT_SAMPLE_SECONDS: u64 = 15;
+
+/// 開盤期間定時取樣觀察清單內股票的報價並記錄成一分鐘K線
+pub async fn execute() -> Result<()> {
+    if SETTINGS.intraday.watchlist.is_empty() {
+        return Ok(());
+    }
+
+    // 檢查是否為交易日
+    if !CALENDAR.is_trading_day(Local::now().date_naive()).await? {
+        return Ok(());
+    }
+
+    task::spawn(record_run());
+
+    Ok(())
+}
+
+async fn record_run() {
+    let seconds = match SETTINGS.intraday.sample_seconds {
+        0 => DEFAULT_SAMPLE_SECONDS,
+        seconds => seconds,
+    };
+    let mut ticker = time::interval(Duration::from_secs(seconds));
+
+    loop {
+        ticker.tick().await;
+
+        // 檢查是否在開盤時間內
+        if !declare::StockExchange::TWSE.is_open() {
+            logging::debug_file_async("已達關盤時間，停止記錄盤中報價".to_string());
+            break;
+        }
+
+        let futures = SETTINGS
+            .intraday
+            .watchlist
+            .iter()
+            .map(|stock_symbol| task::spawn(record_quote(stock_symbol.to_string())))
+            .collect::<Vec<_>>();
+
+        future::join_all(futures).await;
+    }
+}
+
+async fn record_quote(stock_symbol: String) {
+    let quotes = match crawler::fetch_stock_quotes_from_remote_site(&stock_symbol).await {
+        Ok(quotes) => quotes,
+        Err(why) => {
+            logging::error_file_async(format!("{:?}", why));
+            return;
+        }
+    };
+
+    let price = Decimal::from_f64(quotes.price).unwrap_or_default();
+    if price <= Decimal::ZERO {
+        return;
+    }
+
+    if let Err(why) =
+        IntradayQuote::upsert_sample(&stock_symbol, Local::now(), price, quotes.volume).await
+    {
+        logging::error_file_async(format!("{:?}", why));
+    }
+}
+
+/// 將盤中K線彙總的日K與官方收盤數據比對，不一致時記錄於日誌
+pub async fn reconcile(date: NaiveDate) -> Result<()> {
+    let rollups = IntradayDailyRollup::fetch(date).await?;
+
+    for rollup in rollups {
+        let discrepancies = rollup.discrepancies();
+        if discrepancies.is_empty() {
+            continue;
+        }
+
+        logging::warn_file_async(format!(
+            "{} {} 盤中K線與官方收盤數據不一致({}根K線、{}次取樣):{}",
+            date,
+            rollup.security_code,
+            rollup.bars,
+            rollup.samples,
+            discrepancies.join("、")
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cache::SHARE;
+
+    use super::*;
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_record_quote() {
+        dotenv::dotenv().ok();
+        SHARE.load().await;
+        logging::debug_file_async("開始 intraday_quote::record_quote".to_string());
+
+        record_quote("2330".to_string()).await;
+
+        logging::debug_file_async("結束 intraday_quote::record_quote".to_string());
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_reconcile() {
+        dotenv::dotenv().ok();
+        logging::debug_file_async("開始 intraday_quote::reconcile".to_string());
+
+        if let Err(why) = reconcile(Local::now().date_naive()).await {
+            logging::debug_file_async(format!(
+                "Failed to intraday_quote::reconcile because {:?}",
+                why
+            ));
+        }
+
+        logging::debug_file_async("結束 intraday_quote::reconcile".to_string());
+    }
+}
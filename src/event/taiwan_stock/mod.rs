@@ -4,6 +4,8 @@ pub mod annual_eps;
 pub mod closing;
 /// 除息日的事件
 pub mod ex_dividend;
+/// 盤中一分鐘K線記錄
+pub mod intraday_quote;
 /// 股利發放日的事件
 pub mod payable_date;
 /// 公開申購公告
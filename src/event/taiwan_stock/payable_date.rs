@@ -3,7 +3,10 @@ use std::fmt::Write;
 use anyhow::Result;
 use chrono::{Local, NaiveDate};
 
-use crate::{bot, database::table::dividend};
+use crate::{
+    bot::{self, notifier::NotifyEvent},
+    database::table::dividend,
+};
 
 /// 提提醒本日發放股利的股票(只通知自已有的股票)
 pub async fn execute() -> Result<()> {
@@ -38,7 +41,7 @@ pub async fn execute() -> Result<()> {
     }
 
     //群內通知
-    bot::telegram::send(&msg).await;
+    bot::notifier::send(NotifyEvent::PayableDate, &msg).await;
     Ok(())
 }
 
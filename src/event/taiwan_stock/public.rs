@@ -9,15 +9,10 @@ use rust_decimal::{
 use rust_decimal_macros::dec;
 
 use crate::{
-    bot,
+    bot::{self, notifier::NotifyEvent},
     cache::SHARE,
-    crawler,
-    declare,
-    nosql,
-    util::{
-        map::Keyable,
-        convert::FromValue
-    }
+    crawler, declare, nosql,
+    util::{convert::FromValue, map::Keyable},
 };
 
 pub async fn execute() -> Result<()> {
@@ -79,7 +74,7 @@ pub async fn execute() -> Result<()> {
 
     if !msg.is_empty() {
         let to_bot_msg = format!("{} 可以申購的股票如下︰\n{}", now, msg);
-        let _ = bot::telegram::send(&to_bot_msg).await;
+        bot::notifier::send(NotifyEvent::Public, &to_bot_msg).await;
         return Ok(());
     }
 
@@ -0,0 +1,401 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+
+use crate::declare::MovingAverage;
+
+/// 比較方式
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Comparison {
+    /// 大於等於
+    #[serde(rename = ">=")]
+    GreaterOrEqual,
+    /// 小於等於
+    #[serde(rename = "<=")]
+    LessOrEqual,
+}
+
+impl Comparison {
+    fn compare(&self, left: Decimal, right: Decimal) -> bool {
+        match self {
+            Comparison::GreaterOrEqual => left >= right,
+            Comparison::LessOrEqual => left <= right,
+        }
+    }
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            Comparison::GreaterOrEqual => "≥",
+            Comparison::LessOrEqual => "≤",
+        }
+    }
+}
+
+/// 穿越均線的方向
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    /// 由下往上穿越
+    Up,
+    /// 由上往下跌破
+    Down,
+}
+
+/// 估價的區間
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Band {
+    /// 便宜價
+    Cheap,
+    /// 合理價
+    Fair,
+    /// 昂貴價
+    Expensive,
+}
+
+/// 歷史極值
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Extreme {
+    /// 歷史最高
+    Highest,
+    /// 歷史最低
+    Lowest,
+}
+
+/// 提醒規則的觸發條件，以 JSON 儲存於 alert_rule.condition
+///
+/// 例如 `{"type":"all","conditions":[{"type":"change_percent","op":"<=","value":-5},{"type":"estimate","band":"cheap","op":"<="}]}`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Condition {
+    /// 所有條件都成立(AND)
+    All { conditions: Vec<Condition> },
+    /// 任一條件成立(OR)
+    Any { conditions: Vec<Condition> },
+    /// 股價與指定價格比較
+    Price { op: Comparison, value: Decimal },
+    /// 漲跌幅(%)與指定值比較
+    ChangePercent { op: Comparison, value: Decimal },
+    /// 股價穿越指定天數的均線
+    MovingAverageCross { days: i32, direction: Direction },
+    /// 股價與便宜、合理、昂貴價比較
+    Estimate { band: Band, op: Comparison },
+    /// 股價淨值比達到歷史最高或最低
+    PriceToBookRatio { extreme: Extreme },
+    /// 成交量達到前 20 個交易日平均量的指定倍數
+    VolumeSpike { multiple: Decimal },
+    /// 殖利率(%)與指定值比較
+    Yield { op: Comparison, value: Decimal },
+}
+
+/// 評估條件時的行情數據
+#[derive(Debug, Clone, Default)]
+pub struct Market {
+    /// 目前股價(盤中為即時報價，收盤後為收盤價)
+    pub price: Decimal,
+    /// 漲跌幅(%)
+    pub change_range: Decimal,
+    /// 前一個時間點的股價(盤中為最後交易日的收盤價，收盤後為前一個交易日的收盤價)
+    pub previous_price: Decimal,
+    /// 目前的均線
+    pub moving_average: HashMap<MovingAverage, Decimal>,
+    /// 前一個時間點的均線
+    pub previous_moving_average: HashMap<MovingAverage, Decimal>,
+    /// 目前的股價淨值比
+    pub price_to_book_ratio: Decimal,
+    /// 歷史最高股價淨值比
+    pub maximum_price_to_book_ratio: Decimal,
+    /// 歷史最低股價淨值比
+    pub minimum_price_to_book_ratio: Decimal,
+    /// 成交股數，盤中沒有資料
+    pub volume: Option<Decimal>,
+    /// 前 20 個交易日的平均成交股數
+    pub average_volume: Decimal,
+    pub cheap: Decimal,
+    pub fair: Decimal,
+    pub expensive: Decimal,
+    /// 年度合計股利
+    pub dividend: Decimal,
+}
+
+impl Market {
+    /// 以年度合計股利與目前股價計算的殖利率(%)
+    pub fn dividend_yield(&self) -> Decimal {
+        if self.price <= Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+
+        self.dividend / self.price * dec!(100)
+    }
+}
+
+impl Condition {
+    /// 由 JSON 解析條件並檢查參數
+    pub fn parse(json: &str) -> Result<Condition> {
+        let condition: Condition = serde_json::from_str(json)?;
+        condition.validate()?;
+        Ok(condition)
+    }
+
+    fn validate(&self) -> Result<()> {
+        match self {
+            Condition::All { conditions } | Condition::Any { conditions } => {
+                if conditions.is_empty() {
+                    return Err(anyhow!("conditions is empty"));
+                }
+                conditions.iter().try_for_each(|c| c.validate())
+            }
+            Condition::MovingAverageCross { days, .. } => match MovingAverage::from_days(*days) {
+                Some(_) => Ok(()),
+                None => Err(anyhow!("moving average of {} days is not supported", days)),
+            },
+            Condition::VolumeSpike { multiple } if *multiple <= Decimal::ZERO => {
+                Err(anyhow!("multiple must be greater than zero"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// 條件是否成立，缺少評估所需的數據時視為不成立
+    pub fn evaluate(&self, market: &Market) -> bool {
+        let price = market.price;
+        if price <= Decimal::ZERO {
+            return false;
+        }
+
+        match self {
+            Condition::All { conditions } => conditions.iter().all(|c| c.evaluate(market)),
+            Condition::Any { conditions } => conditions.iter().any(|c| c.evaluate(market)),
+            Condition::Price { op, value } => op.compare(price, *value),
+            Condition::ChangePercent { op, value } => op.compare(market.change_range, *value),
+            Condition::MovingAverageCross { days, direction } => {
+                let Some(ma) = MovingAverage::from_days(*days) else {
+                    return false;
+                };
+                let current = market.moving_average.get(&ma).copied().unwrap_or_default();
+                let previous = market
+                    .previous_moving_average
+                    .get(&ma)
+                    .copied()
+                    .unwrap_or_default();
+                if current <= Decimal::ZERO
+                    || previous <= Decimal::ZERO
+                    || market.previous_price <= Decimal::ZERO
+                {
+                    return false;
+                }
+
+                match direction {
+                    Direction::Up => market.previous_price < previous && price >= current,
+                    Direction::Down => market.previous_price > previous && price <= current,
+                }
+            }
+            Condition::Estimate { band, op } => {
+                let target = match band {
+                    Band::Cheap => market.cheap,
+                    Band::Fair => market.fair,
+                    Band::Expensive => market.expensive,
+                };
+                target > Decimal::ZERO && op.compare(price, target)
+            }
+            Condition::PriceToBookRatio { extreme } => {
+                let pbr = market.price_to_book_ratio;
+                if pbr <= Decimal::ZERO {
+                    return false;
+                }
+
+                match extreme {
+                    Extreme::Highest => {
+                        market.maximum_price_to_book_ratio > Decimal::ZERO
+                            && pbr >= market.maximum_price_to_book_ratio
+                    }
+                    Extreme::Lowest => {
+                        market.minimum_price_to_book_ratio > Decimal::ZERO
+                            && pbr <= market.minimum_price_to_book_ratio
+                    }
+                }
+            }
+            Condition::VolumeSpike { multiple } => match market.volume {
+                Some(volume) => {
+                    market.average_volume > Decimal::ZERO
+                        && volume >= market.average_volume * *multiple
+                }
+                None => false,
+            },
+            Condition::Yield { op, value } => {
+                market.dividend > Decimal::ZERO && op.compare(market.dividend_yield(), *value)
+            }
+        }
+    }
+
+    /// 條件的中文說明
+    pub fn describe(&self) -> String {
+        match self {
+            Condition::All { conditions } => join(conditions, " 且 "),
+            Condition::Any { conditions } => join(conditions, " 或 "),
+            Condition::Price { op, value } => format!("股價{}{}", op.symbol(), value.normalize()),
+            Condition::ChangePercent { op, value } => {
+                format!("漲跌幅{}{}%", op.symbol(), value.normalize())
+            }
+            Condition::MovingAverageCross { days, direction } => match direction {
+                Direction::Up => format!("向上穿越{}日均線", days),
+                Direction::Down => format!("向下跌破{}日均線", days),
+            },
+            Condition::Estimate { band, op } => {
+                let band = match band {
+                    Band::Cheap => "便宜價",
+                    Band::Fair => "合理價",
+                    Band::Expensive => "昂貴價",
+                };
+                format!("股價{}{}", op.symbol(), band)
+            }
+            Condition::PriceToBookRatio { extreme } => match extreme {
+                Extreme::Highest => "股價淨值比達歷史新高".to_string(),
+                Extreme::Lowest => "股價淨值比達歷史新低".to_string(),
+            },
+            Condition::VolumeSpike { multiple } => {
+                format!("成交量達20日均量{}倍", multiple.normalize())
+            }
+            Condition::Yield { op, value } => {
+                format!("殖利率{}{}%", op.symbol(), value.normalize())
+            }
+        }
+    }
+}
+
+fn join(conditions: &[Condition], separator: &str) -> String {
+    let descriptions: Vec<String> = conditions
+        .iter()
+        .map(|c| match c {
+            Condition::All { .. } | Condition::Any { .. } => format!("({})", c.describe()),
+            _ => c.describe(),
+        })
+        .collect();
+
+    descriptions.join(separator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market() -> Market {
+        Market {
+            price: dec!(100),
+            change_range: dec!(-5.5),
+            previous_price: dec!(95),
+            moving_average: HashMap::from([(MovingAverage::Ma20, dec!(98))]),
+            previous_moving_average: HashMap::from([(MovingAverage::Ma20, dec!(97))]),
+            price_to_book_ratio: dec!(1.2),
+            maximum_price_to_book_ratio: dec!(3),
+            minimum_price_to_book_ratio: dec!(1.2),
+            volume: Some(dec!(30000)),
+            average_volume: dec!(10000),
+            cheap: dec!(105),
+            fair: dec!(120),
+            expensive: dec!(150),
+            dividend: dec!(6),
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let condition = Condition::parse(
+            r#"{"type":"all","conditions":[{"type":"change_percent","op":"<=","value":-5},{"type":"estimate","band":"cheap","op":"<="}]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            condition,
+            Condition::All {
+                conditions: vec![
+                    Condition::ChangePercent {
+                        op: Comparison::LessOrEqual,
+                        value: dec!(-5),
+                    },
+                    Condition::Estimate {
+                        band: Band::Cheap,
+                        op: Comparison::LessOrEqual,
+                    },
+                ]
+            }
+        );
+        assert!(
+            Condition::parse(r#"{"type":"moving_average_cross","days":30,"direction":"up"}"#)
+                .is_err()
+        );
+        assert!(Condition::parse(r#"{"type":"any","conditions":[]}"#).is_err());
+        assert!(Condition::parse(r#"{"type":"unknown"}"#).is_err());
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let market = market();
+        let cases = [
+            (r#"{"type":"price","op":">=","value":100}"#, true),
+            (r#"{"type":"price","op":"<=","value":99}"#, false),
+            (r#"{"type":"change_percent","op":"<=","value":-5}"#, true),
+            (
+                r#"{"type":"moving_average_cross","days":20,"direction":"up"}"#,
+                true,
+            ),
+            (
+                r#"{"type":"moving_average_cross","days":20,"direction":"down"}"#,
+                false,
+            ),
+            (
+                r#"{"type":"moving_average_cross","days":60,"direction":"up"}"#,
+                false,
+            ),
+            (r#"{"type":"estimate","band":"cheap","op":"<="}"#, true),
+            (r#"{"type":"estimate","band":"expensive","op":">="}"#, false),
+            (r#"{"type":"price_to_book_ratio","extreme":"lowest"}"#, true),
+            (
+                r#"{"type":"price_to_book_ratio","extreme":"highest"}"#,
+                false,
+            ),
+            (r#"{"type":"volume_spike","multiple":3}"#, true),
+            (r#"{"type":"volume_spike","multiple":3.5}"#, false),
+            (r#"{"type":"yield","op":">=","value":6}"#, true),
+            (
+                r#"{"type":"all","conditions":[{"type":"yield","op":">=","value":6},{"type":"price","op":"<=","value":90}]}"#,
+                false,
+            ),
+            (
+                r#"{"type":"any","conditions":[{"type":"yield","op":">=","value":6},{"type":"price","op":"<=","value":90}]}"#,
+                true,
+            ),
+        ];
+
+        for (json, expected) in cases {
+            let condition = Condition::parse(json).unwrap();
+            assert_eq!(condition.evaluate(&market), expected, "{}", json);
+        }
+    }
+
+    #[test]
+    fn test_evaluate_without_volume() {
+        let mut market = market();
+        market.volume = None;
+        let condition = Condition::VolumeSpike { multiple: dec!(2) };
+
+        assert!(!condition.evaluate(&market));
+    }
+
+    #[test]
+    fn test_describe() {
+        let condition = Condition::parse(
+            r#"{"type":"any","conditions":[{"type":"yield","op":">=","value":6},{"type":"all","conditions":[{"type":"price","op":"<=","value":90},{"type":"moving_average_cross","days":240,"direction":"down"}]}]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            condition.describe(),
+            "殖利率≥6% 或 (股價≤90 且 向下跌破240日均線)"
+        );
+    }
+}
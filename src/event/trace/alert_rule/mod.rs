@@ -0,0 +1,304 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use chrono::{Local, NaiveDate, TimeDelta};
+use futures::future;
+use rust_decimal::{prelude::FromPrimitive, Decimal};
+
+use crate::{
+    bot::{self, notifier::NotifyEvent},
+    cache::SHARE,
+    crawler,
+    database::table::alert_rule::{
+        extension::alert_snapshot::{self, AlertSnapshot},
+        AlertRule,
+    },
+    declare::{MovingAverage, StockQuotes},
+    event::trace::alert_rule::condition::{Condition, Market},
+    logging,
+};
+
+/// 提醒規則的觸發條件
+pub mod condition;
+
+/// 盤中以即時報價評估所有啟用中的提醒規則
+pub async fn evaluate_intraday() -> Result<()> {
+    let rules = fetch_rules().await?;
+    if rules.is_empty() {
+        return Ok(());
+    }
+
+    let security_codes = distinct_security_codes(&rules);
+    // 盤中只有到前一個交易日的收盤數據
+    let yesterday = Local::now().date_naive() - TimeDelta::try_days(1).unwrap();
+    let snapshots = alert_snapshot::fetch(&security_codes, yesterday).await?;
+    let quotes = future::join_all(
+        snapshots
+            .iter()
+            .map(|s| crawler::fetch_stock_quotes_from_remote_site(&s.security_code)),
+    )
+    .await;
+
+    let mut markets: HashMap<String, Market> = HashMap::with_capacity(snapshots.len());
+    for (snapshot, quotes) in snapshots.iter().zip(quotes) {
+        match quotes {
+            Ok(quotes) => {
+                let market = intraday_market(snapshot, &quotes).await;
+                markets.insert(snapshot.security_code.to_string(), market);
+            }
+            Err(why) => logging::error_file_async(format!("{:?}", why)),
+        }
+    }
+
+    notify(rules, &markets).await
+}
+
+/// 收盤後以指定日期的收盤數據評估所有啟用中的提醒規則
+pub async fn evaluate_closing(date: NaiveDate) -> Result<()> {
+    let rules = fetch_rules().await?;
+    if rules.is_empty() {
+        return Ok(());
+    }
+
+    let security_codes = distinct_security_codes(&rules);
+    let markets: HashMap<String, Market> = alert_snapshot::fetch(&security_codes, date)
+        .await?
+        .iter()
+        .filter(|snapshot| snapshot.date == date)
+        .map(|snapshot| (snapshot.security_code.to_string(), closing_market(snapshot)))
+        .collect();
+
+    notify(rules, &markets).await
+}
+
+/// 取得啟用中的規則並解析其條件，無法解析的規則會記錄於日誌後略過
+async fn fetch_rules() -> Result<Vec<(AlertRule, Condition)>> {
+    let rules = AlertRule::fetch_enabled()
+        .await?
+        .into_iter()
+        .filter_map(|rule| match Condition::parse(&rule.condition) {
+            Ok(condition) => Some((rule, condition)),
+            Err(why) => {
+                logging::error_file_async(format!(
+                    "Failed to parse condition of alert rule({}) {} because {:?}",
+                    rule.serial, rule.condition, why
+                ));
+                None
+            }
+        })
+        .collect();
+
+    Ok(rules)
+}
+
+fn distinct_security_codes(rules: &[(AlertRule, Condition)]) -> Vec<String> {
+    rules
+        .iter()
+        .map(|(rule, _)| rule.security_code.to_string())
+        .collect::<HashSet<String>>()
+        .into_iter()
+        .collect()
+}
+
+/// 評估規則並將成立且不在冷卻時間內的規則合併成一則訊息發送
+async fn notify(
+    rules: Vec<(AlertRule, Condition)>,
+    markets: &HashMap<String, Market>,
+) -> Result<()> {
+    let now = Local::now();
+    let mut messages: Vec<String> = Vec::new();
+
+    for (mut rule, condition) in rules {
+        if rule.is_cooling_down(now) {
+            continue;
+        }
+
+        let market = match markets.get(&rule.security_code) {
+            Some(market) => market,
+            None => continue,
+        };
+
+        if !condition.evaluate(market) {
+            continue;
+        }
+
+        if let Err(why) = rule.update_last_triggered_time(now).await {
+            logging::error_file_async(format!("{:?}", why));
+            continue;
+        }
+
+        messages.push(format_alert_message(&rule, &condition, market).await);
+    }
+
+    if !messages.is_empty() {
+        bot::notifier::send(NotifyEvent::Alert, &messages.join("\n")).await;
+    }
+
+    Ok(())
+}
+
+async fn format_alert_message(rule: &AlertRule, condition: &Condition, market: &Market) -> String {
+    let stock_name = SHARE
+        .get_stock(&rule.security_code)
+        .await
+        .map_or_else(String::new, |stock| stock.name);
+
+    format!(
+        "{stock_name} {name}:{description}，目前報價:{price} https://tw.stock.yahoo.com/quote/{stock_symbol}",
+        stock_name = stock_name,
+        name = rule.name,
+        description = condition.describe(),
+        price = market.price.normalize(),
+        stock_symbol = rule.security_code
+    )
+}
+
+/// 盤中的行情，均線與前一個時間點皆以最後交易日的收盤數據為準
+async fn intraday_market(snapshot: &AlertSnapshot, quotes: &StockQuotes) -> Market {
+    let price = Decimal::from_f64(quotes.price).unwrap_or_default();
+    let moving_average = moving_averages(snapshot, false);
+    let price_to_book_ratio = match SHARE.get_stock(&snapshot.security_code).await {
+        Some(stock) if stock.net_asset_value_per_share > Decimal::ZERO => {
+            (price / stock.net_asset_value_per_share).round_dp(2)
+        }
+        _ => Decimal::ZERO,
+    };
+
+    let mut market = base_market(snapshot);
+    market.price = price;
+    market.change_range = Decimal::from_f64(quotes.change_range).unwrap_or_default();
+    market.previous_price = snapshot.closing_price;
+    market.previous_moving_average = moving_average.clone();
+    market.moving_average = moving_average;
+    market.price_to_book_ratio = price_to_book_ratio;
+    market
+}
+
+/// 收盤後的行情
+fn closing_market(snapshot: &AlertSnapshot) -> Market {
+    let mut market = base_market(snapshot);
+    market.price = snapshot.closing_price;
+    market.change_range = snapshot.change_range;
+    market.previous_price = snapshot.previous_closing_price;
+    market.moving_average = moving_averages(snapshot, false);
+    market.previous_moving_average = moving_averages(snapshot, true);
+    market.price_to_book_ratio = snapshot.price_to_book_ratio;
+    market.volume = Some(snapshot.trading_volume);
+    market
+}
+
+fn base_market(snapshot: &AlertSnapshot) -> Market {
+    let (maximum_price_to_book_ratio, minimum_price_to_book_ratio) =
+        match SHARE.quote_history_records.read() {
+            Ok(records) => records.get(&snapshot.security_code).map_or(
+                (Decimal::ZERO, Decimal::ZERO),
+                |record| {
+                    (
+                        record.maximum_price_to_book_ratio,
+                        record.minimum_price_to_book_ratio,
+                    )
+                },
+            ),
+            Err(_) => (Decimal::ZERO, Decimal::ZERO),
+        };
+
+    Market {
+        maximum_price_to_book_ratio,
+        minimum_price_to_book_ratio,
+        average_volume: snapshot.average_volume,
+        cheap: snapshot.cheap,
+        fair: snapshot.fair,
+        expensive: snapshot.expensive,
+        dividend: snapshot.dividend,
+        ..Default::default()
+    }
+}
+
+fn moving_averages(snapshot: &AlertSnapshot, previous: bool) -> HashMap<MovingAverage, Decimal> {
+    let values = if previous {
+        [
+            snapshot.previous_moving_average_5,
+            snapshot.previous_moving_average_10,
+            snapshot.previous_moving_average_20,
+            snapshot.previous_moving_average_60,
+            snapshot.previous_moving_average_120,
+            snapshot.previous_moving_average_240,
+        ]
+    } else {
+        [
+            snapshot.moving_average_5,
+            snapshot.moving_average_10,
+            snapshot.moving_average_20,
+            snapshot.moving_average_60,
+            snapshot.moving_average_120,
+            snapshot.moving_average_240,
+        ]
+    };
+
+    MovingAverage::iterator().zip(values).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    #[test]
+    fn test_closing_market() {
+        let snapshot = AlertSnapshot {
+            security_code: "2330".to_string(),
+            closing_price: dec!(100),
+            previous_closing_price: dec!(95),
+            moving_average_20: dec!(98),
+            previous_moving_average_20: dec!(97),
+            trading_volume: dec!(30000),
+            ..Default::default()
+        };
+
+        let market = closing_market(&snapshot);
+
+        assert_eq!(market.price, dec!(100));
+        assert_eq!(market.previous_price, dec!(95));
+        assert_eq!(market.moving_average[&MovingAverage::Ma20], dec!(98));
+        assert_eq!(
+            market.previous_moving_average[&MovingAverage::Ma20],
+            dec!(97)
+        );
+        assert_eq!(market.volume, Some(dec!(30000)));
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_evaluate_intraday() {
+        dotenv::dotenv().ok();
+        SHARE.load().await;
+        logging::debug_file_async("開始 alert_rule::evaluate_intraday".to_string());
+
+        if let Err(why) = evaluate_intraday().await {
+            logging::debug_file_async(format!(
+                "Failed to alert_rule::evaluate_intraday because {:?}",
+                why
+            ));
+        }
+
+        logging::debug_file_async("結束 alert_rule::evaluate_intraday".to_string());
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_evaluate_closing() {
+        dotenv::dotenv().ok();
+        SHARE.load().await;
+        logging::debug_file_async("開始 alert_rule::evaluate_closing".to_string());
+
+        if let Err(why) = evaluate_closing(Local::now().date_naive()).await {
+            logging::debug_file_async(format!(
+                "Failed to alert_rule::evaluate_closing because {:?}",
+                why
+            ));
+        }
+
+        logging::debug_file_async("結束 alert_rule::evaluate_closing".to_string());
+    }
+}
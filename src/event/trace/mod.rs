@@ -1 +1,3 @@
+/// 股價提醒規則
+pub mod alert_rule;
 pub mod stock_price;
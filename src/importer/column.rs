@@ -0,0 +1,228 @@
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use csv::StringRecord;
+use rust_decimal::Decimal;
+
+use crate::{
+    database::table::stock_transaction::TransactionKind,
+    importer::{Importer, Trade},
+    util::{datetime, text},
+};
+
+/// 以標題列的欄位名稱找出各欄位的格式，各券商只是欄位名稱不同
+pub struct ColumnImporter {
+    name: &'static str,
+    date: &'static [&'static str],
+    security_code: &'static [&'static str],
+    side: &'static [&'static str],
+    quantity: &'static [&'static str],
+    price: &'static [&'static str],
+    fee: &'static [&'static str],
+    tax: &'static [&'static str],
+}
+
+impl ColumnImporter {
+    /// 對帳單(交割明細)
+    pub fn statement() -> Self {
+        ColumnImporter {
+            name: "statement",
+            date: &["成交日期"],
+            security_code: &["股票代號", "證券代號"],
+            side: &["買賣別"],
+            quantity: &["成交股數", "股數"],
+            price: &["成交價", "成交單價"],
+            fee: &["手續費"],
+            tax: &["交易稅", "證交稅"],
+        }
+    }
+
+    /// 成交回報
+    pub fn confirmation() -> Self {
+        ColumnImporter {
+            name: "confirmation",
+            date: &["交易日期", "日期"],
+            security_code: &["代號", "商品代號"],
+            side: &["交易類別", "委託別", "買賣"],
+            quantity: &["成交數量", "數量"],
+            price: &["成交均價", "價格", "單價"],
+            fee: &["手續費"],
+            tax: &["交易稅", "證交稅"],
+        }
+    }
+
+    fn required(&self) -> [&'static [&'static str]; 5] {
+        [
+            self.date,
+            self.security_code,
+            self.side,
+            self.quantity,
+            self.price,
+        ]
+    }
+}
+
+impl Importer for ColumnImporter {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn matches(&self, headers: &StringRecord) -> bool {
+        self.required()
+            .iter()
+            .all(|aliases| position(headers, aliases).is_some())
+    }
+
+    fn parse_record(&self, headers: &StringRecord, record: &StringRecord) -> Result<Option<Trade>> {
+        let kind = match field(headers, record, self.side) {
+            side if side.contains('買') => TransactionKind::Buy,
+            side if side.contains('賣') => TransactionKind::Sell,
+            _ => return Ok(None),
+        };
+
+        Ok(Some(Trade {
+            trade_date: parse_date(field(headers, record, self.date))?,
+            security_code: parse_security_code(field(headers, record, self.security_code))?,
+            kind,
+            quantity: text::parse_i64(field(headers, record, self.quantity), Some(vec![',']))?,
+            price: text::parse_decimal(field(headers, record, self.price), Some(vec![',']))?,
+            fee: parse_amount(field(headers, record, self.fee))?,
+            tax: parse_amount(field(headers, record, self.tax))?,
+        }))
+    }
+}
+
+/// 依別名的順序找出欄位，同時有多個別名的欄位時以排在前面的別名為準
+fn position(headers: &StringRecord, aliases: &[&str]) -> Option<usize> {
+    aliases.iter().find_map(|alias| {
+        headers
+            .iter()
+            .position(|header| header.trim_start_matches('\u{feff}') == *alias)
+    })
+}
+
+/// 取得欄位的值，沒有此欄位時為空字串
+fn field<'a>(headers: &StringRecord, record: &'a StringRecord, aliases: &[&str]) -> &'a str {
+    position(headers, aliases)
+        .and_then(|i| record.get(i))
+        .unwrap_or_default()
+}
+
+/// 支援西元與民國年，例如 2024/01/02、2024-01-02、20240102、113/01/02
+fn parse_date(value: &str) -> Result<NaiveDate> {
+    let date = match value.split(['/', '-']).next() {
+        Some(year) if year.len() <= 3 => datetime::parse_taiwan_date(value),
+        _ => ["%Y/%m/%d", "%Y-%m-%d", "%Y%m%d"]
+            .iter()
+            .find_map(|format| NaiveDate::parse_from_str(value, format).ok()),
+    };
+
+    date.ok_or_else(|| anyhow!("Invalid date '{}'", value))
+}
+
+/// 去除試算表的 ="0050" 格式與代號後的股票名稱
+fn parse_security_code(value: &str) -> Result<String> {
+    let code = value
+        .trim_start_matches('=')
+        .trim_matches('"')
+        .split_whitespace()
+        .next()
+        .unwrap_or_default();
+    if code.is_empty() {
+        return Err(anyhow!("Empty security code"));
+    }
+
+    Ok(code.to_string())
+}
+
+/// 手續費、交易稅可能留空
+fn parse_amount(value: &str) -> Result<Decimal> {
+    if value.is_empty() {
+        return Ok(Decimal::ZERO);
+    }
+
+    text::parse_decimal(value, Some(vec![',']))
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_date() {
+        let expected = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        for value in [
+            "2024/01/02",
+            "2024-01-02",
+            "20240102",
+            "113/01/02",
+            "113-1-2",
+        ] {
+            assert_eq!(parse_date(value).unwrap(), expected, "{}", value);
+        }
+        assert!(parse_date("01/02").is_err());
+    }
+
+    #[test]
+    fn test_alias_priority() {
+        let importer = ColumnImporter::statement();
+        // 交割日期不是成交日期，與成交日期同時存在時也不能被選到
+        let headers = StringRecord::from(vec![
+            "交割日期",
+            "成交日期",
+            "股票代號",
+            "買賣別",
+            "成交股數",
+            "成交單價",
+            "成交價",
+        ]);
+        assert!(importer.matches(&headers));
+
+        let record = StringRecord::from(vec![
+            "2024/01/04",
+            "2024/01/02",
+            "2330",
+            "現買",
+            "1,000",
+            "593.5",
+            "593",
+        ]);
+        let trade = importer.parse_record(&headers, &record).unwrap().unwrap();
+        assert_eq!(
+            trade.trade_date,
+            NaiveDate::from_ymd_opt(2024, 1, 2).unwrap()
+        );
+        assert_eq!(trade.price, dec!(593));
+
+        // 只有交割日期時不是支援的格式
+        let headers =
+            StringRecord::from(vec!["交割日期", "股票代號", "買賣別", "成交股數", "成交價"]);
+        assert!(!importer.matches(&headers));
+    }
+
+    #[test]
+    fn test_confirmation() {
+        let importer = ColumnImporter::confirmation();
+        let headers = StringRecord::from(vec!["日期", "代號", "名稱", "交易類別", "數量", "價格"]);
+        assert!(importer.matches(&headers));
+        assert!(!ColumnImporter::statement().matches(&headers));
+
+        let record = StringRecord::from(vec![
+            "2024/01/02",
+            "2330 台積電",
+            "",
+            "普賣",
+            "1,000",
+            "1,010",
+        ]);
+        let trade = importer.parse_record(&headers, &record).unwrap().unwrap();
+        assert_eq!(trade.security_code, "2330");
+        assert_eq!(trade.kind, TransactionKind::Sell);
+        assert_eq!(trade.price, dec!(1010));
+        assert_eq!(trade.fee, Decimal::ZERO);
+
+        let record = StringRecord::from(vec!["2024/01/02", "2330", "", "配股", "100", "0"]);
+        assert!(importer.parse_record(&headers, &record).unwrap().is_none());
+    }
+}
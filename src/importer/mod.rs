@@ -0,0 +1,405 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt,
+};
+
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use csv::{ReaderBuilder, StringRecord};
+use once_cell::sync::Lazy;
+use rust_decimal::Decimal;
+
+use crate::{
+    calculation::ledger,
+    database::{
+        self,
+        table::stock_transaction::{StockTransaction, TransactionKind},
+    },
+    logging, util,
+};
+
+/// 依欄位名稱對應的券商格式
+pub mod column;
+
+/// 已支援的券商格式，自動判斷時依序比對標題列
+static IMPORTERS: Lazy<Vec<Box<dyn Importer>>> = Lazy::new(|| {
+    vec![
+        Box::new(column::ColumnImporter::statement()),
+        Box::new(column::ColumnImporter::confirmation()),
+    ]
+});
+
+/// 券商匯出的一筆成交
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trade {
+    pub trade_date: NaiveDate,
+    pub security_code: String,
+    /// 只會是買進或賣出
+    pub kind: TransactionKind,
+    pub quantity: i64,
+    pub price: Decimal,
+    pub fee: Decimal,
+    pub tax: Decimal,
+}
+
+impl Trade {
+    fn into_transaction(self, member_id: i64) -> StockTransaction {
+        let mut t =
+            StockTransaction::new(member_id, &self.security_code, self.trade_date, self.kind);
+        t.quantity = self.quantity;
+        t.price = self.price;
+        t.fee = self.fee;
+        t.tax = self.tax;
+        t
+    }
+}
+
+/// 券商對帳單、成交回報的格式，新增券商時實作此 trait 並加入 IMPORTERS
+pub trait Importer: Send + Sync {
+    /// 格式名稱，匯入時可用來指定格式
+    fn name(&self) -> &'static str;
+
+    /// 依標題列判斷是否為此格式
+    fn matches(&self, headers: &StringRecord) -> bool;
+
+    /// 將一列資料轉為成交，小計、股利等非買賣的列回傳 None
+    fn parse_record(&self, headers: &StringRecord, record: &StringRecord) -> Result<Option<Trade>>;
+}
+
+/// 解析後的對帳單
+#[derive(Debug, Default)]
+pub struct Statement {
+    pub broker: &'static str,
+    pub trades: Vec<Trade>,
+    /// 無法解析的列
+    pub errors: Vec<String>,
+}
+
+/// 匯入的結果，dry run 時只有比對結果不會寫入
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub broker: &'static str,
+    pub dry_run: bool,
+    /// 交易明細尚未有的成交
+    pub new: Vec<StockTransaction>,
+    /// 交易明細已有的成交
+    pub duplicates: Vec<StockTransaction>,
+    pub errors: Vec<String>,
+}
+
+impl fmt::Display for ImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "格式:{}{} 新增:{} 重複:{} 錯誤:{}",
+            self.broker,
+            if self.dry_run { " (dry run)" } else { "" },
+            self.new.len(),
+            self.duplicates.len(),
+            self.errors.len()
+        )?;
+
+        for t in &self.new {
+            writeln!(f, "+ {}", describe(t))?;
+        }
+        for t in &self.duplicates {
+            writeln!(f, "= {}", describe(t))?;
+        }
+        for error in &self.errors {
+            writeln!(f, "! {}", error)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn describe(t: &StockTransaction) -> String {
+    format!(
+        "{} {} {} {} 股 @ {} 手續費 {} 交易稅 {}",
+        t.trade_date,
+        t.security_code,
+        if t.kind == TransactionKind::Sell {
+            "賣出"
+        } else {
+            "買進"
+        },
+        t.quantity,
+        t.price.normalize(),
+        t.fee.normalize(),
+        t.tax.normalize()
+    )
+}
+
+/// 將檔案內容轉為 UTF-8，非 UTF-8 的內容視為 Big5
+pub fn decode(data: &[u8]) -> Result<String> {
+    let data = data.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(data);
+    match std::str::from_utf8(data) {
+        Ok(text) => Ok(text.to_string()),
+        Err(_) => util::text::big5_2_utf8(data),
+    }
+}
+
+/// 對帳單的內容或指定的格式無法解析，與寫入資料庫等其他錯誤區分
+#[derive(Debug)]
+pub struct ParseError(String);
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// 解析對帳單，未指定格式時以標題列自動判斷，標題列之前的帳號等資訊會略過，
+/// 無法解析時回傳 `ParseError`
+pub fn parse(data: &[u8], broker: Option<&str>) -> Result<Statement> {
+    parse_statement(data, broker).map_err(|why| ParseError(why.to_string()).into())
+}
+
+fn parse_statement(data: &[u8], broker: Option<&str>) -> Result<Statement> {
+    let text = decode(data)?;
+    let importers: Vec<&dyn Importer> = match broker.filter(|b| !b.is_empty()) {
+        Some(name) => vec![IMPORTERS
+            .iter()
+            .find(|importer| importer.name() == name)
+            .ok_or_else(|| anyhow!("Unknown broker format: {}", name))?
+            .as_ref()],
+        None => IMPORTERS.iter().map(|importer| importer.as_ref()).collect(),
+    };
+
+    parse_with(&text, &importers)
+}
+
+fn parse_with(text: &str, importers: &[&dyn Importer]) -> Result<Statement> {
+    let mut reader = ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(text.as_bytes());
+
+    let mut found: Option<(&dyn Importer, StringRecord)> = None;
+    let mut statement = Statement::default();
+    for (i, record) in reader.records().enumerate() {
+        let line = i + 1;
+        let record = match record {
+            Ok(record) => record,
+            Err(why) => {
+                statement.errors.push(format!("第 {} 行: {}", line, why));
+                continue;
+            }
+        };
+
+        match &found {
+            None => {
+                if let Some(importer) = importers.iter().find(|importer| importer.matches(&record))
+                {
+                    statement.broker = importer.name();
+                    found = Some((*importer, record));
+                }
+            }
+            Some((importer, headers)) => {
+                if record.iter().all(str::is_empty) {
+                    continue;
+                }
+
+                match importer.parse_record(headers, &record) {
+                    Ok(Some(trade)) => statement.trades.push(trade),
+                    Ok(None) => {}
+                    Err(why) => statement.errors.push(format!("第 {} 行: {}", line, why)),
+                }
+            }
+        }
+    }
+
+    if found.is_none() {
+        return Err(anyhow!("No supported header row found in the statement"));
+    }
+
+    Ok(statement)
+}
+
+/// 比對的鍵值，同一天同價同量的成交可能有多筆，因此以次數比對
+fn key(t: &StockTransaction) -> (NaiveDate, String, String, i64, Decimal) {
+    (
+        t.trade_date,
+        t.security_code.to_string(),
+        t.kind.to_string(),
+        t.quantity,
+        t.price.normalize(),
+    )
+}
+
+/// 將成交分成交易明細尚未有的與重複的
+fn classify(
+    transactions: Vec<StockTransaction>,
+    existing: &[StockTransaction],
+) -> (Vec<StockTransaction>, Vec<StockTransaction>) {
+    let mut remaining: HashMap<_, usize> = HashMap::new();
+    for t in existing {
+        *remaining.entry(key(t)).or_default() += 1;
+    }
+
+    transactions.into_iter().partition(|t| {
+        match remaining.get_mut(&key(t)).filter(|count| **count > 0) {
+            Some(count) => {
+                *count -= 1;
+                false
+            }
+            None => true,
+        }
+    })
+}
+
+/// 匯入對帳單到指定會員的交易明細，非 dry run 時寫入後重建受影響股票的持股
+pub async fn execute(
+    member_id: i64,
+    data: &[u8],
+    broker: Option<&str>,
+    dry_run: bool,
+) -> Result<ImportReport> {
+    let statement = parse(data, broker)?;
+    let transactions: Vec<StockTransaction> = statement
+        .trades
+        .into_iter()
+        .map(|trade| trade.into_transaction(member_id))
+        .collect();
+    let security_codes: BTreeSet<String> = transactions
+        .iter()
+        .map(|t| t.security_code.to_string())
+        .collect();
+
+    let mut existing = Vec::new();
+    for security_code in &security_codes {
+        existing.extend(StockTransaction::fetch(member_id, security_code).await?);
+    }
+
+    let (mut new, duplicates) = classify(transactions, &existing);
+    let report_errors = statement.errors;
+    if !dry_run && !new.is_empty() {
+        let mut tx = database::get_tx().await.ok();
+        for t in new.iter_mut() {
+            if let Err(why) = t.insert(&mut tx).await {
+                if let Some(tx) = tx {
+                    tx.rollback().await?;
+                }
+                return Err(why);
+            }
+        }
+
+        if let Some(tx) = tx {
+            tx.commit().await?;
+        }
+
+        let affected: BTreeSet<&str> = new.iter().map(|t| t.security_code.as_str()).collect();
+        for security_code in affected {
+            if let Err(why) = ledger::rebuild(member_id, security_code).await {
+                logging::error_file_async(format!(
+                    "Failed to rebuild({},{}) after import because {:?}",
+                    member_id, security_code, why
+                ));
+            }
+        }
+    }
+
+    Ok(ImportReport {
+        broker: statement.broker,
+        dry_run,
+        new,
+        duplicates,
+        errors: report_errors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    const STATEMENT: &str = "帳號,9801-1234567\n\
+成交日期,股票代號,股票名稱,買賣別,成交股數,成交價,手續費,交易稅,淨收付\n\
+2024/01/02,2330,台積電,現買,\"1,000\",593,845,0,\"-593,845\"\n\
+113/01/03,=\"0050\",元大台灣50,現賣,500,130.5,92,195,\"64,963\"\n\
+2024/01/04,2330,台積電,現買,abc,593,845,0,0\n\
+,,小計,,,,,,\n";
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode("\u{feff}代號".as_bytes()).unwrap(), "代號");
+        // 「代號」的 Big5 編碼
+        assert_eq!(decode(&[0xA5, 0x4E, 0xB8, 0xB9]).unwrap(), "代號");
+    }
+
+    #[test]
+    fn test_parse() {
+        let statement = parse(STATEMENT.as_bytes(), None).unwrap();
+
+        assert_eq!(statement.broker, "statement");
+        assert_eq!(statement.trades.len(), 2);
+        assert_eq!(
+            statement.trades[0],
+            Trade {
+                trade_date: NaiveDate::from_ymd_opt(2024, 1, 2).unwrap(),
+                security_code: "2330".to_string(),
+                kind: TransactionKind::Buy,
+                quantity: 1000,
+                price: dec!(593),
+                fee: dec!(845),
+                tax: dec!(0),
+            }
+        );
+        assert_eq!(statement.trades[1].security_code, "0050");
+        assert_eq!(statement.trades[1].kind, TransactionKind::Sell);
+        assert_eq!(
+            statement.trades[1].trade_date,
+            NaiveDate::from_ymd_opt(2024, 1, 3).unwrap()
+        );
+        assert_eq!(statement.errors.len(), 1);
+        assert!(statement.errors[0].starts_with("第 5 行"));
+
+        assert!(parse(STATEMENT.as_bytes(), Some("unknown"))
+            .unwrap_err()
+            .is::<ParseError>());
+        assert!(parse("a,b,c\n1,2,3".as_bytes(), None)
+            .unwrap_err()
+            .is::<ParseError>());
+    }
+
+    #[test]
+    fn test_classify() {
+        let statement = parse(STATEMENT.as_bytes(), Some("statement")).unwrap();
+        let mut transactions: Vec<StockTransaction> = statement
+            .trades
+            .into_iter()
+            .map(|trade| trade.into_transaction(1))
+            .collect();
+        // 同一天同價同量的第二筆成交不是重複
+        transactions.push(transactions[0].clone());
+
+        let mut existing = transactions[0].clone();
+        existing.serial = 10;
+        existing.price = dec!(593.0000);
+        let (new, duplicates) = classify(transactions, &[existing]);
+
+        assert_eq!(new.len(), 2);
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].security_code, "2330");
+
+        let report = ImportReport {
+            broker: "statement",
+            dry_run: true,
+            new,
+            duplicates,
+            errors: vec!["第 5 行: abc".to_string()],
+        };
+        assert_eq!(
+            report.to_string(),
+            "格式:statement (dry run) 新增:2 重複:1 錯誤:1\n\
++ 2024-01-03 0050 賣出 500 股 @ 130.5 手續費 92 交易稅 195\n\
++ 2024-01-02 2330 買進 1000 股 @ 593 手續費 845 交易稅 0\n\
+= 2024-01-02 2330 買進 1000 股 @ 593 手續費 845 交易稅 0\n\
+! 第 5 行: abc\n"
+        );
+    }
+}
@@ -1,12 +1,15 @@
 use std::{
     fmt::Write as _,
     fs::{self},
+    future::Future,
     io::Write,
+    panic::Location,
     path::{Path, PathBuf},
 };
 
-use chrono::{format::DelayedFormat, Local};
+use chrono::{format::DelayedFormat, DateTime, Local};
 use once_cell::sync::Lazy;
+use serde_json::{json, Map, Value};
 use tokio::{
     sync::{
         mpsc::UnboundedReceiver,
@@ -15,17 +18,128 @@ use tokio::{
     task
 };
 
-use crate::logging::rotate::Rotate;
+use crate::{
+    config::{LogFormat, LogLevel, Logging, SETTINGS},
+    logging::rotate::{Retention, Rotate},
+};
 
 pub mod rotate;
 
 static LOGGER: Lazy<Logger> = Lazy::new(|| Logger::new("default"));
 
+tokio::task_local! {
+    static CONTEXT: Context;
+}
+
+/// 跟著 task 記錄在每筆日誌上的欄位，不會傳遞到 tokio::spawn 出去的 task
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Context {
+    /// 排程工作的名稱
+    pub job: Option<&'static str>,
+    /// 每次執行排程工作時產生，用來串起同一次執行的日誌
+    pub run_id: Option<String>,
+    pub symbol: Option<String>,
+}
+
+/// 在 context 的範圍內執行 future，期間寫入的日誌都會帶上 context 的欄位
+pub async fn scope<F: Future>(context: Context, f: F) -> F::Output {
+    CONTEXT.scope(context, f).await
+}
+
+/// 保留目前的欄位並加上股票代號後執行 future
+pub async fn with_symbol<F: Future>(symbol: &str, f: F) -> F::Output {
+    let mut context = current_context().unwrap_or_default();
+    context.symbol = Some(symbol.to_string());
+    CONTEXT.scope(context, f).await
+}
+
+fn current_context() -> Option<Context> {
+    CONTEXT.try_with(Context::clone).ok()
+}
+
+/// 一筆日誌
+pub struct Record {
+    time: DateTime<Local>,
+    level: LogLevel,
+    /// 呼叫端的原始碼路徑，寫入時才轉成模組路徑
+    file: &'static str,
+    message: String,
+    context: Option<Context>,
+}
+
+impl Record {
+    #[track_caller]
+    fn new(level: LogLevel, message: String) -> Self {
+        Record {
+            time: Local::now(),
+            level,
+            file: Location::caller().file(),
+            message,
+            context: current_context(),
+        }
+    }
+
+    fn to_text(&self) -> String {
+        format!("{} {}", self.time.format("%F %X%.6f"), self.message)
+    }
+
+    fn to_json(&self) -> String {
+        let mut fields = Map::new();
+        fields.insert("timestamp".to_string(), json!(self.time.to_rfc3339()));
+        fields.insert("level".to_string(), json!(self.level));
+        fields.insert("module".to_string(), json!(module_path_of(self.file)));
+        if let Some(context) = &self.context {
+            if let Some(job) = context.job {
+                fields.insert("job".to_string(), json!(job));
+            }
+            if let Some(run_id) = &context.run_id {
+                fields.insert("run_id".to_string(), json!(run_id));
+            }
+            if let Some(symbol) = &context.symbol {
+                fields.insert("symbol".to_string(), json!(symbol));
+            }
+        }
+        fields.insert("message".to_string(), json!(self.message));
+
+        Value::Object(fields).to_string()
+    }
+}
+
+/// 將原始碼路徑轉成模組路徑，例如 src/crawler/twse/mod.rs 轉成 crawler::twse
+fn module_path_of(file: &str) -> String {
+    let file = file.replace('\\', "/");
+    let path = file.strip_prefix("src/").unwrap_or(&file);
+    let path = path.strip_suffix(".rs").unwrap_or(path);
+    let path = path.strip_suffix("/mod").unwrap_or(path);
+
+    match path {
+        "main" => env!("CARGO_PKG_NAME").to_string(),
+        _ => path.replace('/', "::"),
+    }
+}
+
+/// 依 modules 內最長符合的模組路徑判斷日誌是否要輸出，沒有符合的使用 level
+fn is_enabled(config: &Logging, module: &str, level: LogLevel) -> bool {
+    let min_level = config
+        .modules
+        .iter()
+        .filter(|(prefix, _)| {
+            module == prefix.as_str()
+                || module
+                    .strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.starts_with("::"))
+        })
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map_or(config.level, |(_, level)| *level);
+
+    level >= min_level
+}
+
 pub struct Logger {
-    info_writer: UnboundedSender<String>,
-    warn_writer: UnboundedSender<String>,
-    error_writer: UnboundedSender<String>,
-    debug_writer: UnboundedSender<String>,
+    info_writer: UnboundedSender<Record>,
+    warn_writer: UnboundedSender<Record>,
+    error_writer: UnboundedSender<Record>,
+    debug_writer: UnboundedSender<Record>,
 }
 
 impl Logger {
@@ -38,48 +152,66 @@ impl Logger {
         }
     }
 
+    #[track_caller]
     pub fn info(&self, log: String) {
-        self.send(log, &self.info_writer);
+        self.send(Record::new(LogLevel::Info, log), &self.info_writer);
     }
 
+    #[track_caller]
     pub fn warn(&self, log: String) {
-        self.send(log, &self.warn_writer);
+        self.send(Record::new(LogLevel::Warn, log), &self.warn_writer);
     }
 
+    #[track_caller]
     pub fn error(&self, log: String) {
-        self.send(log, &self.error_writer);
+        self.send(Record::new(LogLevel::Error, log), &self.error_writer);
     }
 
+    #[track_caller]
     pub fn debug(&self, log: String) {
-        self.send(log, &self.debug_writer);
+        self.send(Record::new(LogLevel::Debug, log), &self.debug_writer);
     }
 
-    pub fn send(&self, msg: String, writer: &UnboundedSender<String>) {
-        if let Err(why) = writer.send(msg) {
-            error_console(why.to_string());
+    pub fn send(&self, record: Record, writer: &UnboundedSender<Record>) {
+        if let Err(why) = writer.send(record) {
+            error_console(why.0.message);
         }
     }
 
-    fn create_writer(log_name: &str) -> UnboundedSender<String> {
+    fn create_writer(log_name: &str) -> UnboundedSender<Record> {
         let log_path = Self::get_log_path(log_name).unwrap_or_else(|| {
             panic!("Failed to create log directory.");
         });
 
-        let (tx, rx) = mpsc::unbounded_channel::<String>();
+        let (tx, rx) = mpsc::unbounded_channel::<Record>();
 
         task::spawn(Self::process_messages(rx, log_path.display().to_string()));
 
         tx
     }
 
-    async fn process_messages(mut rx: UnboundedReceiver<String>, log_path: String) {
+    /// 設定檔載入失敗時會寫入日誌，所以等到寫入時才讀取日誌的設定
+    async fn process_messages(mut rx: UnboundedReceiver<Record>, log_path: String) {
         let mut msg = String::with_capacity(2048);
-        let mut rotate = Rotate::new(log_path);
+        let mut rotate = Rotate::with_retention(log_path, Retention::from(&SETTINGS.logging));
+
+        while let Some(record) = rx.recv().await {
+            let config = &SETTINGS.logging;
+            if !is_enabled(config, &module_path_of(record.file), record.level) {
+                continue;
+            }
 
-        while let Some(message) = rx.recv().await {
-            let now = Local::now();
+            let now = record.time;
+            let line = match config.format {
+                LogFormat::Text => record.to_text(),
+                LogFormat::Json => record.to_json(),
+            };
 
-            if let Err(why) = writeln!(&mut msg, "{} {}", now.format("%F %X%.6f"), message) {
+            if config.stdout {
+                println!("{}", line);
+            }
+
+            if let Err(why) = writeln!(&mut msg, "{}", line) {
                 error_console(format!("Failed to writeln a message. because:{:#?}", why));
                 continue;
             }
@@ -88,11 +220,14 @@ impl Logger {
                 continue;
             }
 
-            msg.push('\n');
+            if config.format == LogFormat::Text {
+                msg.push('\n');
+            }
 
             if let Some(writer) = rotate.get_writer(now) {
                 if let Ok(mut w) = writer.write() {
                     let to_write = msg.as_bytes();
+                    rotate.written(to_write.len());
                     if let Err(why) = w.write_all(to_write) {
                         error_console(format!(
                             "Failed to write msg:{}\r\nbecause:{:#?}",
@@ -124,18 +259,22 @@ impl Logger {
     }
 }
 
+#[track_caller]
 pub fn info_file_async(log: String) {
     LOGGER.info(log);
 }
 
+#[track_caller]
 pub fn warn_file_async(log: String) {
     LOGGER.warn(log);
 }
 
+#[track_caller]
 pub fn error_file_async(log: String) {
     LOGGER.error(log);
 }
 
+#[track_caller]
 pub fn debug_file_async(log: String) {
     LOGGER.debug(log);
 }
@@ -155,3 +294,59 @@ pub fn error_console(log: String) {
         log
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_module_path_of() {
+        assert_eq!(module_path_of("src/crawler/twse/mod.rs"), "crawler::twse");
+        assert_eq!(
+            module_path_of("src/crawler/goodinfo/dividend.rs"),
+            "crawler::goodinfo::dividend"
+        );
+        assert_eq!(module_path_of("src/main.rs"), "stock_crawler");
+    }
+
+    #[test]
+    fn test_is_enabled() {
+        let config = Logging {
+            level: LogLevel::Info,
+            modules: HashMap::from([
+                ("crawler".to_string(), LogLevel::Warn),
+                ("crawler::goodinfo".to_string(), LogLevel::Debug),
+            ]),
+            ..Default::default()
+        };
+
+        assert!(!is_enabled(&config, "scheduler", LogLevel::Debug));
+        assert!(is_enabled(&config, "scheduler", LogLevel::Info));
+        assert!(!is_enabled(&config, "crawler::twse", LogLevel::Info));
+        assert!(is_enabled(&config, "crawler::goodinfo::dividend", LogLevel::Debug));
+        assert!(is_enabled(&config, "crawler_extra", LogLevel::Info));
+    }
+
+    #[tokio::test]
+    async fn test_to_json() {
+        let context = Context {
+            job: Some("closing"),
+            run_id: Some("20240102150000-1".to_string()),
+            symbol: None,
+        };
+        let record = scope(context, async {
+            with_symbol("2330", async { Record::new(LogLevel::Warn, "收盤".to_string()) }).await
+        })
+        .await;
+
+        let value: Value = serde_json::from_str(&record.to_json()).unwrap();
+        assert_eq!(value["level"], "warn");
+        assert_eq!(value["module"], "logging");
+        assert_eq!(value["job"], "closing");
+        assert_eq!(value["run_id"], "20240102150000-1");
+        assert_eq!(value["symbol"], "2330");
+        assert_eq!(value["message"], "收盤");
+    }
+}
@@ -1,54 +1,137 @@
 use std::{
+    cmp::Reverse,
     fs::{self, File, OpenOptions},
-    io::{self, BufWriter},
+    io::{self, BufReader, BufWriter},
     path::{Path, PathBuf},
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc, RwLock,
     },
-    time::UNIX_EPOCH,
+    time::SystemTime,
 };
 
 use anyhow::Result;
 use chrono::{DateTime, Local, TimeDelta};
-use rayon::prelude::*;
+use flate2::{write::GzEncoder, Compression};
+use regex::Regex;
 
-use crate::logging;
+use crate::{config::Logging, logging};
+
+/// 未設定時保留的天數
+const DEFAULT_MAX_AGE_DAYS: i64 = 7;
+const MB: u64 = 1024 * 1024;
+
+/// 舊日誌的保留與壓縮方式，數值為零時表示不限制
+#[derive(Debug, Clone, PartialEq)]
+pub struct Retention {
+    pub max_age: TimeDelta,
+    /// 同一種日誌所有檔案合計的上限
+    pub max_total_bytes: u64,
+    /// 同一種日誌最多保留的檔案數
+    pub max_files: usize,
+    /// 單一檔案超過此大小時換到下一個序號的檔案
+    pub max_file_bytes: u64,
+    /// 以 gzip 壓縮已關閉的檔案
+    pub compress: bool,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Retention {
+            max_age: TimeDelta::try_days(DEFAULT_MAX_AGE_DAYS).unwrap(),
+            max_total_bytes: 0,
+            max_files: 0,
+            max_file_bytes: 0,
+            compress: false,
+        }
+    }
+}
+
+impl From<&Logging> for Retention {
+    fn from(config: &Logging) -> Self {
+        let max_age_days = match config.max_age_days {
+            0 => DEFAULT_MAX_AGE_DAYS,
+            days => days as i64,
+        };
+
+        Retention {
+            max_age: TimeDelta::try_days(max_age_days).unwrap_or(TimeDelta::MAX),
+            max_total_bytes: config.max_total_size_mb.saturating_mul(MB),
+            max_files: config.max_files,
+            max_file_bytes: config.max_file_size_mb.saturating_mul(MB),
+            compress: config.compress,
+        }
+    }
+}
 
 pub struct Rotate {
     /// log/%Y-%m-%d-name.log
     fn_pattern: String,
+    /// 比對屬於此日誌的檔案，包含換檔的序號與壓縮後的檔案
+    fn_regex: Option<Regex>,
     cur_fn: String,
     cur_fn_lock: RwLock<String>,
     cur_base_fn: String,
     out_fh: Option<Arc<RwLock<BufWriter<File>>>>,
+    /// 同一個時間區間內依大小換檔的序號，0 為不帶序號的檔案
     generation: i64,
-    max_age: chrono::Duration,
-    on_rotate: AtomicBool,
+    /// 目前的檔案已寫入的位元組
+    cur_size: u64,
+    retention: Retention,
+    on_rotate: Arc<AtomicBool>,
 }
 
 impl Rotate {
     pub fn new(fn_pattern: String) -> Self {
+        Self::with_retention(fn_pattern, Retention::default())
+    }
+
+    pub fn with_retention(fn_pattern: String, retention: Retention) -> Self {
         Rotate {
+            fn_regex: file_name_regex(&fn_pattern),
             fn_pattern,
             generation: 0,
             cur_fn: "".to_string(),
             cur_fn_lock: Default::default(),
             cur_base_fn: "".to_string(),
             out_fh: None,
-            max_age: TimeDelta::try_days(7).unwrap(),
+            cur_size: 0,
+            retention,
             on_rotate: Default::default(),
         }
     }
 
+    /// 記錄寫入的位元組，超過單一檔案的上限時下一次 get_writer 會換檔
+    pub fn written(&mut self, bytes: usize) {
+        self.cur_size += bytes as u64;
+    }
+
     pub fn get_writer(&mut self, now: DateTime<Local>) -> Option<Arc<RwLock<BufWriter<File>>>> {
         let base_fn = self.generate_fn(now);
-        if base_fn == self.cur_base_fn {
+        let is_full =
+            self.retention.max_file_bytes > 0 && self.cur_size >= self.retention.max_file_bytes;
+        if base_fn == self.cur_base_fn && !is_full {
             return self.out_fh.clone();
         }
 
-        let filename = base_fn.clone();
-        let mut generation = self.generation;
+        let mut generation = if base_fn == self.cur_base_fn {
+            self.generation + 1
+        } else {
+            0
+        };
+        // 重新啟動時接續寫入同一個時間區間內尚未寫滿的檔案
+        let (filename, size) = loop {
+            let filename = generation_fn(&base_fn, generation);
+            let size = fs::metadata(&filename).map_or(0, |metadata| metadata.len());
+            let compressed = Path::new(&format!("{}.gz", filename)).exists();
+            if !compressed
+                && (self.retention.max_file_bytes == 0 || size < self.retention.max_file_bytes)
+            {
+                break (filename, size);
+            }
+            generation += 1;
+        };
+
         match self.cur_fn_lock.write() {
             Ok(mut cur_fn) => {
                 let file = OpenOptions::new()
@@ -58,11 +141,10 @@ impl Rotate {
                     .open(&filename)
                     .expect("Failed to open log file");
 
-                generation = 0;
-
                 self.out_fh = Some(Arc::new(RwLock::new(BufWriter::with_capacity(2048, file))));
                 self.cur_base_fn = base_fn;
                 self.cur_fn = filename;
+                self.cur_size = size;
                 self.generation = generation;
                 self.rotate(now);
 
@@ -82,95 +164,192 @@ impl Rotate {
         now.format(&self.fn_pattern).to_string()
     }
 
+    /// 在背景壓縮已關閉的檔案並刪除超過保留條件的檔案，不阻塞寫入日誌
     fn rotate(&self, now: DateTime<Local>) {
         if self.on_rotate.swap(true, Ordering::Relaxed) {
             return;
         }
 
-        //self.on_rotate.store(true, Ordering::Relaxed);
-
-        match Self::files_in_directory(&self.cur_fn) {
-            Ok(files) => {
-                let cut_off = (now - self.max_age).timestamp() as u64;
-                let to_unlink: Vec<PathBuf> = files
-                    .into_iter()
-                    .filter_map(|file| {
-                        fs::metadata(&file)
-                            .and_then(|metadata| metadata.modified())
-                            .map(|system_time| system_time.duration_since(UNIX_EPOCH))
-                            .ok()
-                            .filter(|file_duration| match file_duration {
-                                Ok(duration) => duration.as_secs() <= cut_off,
-                                Err(_) => false,
-                            })
-                            .map(|_| file)
-                    })
-                    .collect();
-
-                if !to_unlink.is_empty() {
-                    to_unlink
-                        .par_iter()
-                        .with_min_len(num_cpus::get())
-                        .for_each(|unlink| match fs::remove_file(unlink) {
-                            Err(why) => {
-                                logging::error_console(format!(
-                                    "couldn't remove the file({}). because {:?}",
-                                    &unlink.display().to_string(),
-                                    why
-                                ));
-                            }
-                            Ok(_) => {
-                                logging::info_file_async(format!(
-                                    "the file has been deleted:{}",
-                                    &unlink.display().to_string()
-                                ));
-                            }
-                        });
+        let on_rotate = self.on_rotate.clone();
+        let cur_fn = self.cur_fn.clone();
+        let fn_regex = self.fn_regex.clone();
+        let retention = self.retention.clone();
+        let clean_up = move || {
+            if let Err(why) = clean_up(&cur_fn, fn_regex.as_ref(), &retention, now) {
+                logging::error_console(format!("Failed to clean up logs because {:?}", why));
+            }
+            on_rotate.store(false, Ordering::Relaxed);
+        };
+
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn_blocking(clean_up);
+            }
+            Err(_) => clean_up(),
+        }
+    }
+}
+
+/// 依序號產生檔案名稱，例如 log/2024-01-02_default_info.1.log
+fn generation_fn(base_fn: &str, generation: i64) -> String {
+    if generation == 0 {
+        return base_fn.to_string();
+    }
+
+    let path = Path::new(base_fn);
+    match (path.file_stem(), path.extension()) {
+        (Some(stem), Some(ext)) => path
+            .with_file_name(format!(
+                "{}.{}.{}",
+                stem.to_string_lossy(),
+                generation,
+                ext.to_string_lossy()
+            ))
+            .display()
+            .to_string(),
+        _ => format!("{}.{}", base_fn, generation),
+    }
+}
+
+/// 將檔名的格式轉成比對同一種日誌所有檔案的正規表示式
+fn file_name_regex(fn_pattern: &str) -> Option<Regex> {
+    let path = Path::new(fn_pattern);
+    let stem = path.file_stem()?.to_str()?;
+    let ext = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map_or(String::new(), |ext| format!(".{}", ext));
+
+    let mut pattern = String::from("^");
+    let mut chars = stem.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            pattern.push_str(&regex::escape(&c.to_string()));
+            continue;
+        }
+
+        match chars.next() {
+            Some('Y') => pattern.push_str(r"\d{4}"),
+            Some('m' | 'd' | 'H' | 'M' | 'S') => pattern.push_str(r"\d{2}"),
+            _ => pattern.push_str(".+?"),
+        }
+    }
+    pattern.push_str(r"(\.\d+)?");
+    pattern.push_str(&regex::escape(&ext));
+    pattern.push_str(r"(\.gz)?$");
+
+    Regex::new(&pattern).ok()
+}
+
+fn clean_up(
+    cur_fn: &str,
+    fn_regex: Option<&Regex>,
+    retention: &Retention,
+    now: DateTime<Local>,
+) -> Result<()> {
+    let cur_path = Path::new(cur_fn);
+    let mut files: Vec<(PathBuf, SystemTime, u64)> = Vec::new();
+    for file in files_in_directory(cur_path)? {
+        if file == cur_path {
+            continue;
+        }
+
+        let is_own = file
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| fn_regex.is_none_or(|re| re.is_match(name)));
+        if !is_own {
+            continue;
+        }
+
+        let file = if retention.compress && file.extension().is_some_and(|ext| ext != "gz") {
+            match compress(&file) {
+                Ok(compressed) => compressed,
+                Err(why) => {
+                    logging::error_console(format!(
+                        "Failed to compress the file({}) because {:?}",
+                        file.display(),
+                        why
+                    ));
+                    file
                 }
             }
+        } else {
+            file
+        };
+
+        let metadata = fs::metadata(&file)?;
+        files.push((file, metadata.modified()?, metadata.len()));
+    }
+
+    // 由新到舊，目前寫入中的檔案也算在檔案數與總大小內
+    files.sort_by_key(|file| Reverse(file.1));
+    let cut_off = SystemTime::from(now - retention.max_age);
+    let mut count = 1;
+    let mut total = fs::metadata(cur_path).map_or(0, |metadata| metadata.len());
+
+    for (file, modified, size) in files {
+        count += 1;
+        total += size;
+
+        let expired = modified <= cut_off
+            || (retention.max_files > 0 && count > retention.max_files)
+            || (retention.max_total_bytes > 0 && total > retention.max_total_bytes);
+        if !expired {
+            continue;
+        }
+
+        match fs::remove_file(&file) {
+            Ok(_) => {
+                logging::info_file_async(format!(
+                    "the file has been deleted:{}",
+                    file.display()
+                ));
+            }
             Err(why) => {
                 logging::error_console(format!(
-                    "Failed to list_files_in_directory because {:?}",
+                    "couldn't remove the file({}). because {:?}",
+                    file.display(),
                     why
                 ));
             }
         }
-
-        self.on_rotate.store(false, Ordering::Relaxed);
     }
 
-    fn files_in_directory<P: AsRef<Path>>(file_path: P) -> Result<Vec<PathBuf>, io::Error> {
-        let path = file_path.as_ref();
-        let parent_dir = path.parent().ok_or(io::Error::new(
-            io::ErrorKind::NotFound,
-            "Parent directory not found",
-        ))?;
+    Ok(())
+}
+
+/// 將檔案壓縮為 .gz 並刪除原檔，保留原檔的修改時間讓保留天數的計算不受影響
+fn compress(file: &Path) -> io::Result<PathBuf> {
+    let modified = fs::metadata(file)?.modified()?;
+    let compressed = PathBuf::from(format!("{}.gz", file.display()));
 
-        let mut files = Vec::new();
-        for entry in fs::read_dir(parent_dir)? {
-            let entry = entry?;
-            let file_path = entry.path();
-            files.push(file_path);
-        }
+    let mut reader = BufReader::new(File::open(file)?);
+    let mut encoder = GzEncoder::new(File::create(&compressed)?, Compression::default());
+    io::copy(&mut reader, &mut encoder)?;
+    encoder.finish()?.set_modified(modified)?;
+    fs::remove_file(file)?;
 
-        Ok(files)
-    }
+    Ok(compressed)
+}
 
-    /* fn list_files_in_directory<P: AsRef<Path>>(file_path: P) -> Result<Vec<String>> {
-        let path = file_path.as_ref();
-        let parent_dir = path.parent().ok_or(io::Error::new(
-            io::ErrorKind::NotFound,
-            "Parent directory not found",
-        ))?;
+fn files_in_directory<P: AsRef<Path>>(file_path: P) -> Result<Vec<PathBuf>, io::Error> {
+    let path = file_path.as_ref();
+    let parent_dir = path.parent().ok_or(io::Error::new(
+        io::ErrorKind::NotFound,
+        "Parent directory not found",
+    ))?;
 
-        let mut files = Vec::new();
-        for entry in fs::read_dir(parent_dir)? {
-            let entry = entry?;
-            let file_path = entry.path().display().to_string();
+    let mut files = Vec::new();
+    for entry in fs::read_dir(parent_dir)? {
+        let entry = entry?;
+        let file_path = entry.path();
+        if file_path.is_file() {
             files.push(file_path);
         }
-        Ok(files)
-    }*/
+    }
+
+    Ok(files)
 }
 
 #[cfg(test)]
@@ -221,8 +400,59 @@ mod tests {
         dotenv::dotenv().ok();
         logging::debug_file_async("開始 test_rotate".to_string());
         let mut r = Rotate::new("log/%Y-%m-%d-test.log".to_string());
-        if r.get_writer(Local::now()).is_some() {}
+        assert!(r.get_writer(Local::now()).is_some());
 
         logging::debug_file_async("結束 test_rotate".to_string());
     }
+
+    #[test]
+    fn test_file_name_regex() {
+        let re = file_name_regex("log/%Y-%m-%d_default_info.log").unwrap();
+        assert!(re.is_match("2024-01-02_default_info.log"));
+        assert!(re.is_match("2024-01-02_default_info.3.log"));
+        assert!(re.is_match("2024-01-02_default_info.log.gz"));
+        assert!(!re.is_match("2024-01-02_default_error.log"));
+        assert!(!re.is_match("2024-01-02_http_info.log"));
+    }
+
+    #[test]
+    fn test_generation_fn() {
+        let base_fn = "log/2024-01-02_default_info.log";
+        assert_eq!(generation_fn(base_fn, 0), base_fn);
+        assert_eq!(generation_fn(base_fn, 2), "log/2024-01-02_default_info.2.log");
+    }
+
+    #[tokio::test]
+    async fn test_clean_up() {
+        let dir = std::env::temp_dir().join(format!("rotate_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let pattern = dir.join("%Y-%m-%d_test.log").display().to_string();
+        let now = Local::now();
+        for day in 0..4 {
+            let name = (now - TimeDelta::try_days(day).unwrap())
+                .format(&pattern)
+                .to_string();
+            fs::write(&name, "測試\n").unwrap();
+        }
+        fs::write(dir.join("other.log"), "").unwrap();
+
+        let retention = Retention {
+            max_files: 3,
+            compress: true,
+            ..Default::default()
+        };
+        let cur_fn = now.format(&pattern).to_string();
+        clean_up(&cur_fn, file_name_regex(&pattern).as_ref(), &retention, now).unwrap();
+
+        let mut names: Vec<String> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(names.len(), 4);
+        assert!(names.contains(&"other.log".to_string()));
+        assert_eq!(names.iter().filter(|name| name.ends_with(".gz")).count(), 2);
+    }
 }
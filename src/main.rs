@@ -14,14 +14,23 @@ use tokio::signal;
 use tokio::signal::unix::{signal as unix_signal, SignalKind};
 use tokio_cron_scheduler::JobScheduler;
 
+/// 收到停止訊號後等待 gRPC 與 HTTP 請求結束的上限
+const SERVER_DRAIN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
 /// 數據回補
 pub mod backfill;
+/// 歷史數據回測
+pub mod backtest;
 /// 聊天機器人
 pub mod bot;
 /// 數據快取
 pub mod cache;
 /// 計算類
 pub mod calculation;
+/// 台股交易日曆
+pub mod calendar;
+/// 命令列子命令
+pub mod cli;
 /// 設定檔
 pub mod config;
 /// 抓取數據類
@@ -32,6 +41,8 @@ pub mod database;
 pub mod declare;
 /// 事件
 pub mod event;
+/// 匯入券商對帳單
+pub mod importer;
 /// 日誌
 pub mod logging;
 /// nosql
@@ -93,6 +104,13 @@ async fn shutdown_signal_handler(received_signal: Arc<AtomicBool>) {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(command) = cli::Command::parse(&args)? {
+        dotenv::dotenv().ok();
+        command.execute().await?;
+        return Ok(());
+    }
+
     let received_signal = Arc::new(AtomicBool::new(false));
 
     tokio::spawn(shutdown_signal_handler(received_signal.clone()));
@@ -108,11 +126,20 @@ async fn main() -> Result<(), Box<dyn Error>> {
     });
 
     dotenv::dotenv().ok();
+    util::metrics::init();
     cache::SHARE.load().await;
 
-    let sched = JobScheduler::new().await?;
+    let mut sched = JobScheduler::new().await?;
     scheduler::start(&sched).await?;
-    rpc::server::start().await?;
+    let grpc_server = rpc::server::start(received_signal.clone()).await?;
+    let http_server = rpc::http::start(received_signal.clone()).await?;
+
+    if let Err(why) = bot::telegram::receiver::start().await {
+        logging::error_file_async(format!(
+            "Failed to start telegram receiver because {:?}",
+            why
+        ));
+    }
 
     let pong = nosql::redis::CLIENT.ping().await;
     if let Ok(pong) = pong {
@@ -123,6 +150,18 @@ async fn main() -> Result<(), Box<dyn Error>> {
         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
     }
 
+    if let Err(why) = sched.shutdown().await {
+        eprintln!("Failed to shutdown scheduler: {:?}", why);
+    }
+    scheduler::lease::LEASE.release_all().await;
+
+    // 等待處理中的請求結束，串流的請求可能不會自行結束所以設定上限
+    for server in [grpc_server, http_server].into_iter().flatten() {
+        if tokio::time::timeout(SERVER_DRAIN_TIMEOUT, server).await.is_err() {
+            eprintln!("Timed out waiting for in-flight requests");
+        }
+    }
+
     println!("Server stopped: {:?}", received_signal);
 
     Ok(())
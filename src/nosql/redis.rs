@@ -13,6 +13,24 @@ use crate::{config::SETTINGS, util::text};
 
 pub static CLIENT: Lazy<Arc<Redis>> = Lazy::new(|| Arc::new(Redis::new()));
 
+/// Grants the lease when the key is free, or renews it when the caller already holds it.
+const ACQUIRE_LEASE_SCRIPT: &str = r#"
+local holder = redis.call('GET', KEYS[1])
+if holder == false or holder == ARGV[1] then
+    redis.call('SET', KEYS[1], ARGV[1], 'PX', ARGV[2])
+    return 1
+end
+return 0
+"#;
+
+/// Deletes the lease only when the caller still holds it.
+const RELEASE_LEASE_SCRIPT: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('DEL', KEYS[1])
+end
+return 0
+"#;
+
 pub struct Redis {
     pub pool: Pool,
 }
@@ -238,6 +256,59 @@ impl Redis {
         let keys = self.get_key(pattern.to_string()).await?;
         Ok(!keys.is_empty())
     }
+
+    /// Acquires or renews a lease held by the given owner.
+    ///
+    /// The lease is granted when the key does not exist, and renewed when the key already
+    /// holds the same owner. Both cases reset the time-to-live.
+    ///
+    /// # Arguments
+    ///
+    /// * key: The key of the lease.
+    /// * owner: The value identifying the holder of the lease.
+    /// * ttl_in_millis: The time-to-live of the lease in milliseconds.
+    ///
+    /// # Returns
+    ///
+    /// * Result<bool>: Whether the owner holds the lease after the call.
+    pub async fn acquire_lease(&self, key: &str, owner: &str, ttl_in_millis: u64) -> Result<bool> {
+        let mut conn = self.pool.get().await?;
+        let held: i64 = cmd("EVAL")
+            .arg(ACQUIRE_LEASE_SCRIPT)
+            .arg(1)
+            .arg(key)
+            .arg(owner)
+            .arg(ttl_in_millis)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| anyhow!("Failed to acquire lease({}) from Redis: {}", key, e))?;
+
+        Ok(held == 1)
+    }
+
+    /// Releases a lease if it is still held by the given owner.
+    ///
+    /// # Arguments
+    ///
+    /// * key: The key of the lease.
+    /// * owner: The value identifying the holder of the lease.
+    ///
+    /// # Returns
+    ///
+    /// * Result<bool>: Whether the lease was held by the owner and has been released.
+    pub async fn release_lease(&self, key: &str, owner: &str) -> Result<bool> {
+        let mut conn = self.pool.get().await?;
+        let released: i64 = cmd("EVAL")
+            .arg(RELEASE_LEASE_SCRIPT)
+            .arg(1)
+            .arg(key)
+            .arg(owner)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| anyhow!("Failed to release lease({}) from Redis: {}", key, e))?;
+
+        Ok(released == 1)
+    }
 }
 
 impl Default for Redis {
@@ -0,0 +1,379 @@
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use axum::{
+    extract::{Path, Query, Request as HttpRequest},
+    http::StatusCode,
+    middleware::{self, Next},
+    response::{IntoResponse, Response as HttpResponse},
+    routing::get,
+    Json, Router,
+};
+use chrono::{Datelike, Local};
+use serde::Deserialize;
+use serde_json::json;
+use tokio::{net::TcpListener, task::JoinHandle};
+use tonic::{Code, Request, Response, Status};
+
+use crate::{
+    calculation::portfolio::{self, Portfolio},
+    config::{GrpcAuth, GrpcScope, SETTINGS},
+    logging,
+    rpc::{
+        server::{auth, stock_service::StockService},
+        stock::{
+            stock_server::Stock, DailyQuotesReply, DailyQuotesRequest, DailyStockPriceStatsReply,
+            DailyStockPriceStatsRequest, DividendsReply, DividendsRequest, EstimatesReply,
+            EstimatesRequest, FinancialStatementsReply, FinancialStatementsRequest,
+            HolidayScheduleReply, HolidayScheduleRequest, QuoteHistoryRecordsReply,
+            QuoteHistoryRecordsRequest, RevenuesReply, RevenuesRequest, StockQuotesReply,
+            StockQuotesRequest, YieldRanksReply, YieldRanksRequest,
+        },
+    },
+    util::metrics,
+};
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+/// 以 gRPC 的錯誤碼對應 HTTP 狀態碼，回應內容為 {"code": "...", "message": "..."}
+pub struct ApiError(Status);
+
+impl From<Status> for ApiError {
+    fn from(status: Status) -> Self {
+        ApiError(status)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> HttpResponse {
+        let body = json!({
+            "code": format!("{:?}", self.0.code()),
+            "message": self.0.message(),
+        });
+
+        (to_status_code(self.0.code()), Json(body)).into_response()
+    }
+}
+
+fn to_status_code(code: Code) -> StatusCode {
+    match code {
+        Code::Ok => StatusCode::OK,
+        Code::InvalidArgument | Code::OutOfRange => StatusCode::BAD_REQUEST,
+        Code::Unauthenticated => StatusCode::UNAUTHORIZED,
+        Code::PermissionDenied => StatusCode::FORBIDDEN,
+        Code::NotFound => StatusCode::NOT_FOUND,
+        Code::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+        Code::Unimplemented => StatusCode::NOT_IMPLEMENTED,
+        Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        Code::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// 以逗號分隔多個股票代號，例如 ?symbols=2330,2317
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct SymbolsQuery {
+    symbols: String,
+    page_size: i32,
+    page_token: String,
+}
+
+fn split_symbols(symbols: &str) -> Vec<String> {
+    symbols
+        .split(',')
+        .map(str::trim)
+        .filter(|symbol| !symbol.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// HTTP 以明文傳送 api key，只在本機的介面上提供服務，
+/// 對外需由同一台主機上的反向代理負責 TLS 後轉送過來
+const BIND_ADDRESS: &str = "127.0.0.1";
+
+/// 啟動 HTTP/JSON API，received_signal 為 true 後不再接受新的連線
+pub async fn start(received_signal: Arc<AtomicBool>) -> Result<Option<JoinHandle<()>>> {
+    if SETTINGS.system.http_use_port == 0 {
+        return Ok(None);
+    }
+
+    if !supports_auth(&SETTINGS.system.grpc_auth) {
+        logging::warn_file_async(
+            "grpc_auth 只設定了 mTLS 而沒有 api key，HTTP 無法驗證用戶端憑證，不啟動 HTTP 服務"
+                .to_string(),
+        );
+        return Ok(None);
+    }
+
+    let addr = format!("{}:{}", BIND_ADDRESS, SETTINGS.system.http_use_port);
+    let listener = TcpListener::bind(&addr)
+        .await
+        .context(format!("Failed to bind {}", addr))?;
+    let shutdown = async move {
+        while !received_signal.load(Ordering::SeqCst) {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    };
+
+    let handle = tokio::spawn(async move {
+        if let Err(why) = axum::serve(listener, router())
+            .with_graceful_shutdown(shutdown)
+            .await
+        {
+            logging::error_file_async(format!("HTTP伺服器錯誤: {:?}", why));
+        }
+    });
+
+    logging::info_file_async(format!("啟動 HTTP({}) 服務", addr));
+
+    Ok(Some(handle))
+}
+
+fn router() -> Router {
+    Router::new()
+        .route("/api/quotes", get(quotes))
+        .route("/api/quote-history-records", get(quote_history_records))
+        .route("/api/holidays", get(holidays))
+        .route("/api/yield-ranks", get(yield_ranks))
+        .route("/api/daily-stock-price-stats", get(daily_stock_price_stats))
+        .route("/api/portfolio", get(portfolio))
+        .route("/metrics", get(prometheus_metrics))
+        .route("/api/stocks/:symbol/daily-quotes", get(daily_quotes))
+        .route("/api/stocks/:symbol/estimates", get(estimates))
+        .route("/api/stocks/:symbol/dividends", get(dividends))
+        .route("/api/stocks/:symbol/revenues", get(revenues))
+        .route(
+            "/api/stocks/:symbol/financial-statements",
+            get(financial_statements),
+        )
+        .layer(middleware::from_fn(require_scope))
+}
+
+/// HTTP 沒有用戶端憑證只能以 api key 驗證，啟用驗證卻沒有任何 api key 時所有的請求都會被拒絕
+fn supports_auth(auth: &GrpcAuth) -> bool {
+    !auth.is_enabled() || !auth.api_keys.is_empty()
+}
+
+/// 呼叫 HTTP 路徑所需的權限，持股明細為個人資料，需要與匯入對帳單相同的 Write 權限
+fn required_scope(path: &str) -> GrpcScope {
+    match path {
+        "/api/portfolio" => GrpcScope::Write,
+        _ => GrpcScope::Read,
+    }
+}
+
+/// 沿用 gRPC 的 api key 檢查路徑所需的權限
+async fn require_scope(req: HttpRequest, next: Next) -> HttpResponse {
+    let result = auth::authorize_scope(
+        &SETTINGS.system.grpc_auth,
+        required_scope(req.uri().path()),
+        auth::api_key(req.headers()),
+        false,
+    );
+
+    match result {
+        Ok(()) => next.run(req).await,
+        Err(status) => {
+            logging::warn_file_async(format!(
+                "Rejected HTTP call {} because {}",
+                req.uri().path(),
+                status.message()
+            ));
+            ApiError(status).into_response()
+        }
+    }
+}
+
+/// 呼叫 gRPC 服務的實作並將回應轉為 JSON
+async fn reply<T>(call: impl Future<Output = Result<Response<T>, Status>>) -> ApiResult<T> {
+    Ok(Json(call.await?.into_inner()))
+}
+
+async fn quotes(Query(query): Query<SymbolsQuery>) -> ApiResult<StockQuotesReply> {
+    let request = StockQuotesRequest {
+        stock_symbols: split_symbols(&query.symbols),
+    };
+    if request.stock_symbols.is_empty() {
+        return Err(Status::invalid_argument("symbols is required").into());
+    }
+
+    reply(StockService::default().fetch_current_stock_quotes(Request::new(request))).await
+}
+
+async fn quote_history_records(
+    Query(query): Query<SymbolsQuery>,
+) -> ApiResult<QuoteHistoryRecordsReply> {
+    let request = QuoteHistoryRecordsRequest {
+        stock_symbols: split_symbols(&query.symbols),
+        page_size: query.page_size,
+        page_token: query.page_token,
+    };
+
+    reply(StockService::default().fetch_quote_history_records(Request::new(request))).await
+}
+
+async fn holidays(
+    Query(mut request): Query<HolidayScheduleRequest>,
+) -> ApiResult<HolidayScheduleReply> {
+    if request.year == 0 {
+        request.year = Local::now().year();
+    }
+
+    reply(StockService::default().fetch_holiday_schedule(Request::new(request))).await
+}
+
+async fn yield_ranks(Query(request): Query<YieldRanksRequest>) -> ApiResult<YieldRanksReply> {
+    reply(StockService::default().fetch_yield_ranks(Request::new(request))).await
+}
+
+async fn daily_stock_price_stats(
+    Query(request): Query<DailyStockPriceStatsRequest>,
+) -> ApiResult<DailyStockPriceStatsReply> {
+    reply(StockService::default().fetch_daily_stock_price_stats(Request::new(request))).await
+}
+
+async fn portfolio() -> ApiResult<Portfolio> {
+    portfolio::current().await.map(Json).map_err(|why| {
+        logging::error_file_async(format!("Failed to portfolio::current because {:?}", why));
+        Status::internal("Failed to calculate the portfolio").into()
+    })
+}
+
+async fn prometheus_metrics() -> String {
+    metrics::render()
+}
+
+async fn daily_quotes(
+    Path(symbol): Path<String>,
+    Query(mut request): Query<DailyQuotesRequest>,
+) -> ApiResult<DailyQuotesReply> {
+    request.stock_symbol = symbol;
+    reply(StockService::default().fetch_daily_quotes(Request::new(request))).await
+}
+
+async fn estimates(
+    Path(symbol): Path<String>,
+    Query(mut request): Query<EstimatesRequest>,
+) -> ApiResult<EstimatesReply> {
+    request.stock_symbol = symbol;
+    reply(StockService::default().fetch_estimates(Request::new(request))).await
+}
+
+async fn dividends(
+    Path(symbol): Path<String>,
+    Query(mut request): Query<DividendsRequest>,
+) -> ApiResult<DividendsReply> {
+    request.stock_symbol = symbol;
+    reply(StockService::default().fetch_dividends(Request::new(request))).await
+}
+
+async fn revenues(
+    Path(symbol): Path<String>,
+    Query(mut request): Query<RevenuesRequest>,
+) -> ApiResult<RevenuesReply> {
+    request.stock_symbol = symbol;
+    reply(StockService::default().fetch_revenues(Request::new(request))).await
+}
+
+async fn financial_statements(
+    Path(symbol): Path<String>,
+    Query(mut request): Query<FinancialStatementsRequest>,
+) -> ApiResult<FinancialStatementsReply> {
+    request.stock_symbol = symbol;
+    reply(StockService::default().fetch_financial_statements(Request::new(request))).await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::GrpcApiKey;
+
+    use super::*;
+
+    #[test]
+    fn test_split_symbols() {
+        assert_eq!(split_symbols(" 2330, ,2317,"), vec!["2330", "2317"]);
+        assert!(split_symbols("").is_empty());
+    }
+
+    #[test]
+    fn test_to_status_code() {
+        assert_eq!(
+            to_status_code(Code::InvalidArgument),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            to_status_code(Code::Unauthenticated),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            to_status_code(Code::PermissionDenied),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            to_status_code(Code::Internal),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn test_supports_auth() {
+        let mut auth = GrpcAuth::default();
+        assert!(supports_auth(&auth));
+
+        auth.client_ca_file = "ca.pem".to_string();
+        assert!(!supports_auth(&auth));
+
+        auth.api_keys.push(GrpcApiKey {
+            name: "web".to_string(),
+            key: "secret".to_string(),
+            scopes: vec![GrpcScope::Read],
+        });
+        assert!(supports_auth(&auth));
+    }
+
+    #[test]
+    fn test_required_scope() {
+        assert_eq!(required_scope("/api/quotes"), GrpcScope::Read);
+        assert_eq!(required_scope("/metrics"), GrpcScope::Read);
+        assert_eq!(required_scope("/api/portfolio"), GrpcScope::Write);
+
+        let auth = GrpcAuth {
+            api_keys: vec![GrpcApiKey {
+                name: "web".to_string(),
+                key: "secret".to_string(),
+                scopes: vec![GrpcScope::Read],
+            }],
+            ..Default::default()
+        };
+        let result = auth::authorize_scope(
+            &auth,
+            required_scope("/api/portfolio"),
+            Some("secret"),
+            false,
+        );
+        assert_eq!(result.unwrap_err().code(), Code::PermissionDenied);
+    }
+
+    #[test]
+    fn test_query_uses_proto_defaults() {
+        let Query(request): Query<DailyQuotesRequest> = Query::try_from_uri(
+            &"/api/stocks/2330/daily-quotes?start_date=2024-01-02&page_size=10"
+                .parse()
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(request.start_date, "2024-01-02");
+        assert_eq!(request.page_size, 10);
+        assert_eq!(request.end_date, "");
+        assert_eq!(request.page_token, "");
+        assert_eq!(request.series, "");
+    }
+}
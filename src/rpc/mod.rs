@@ -1,6 +1,11 @@
 pub mod client;
+/// 提供無法使用 gRPC 的用戶端查詢的 HTTP/JSON API 與 Prometheus 指標
+pub mod http;
 pub mod server;
 
+/// 所有 proto 的描述檔，提供 gRPC reflection 使用
+pub const FILE_DESCRIPTOR_SET: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/descriptor.bin"));
+
 pub mod stock {
     include!("stock.rs");
 }
@@ -0,0 +1,282 @@
+use std::{
+    convert::Infallible,
+    task::{Context, Poll},
+};
+
+use tonic::{
+    body::BoxBody,
+    codegen::{http, BoxFuture, Service},
+    server::NamedService,
+    transport::server::{TcpConnectInfo, TlsConnectInfo},
+    Status,
+};
+
+use crate::{
+    config::{GrpcAuth, GrpcScope, SETTINGS},
+    logging,
+};
+
+/// 帶入 api key 的 metadata
+const API_KEY_HEADER: &str = "x-api-key";
+
+/// 在請求進入 gRPC 服務前驗證用戶端並檢查方法所需的權限
+#[derive(Clone)]
+pub struct Authenticated<S> {
+    inner: S,
+}
+
+impl<S> Authenticated<S> {
+    pub fn new(inner: S) -> Self {
+        Authenticated { inner }
+    }
+}
+
+impl<S: NamedService> NamedService for Authenticated<S> {
+    const NAME: &'static str = S::NAME;
+}
+
+impl<S> Service<http::Request<BoxBody>> for Authenticated<S>
+where
+    S: Service<http::Request<BoxBody>, Response = http::Response<BoxBody>, Error = Infallible>
+        + Clone
+        + Send
+        + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<BoxBody>) -> Self::Future {
+        let api_key = api_key(req.headers());
+        let has_client_cert = req
+            .extensions()
+            .get::<TlsConnectInfo<TcpConnectInfo>>()
+            .and_then(|info| info.peer_certs())
+            .is_some_and(|certs| !certs.is_empty());
+
+        match authorize(
+            &SETTINGS.system.grpc_auth,
+            req.uri().path(),
+            api_key,
+            has_client_cert,
+        ) {
+            Ok(()) => Box::pin(self.inner.call(req)),
+            Err(status) => {
+                logging::warn_file_async(format!(
+                    "Rejected gRPC call {} because {}",
+                    req.uri().path(),
+                    status.message()
+                ));
+                Box::pin(async move { Ok(status.into_http()) })
+            }
+        }
+    }
+}
+
+/// 從 x-api-key 或 authorization: Bearer 取得 api key
+pub fn api_key(headers: &http::HeaderMap) -> Option<&str> {
+    if let Some(key) = headers.get(API_KEY_HEADER) {
+        return key.to_str().ok();
+    }
+
+    headers
+        .get(http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+}
+
+/// 健康檢查不需驗證，讓負載平衡與容器編排可以直接探測
+const HEALTH_SERVICE_PREFIX: &str = "/grpc.health.v1.Health/";
+
+/// 呼叫 gRPC 方法所需的權限，未列出的方法一律需要 Control 權限
+pub fn required_scope(path: &str) -> GrpcScope {
+    match path {
+        "/stock.Stock/UpdateStockInfo" | "/stock.Stock/ImportBrokerStatement" => GrpcScope::Write,
+        _ if path.starts_with("/stock.Stock/") => GrpcScope::Read,
+        _ if path.starts_with("/grpc.reflection.") => GrpcScope::Read,
+        _ => GrpcScope::Control,
+    }
+}
+
+/// 依 api key 與是否通過 mTLS 驗證判斷用戶端是否可以呼叫指定的方法
+#[allow(clippy::result_large_err)]
+pub fn authorize(
+    auth: &GrpcAuth,
+    path: &str,
+    api_key: Option<&str>,
+    has_client_cert: bool,
+) -> Result<(), Status> {
+    if path.starts_with(HEALTH_SERVICE_PREFIX) {
+        return Ok(());
+    }
+
+    authorize_scope(auth, required_scope(path), api_key, has_client_cert)
+}
+
+/// 依 api key 與是否通過 mTLS 驗證判斷用戶端是否擁有指定的權限
+#[allow(clippy::result_large_err)]
+pub fn authorize_scope(
+    auth: &GrpcAuth,
+    required: GrpcScope,
+    api_key: Option<&str>,
+    has_client_cert: bool,
+) -> Result<(), Status> {
+    // 未設定驗證時只開放查詢，Write 與 Control 一律拒絕，避免任何人都能觸發排程或寫入資料
+    if !auth.is_enabled() {
+        return match required {
+            GrpcScope::Read => Ok(()),
+            _ => Err(Status::unauthenticated(format!(
+                "Authentication is not configured, {:?} scope is unavailable",
+                required
+            ))),
+        };
+    }
+
+    let mut scopes: Vec<GrpcScope> = Vec::new();
+    if has_client_cert {
+        scopes.extend(&auth.client_scopes);
+    }
+
+    if let Some(api_key) = api_key {
+        match auth
+            .api_keys
+            .iter()
+            .find(|k| constant_time_eq(k.key.as_bytes(), api_key.as_bytes()))
+        {
+            Some(k) => scopes.extend(&k.scopes),
+            None => return Err(Status::unauthenticated("Invalid api key")),
+        }
+    } else if !has_client_cert {
+        return Err(Status::unauthenticated(
+            "Missing api key or client certificate",
+        ));
+    }
+
+    if scopes.contains(&required) {
+        Ok(())
+    } else {
+        Err(Status::permission_denied(format!(
+            "{:?} scope is required",
+            required
+        )))
+    }
+}
+
+/// 比對金鑰時不因第一個不同的位元組而提早結束
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use tonic::Code;
+
+    use crate::config::GrpcApiKey;
+
+    use super::*;
+
+    fn auth() -> GrpcAuth {
+        GrpcAuth {
+            api_keys: vec![GrpcApiKey {
+                name: "go".to_string(),
+                key: "secret".to_string(),
+                scopes: vec![GrpcScope::Read, GrpcScope::Write],
+            }],
+            client_ca_file: "ca.pem".to_string(),
+            client_scopes: vec![GrpcScope::Control],
+        }
+    }
+
+    #[test]
+    fn test_required_scope() {
+        assert_eq!(
+            required_scope("/stock.Stock/FetchDailyQuotes"),
+            GrpcScope::Read
+        );
+        assert_eq!(
+            required_scope("/stock.Stock/UpdateStockInfo"),
+            GrpcScope::Write
+        );
+        assert_eq!(
+            required_scope("/control.Control/TriggerJob"),
+            GrpcScope::Control
+        );
+        assert_eq!(
+            required_scope("/grpc.reflection.v1.ServerReflection/ServerReflectionInfo"),
+            GrpcScope::Read
+        );
+        assert_eq!(required_scope("/unknown.Service/Call"), GrpcScope::Control);
+    }
+
+    #[test]
+    fn test_authorize() {
+        let auth = auth();
+        let read = "/stock.Stock/FetchDailyQuotes";
+        let control = "/control.Control/ListJobs";
+
+        assert!(authorize(&auth, read, Some("secret"), false).is_ok());
+        assert!(authorize(&auth, control, None, true).is_ok());
+        assert!(authorize(&auth, control, Some("secret"), true).is_ok());
+        assert!(authorize(&auth, "/grpc.health.v1.Health/Check", None, false).is_ok());
+
+        let code = |result: Result<(), Status>| result.unwrap_err().code();
+        assert_eq!(
+            code(authorize(&auth, read, None, false)),
+            Code::Unauthenticated
+        );
+        assert_eq!(
+            code(authorize(&auth, read, Some("wrong"), true)),
+            Code::Unauthenticated
+        );
+        assert_eq!(
+            code(authorize(&auth, control, Some("secret"), false)),
+            Code::PermissionDenied
+        );
+        assert_eq!(
+            code(authorize(&auth, read, None, true)),
+            Code::PermissionDenied
+        );
+    }
+
+    #[test]
+    fn test_authorize_without_auth() {
+        let auth = GrpcAuth::default();
+        let code = |result: Result<(), Status>| result.unwrap_err().code();
+
+        assert!(authorize(&auth, "/stock.Stock/FetchDailyQuotes", None, false).is_ok());
+        assert!(authorize(&auth, "/grpc.health.v1.Health/Check", None, false).is_ok());
+        assert_eq!(
+            code(authorize(&auth, "/control.Control/TriggerJob", None, false)),
+            Code::Unauthenticated
+        );
+        assert_eq!(
+            code(authorize(
+                &auth,
+                "/stock.Stock/ImportBrokerStatement",
+                Some("any"),
+                true
+            )),
+            Code::Unauthenticated
+        );
+    }
+
+    #[test]
+    fn test_api_key() {
+        let mut headers = http::HeaderMap::new();
+        assert_eq!(api_key(&headers), None);
+
+        headers.insert(
+            http::header::AUTHORIZATION,
+            "Bearer secret".parse().unwrap(),
+        );
+        assert_eq!(api_key(&headers), Some("secret"));
+
+        headers.insert(API_KEY_HEADER, "key".parse().unwrap());
+        assert_eq!(api_key(&headers), Some("key"));
+    }
+}
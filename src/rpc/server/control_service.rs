@@ -1,9 +1,18 @@
 use anyhow::Result;
+use chrono::{DateTime, Local, NaiveDate};
 use tonic::{Request, Response, Status};
 
-use crate::rpc::{
-    basic::BaseResponse,
-    control::{control_server::Control, ControlRequest, ControlResponse},
+use crate::{
+    cache::SHARE,
+    logging,
+    rpc::{
+        basic::BaseResponse,
+        control::{
+            control_server::Control, CancelJobRequest, ControlRequest, ControlResponse, Job,
+            JobRun, ListJobsReply, ListJobsRequest, ReloadCacheRequest, TriggerJobRequest,
+        },
+    },
+    scheduler::registry::{self, JobOutcome, Trigger, JOBS},
 };
 
 #[derive(Default)]
@@ -29,6 +38,103 @@ impl Control for ControlService {
 
         Ok(Response::new(response))
     }
+
+    async fn list_jobs(
+        &self,
+        _req: Request<ListJobsRequest>,
+    ) -> Result<Response<ListJobsReply>, Status> {
+        let mut jobs = Vec::new();
+        for status in JOBS.list() {
+            let next_run_at = JOBS.next_run_at(status.name).await;
+            jobs.push(Job {
+                name: status.name.to_string(),
+                cron: status.schedule.cron,
+                dated: status.dated,
+                next_run_at: format_time(next_run_at),
+                running: status.running_since.is_some(),
+                running_since: format_time(status.running_since),
+                last_run: status.last_run.map(JobRun::from),
+            });
+        }
+
+        Ok(Response::new(ListJobsReply { jobs }))
+    }
+
+    async fn trigger_job(
+        &self,
+        req: Request<TriggerJobRequest>,
+    ) -> Result<Response<ControlResponse>, Status> {
+        let request = req.into_inner();
+        if !JOBS.contains(&request.name) {
+            return Err(Status::not_found(format!("Job {} not found", request.name)));
+        }
+
+        let date = match request.date.as_str() {
+            "" => None,
+            date => Some(NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| {
+                Status::invalid_argument(format!("Invalid date '{}'", date))
+            })?),
+        };
+
+        JOBS.run(&request.name, date, Trigger::Manual)
+            .map_err(|why| Status::failed_precondition(why.to_string()))?;
+        logging::info_file_async(format!("手動觸發工作 {} {:?}", request.name, date));
+
+        Ok(ok(format!("Job {} triggered", request.name)))
+    }
+
+    async fn cancel_job(
+        &self,
+        req: Request<CancelJobRequest>,
+    ) -> Result<Response<ControlResponse>, Status> {
+        let request = req.into_inner();
+        if !JOBS.contains(&request.name) {
+            return Err(Status::not_found(format!("Job {} not found", request.name)));
+        }
+
+        JOBS.cancel(&request.name)
+            .map_err(|why| Status::failed_precondition(why.to_string()))?;
+        logging::info_file_async(format!("手動取消工作 {}", request.name));
+
+        Ok(ok(format!("Job {} cancelled", request.name)))
+    }
+
+    async fn reload_cache(
+        &self,
+        _req: Request<ReloadCacheRequest>,
+    ) -> Result<Response<ControlResponse>, Status> {
+        SHARE.load().await;
+        logging::info_file_async("手動重新載入快取".to_string());
+
+        Ok(ok("Cache reloaded".to_string()))
+    }
+}
+
+fn ok(message: String) -> Response<ControlResponse> {
+    Response::new(ControlResponse {
+        message: Some(BaseResponse { message, code: 200 }),
+    })
+}
+
+fn format_time(time: Option<DateTime<Local>>) -> String {
+    time.map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_default()
+}
+
+impl From<registry::JobRun> for JobRun {
+    fn from(run: registry::JobRun) -> Self {
+        let error = match &run.outcome {
+            JobOutcome::Failed(why) => why.to_string(),
+            _ => String::new(),
+        };
+
+        JobRun {
+            started_at: format_time(Some(run.started_at)),
+            finished_at: format_time(Some(run.finished_at)),
+            outcome: run.outcome.to_string(),
+            error,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,108 @@
+use std::time::Duration;
+
+use tokio::time;
+use tonic_health::{server::HealthReporter, ServingStatus};
+
+use crate::{
+    database, logging, nosql,
+    rpc::{control::control_server::ControlServer, stock::stock_server::StockServer},
+    scheduler::registry::JOBS,
+};
+
+use super::{control_service::ControlService, stock_service::StockService};
+
+/// 檢查相依服務狀態的間隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(15);
+/// 單一相依服務檢查的逾時
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub const POSTGRESQL: &str = "postgresql";
+pub const REDIS: &str = "redis";
+pub const SCHEDULER: &str = "scheduler";
+
+/// 定時檢查 PostgreSQL、Redis 與排程的狀態並回報給 grpc.health.v1，
+/// 空白的服務名稱代表整體狀態，全部正常時才是 SERVING
+pub async fn watch(mut reporter: HealthReporter) {
+    let mut ticker = time::interval(CHECK_INTERVAL);
+
+    loop {
+        ticker.tick().await;
+
+        let (postgresql, redis, scheduler) =
+            tokio::join!(check_postgresql(), check_redis(), check_scheduler());
+        let all = postgresql && redis && scheduler;
+
+        for (service, healthy) in [
+            (POSTGRESQL, postgresql),
+            (REDIS, redis),
+            (SCHEDULER, scheduler),
+            ("", all),
+        ] {
+            reporter
+                .set_service_status(service, to_status(healthy))
+                .await;
+        }
+
+        if all {
+            reporter.set_serving::<StockServer<StockService>>().await;
+            reporter.set_serving::<ControlServer<ControlService>>().await;
+        } else {
+            reporter.set_not_serving::<StockServer<StockService>>().await;
+            reporter.set_not_serving::<ControlServer<ControlService>>().await;
+        }
+    }
+}
+
+/// 關機時先回報 NOT_SERVING，讓負載平衡不再送新的請求進來
+pub async fn shutdown(reporter: &mut HealthReporter) {
+    for service in [POSTGRESQL, REDIS, SCHEDULER, ""] {
+        reporter
+            .set_service_status(service, ServingStatus::NotServing)
+            .await;
+    }
+
+    reporter.set_not_serving::<StockServer<StockService>>().await;
+    reporter.set_not_serving::<ControlServer<ControlService>>().await;
+}
+
+fn to_status(healthy: bool) -> ServingStatus {
+    if healthy {
+        ServingStatus::Serving
+    } else {
+        ServingStatus::NotServing
+    }
+}
+
+async fn check_postgresql() -> bool {
+    let ping = sqlx::query("SELECT 1").execute(database::get_connection());
+    match time::timeout(CHECK_TIMEOUT, ping).await {
+        Ok(Ok(_)) => true,
+        Ok(Err(why)) => {
+            logging::error_file_async(format!("PostgreSQL health check failed: {:?}", why));
+            false
+        }
+        Err(_) => {
+            logging::error_file_async("PostgreSQL health check timed out".to_string());
+            false
+        }
+    }
+}
+
+async fn check_redis() -> bool {
+    match time::timeout(CHECK_TIMEOUT, nosql::redis::CLIENT.ping()).await {
+        Ok(Ok(_)) => true,
+        Ok(Err(why)) => {
+            logging::error_file_async(format!("Redis health check failed: {:?}", why));
+            false
+        }
+        Err(_) => {
+            logging::error_file_async("Redis health check timed out".to_string());
+            false
+        }
+    }
+}
+
+async fn check_scheduler() -> bool {
+    JOBS.is_started().await
+}
+
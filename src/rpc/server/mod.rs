@@ -1,51 +1,96 @@
-use std::net::SocketAddr;
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
-use anyhow::Result;
-use tonic::transport::{Identity, Server, ServerTlsConfig};
+use anyhow::{anyhow, Result};
+use tokio::task::JoinHandle;
+use tonic::transport::{Certificate, Identity, Server, ServerTlsConfig};
 
 use crate::{
     config::SETTINGS,
     logging,
     rpc::{
         control::control_server::ControlServer, server::control_service::ControlService,
-        server::stock_service::StockService, stock::stock_server::StockServer,
+        server::{auth::Authenticated, stock_service::StockService},
+        stock::stock_server::StockServer,
+        FILE_DESCRIPTOR_SET,
     },
 };
 
+pub mod auth;
 pub mod control_service;
+pub mod health;
+pub mod pagination;
+pub mod quote_subscription;
+pub mod stock_info;
 pub mod stock_service;
 
-/// 啟動 GRPC Server
-pub async fn start() -> Result<()> {
+/// 啟動 GRPC Server，received_signal 為 true 後不再接受新的連線，
+/// 回傳的 JoinHandle 會在處理中的請求都結束後完成
+pub async fn start(received_signal: Arc<AtomicBool>) -> Result<Option<JoinHandle<()>>> {
     if SETTINGS.system.grpc_use_port == 0 {
-        return Ok(());
+        return Ok(None);
     }
 
     let addr = format!("0.0.0.0:{}", SETTINGS.system.grpc_use_port).parse()?;
 
     // 使用 tokio::spawn 啟動一個新的異步任務
-    tokio::spawn(async move {
-        if let Err(why) = run_grpc_server(addr).await {
+    let handle = tokio::spawn(async move {
+        if let Err(why) = run_grpc_server(addr, received_signal).await {
             logging::error_file_async(format!("gRPC伺服器錯誤: {}", why));
         }
     });
 
     logging::info_file_async(format!("啟動 gRPC({:?}) 服務", addr));
 
-    Ok(())
+    Ok(Some(handle))
 }
 
-async fn run_grpc_server(addr: SocketAddr) -> Result<()> {
+async fn run_grpc_server(addr: SocketAddr, received_signal: Arc<AtomicBool>) -> Result<()> {
     let builder = Server::builder();
     let mut server = match get_tls_config() {
         Some(config) => configure_tls(builder, config)?,
+        None if !SETTINGS.system.grpc_auth.client_ca_file.is_empty() => {
+            return Err(anyhow!("mTLS requires ssl_cert_file and ssl_key_file"))
+        }
         None => builder,
     };
 
+    if !SETTINGS.system.grpc_auth.is_enabled() {
+        logging::warn_file_async(
+            "gRPC 未設定用戶端驗證，只開放查詢，寫入與維運的方法一律拒絕".to_string(),
+        );
+    }
+
+    let (mut reporter, health_service) = tonic_health::server::health_reporter();
+    let watcher = tokio::spawn(health::watch(reporter.clone()));
+    let reflection_service = tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(FILE_DESCRIPTOR_SET)
+        .build_v1()?;
+
+    let shutdown = async move {
+        while !received_signal.load(Ordering::SeqCst) {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+
+        watcher.abort();
+        health::shutdown(&mut reporter).await;
+        logging::info_file_async("gRPC 服務停止接受新的請求，等待處理中的請求結束".to_string());
+    };
+
     Ok(server
-        .add_service(ControlServer::new(ControlService::default()))
-        .add_service(StockServer::new(StockService::default()))
-        .serve(addr)
+        .add_service(Authenticated::new(health_service))
+        .add_service(Authenticated::new(reflection_service))
+        .add_service(Authenticated::new(ControlServer::new(
+            ControlService::default(),
+        )))
+        .add_service(Authenticated::new(StockServer::new(StockService::default())))
+        .serve_with_shutdown(addr, shutdown)
         .await?)
 }
 
@@ -64,8 +109,18 @@ fn configure_tls(builder: Server, (cert_file, key_file): (String, String)) -> Re
     let cert_content = std::fs::read_to_string(cert_file)?;
     let key_content = std::fs::read_to_string(key_file)?;
     let identity = Identity::from_pem(cert_content, key_content);
+    let mut tls = ServerTlsConfig::new().identity(identity);
+
+    // 設定 client CA 後驗證用戶端憑證，同時有 api key 時允許未帶憑證的用戶端改用 api key
+    let auth = &SETTINGS.system.grpc_auth;
+    if !auth.client_ca_file.is_empty() {
+        let ca_content = std::fs::read_to_string(&auth.client_ca_file)?;
+        tls = tls
+            .client_ca_root(Certificate::from_pem(ca_content))
+            .client_auth_optional(!auth.api_keys.is_empty());
+    }
 
-    Ok(builder.tls_config(ServerTlsConfig::new().identity(identity))?)
+    Ok(builder.tls_config(tls)?)
 }
 
 #[cfg(test)]
@@ -79,7 +134,7 @@ mod tests {
         dotenv::dotenv().ok();
         logging::debug_file_async("開始 rpc::start()".to_string());
 
-        tokio::spawn(start());
+        tokio::spawn(start(Arc::new(AtomicBool::new(false))));
         tokio::time::sleep(Duration::from_secs(10)).await;
         /* match  start().await {
             Ok(_) => {}
@@ -0,0 +1,96 @@
+use tonic::Status;
+
+/// 未指定每頁筆數時的預設值
+pub const DEFAULT_PAGE_SIZE: i64 = 100;
+/// 每頁筆數的上限
+pub const MAX_PAGE_SIZE: i64 = 1000;
+
+/// 查詢類 RPC 的分頁，page_token 為下一頁第一筆的位置
+#[derive(Debug, PartialEq)]
+pub struct Page {
+    pub offset: i64,
+    pub size: i64,
+}
+
+impl Page {
+    #[allow(clippy::result_large_err)]
+    pub fn new(page_size: i32, page_token: &str) -> Result<Self, Status> {
+        let size = match page_size {
+            0 => DEFAULT_PAGE_SIZE,
+            size if size < 0 => {
+                return Err(Status::invalid_argument(format!(
+                    "Invalid page_size {}",
+                    page_size
+                )))
+            }
+            size => (size as i64).min(MAX_PAGE_SIZE),
+        };
+        let offset = match page_token {
+            "" => 0,
+            token => token
+                .parse::<i64>()
+                .ok()
+                .filter(|offset| *offset >= 0)
+                .ok_or_else(|| {
+                    Status::invalid_argument(format!("Invalid page_token '{}'", token))
+                })?,
+        };
+
+        Ok(Page { offset, size })
+    }
+
+    /// 查詢時多取一筆，用來判斷是否還有下一頁
+    pub fn limit(&self) -> i64 {
+        self.size + 1
+    }
+
+    /// 去掉多取的那一筆並回傳下一頁的 page_token，沒有下一頁時為空字串
+    pub fn finish<T>(&self, items: &mut Vec<T>) -> String {
+        if items.len() as i64 <= self.size {
+            return String::new();
+        }
+
+        items.truncate(self.size as usize);
+        (self.offset + self.size).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        assert_eq!(
+            Page::new(0, "").unwrap(),
+            Page {
+                offset: 0,
+                size: DEFAULT_PAGE_SIZE
+            }
+        );
+        assert_eq!(
+            Page::new(5000, "200").unwrap(),
+            Page {
+                offset: 200,
+                size: MAX_PAGE_SIZE
+            }
+        );
+        assert!(Page::new(-1, "").is_err());
+        assert!(Page::new(10, "abc").is_err());
+        assert!(Page::new(10, "-10").is_err());
+    }
+
+    #[test]
+    fn test_finish() {
+        let page = Page::new(2, "4").unwrap();
+        assert_eq!(page.limit(), 3);
+
+        let mut items = vec![1, 2, 3];
+        assert_eq!(page.finish(&mut items), "6");
+        assert_eq!(items, vec![1, 2]);
+
+        let mut items = vec![1, 2];
+        assert_eq!(page.finish(&mut items), "");
+        assert_eq!(items, vec![1, 2]);
+    }
+}
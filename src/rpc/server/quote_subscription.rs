@@ -0,0 +1,305 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use chrono::Local;
+use futures::future::join_all;
+use once_cell::sync::Lazy;
+use tokio::{
+    sync::mpsc::{self, error::TrySendError},
+    task, time,
+};
+use tonic::Status;
+
+use crate::{
+    calendar::CALENDAR, declare, logging, rpc::server::stock_service, rpc::stock::StockQuotes,
+};
+
+/// 共用輪詢迴圈取得報價的間隔
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+/// 每個訂閱者尚未送出的報價上限，超過時會略過該次更新
+const CHANNEL_CAPACITY: usize = 256;
+/// 所有訂閱者合計訂閱的股票數上限，避免每次輪詢向遠端網站查詢過多的股票
+const MAX_TOTAL_SYMBOLS: usize = 1000;
+
+pub static HUB: Lazy<QuoteHub> = Lazy::new(QuoteHub::new);
+
+pub type QuoteReceiver = mpsc::Receiver<Result<StockQuotes, Status>>;
+type QuoteSender = mpsc::Sender<Result<StockQuotes, Status>>;
+
+struct Subscriber {
+    symbols: HashSet<String>,
+    tx: QuoteSender,
+}
+
+#[derive(Default)]
+struct State {
+    subscribers: HashMap<u64, Subscriber>,
+    /// 每檔股票最後一次送出的報價，相同的報價不會重複送出
+    last_quotes: HashMap<String, StockQuotes>,
+    /// 共用的輪詢迴圈是否執行中
+    polling: bool,
+}
+
+impl State {
+    /// 所有訂閱者訂閱中的股票
+    fn subscribed(&self) -> HashSet<&str> {
+        self.subscribers
+            .values()
+            .flat_map(|subscriber| subscriber.symbols.iter().map(String::as_str))
+            .collect()
+    }
+}
+
+/// 即時報價訂閱中心，所有訂閱者共用一個輪詢迴圈，相同的股票只會向遠端網站取一次報價
+pub struct QuoteHub {
+    next_id: AtomicU64,
+    state: Mutex<State>,
+}
+
+impl QuoteHub {
+    pub fn new() -> Self {
+        QuoteHub {
+            next_id: AtomicU64::new(1),
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// 訂閱指定股票的報價，已有的最後報價會先送出，之後僅在開盤期間報價變動時送出，
+    /// 所有訂閱者合計的股票數超過上限時拒絕訂閱
+    #[allow(clippy::result_large_err)]
+    pub fn subscribe(&'static self, symbols: HashSet<String>) -> Result<QuoteReceiver, Status> {
+        let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+        if self.register(symbols, tx)? {
+            task::spawn(self.poll_run());
+        }
+
+        Ok(rx)
+    }
+
+    /// 加入訂閱者，回傳是否需要啟動輪詢迴圈
+    #[allow(clippy::result_large_err)]
+    fn register(&self, symbols: HashSet<String>, tx: QuoteSender) -> Result<bool, Status> {
+        let mut state = self.state.lock().map_err(|why| {
+            logging::error_file_async(format!("Failed to QuoteHub.state.lock because {:?}", why));
+            Status::internal("Failed to subscribe quotes")
+        })?;
+
+        state
+            .subscribers
+            .retain(|_, subscriber| !subscriber.tx.is_closed());
+        let subscribed = state.subscribed();
+        let added = symbols
+            .iter()
+            .filter(|symbol| !subscribed.contains(symbol.as_str()))
+            .count();
+        if subscribed.len() + added > MAX_TOTAL_SYMBOLS {
+            return Err(Status::resource_exhausted(format!(
+                "Too many subscribed stock symbols, at most {} in total",
+                MAX_TOTAL_SYMBOLS
+            )));
+        }
+
+        for symbol in &symbols {
+            if let Some(quote) = state.last_quotes.get(symbol) {
+                let _ = tx.try_send(Ok(quote.clone()));
+            }
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        state.subscribers.insert(id, Subscriber { symbols, tx });
+
+        let start = !state.polling;
+        state.polling = true;
+
+        Ok(start)
+    }
+
+    /// 移除已斷線的訂閱者與不再有人訂閱的最後報價，並回傳目前所有訂閱中的股票，
+    /// 沒有訂閱者時回傳 None 並標記輪詢迴圈結束
+    fn symbols(&self) -> Option<Vec<String>> {
+        let mut state = self.state.lock().ok()?;
+        state
+            .subscribers
+            .retain(|_, subscriber| !subscriber.tx.is_closed());
+
+        let symbols: HashSet<String> = state.subscribed().into_iter().map(str::to_string).collect();
+        state
+            .last_quotes
+            .retain(|symbol, _| symbols.contains(symbol));
+
+        if state.subscribers.is_empty() {
+            state.polling = false;
+            return None;
+        }
+
+        Some(symbols.into_iter().collect())
+    }
+
+    /// 將有變動的報價送給訂閱該股票的訂閱者
+    fn publish(&self, quotes: Vec<StockQuotes>) {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(why) => {
+                logging::error_file_async(format!(
+                    "Failed to QuoteHub.state.lock because {:?}",
+                    why
+                ));
+                return;
+            }
+        };
+
+        for quote in quotes {
+            if state.last_quotes.get(&quote.stock_symbol) == Some(&quote) {
+                continue;
+            }
+
+            for subscriber in state.subscribers.values() {
+                if !subscriber.symbols.contains(&quote.stock_symbol) {
+                    continue;
+                }
+
+                if let Err(TrySendError::Full(_)) = subscriber.tx.try_send(Ok(quote.clone())) {
+                    logging::warn_file_async(format!(
+                        "Subscriber is too slow, skip quote of {}",
+                        quote.stock_symbol
+                    ));
+                }
+            }
+
+            state
+                .last_quotes
+                .insert(quote.stock_symbol.to_string(), quote);
+        }
+    }
+
+    async fn poll_run(&self) {
+        let mut ticker = time::interval(POLL_INTERVAL);
+
+        loop {
+            ticker.tick().await;
+
+            let symbols = match self.symbols() {
+                Some(symbols) => symbols,
+                None => {
+                    logging::debug_file_async("已無報價訂閱者，停止輪詢".to_string());
+                    break;
+                }
+            };
+
+            if !is_trading_time().await {
+                continue;
+            }
+
+            let futures = symbols
+                .iter()
+                .map(|stock_symbol| stock_service::fetch_current_quotes_for_symbol(stock_symbol));
+            let quotes = join_all(futures).await.into_iter().flatten().collect();
+
+            self.publish(quotes);
+        }
+    }
+}
+
+impl Default for QuoteHub {
+    fn default() -> Self {
+        QuoteHub::new()
+    }
+}
+
+/// 是否為交易日的開盤時間
+async fn is_trading_time() -> bool {
+    if !declare::StockExchange::TWSE.is_open() {
+        return false;
+    }
+
+    match CALENDAR.is_trading_day(Local::now().date_naive()).await {
+        Ok(is_trading_day) => is_trading_day,
+        Err(why) => {
+            logging::error_file_async(format!("{:?}", why));
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(stock_symbol: &str, price: f64) -> StockQuotes {
+        StockQuotes {
+            stock_symbol: stock_symbol.to_string(),
+            price,
+            change: 0.0,
+            change_range: 0.0,
+        }
+    }
+
+    fn symbols(items: &[&str]) -> HashSet<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    #[test]
+    fn test_publish() {
+        let hub = QuoteHub::new();
+        let (tx1, mut rx1) = mpsc::channel(CHANNEL_CAPACITY);
+        let (tx2, mut rx2) = mpsc::channel(CHANNEL_CAPACITY);
+        assert!(hub.register(symbols(&["2330", "2888"]), tx1).unwrap());
+        assert!(!hub.register(symbols(&["2330"]), tx2).unwrap());
+
+        let mut all = hub.symbols().unwrap();
+        all.sort();
+        assert_eq!(all, vec!["2330", "2888"]);
+
+        hub.publish(vec![quote("2330", 1000.0), quote("2888", 10.0)]);
+        hub.publish(vec![quote("2330", 1000.0), quote("2888", 10.5)]);
+
+        let received: Vec<StockQuotes> = std::iter::from_fn(|| rx1.try_recv().ok())
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            received,
+            vec![
+                quote("2330", 1000.0),
+                quote("2888", 10.0),
+                quote("2888", 10.5)
+            ]
+        );
+        assert_eq!(rx2.try_recv().unwrap().unwrap(), quote("2330", 1000.0));
+        assert!(rx2.try_recv().is_err());
+
+        // 沒有訂閱者的股票不再保留最後報價
+        drop(rx1);
+        assert_eq!(hub.symbols().unwrap(), vec!["2330"]);
+        assert!(!hub.state.lock().unwrap().last_quotes.contains_key("2888"));
+
+        drop(rx2);
+        assert!(hub.symbols().is_none());
+        assert!(hub.state.lock().unwrap().last_quotes.is_empty());
+    }
+
+    #[test]
+    fn test_max_total_symbols() {
+        let hub = QuoteHub::new();
+        let all: HashSet<String> = (0..MAX_TOTAL_SYMBOLS).map(|i| i.to_string()).collect();
+        let (tx1, rx1) = mpsc::channel(CHANNEL_CAPACITY);
+        assert!(hub.register(all, tx1).unwrap());
+
+        // 已訂閱的股票不計入新增的數量
+        let (tx2, _rx2) = mpsc::channel(CHANNEL_CAPACITY);
+        assert!(hub.register(symbols(&["0"]), tx2).is_ok());
+        let (tx3, _rx3) = mpsc::channel(CHANNEL_CAPACITY);
+        let status = hub.register(symbols(&["2330"]), tx3).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+
+        // 斷線的訂閱者釋出額度
+        drop(rx1);
+        let (tx4, _rx4) = mpsc::channel(CHANNEL_CAPACITY);
+        assert!(hub.register(symbols(&["2330"]), tx4).is_ok());
+    }
+}
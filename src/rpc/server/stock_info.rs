@@ -0,0 +1,137 @@
+use anyhow::{anyhow, Result};
+use rust_decimal::{prelude::FromPrimitive, Decimal};
+
+use crate::{
+    cache::SHARE,
+    database::table::stock::{self, extension::net_asset_value_per_share},
+    declare::StockExchangeMarket,
+    rpc::stock::{FieldViolation, StockInfoRequest},
+};
+
+/// 檢查 StockInfoRequest 的各欄位，回傳所有不合法的欄位
+pub fn validate(request: &StockInfoRequest) -> Vec<FieldViolation> {
+    let mut violations = Vec::new();
+    let mut reject = |field: &str, reason: String| {
+        violations.push(FieldViolation {
+            field: field.to_string(),
+            reason,
+        })
+    };
+
+    if request.stock_symbol.trim().is_empty() {
+        reject("stock_symbol", "stock_symbol is required".to_string());
+    }
+
+    if request.name.trim().is_empty() {
+        reject("name", "name is required".to_string());
+    }
+
+    if StockExchangeMarket::from(request.stock_exchange_market_id).is_none() {
+        reject(
+            "stock_exchange_market_id",
+            format!(
+                "Unknown stock exchange market {}",
+                request.stock_exchange_market_id
+            ),
+        );
+    }
+
+    if SHARE.get_industry_name(request.stock_industry_id).is_none() {
+        reject(
+            "stock_industry_id",
+            format!("Unknown stock industry {}", request.stock_industry_id),
+        );
+    }
+
+    if !request.net_asset_value_per_share.is_finite() {
+        reject(
+            "net_asset_value_per_share",
+            format!(
+                "Invalid net asset value per share {}",
+                request.net_asset_value_per_share
+            ),
+        );
+    }
+
+    violations
+}
+
+/// 將 StockInfoRequest 寫入 stocks 並重建 stock_word、stock_index，資料庫更新後會更新 SHARE.stocks
+///
+/// 呼叫前需先通過 validate
+pub async fn save(request: &StockInfoRequest) -> Result<stock::Stock> {
+    let stock_symbol = request.stock_symbol.trim();
+    let mut stock = SHARE.get_stock(stock_symbol).await.unwrap_or_else(|| {
+        let mut stock = stock::Stock::new();
+        stock.stock_symbol = stock_symbol.to_string();
+        stock
+    });
+    stock.name = request.name.trim().to_string();
+    stock.stock_exchange_market_id = request.stock_exchange_market_id;
+    stock.stock_industry_id = request.stock_industry_id;
+    stock.suspend_listing = request.suspend_listing;
+    stock.net_asset_value_per_share = Decimal::from_f64(request.net_asset_value_per_share)
+        .ok_or_else(|| {
+            anyhow!(
+                "Failed to convert net_asset_value_per_share {}",
+                request.net_asset_value_per_share
+            )
+        })?;
+
+    // upsert 會一併重建 stock_word、stock_index
+    stock.upsert().await?;
+    net_asset_value_per_share::SymbolAndNetAssetValuePerShare::from(&stock)
+        .update()
+        .await?;
+
+    // 以整筆取代的方式更新快取，其他工作不會讀到只更新一半的數據
+    match SHARE.stocks.write() {
+        Ok(mut stocks) => {
+            stocks.insert(stock.stock_symbol.to_string(), stock.clone());
+        }
+        Err(why) => return Err(anyhow!("Failed to stocks.write because {:?}", why)),
+    }
+
+    Ok(stock)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> StockInfoRequest {
+        StockInfoRequest {
+            stock_symbol: "2330".to_string(),
+            name: "台積電".to_string(),
+            stock_exchange_market_id: 2,
+            stock_industry_id: 24,
+            net_asset_value_per_share: 130.5,
+            suspend_listing: false,
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate(&request()).is_empty());
+
+        let mut invalid = request();
+        invalid.stock_symbol = " ".to_string();
+        invalid.stock_exchange_market_id = 3;
+        invalid.stock_industry_id = 100;
+        invalid.net_asset_value_per_share = f64::NAN;
+        let fields: Vec<String> = validate(&invalid)
+            .into_iter()
+            .map(|violation| violation.field)
+            .collect();
+
+        assert_eq!(
+            fields,
+            vec![
+                "stock_symbol",
+                "stock_exchange_market_id",
+                "stock_industry_id",
+                "net_asset_value_per_share"
+            ]
+        );
+    }
+}
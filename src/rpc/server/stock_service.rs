@@ -1,10 +1,25 @@
-use futures::future::join_all;
+use std::{collections::HashSet, pin::Pin};
+
+use chrono::{Local, NaiveDate};
+use futures::{future::join_all, stream, Stream, TryStreamExt};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
 use tonic::{Request, Response, Status};
 
 use crate::{
+    cache::SHARE,
+    calculation::adjusted_price::{self, PriceSeries},
+    calendar::CALENDAR,
     crawler,
+    database::table::{
+        daily_quote, daily_stock_price_stats, dividend, estimate, financial_statement,
+        quote_history_record, revenue, yield_rank,
+    },
     logging,
     rpc::{
+        server::{
+            pagination::{Page, MAX_PAGE_SIZE},
+            quote_subscription, stock_info,
+        },
         stock::{
             StockQuotesRequest,
             stock_server::Stock,
@@ -14,12 +29,44 @@ use crate::{
             StockQuotesReply,
             HolidayScheduleReply,
             HolidayScheduleRequest,
-            HolidaySchedule
+            HolidaySchedule,
+            ImportBrokerStatementRequest,
+            ImportBrokerStatementReply,
+            DailyQuote,
+            DailyQuotesReply,
+            DailyQuotesRequest,
+            DailyStockPriceStats,
+            DailyStockPriceStatsReply,
+            DailyStockPriceStatsRequest,
+            Dividend,
+            DividendsReply,
+            DividendsRequest,
+            Estimate,
+            EstimatesReply,
+            EstimatesRequest,
+            FinancialStatement,
+            FinancialStatementsReply,
+            FinancialStatementsRequest,
+            PriceBand,
+            QuoteHistoryRecord,
+            QuoteHistoryRecordsReply,
+            QuoteHistoryRecordsRequest,
+            Revenue,
+            RevenuesReply,
+            RevenuesRequest,
+            YieldRank,
+            YieldRanksReply,
+            YieldRanksRequest
         }
     },
-    crawler::twse,
+    importer,
+    util::datetime::EARLIEST_DATE,
 };
 
+
+/// 單一訂閱可訂閱的股票數上限
+const MAX_SUBSCRIBE_SYMBOLS: usize = 200;
+
 #[derive(Default)]
 pub struct StockService {}
 
@@ -27,11 +74,27 @@ pub struct StockService {}
 impl Stock for StockService {
     async fn update_stock_info(
         &self,
-        _req: Request<StockInfoRequest>,
+        req: Request<StockInfoRequest>,
     ) -> Result<Response<StockInfoReply>, Status> {
-        Err(Status::unimplemented(
-            "update_stock_info is not implemented",
-        ))
+        let request = req.into_inner();
+        let violations = stock_info::validate(&request);
+        if !violations.is_empty() {
+            return Ok(Response::new(StockInfoReply {
+                message: format!("Rejected {} field(s)", violations.len()),
+                violations,
+            }));
+        }
+
+        let stock = stock_info::save(&request).await.map_err(internal)?;
+        logging::info_file_async(format!(
+            "update_stock_info {} {} market:{} industry:{}",
+            stock.stock_symbol, stock.name, stock.stock_exchange_market_id, stock.stock_industry_id
+        ));
+
+        Ok(Response::new(StockInfoReply {
+            message: format!("{} updated", stock.stock_symbol),
+            violations: vec![],
+        }))
     }
 
     async fn fetch_current_stock_quotes(
@@ -52,10 +115,54 @@ impl Stock for StockService {
         }))
     }
 
+    type SubscribeQuotesStream =
+        Pin<Box<dyn Stream<Item = Result<StockQuotes, Status>> + Send + 'static>>;
+
+    async fn subscribe_quotes(
+        &self,
+        req: Request<StockQuotesRequest>,
+    ) -> Result<Response<Self::SubscribeQuotesStream>, Status> {
+        let symbols: HashSet<String> = req
+            .into_inner()
+            .stock_symbols
+            .into_iter()
+            .map(|stock_symbol| stock_symbol.trim().to_string())
+            .filter(|stock_symbol| !stock_symbol.is_empty())
+            .collect();
+        if symbols.is_empty() {
+            return Err(Status::invalid_argument("stock_symbols is required"));
+        }
+        if symbols.len() > MAX_SUBSCRIBE_SYMBOLS {
+            return Err(Status::invalid_argument(format!(
+                "Too many stock_symbols, at most {}",
+                MAX_SUBSCRIBE_SYMBOLS
+            )));
+        }
+        let mut unknown: Vec<&str> = symbols
+            .iter()
+            .map(String::as_str)
+            .filter(|stock_symbol| !SHARE.stock_contains_key(stock_symbol))
+            .collect();
+        if !unknown.is_empty() {
+            unknown.sort_unstable();
+            return Err(Status::invalid_argument(format!(
+                "Unknown stock_symbols: {}",
+                unknown.join(", ")
+            )));
+        }
+
+        let rx = quote_subscription::HUB.subscribe(symbols)?;
+        let quotes = stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|quote| (quote, rx))
+        });
+
+        Ok(Response::new(Box::pin(quotes)))
+    }
+
     //
     async fn fetch_holiday_schedule(&self, req: Request<HolidayScheduleRequest>) -> Result<Response<HolidayScheduleReply>, Status> {
         let request = req.into_inner();
-        let holiday_schedules = match twse::holiday_schedule::visit(request.year).await {
+        let holiday_schedules = match CALENDAR.holidays(request.year).await {
             Ok(holidays) => holidays.iter()
                 .map(|holiday| {
                     HolidaySchedule {
@@ -65,7 +172,7 @@ impl Stock for StockService {
                 })
                 .collect(),
             Err(why) => {
-                logging::error_file_async(format!("Failed to visit twse::holiday_schedule because {:?}", why));
+                logging::error_file_async(format!("Failed to CALENDAR.holidays because {:?}", why));
                 vec![]
             }
         };
@@ -74,9 +181,500 @@ impl Stock for StockService {
             holiday: holiday_schedules,
         }))
     }
+
+    async fn import_broker_statement(
+        &self,
+        req: Request<ImportBrokerStatementRequest>,
+    ) -> Result<Response<ImportBrokerStatementReply>, Status> {
+        let request = req.into_inner();
+        let report = importer::execute(
+            request.member_id,
+            &request.content,
+            Some(request.broker.as_str()),
+            request.dry_run,
+        )
+        .await
+        .map_err(|why| {
+            if why.is::<importer::ParseError>() {
+                return Status::invalid_argument(why.to_string());
+            }
+
+            logging::error_file_async(format!(
+                "Failed to import_broker_statement({}) because {:?}",
+                request.member_id, why
+            ));
+            Status::internal("Failed to import the broker statement")
+        })?;
+
+        Ok(Response::new(ImportBrokerStatementReply {
+            broker: report.broker.to_string(),
+            inserted: report.new.len() as i32,
+            duplicated: report.duplicates.len() as i32,
+            report: report.to_string(),
+            errors: report.errors,
+        }))
+    }
+
+    async fn fetch_daily_quotes(
+        &self,
+        req: Request<DailyQuotesRequest>,
+    ) -> Result<Response<DailyQuotesReply>, Status> {
+        let request = req.into_inner();
+        let (start_date, end_date) = parse_date_range(&request.start_date, &request.end_date)?;
+        let series = parse_series(&request.series)?;
+        let page = Page::new(request.page_size, &request.page_token)?;
+        let mut quotes = adjusted_price::fetch_daily_quotes_page(
+            &request.stock_symbol,
+            start_date,
+            end_date,
+            page.offset,
+            page.limit(),
+            series,
+        )
+        .await
+        .map_err(internal)?;
+        let next_page_token = page.finish(&mut quotes);
+
+        Ok(Response::new(DailyQuotesReply {
+            quotes: quotes.into_iter().map(DailyQuote::from).collect(),
+            next_page_token,
+        }))
+    }
+
+    type StreamDailyQuotesStream =
+        Pin<Box<dyn Stream<Item = Result<DailyQuote, Status>> + Send + 'static>>;
+
+    #[allow(clippy::result_large_err)]
+    async fn stream_daily_quotes(
+        &self,
+        req: Request<DailyQuotesRequest>,
+    ) -> Result<Response<Self::StreamDailyQuotesStream>, Status> {
+        let request = req.into_inner();
+        let (start_date, end_date) = parse_date_range(&request.start_date, &request.end_date)?;
+        let series = parse_series(&request.series)?;
+        let stock_symbol = request.stock_symbol;
+
+        // 每次從資料庫取一頁，送完後再從該頁最後的日期之後取下一頁，避免一次載入整個區間
+        let after = start_date.pred_opt().unwrap_or(start_date);
+        let quotes = stream::try_unfold(Some(after), move |after| {
+            let stock_symbol = stock_symbol.clone();
+            async move {
+                let after = match after {
+                    Some(after) => after,
+                    None => return Ok::<_, Status>(None),
+                };
+                let quotes = adjusted_price::fetch_daily_quotes_after(
+                    &stock_symbol,
+                    after,
+                    end_date,
+                    MAX_PAGE_SIZE,
+                    series,
+                )
+                .await
+                .map_err(internal)?;
+                let last_date = match quotes.last() {
+                    Some(quote) => quote.date,
+                    None => return Ok(None),
+                };
+
+                let next = if (quotes.len() as i64) < MAX_PAGE_SIZE {
+                    None
+                } else {
+                    Some(last_date)
+                };
+                let chunk = stream::iter(quotes.into_iter().map(|dq| Ok(DailyQuote::from(dq))));
+
+                Ok(Some((chunk, next)))
+            }
+        })
+        .try_flatten();
+
+        Ok(Response::new(Box::pin(quotes)))
+    }
+
+    async fn fetch_estimates(
+        &self,
+        req: Request<EstimatesRequest>,
+    ) -> Result<Response<EstimatesReply>, Status> {
+        let request = req.into_inner();
+        let (start_date, end_date) = parse_date_range(&request.start_date, &request.end_date)?;
+        let page = Page::new(request.page_size, &request.page_token)?;
+        let mut estimates = estimate::Estimate::fetch_page_by_security_code(
+            &request.stock_symbol,
+            start_date,
+            end_date,
+            page.offset,
+            page.limit(),
+        )
+        .await
+        .map_err(internal)?;
+        let next_page_token = page.finish(&mut estimates);
+
+        Ok(Response::new(EstimatesReply {
+            estimates: estimates.into_iter().map(Estimate::from).collect(),
+            next_page_token,
+        }))
+    }
+
+    async fn fetch_dividends(
+        &self,
+        req: Request<DividendsRequest>,
+    ) -> Result<Response<DividendsReply>, Status> {
+        let request = req.into_inner();
+        let page = Page::new(request.page_size, &request.page_token)?;
+        let mut dividends = dividend::Dividend::fetch_page_by_security_code(
+            &request.stock_symbol,
+            page.offset,
+            page.limit(),
+        )
+        .await
+        .map_err(internal)?;
+        let next_page_token = page.finish(&mut dividends);
+
+        Ok(Response::new(DividendsReply {
+            dividends: dividends.into_iter().map(Dividend::from).collect(),
+            next_page_token,
+        }))
+    }
+
+    async fn fetch_revenues(
+        &self,
+        req: Request<RevenuesRequest>,
+    ) -> Result<Response<RevenuesReply>, Status> {
+        let request = req.into_inner();
+        let page = Page::new(request.page_size, &request.page_token)?;
+        let end_month = match request.end_month {
+            0 => i64::MAX,
+            month => month,
+        };
+        let mut revenues = revenue::Revenue::fetch_page_by_security_code(
+            &request.stock_symbol,
+            request.start_month,
+            end_month,
+            page.offset,
+            page.limit(),
+        )
+        .await
+        .map_err(internal)?;
+        let next_page_token = page.finish(&mut revenues);
+
+        Ok(Response::new(RevenuesReply {
+            revenues: revenues.into_iter().map(Revenue::from).collect(),
+            next_page_token,
+        }))
+    }
+
+    async fn fetch_financial_statements(
+        &self,
+        req: Request<FinancialStatementsRequest>,
+    ) -> Result<Response<FinancialStatementsReply>, Status> {
+        let request = req.into_inner();
+        let page = Page::new(request.page_size, &request.page_token)?;
+        let end_year = match request.end_year {
+            0 => i32::MAX,
+            year => year,
+        };
+        let mut financial_statements = financial_statement::fetch_page_by_security_code(
+            &request.stock_symbol,
+            request.start_year,
+            end_year,
+            request.annual,
+            page.offset,
+            page.limit(),
+        )
+        .await
+        .map_err(internal)?;
+        let next_page_token = page.finish(&mut financial_statements);
+
+        Ok(Response::new(FinancialStatementsReply {
+            financial_statements: financial_statements
+                .into_iter()
+                .map(FinancialStatement::from)
+                .collect(),
+            next_page_token,
+        }))
+    }
+
+    async fn fetch_quote_history_records(
+        &self,
+        req: Request<QuoteHistoryRecordsRequest>,
+    ) -> Result<Response<QuoteHistoryRecordsReply>, Status> {
+        let request = req.into_inner();
+        let page = Page::new(request.page_size, &request.page_token)?;
+        let mut records = quote_history_record::QuoteHistoryRecord::fetch_page(
+            &request.stock_symbols,
+            page.offset,
+            page.limit(),
+        )
+        .await
+        .map_err(internal)?;
+        let next_page_token = page.finish(&mut records);
+
+        Ok(Response::new(QuoteHistoryRecordsReply {
+            records: records.into_iter().map(QuoteHistoryRecord::from).collect(),
+            next_page_token,
+        }))
+    }
+
+    async fn fetch_yield_ranks(
+        &self,
+        req: Request<YieldRanksRequest>,
+    ) -> Result<Response<YieldRanksReply>, Status> {
+        let request = req.into_inner();
+        let page = Page::new(request.page_size, &request.page_token)?;
+        let date = match request.date.as_str() {
+            "" => match yield_rank::YieldRank::fetch_latest_date()
+                .await
+                .map_err(internal)?
+            {
+                Some(date) => date,
+                None => return Ok(Response::new(YieldRanksReply::default())),
+            },
+            date => parse_date(date)?,
+        };
+        let mut ranks =
+            yield_rank::YieldRank::fetch_page_by_date(date, page.offset, page.limit())
+                .await
+                .map_err(internal)?;
+        let next_page_token = page.finish(&mut ranks);
+
+        Ok(Response::new(YieldRanksReply {
+            date: date.to_string(),
+            ranks: ranks
+                .into_iter()
+                .zip(page.offset + 1..)
+                .map(|(yr, rank)| YieldRank {
+                    rank: rank as i32,
+                    stock_symbol: yr.security_code,
+                    dividend: yr.dividend,
+                    closing_price: yr.closing_price,
+                    r#yield: yr.r#yield,
+                })
+                .collect(),
+            next_page_token,
+        }))
+    }
+
+    async fn fetch_daily_stock_price_stats(
+        &self,
+        req: Request<DailyStockPriceStatsRequest>,
+    ) -> Result<Response<DailyStockPriceStatsReply>, Status> {
+        let request = req.into_inner();
+        let (start_date, end_date) = parse_date_range(&request.start_date, &request.end_date)?;
+        let page = Page::new(request.page_size, &request.page_token)?;
+        let mut stats = daily_stock_price_stats::DailyStockPriceStats::fetch_page(
+            start_date,
+            end_date,
+            request.stock_exchange_market_id,
+            page.offset,
+            page.limit(),
+        )
+        .await
+        .map_err(internal)?;
+        let next_page_token = page.finish(&mut stats);
+
+        Ok(Response::new(DailyStockPriceStatsReply {
+            stats: stats.into_iter().map(DailyStockPriceStats::from).collect(),
+            next_page_token,
+        }))
+    }
+}
+
+fn internal(why: anyhow::Error) -> Status {
+    logging::error_file_async(format!("Failed to query for rpc because {:?}", why));
+    Status::internal("Failed to query the data")
+}
+
+#[allow(clippy::result_large_err)]
+fn parse_date(value: &str) -> Result<NaiveDate, Status> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| Status::invalid_argument(format!("Invalid date '{}'", value)))
+}
+
+/// 解析查詢的日期區間，起始日期未指定時不限制，結束日期未指定時為今天
+#[allow(clippy::result_large_err)]
+fn parse_date_range(start_date: &str, end_date: &str) -> Result<(NaiveDate, NaiveDate), Status> {
+    let start_date = match start_date {
+        "" => EARLIEST_DATE,
+        date => parse_date(date)?,
+    };
+    let end_date = match end_date {
+        "" => Local::now().date_naive(),
+        date => parse_date(date)?,
+    };
+
+    Ok((start_date, end_date))
+}
+
+/// 解析查詢的價格序列，未指定時為原始股價
+#[allow(clippy::result_large_err)]
+fn parse_series(value: &str) -> Result<PriceSeries, Status> {
+    value
+        .parse()
+        .map_err(|_| Status::invalid_argument(format!("Invalid series '{}'", value)))
+}
+
+fn to_f64(value: Decimal) -> f64 {
+    value.to_f64().unwrap_or_default()
+}
+
+impl From<daily_quote::DailyQuote> for DailyQuote {
+    fn from(dq: daily_quote::DailyQuote) -> Self {
+        DailyQuote {
+            stock_symbol: dq.security_code,
+            date: dq.date.to_string(),
+            opening_price: to_f64(dq.opening_price),
+            highest_price: to_f64(dq.highest_price),
+            lowest_price: to_f64(dq.lowest_price),
+            closing_price: to_f64(dq.closing_price),
+            change: to_f64(dq.change),
+            change_range: to_f64(dq.change_range),
+            trading_volume: to_f64(dq.trading_volume),
+            transaction: to_f64(dq.transaction),
+            trade_value: to_f64(dq.trade_value),
+            price_earning_ratio: to_f64(dq.price_earning_ratio),
+            price_to_book_ratio: to_f64(dq.price_to_book_ratio),
+            moving_average_5: to_f64(dq.moving_average_5),
+            moving_average_10: to_f64(dq.moving_average_10),
+            moving_average_20: to_f64(dq.moving_average_20),
+            moving_average_60: to_f64(dq.moving_average_60),
+            moving_average_120: to_f64(dq.moving_average_120),
+            moving_average_240: to_f64(dq.moving_average_240),
+        }
+    }
+}
+
+fn price_band(cheap: f64, fair: f64, expensive: f64) -> Option<PriceBand> {
+    Some(PriceBand {
+        cheap,
+        fair,
+        expensive,
+    })
+}
+
+impl From<estimate::Estimate> for Estimate {
+    fn from(e: estimate::Estimate) -> Self {
+        Estimate {
+            stock_symbol: e.security_code,
+            date: e.date.to_string(),
+            closing_price: e.closing_price,
+            percentage: e.percentage,
+            average: price_band(e.cheap, e.fair, e.expensive),
+            price: price_band(e.price_cheap, e.price_fair, e.price_expensive),
+            dividend: price_band(e.dividend_cheap, e.dividend_fair, e.dividend_expensive),
+            eps: price_band(e.eps_cheap, e.eps_fair, e.eps_expensive),
+            pbr: price_band(e.pbr_cheap, e.pbr_fair, e.pbr_expensive),
+            year_count: e.year_count,
+        }
+    }
+}
+
+impl From<dividend::Dividend> for Dividend {
+    fn from(d: dividend::Dividend) -> Self {
+        Dividend {
+            stock_symbol: d.security_code,
+            year: d.year,
+            year_of_dividend: d.year_of_dividend,
+            quarter: d.quarter,
+            cash_dividend: to_f64(d.cash_dividend),
+            stock_dividend: to_f64(d.stock_dividend),
+            sum: to_f64(d.sum),
+            payout_ratio: to_f64(d.payout_ratio),
+            ex_dividend_date: d.ex_dividend_date1,
+            ex_rights_date: d.ex_dividend_date2,
+            cash_payable_date: d.payable_date1,
+            stock_payable_date: d.payable_date2,
+        }
+    }
+}
+
+impl From<revenue::Revenue> for Revenue {
+    fn from(r: revenue::Revenue) -> Self {
+        Revenue {
+            stock_symbol: r.security_code,
+            month: r.date,
+            monthly: to_f64(r.monthly),
+            last_month: to_f64(r.last_month),
+            last_year_this_month: to_f64(r.last_year_this_month),
+            monthly_accumulated: to_f64(r.monthly_accumulated),
+            last_year_monthly_accumulated: to_f64(r.last_year_monthly_accumulated),
+            compared_with_last_month: to_f64(r.compared_with_last_month),
+            compared_with_last_year_same_month: to_f64(r.compared_with_last_year_same_month),
+            accumulated_compared_with_last_year: to_f64(r.accumulated_compared_with_last_year),
+            avg_price: to_f64(r.avg_price),
+            lowest_price: to_f64(r.lowest_price),
+            highest_price: to_f64(r.highest_price),
+        }
+    }
+}
+
+impl From<financial_statement::FinancialStatement> for FinancialStatement {
+    fn from(fs: financial_statement::FinancialStatement) -> Self {
+        FinancialStatement {
+            stock_symbol: fs.security_code,
+            year: fs.year,
+            quarter: fs.quarter,
+            gross_profit: to_f64(fs.gross_profit),
+            operating_profit_margin: to_f64(fs.operating_profit_margin),
+            pre_tax_income: to_f64(fs.pre_tax_income),
+            net_income: to_f64(fs.net_income),
+            net_asset_value_per_share: to_f64(fs.net_asset_value_per_share),
+            sales_per_share: to_f64(fs.sales_per_share),
+            earnings_per_share: to_f64(fs.earnings_per_share),
+            profit_before_tax: to_f64(fs.profit_before_tax),
+            return_on_equity: to_f64(fs.return_on_equity),
+            return_on_assets: to_f64(fs.return_on_assets),
+        }
+    }
+}
+
+impl From<quote_history_record::QuoteHistoryRecord> for QuoteHistoryRecord {
+    fn from(qhr: quote_history_record::QuoteHistoryRecord) -> Self {
+        QuoteHistoryRecord {
+            stock_symbol: qhr.security_code,
+            maximum_price: to_f64(qhr.maximum_price),
+            maximum_price_date_on: qhr.maximum_price_date_on.to_string(),
+            minimum_price: to_f64(qhr.minimum_price),
+            minimum_price_date_on: qhr.minimum_price_date_on.to_string(),
+            maximum_price_to_book_ratio: to_f64(qhr.maximum_price_to_book_ratio),
+            maximum_price_to_book_ratio_date_on: qhr
+                .maximum_price_to_book_ratio_date_on
+                .to_string(),
+            minimum_price_to_book_ratio: to_f64(qhr.minimum_price_to_book_ratio),
+            minimum_price_to_book_ratio_date_on: qhr
+                .minimum_price_to_book_ratio_date_on
+                .to_string(),
+        }
+    }
+}
+
+impl From<daily_stock_price_stats::DailyStockPriceStats> for DailyStockPriceStats {
+    fn from(s: daily_stock_price_stats::DailyStockPriceStats) -> Self {
+        DailyStockPriceStats {
+            date: s.date.to_string(),
+            stock_exchange_market_id: s.stock_exchange_market_id,
+            undervalued: s.undervalued,
+            fair_valued: s.fair_valued,
+            overvalued: s.overvalued,
+            highly_overvalued: s.highly_overvalued,
+            below_5_day_moving_average: s.below_5_day_moving_average,
+            above_5_day_moving_average: s.above_5_day_moving_average,
+            below_20_day_moving_average: s.below_20_day_moving_average,
+            above_20_day_moving_average: s.above_20_day_moving_average,
+            below_60_day_moving_average: s.below_60_day_moving_average,
+            above_60_day_moving_average: s.above_60_day_moving_average,
+            below_120_day_moving_average: s.below_120_day_moving_average,
+            above_120_day_moving_average: s.above_120_day_moving_average,
+            below_240_day_moving_average: s.below_240_day_moving_average,
+            above_240_day_moving_average: s.above_240_day_moving_average,
+            stocks_up: s.stocks_up,
+            stocks_down: s.stocks_down,
+            stocks_unchanged: s.stocks_unchanged,
+        }
+    }
 }
 
-async fn fetch_current_quotes_for_symbol(stock_symbol: &str) -> Option<StockQuotes> {
+pub(super) async fn fetch_current_quotes_for_symbol(stock_symbol: &str) -> Option<StockQuotes> {
     if let Ok(sq) = crawler::fetch_stock_quotes_from_remote_site(stock_symbol).await {
         return Some(StockQuotes {
             stock_symbol: stock_symbol.to_string(),
@@ -1,4 +1,6 @@
 // This file is @generated by prost-build.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct StockInfoRequest {
     #[prost(string, tag = "1")]
@@ -15,11 +17,27 @@ pub struct StockInfoRequest {
     #[prost(bool, tag = "6")]
     pub suspend_listing: bool,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct StockInfoReply {
     #[prost(string, tag = "1")]
     pub message: ::prost::alloc::string::String,
+    /// 被拒絕的欄位，為空時表示已更新
+    #[prost(message, repeated, tag = "2")]
+    pub violations: ::prost::alloc::vec::Vec<FieldViolation>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FieldViolation {
+    #[prost(string, tag = "1")]
+    pub field: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub reason: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct StockQuotes {
     #[prost(string, tag = "1")]
@@ -31,21 +49,29 @@ pub struct StockQuotes {
     #[prost(double, tag = "4")]
     pub change_range: f64,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct StockQuotesRequest {
     #[prost(string, repeated, tag = "1")]
     pub stock_symbols: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct StockQuotesReply {
     #[prost(message, repeated, tag = "1")]
     pub stock_prices: ::prost::alloc::vec::Vec<StockQuotes>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[derive(Clone, Copy, PartialEq, ::prost::Message)]
 pub struct HolidayScheduleRequest {
     #[prost(int32, tag = "1")]
     pub year: i32,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct HolidaySchedule {
     #[prost(string, tag = "1")]
@@ -53,11 +79,505 @@ pub struct HolidaySchedule {
     #[prost(string, tag = "2")]
     pub why: ::prost::alloc::string::String,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct HolidayScheduleReply {
     #[prost(message, repeated, tag = "1")]
     pub holiday: ::prost::alloc::vec::Vec<HolidaySchedule>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ImportBrokerStatementRequest {
+    #[prost(int64, tag = "1")]
+    pub member_id: i64,
+    /// 券商格式，空字串時自動判斷
+    #[prost(string, tag = "2")]
+    pub broker: ::prost::alloc::string::String,
+    /// CSV 檔案內容，Big5 或 UTF-8
+    #[prost(bytes = "vec", tag = "3")]
+    pub content: ::prost::alloc::vec::Vec<u8>,
+    /// 只比對不寫入
+    #[prost(bool, tag = "4")]
+    pub dry_run: bool,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ImportBrokerStatementReply {
+    #[prost(string, tag = "1")]
+    pub broker: ::prost::alloc::string::String,
+    #[prost(int32, tag = "2")]
+    pub inserted: i32,
+    #[prost(int32, tag = "3")]
+    pub duplicated: i32,
+    #[prost(string, repeated, tag = "4")]
+    pub errors: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// 比對結果，+ 新增、= 重複、! 錯誤
+    #[prost(string, tag = "5")]
+    pub report: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DailyQuotesRequest {
+    #[prost(string, tag = "1")]
+    pub stock_symbol: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub start_date: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub end_date: ::prost::alloc::string::String,
+    #[prost(int32, tag = "4")]
+    pub page_size: i32,
+    #[prost(string, tag = "5")]
+    pub page_token: ::prost::alloc::string::String,
+    /// 價格序列，raw 原始股價(未指定時)、adjusted 除權息還原股價(均線與年度高低價亦以還原股價計算)
+    #[prost(string, tag = "6")]
+    pub series: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DailyQuote {
+    #[prost(string, tag = "1")]
+    pub stock_symbol: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub date: ::prost::alloc::string::String,
+    #[prost(double, tag = "3")]
+    pub opening_price: f64,
+    #[prost(double, tag = "4")]
+    pub highest_price: f64,
+    #[prost(double, tag = "5")]
+    pub lowest_price: f64,
+    #[prost(double, tag = "6")]
+    pub closing_price: f64,
+    #[prost(double, tag = "7")]
+    pub change: f64,
+    #[prost(double, tag = "8")]
+    pub change_range: f64,
+    #[prost(double, tag = "9")]
+    pub trading_volume: f64,
+    #[prost(double, tag = "10")]
+    pub transaction: f64,
+    #[prost(double, tag = "11")]
+    pub trade_value: f64,
+    #[prost(double, tag = "12")]
+    pub price_earning_ratio: f64,
+    #[prost(double, tag = "13")]
+    pub price_to_book_ratio: f64,
+    #[prost(double, tag = "14")]
+    pub moving_average_5: f64,
+    #[prost(double, tag = "15")]
+    pub moving_average_10: f64,
+    #[prost(double, tag = "16")]
+    pub moving_average_20: f64,
+    #[prost(double, tag = "17")]
+    pub moving_average_60: f64,
+    #[prost(double, tag = "18")]
+    pub moving_average_120: f64,
+    #[prost(double, tag = "19")]
+    pub moving_average_240: f64,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DailyQuotesReply {
+    #[prost(message, repeated, tag = "1")]
+    pub quotes: ::prost::alloc::vec::Vec<DailyQuote>,
+    #[prost(string, tag = "2")]
+    pub next_page_token: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EstimatesRequest {
+    #[prost(string, tag = "1")]
+    pub stock_symbol: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub start_date: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub end_date: ::prost::alloc::string::String,
+    #[prost(int32, tag = "4")]
+    pub page_size: i32,
+    #[prost(string, tag = "5")]
+    pub page_token: ::prost::alloc::string::String,
+}
+/// 便宜、合理、昂貴價
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct PriceBand {
+    #[prost(double, tag = "1")]
+    pub cheap: f64,
+    #[prost(double, tag = "2")]
+    pub fair: f64,
+    #[prost(double, tag = "3")]
+    pub expensive: f64,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Estimate {
+    #[prost(string, tag = "1")]
+    pub stock_symbol: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub date: ::prost::alloc::string::String,
+    #[prost(double, tag = "3")]
+    pub closing_price: f64,
+    #[prost(double, tag = "4")]
+    pub percentage: f64,
+    /// 各估價方式的平均
+    #[prost(message, optional, tag = "5")]
+    pub average: ::core::option::Option<PriceBand>,
+    /// 以歷年股價估算
+    #[prost(message, optional, tag = "6")]
+    pub price: ::core::option::Option<PriceBand>,
+    /// 以股利估算
+    #[prost(message, optional, tag = "7")]
+    pub dividend: ::core::option::Option<PriceBand>,
+    /// 以 EPS 估算
+    #[prost(message, optional, tag = "8")]
+    pub eps: ::core::option::Option<PriceBand>,
+    /// 以股價淨值比估算
+    #[prost(message, optional, tag = "9")]
+    pub pbr: ::core::option::Option<PriceBand>,
+    #[prost(int32, tag = "10")]
+    pub year_count: i32,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EstimatesReply {
+    #[prost(message, repeated, tag = "1")]
+    pub estimates: ::prost::alloc::vec::Vec<Estimate>,
+    #[prost(string, tag = "2")]
+    pub next_page_token: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DividendsRequest {
+    #[prost(string, tag = "1")]
+    pub stock_symbol: ::prost::alloc::string::String,
+    #[prost(int32, tag = "2")]
+    pub page_size: i32,
+    #[prost(string, tag = "3")]
+    pub page_token: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Dividend {
+    #[prost(string, tag = "1")]
+    pub stock_symbol: ::prost::alloc::string::String,
+    /// 發放年度
+    #[prost(int32, tag = "2")]
+    pub year: i32,
+    /// 股利所屬年度
+    #[prost(int32, tag = "3")]
+    pub year_of_dividend: i32,
+    /// 發放季度，空字串為全年度合計
+    #[prost(string, tag = "4")]
+    pub quarter: ::prost::alloc::string::String,
+    #[prost(double, tag = "5")]
+    pub cash_dividend: f64,
+    #[prost(double, tag = "6")]
+    pub stock_dividend: f64,
+    #[prost(double, tag = "7")]
+    pub sum: f64,
+    #[prost(double, tag = "8")]
+    pub payout_ratio: f64,
+    /// 除息日
+    #[prost(string, tag = "9")]
+    pub ex_dividend_date: ::prost::alloc::string::String,
+    /// 除權日
+    #[prost(string, tag = "10")]
+    pub ex_rights_date: ::prost::alloc::string::String,
+    /// 現金股利發放日
+    #[prost(string, tag = "11")]
+    pub cash_payable_date: ::prost::alloc::string::String,
+    /// 股票股利發放日
+    #[prost(string, tag = "12")]
+    pub stock_payable_date: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DividendsReply {
+    #[prost(message, repeated, tag = "1")]
+    pub dividends: ::prost::alloc::vec::Vec<Dividend>,
+    #[prost(string, tag = "2")]
+    pub next_page_token: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RevenuesRequest {
+    #[prost(string, tag = "1")]
+    pub stock_symbol: ::prost::alloc::string::String,
+    /// 月份格式為 yyyymm，0 時不限制
+    #[prost(int64, tag = "2")]
+    pub start_month: i64,
+    #[prost(int64, tag = "3")]
+    pub end_month: i64,
+    #[prost(int32, tag = "4")]
+    pub page_size: i32,
+    #[prost(string, tag = "5")]
+    pub page_token: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Revenue {
+    #[prost(string, tag = "1")]
+    pub stock_symbol: ::prost::alloc::string::String,
+    /// yyyymm
+    #[prost(int64, tag = "2")]
+    pub month: i64,
+    #[prost(double, tag = "3")]
+    pub monthly: f64,
+    #[prost(double, tag = "4")]
+    pub last_month: f64,
+    #[prost(double, tag = "5")]
+    pub last_year_this_month: f64,
+    #[prost(double, tag = "6")]
+    pub monthly_accumulated: f64,
+    #[prost(double, tag = "7")]
+    pub last_year_monthly_accumulated: f64,
+    #[prost(double, tag = "8")]
+    pub compared_with_last_month: f64,
+    #[prost(double, tag = "9")]
+    pub compared_with_last_year_same_month: f64,
+    #[prost(double, tag = "10")]
+    pub accumulated_compared_with_last_year: f64,
+    #[prost(double, tag = "11")]
+    pub avg_price: f64,
+    #[prost(double, tag = "12")]
+    pub lowest_price: f64,
+    #[prost(double, tag = "13")]
+    pub highest_price: f64,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RevenuesReply {
+    #[prost(message, repeated, tag = "1")]
+    pub revenues: ::prost::alloc::vec::Vec<Revenue>,
+    #[prost(string, tag = "2")]
+    pub next_page_token: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FinancialStatementsRequest {
+    #[prost(string, tag = "1")]
+    pub stock_symbol: ::prost::alloc::string::String,
+    /// 0 時不限制
+    #[prost(int32, tag = "2")]
+    pub start_year: i32,
+    #[prost(int32, tag = "3")]
+    pub end_year: i32,
+    /// true 只取年度財報，false 只取季度財報
+    #[prost(bool, tag = "4")]
+    pub annual: bool,
+    #[prost(int32, tag = "5")]
+    pub page_size: i32,
+    #[prost(string, tag = "6")]
+    pub page_token: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FinancialStatement {
+    #[prost(string, tag = "1")]
+    pub stock_symbol: ::prost::alloc::string::String,
+    #[prost(int64, tag = "2")]
+    pub year: i64,
+    /// Q1~Q4，年度財報為空字串
+    #[prost(string, tag = "3")]
+    pub quarter: ::prost::alloc::string::String,
+    #[prost(double, tag = "4")]
+    pub gross_profit: f64,
+    #[prost(double, tag = "5")]
+    pub operating_profit_margin: f64,
+    #[prost(double, tag = "6")]
+    pub pre_tax_income: f64,
+    #[prost(double, tag = "7")]
+    pub net_income: f64,
+    #[prost(double, tag = "8")]
+    pub net_asset_value_per_share: f64,
+    #[prost(double, tag = "9")]
+    pub sales_per_share: f64,
+    #[prost(double, tag = "10")]
+    pub earnings_per_share: f64,
+    #[prost(double, tag = "11")]
+    pub profit_before_tax: f64,
+    #[prost(double, tag = "12")]
+    pub return_on_equity: f64,
+    #[prost(double, tag = "13")]
+    pub return_on_assets: f64,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FinancialStatementsReply {
+    #[prost(message, repeated, tag = "1")]
+    pub financial_statements: ::prost::alloc::vec::Vec<FinancialStatement>,
+    #[prost(string, tag = "2")]
+    pub next_page_token: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QuoteHistoryRecordsRequest {
+    /// 未指定時取得所有股票
+    #[prost(string, repeated, tag = "1")]
+    pub stock_symbols: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(int32, tag = "2")]
+    pub page_size: i32,
+    #[prost(string, tag = "3")]
+    pub page_token: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QuoteHistoryRecord {
+    #[prost(string, tag = "1")]
+    pub stock_symbol: ::prost::alloc::string::String,
+    #[prost(double, tag = "2")]
+    pub maximum_price: f64,
+    #[prost(string, tag = "3")]
+    pub maximum_price_date_on: ::prost::alloc::string::String,
+    #[prost(double, tag = "4")]
+    pub minimum_price: f64,
+    #[prost(string, tag = "5")]
+    pub minimum_price_date_on: ::prost::alloc::string::String,
+    #[prost(double, tag = "6")]
+    pub maximum_price_to_book_ratio: f64,
+    #[prost(string, tag = "7")]
+    pub maximum_price_to_book_ratio_date_on: ::prost::alloc::string::String,
+    #[prost(double, tag = "8")]
+    pub minimum_price_to_book_ratio: f64,
+    #[prost(string, tag = "9")]
+    pub minimum_price_to_book_ratio_date_on: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QuoteHistoryRecordsReply {
+    #[prost(message, repeated, tag = "1")]
+    pub records: ::prost::alloc::vec::Vec<QuoteHistoryRecord>,
+    #[prost(string, tag = "2")]
+    pub next_page_token: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct YieldRanksRequest {
+    /// 未指定時為最近一次排行的日期
+    #[prost(string, tag = "1")]
+    pub date: ::prost::alloc::string::String,
+    #[prost(int32, tag = "2")]
+    pub page_size: i32,
+    #[prost(string, tag = "3")]
+    pub page_token: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct YieldRank {
+    /// 名次，從 1 開始
+    #[prost(int32, tag = "1")]
+    pub rank: i32,
+    #[prost(string, tag = "2")]
+    pub stock_symbol: ::prost::alloc::string::String,
+    #[prost(double, tag = "3")]
+    pub dividend: f64,
+    #[prost(double, tag = "4")]
+    pub closing_price: f64,
+    #[prost(double, tag = "5")]
+    pub r#yield: f64,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct YieldRanksReply {
+    #[prost(string, tag = "1")]
+    pub date: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag = "2")]
+    pub ranks: ::prost::alloc::vec::Vec<YieldRank>,
+    #[prost(string, tag = "3")]
+    pub next_page_token: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DailyStockPriceStatsRequest {
+    #[prost(string, tag = "1")]
+    pub start_date: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub end_date: ::prost::alloc::string::String,
+    /// 市場類型 TWSE: 2, TPEx: 4, 全部: 0，未指定時取得所有市場
+    #[prost(int32, optional, tag = "3")]
+    pub stock_exchange_market_id: ::core::option::Option<i32>,
+    #[prost(int32, tag = "4")]
+    pub page_size: i32,
+    #[prost(string, tag = "5")]
+    pub page_token: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DailyStockPriceStats {
+    #[prost(string, tag = "1")]
+    pub date: ::prost::alloc::string::String,
+    #[prost(int32, tag = "2")]
+    pub stock_exchange_market_id: i32,
+    #[prost(int32, tag = "3")]
+    pub undervalued: i32,
+    #[prost(int32, tag = "4")]
+    pub fair_valued: i32,
+    #[prost(int32, tag = "5")]
+    pub overvalued: i32,
+    #[prost(int32, tag = "6")]
+    pub highly_overvalued: i32,
+    #[prost(int32, tag = "7")]
+    pub below_5_day_moving_average: i32,
+    #[prost(int32, tag = "8")]
+    pub above_5_day_moving_average: i32,
+    #[prost(int32, tag = "9")]
+    pub below_20_day_moving_average: i32,
+    #[prost(int32, tag = "10")]
+    pub above_20_day_moving_average: i32,
+    #[prost(int32, tag = "11")]
+    pub below_60_day_moving_average: i32,
+    #[prost(int32, tag = "12")]
+    pub above_60_day_moving_average: i32,
+    #[prost(int32, tag = "13")]
+    pub below_120_day_moving_average: i32,
+    #[prost(int32, tag = "14")]
+    pub above_120_day_moving_average: i32,
+    #[prost(int32, tag = "15")]
+    pub below_240_day_moving_average: i32,
+    #[prost(int32, tag = "16")]
+    pub above_240_day_moving_average: i32,
+    #[prost(int32, tag = "17")]
+    pub stocks_up: i32,
+    #[prost(int32, tag = "18")]
+    pub stocks_down: i32,
+    #[prost(int32, tag = "19")]
+    pub stocks_unchanged: i32,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DailyStockPriceStatsReply {
+    #[prost(message, repeated, tag = "1")]
+    pub stats: ::prost::alloc::vec::Vec<DailyStockPriceStats>,
+    #[prost(string, tag = "2")]
+    pub next_page_token: ::prost::alloc::string::String,
+}
 /// Generated client implementations.
 pub mod stock_client {
     #![allow(
@@ -149,6 +669,7 @@ pub mod stock_client {
             self.inner = self.inner.max_encoding_message_size(limit);
             self
         }
+        /// 新增或更新股票基本資料，欄位不合法時會在 violations 內逐一列出且不會更新
         pub async fn update_stock_info(
             &mut self,
             request: impl tonic::IntoRequest<super::StockInfoRequest>,
@@ -195,6 +716,31 @@ pub mod stock_client {
                 .insert(GrpcMethod::new("stock.Stock", "FetchCurrentStockQuotes"));
             self.inner.unary(req, path, codec).await
         }
+        /// 訂閱即時報價，開盤期間報價變動時送出，訂閱時會先送出已知的最後報價
+        pub async fn subscribe_quotes(
+            &mut self,
+            request: impl tonic::IntoRequest<super::StockQuotesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::StockQuotes>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/stock.Stock/SubscribeQuotes",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("stock.Stock", "SubscribeQuotes"));
+            self.inner.server_streaming(req, path, codec).await
+        }
         /// 取得股市休市日
         pub async fn fetch_holiday_schedule(
             &mut self,
@@ -220,68 +766,397 @@ pub mod stock_client {
                 .insert(GrpcMethod::new("stock.Stock", "FetchHolidaySchedule"));
             self.inner.unary(req, path, codec).await
         }
-    }
-}
-/// Generated server implementations.
-pub mod stock_server {
-    #![allow(
-        unused_variables,
-        dead_code,
-        missing_docs,
-        clippy::wildcard_imports,
-        clippy::let_unit_value,
-    )]
-    use tonic::codegen::*;
-    /// Generated trait containing gRPC methods that should be implemented for use with StockServer.
-    #[async_trait]
-    pub trait Stock: std::marker::Send + std::marker::Sync + 'static {
-        async fn update_stock_info(
-            &self,
-            request: tonic::Request<super::StockInfoRequest>,
-        ) -> std::result::Result<tonic::Response<super::StockInfoReply>, tonic::Status>;
-        /// 取得目前的股價
-        async fn fetch_current_stock_quotes(
-            &self,
-            request: tonic::Request<super::StockQuotesRequest>,
+        /// 匯入券商對帳單到會員的交易明細
+        pub async fn import_broker_statement(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ImportBrokerStatementRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::StockQuotesReply>,
+            tonic::Response<super::ImportBrokerStatementReply>,
             tonic::Status,
-        >;
-        /// 取得股市休市日
-        async fn fetch_holiday_schedule(
-            &self,
-            request: tonic::Request<super::HolidayScheduleRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/stock.Stock/ImportBrokerStatement",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("stock.Stock", "ImportBrokerStatement"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// 取得每日收盤數據，日期由舊到新
+        pub async fn fetch_daily_quotes(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DailyQuotesRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::HolidayScheduleReply>,
+            tonic::Response<super::DailyQuotesReply>,
             tonic::Status,
-        >;
-    }
-    #[derive(Debug)]
-    pub struct StockServer<T> {
-        inner: Arc<T>,
-        accept_compression_encodings: EnabledCompressionEncodings,
-        send_compression_encodings: EnabledCompressionEncodings,
-        max_decoding_message_size: Option<usize>,
-        max_encoding_message_size: Option<usize>,
-    }
-    impl<T> StockServer<T> {
-        pub fn new(inner: T) -> Self {
-            Self::from_arc(Arc::new(inner))
-        }
-        pub fn from_arc(inner: Arc<T>) -> Self {
-            Self {
-                inner,
-                accept_compression_encodings: Default::default(),
-                send_compression_encodings: Default::default(),
-                max_decoding_message_size: None,
-                max_encoding_message_size: None,
-            }
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/stock.Stock/FetchDailyQuotes",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("stock.Stock", "FetchDailyQuotes"));
+            self.inner.unary(req, path, codec).await
         }
-        pub fn with_interceptor<F>(
-            inner: T,
-            interceptor: F,
-        ) -> InterceptedService<Self, F>
-        where
+        /// 以串流取得日期區間內所有的每日收盤數據，日期由舊到新，會忽略分頁參數
+        pub async fn stream_daily_quotes(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DailyQuotesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::DailyQuote>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/stock.Stock/StreamDailyQuotes",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("stock.Stock", "StreamDailyQuotes"));
+            self.inner.server_streaming(req, path, codec).await
+        }
+        /// 取得每日估算的便宜、合理、昂貴價，日期由新到舊
+        pub async fn fetch_estimates(
+            &mut self,
+            request: impl tonic::IntoRequest<super::EstimatesRequest>,
+        ) -> std::result::Result<tonic::Response<super::EstimatesReply>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/stock.Stock/FetchEstimates",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("stock.Stock", "FetchEstimates"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// 取得股利發放記錄，年度由新到舊
+        pub async fn fetch_dividends(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DividendsRequest>,
+        ) -> std::result::Result<tonic::Response<super::DividendsReply>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/stock.Stock/FetchDividends",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("stock.Stock", "FetchDividends"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// 取得每月營收，月份由新到舊
+        pub async fn fetch_revenues(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RevenuesRequest>,
+        ) -> std::result::Result<tonic::Response<super::RevenuesReply>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/stock.Stock/FetchRevenues",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("stock.Stock", "FetchRevenues"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// 取得季度或年度財報，年度由新到舊
+        pub async fn fetch_financial_statements(
+            &mut self,
+            request: impl tonic::IntoRequest<super::FinancialStatementsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::FinancialStatementsReply>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/stock.Stock/FetchFinancialStatements",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("stock.Stock", "FetchFinancialStatements"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// 取得歷史最高、最低股價與股價淨值比
+        pub async fn fetch_quote_history_records(
+            &mut self,
+            request: impl tonic::IntoRequest<super::QuoteHistoryRecordsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::QuoteHistoryRecordsReply>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/stock.Stock/FetchQuoteHistoryRecords",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("stock.Stock", "FetchQuoteHistoryRecords"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// 取得殖利率排行，殖利率由高到低
+        pub async fn fetch_yield_ranks(
+            &mut self,
+            request: impl tonic::IntoRequest<super::YieldRanksRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::YieldRanksReply>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/stock.Stock/FetchYieldRanks",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("stock.Stock", "FetchYieldRanks"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// 取得每日股價統計，日期由新到舊
+        pub async fn fetch_daily_stock_price_stats(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DailyStockPriceStatsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::DailyStockPriceStatsReply>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/stock.Stock/FetchDailyStockPriceStats",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("stock.Stock", "FetchDailyStockPriceStats"));
+            self.inner.unary(req, path, codec).await
+        }
+    }
+}
+/// Generated server implementations.
+pub mod stock_server {
+    #![allow(
+        unused_variables,
+        dead_code,
+        missing_docs,
+        clippy::wildcard_imports,
+        clippy::let_unit_value,
+    )]
+    use tonic::codegen::*;
+    /// Generated trait containing gRPC methods that should be implemented for use with StockServer.
+    #[async_trait]
+    pub trait Stock: std::marker::Send + std::marker::Sync + 'static {
+        /// 新增或更新股票基本資料，欄位不合法時會在 violations 內逐一列出且不會更新
+        async fn update_stock_info(
+            &self,
+            request: tonic::Request<super::StockInfoRequest>,
+        ) -> std::result::Result<tonic::Response<super::StockInfoReply>, tonic::Status>;
+        /// 取得目前的股價
+        async fn fetch_current_stock_quotes(
+            &self,
+            request: tonic::Request<super::StockQuotesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::StockQuotesReply>,
+            tonic::Status,
+        >;
+        /// Server streaming response type for the SubscribeQuotes method.
+        type SubscribeQuotesStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::StockQuotes, tonic::Status>,
+            >
+            + std::marker::Send
+            + 'static;
+        /// 訂閱即時報價，開盤期間報價變動時送出，訂閱時會先送出已知的最後報價
+        async fn subscribe_quotes(
+            &self,
+            request: tonic::Request<super::StockQuotesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<Self::SubscribeQuotesStream>,
+            tonic::Status,
+        >;
+        /// 取得股市休市日
+        async fn fetch_holiday_schedule(
+            &self,
+            request: tonic::Request<super::HolidayScheduleRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::HolidayScheduleReply>,
+            tonic::Status,
+        >;
+        /// 匯入券商對帳單到會員的交易明細
+        async fn import_broker_statement(
+            &self,
+            request: tonic::Request<super::ImportBrokerStatementRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ImportBrokerStatementReply>,
+            tonic::Status,
+        >;
+        /// 取得每日收盤數據，日期由舊到新
+        async fn fetch_daily_quotes(
+            &self,
+            request: tonic::Request<super::DailyQuotesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::DailyQuotesReply>,
+            tonic::Status,
+        >;
+        /// Server streaming response type for the StreamDailyQuotes method.
+        type StreamDailyQuotesStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::DailyQuote, tonic::Status>,
+            >
+            + std::marker::Send
+            + 'static;
+        /// 以串流取得日期區間內所有的每日收盤數據，日期由舊到新，會忽略分頁參數
+        async fn stream_daily_quotes(
+            &self,
+            request: tonic::Request<super::DailyQuotesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<Self::StreamDailyQuotesStream>,
+            tonic::Status,
+        >;
+        /// 取得每日估算的便宜、合理、昂貴價，日期由新到舊
+        async fn fetch_estimates(
+            &self,
+            request: tonic::Request<super::EstimatesRequest>,
+        ) -> std::result::Result<tonic::Response<super::EstimatesReply>, tonic::Status>;
+        /// 取得股利發放記錄，年度由新到舊
+        async fn fetch_dividends(
+            &self,
+            request: tonic::Request<super::DividendsRequest>,
+        ) -> std::result::Result<tonic::Response<super::DividendsReply>, tonic::Status>;
+        /// 取得每月營收，月份由新到舊
+        async fn fetch_revenues(
+            &self,
+            request: tonic::Request<super::RevenuesRequest>,
+        ) -> std::result::Result<tonic::Response<super::RevenuesReply>, tonic::Status>;
+        /// 取得季度或年度財報，年度由新到舊
+        async fn fetch_financial_statements(
+            &self,
+            request: tonic::Request<super::FinancialStatementsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::FinancialStatementsReply>,
+            tonic::Status,
+        >;
+        /// 取得歷史最高、最低股價與股價淨值比
+        async fn fetch_quote_history_records(
+            &self,
+            request: tonic::Request<super::QuoteHistoryRecordsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::QuoteHistoryRecordsReply>,
+            tonic::Status,
+        >;
+        /// 取得殖利率排行，殖利率由高到低
+        async fn fetch_yield_ranks(
+            &self,
+            request: tonic::Request<super::YieldRanksRequest>,
+        ) -> std::result::Result<tonic::Response<super::YieldRanksReply>, tonic::Status>;
+        /// 取得每日股價統計，日期由新到舊
+        async fn fetch_daily_stock_price_stats(
+            &self,
+            request: tonic::Request<super::DailyStockPriceStatsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::DailyStockPriceStatsReply>,
+            tonic::Status,
+        >;
+    }
+    #[derive(Debug)]
+    pub struct StockServer<T> {
+        inner: Arc<T>,
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
+        max_decoding_message_size: Option<usize>,
+        max_encoding_message_size: Option<usize>,
+    }
+    impl<T> StockServer<T> {
+        pub fn new(inner: T) -> Self {
+            Self::from_arc(Arc::new(inner))
+        }
+        pub fn from_arc(inner: Arc<T>) -> Self {
+            Self {
+                inner,
+                accept_compression_encodings: Default::default(),
+                send_compression_encodings: Default::default(),
+                max_decoding_message_size: None,
+                max_encoding_message_size: None,
+            }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> InterceptedService<Self, F>
+        where
             F: tonic::service::Interceptor,
         {
             InterceptedService::new(Self::new(inner), interceptor)
@@ -419,25 +1294,26 @@ pub mod stock_server {
                     };
                     Box::pin(fut)
                 }
-                "/stock.Stock/FetchHolidaySchedule" => {
+                "/stock.Stock/SubscribeQuotes" => {
                     #[allow(non_camel_case_types)]
-                    struct FetchHolidayScheduleSvc<T: Stock>(pub Arc<T>);
+                    struct SubscribeQuotesSvc<T: Stock>(pub Arc<T>);
                     impl<
                         T: Stock,
-                    > tonic::server::UnaryService<super::HolidayScheduleRequest>
-                    for FetchHolidayScheduleSvc<T> {
-                        type Response = super::HolidayScheduleReply;
+                    > tonic::server::ServerStreamingService<super::StockQuotesRequest>
+                    for SubscribeQuotesSvc<T> {
+                        type Response = super::StockQuotes;
+                        type ResponseStream = T::SubscribeQuotesStream;
                         type Future = BoxFuture<
-                            tonic::Response<Self::Response>,
+                            tonic::Response<Self::ResponseStream>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::HolidayScheduleRequest>,
+                            request: tonic::Request<super::StockQuotesRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as Stock>::fetch_holiday_schedule(&inner, request).await
+                                <T as Stock>::subscribe_quotes(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -448,7 +1324,496 @@ pub mod stock_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = FetchHolidayScheduleSvc(inner);
+                        let method = SubscribeQuotesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/stock.Stock/FetchHolidaySchedule" => {
+                    #[allow(non_camel_case_types)]
+                    struct FetchHolidayScheduleSvc<T: Stock>(pub Arc<T>);
+                    impl<
+                        T: Stock,
+                    > tonic::server::UnaryService<super::HolidayScheduleRequest>
+                    for FetchHolidayScheduleSvc<T> {
+                        type Response = super::HolidayScheduleReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::HolidayScheduleRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Stock>::fetch_holiday_schedule(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = FetchHolidayScheduleSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/stock.Stock/ImportBrokerStatement" => {
+                    #[allow(non_camel_case_types)]
+                    struct ImportBrokerStatementSvc<T: Stock>(pub Arc<T>);
+                    impl<
+                        T: Stock,
+                    > tonic::server::UnaryService<super::ImportBrokerStatementRequest>
+                    for ImportBrokerStatementSvc<T> {
+                        type Response = super::ImportBrokerStatementReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ImportBrokerStatementRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Stock>::import_broker_statement(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ImportBrokerStatementSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/stock.Stock/FetchDailyQuotes" => {
+                    #[allow(non_camel_case_types)]
+                    struct FetchDailyQuotesSvc<T: Stock>(pub Arc<T>);
+                    impl<T: Stock> tonic::server::UnaryService<super::DailyQuotesRequest>
+                    for FetchDailyQuotesSvc<T> {
+                        type Response = super::DailyQuotesReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DailyQuotesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Stock>::fetch_daily_quotes(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = FetchDailyQuotesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/stock.Stock/StreamDailyQuotes" => {
+                    #[allow(non_camel_case_types)]
+                    struct StreamDailyQuotesSvc<T: Stock>(pub Arc<T>);
+                    impl<
+                        T: Stock,
+                    > tonic::server::ServerStreamingService<super::DailyQuotesRequest>
+                    for StreamDailyQuotesSvc<T> {
+                        type Response = super::DailyQuote;
+                        type ResponseStream = T::StreamDailyQuotesStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DailyQuotesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Stock>::stream_daily_quotes(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = StreamDailyQuotesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/stock.Stock/FetchEstimates" => {
+                    #[allow(non_camel_case_types)]
+                    struct FetchEstimatesSvc<T: Stock>(pub Arc<T>);
+                    impl<T: Stock> tonic::server::UnaryService<super::EstimatesRequest>
+                    for FetchEstimatesSvc<T> {
+                        type Response = super::EstimatesReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::EstimatesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Stock>::fetch_estimates(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = FetchEstimatesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/stock.Stock/FetchDividends" => {
+                    #[allow(non_camel_case_types)]
+                    struct FetchDividendsSvc<T: Stock>(pub Arc<T>);
+                    impl<T: Stock> tonic::server::UnaryService<super::DividendsRequest>
+                    for FetchDividendsSvc<T> {
+                        type Response = super::DividendsReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DividendsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Stock>::fetch_dividends(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = FetchDividendsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/stock.Stock/FetchRevenues" => {
+                    #[allow(non_camel_case_types)]
+                    struct FetchRevenuesSvc<T: Stock>(pub Arc<T>);
+                    impl<T: Stock> tonic::server::UnaryService<super::RevenuesRequest>
+                    for FetchRevenuesSvc<T> {
+                        type Response = super::RevenuesReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RevenuesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Stock>::fetch_revenues(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = FetchRevenuesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/stock.Stock/FetchFinancialStatements" => {
+                    #[allow(non_camel_case_types)]
+                    struct FetchFinancialStatementsSvc<T: Stock>(pub Arc<T>);
+                    impl<
+                        T: Stock,
+                    > tonic::server::UnaryService<super::FinancialStatementsRequest>
+                    for FetchFinancialStatementsSvc<T> {
+                        type Response = super::FinancialStatementsReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::FinancialStatementsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Stock>::fetch_financial_statements(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = FetchFinancialStatementsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/stock.Stock/FetchQuoteHistoryRecords" => {
+                    #[allow(non_camel_case_types)]
+                    struct FetchQuoteHistoryRecordsSvc<T: Stock>(pub Arc<T>);
+                    impl<
+                        T: Stock,
+                    > tonic::server::UnaryService<super::QuoteHistoryRecordsRequest>
+                    for FetchQuoteHistoryRecordsSvc<T> {
+                        type Response = super::QuoteHistoryRecordsReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::QuoteHistoryRecordsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Stock>::fetch_quote_history_records(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = FetchQuoteHistoryRecordsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/stock.Stock/FetchYieldRanks" => {
+                    #[allow(non_camel_case_types)]
+                    struct FetchYieldRanksSvc<T: Stock>(pub Arc<T>);
+                    impl<T: Stock> tonic::server::UnaryService<super::YieldRanksRequest>
+                    for FetchYieldRanksSvc<T> {
+                        type Response = super::YieldRanksReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::YieldRanksRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Stock>::fetch_yield_ranks(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = FetchYieldRanksSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/stock.Stock/FetchDailyStockPriceStats" => {
+                    #[allow(non_camel_case_types)]
+                    struct FetchDailyStockPriceStatsSvc<T: Stock>(pub Arc<T>);
+                    impl<
+                        T: Stock,
+                    > tonic::server::UnaryService<super::DailyStockPriceStatsRequest>
+                    for FetchDailyStockPriceStatsSvc<T> {
+                        type Response = super::DailyStockPriceStatsReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DailyStockPriceStatsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Stock>::fetch_daily_stock_price_stats(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = FetchDailyStockPriceStatsSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -0,0 +1,312 @@
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+use chrono_tz::Tz;
+use croner::Cron;
+
+use crate::{
+    database::table::job_run::JobRun,
+    logging,
+    scheduler::{
+        lease,
+        registry::{Trigger, JOBS},
+    },
+};
+
+/// 補執行時最多往回找多久之前的排程
+const CATCH_UP_LOOKBACK: TimeDelta = TimeDelta::days(7);
+
+/// 排程觸發時往回找對應排程時間的範圍
+const TICK_LOOKBACK: TimeDelta = TimeDelta::minutes(5);
+
+/// 開盤時間內重新啟動時往回找當天排程的範圍
+const INTRADAY_LOOKBACK: TimeDelta = TimeDelta::hours(12);
+
+/// 排程器可能在排程時間前一點點就觸發，往後多看一秒避免對應到上一次的排程
+const TICK_SKEW: TimeDelta = TimeDelta::seconds(1);
+
+/// 取得 now 之前(含)最近的一次排程時間，只往回找 lookback 的範圍，排程以 timezone 的時間計算
+pub fn latest_occurrence(
+    cron_expr: &str,
+    timezone: Tz,
+    now: DateTime<Utc>,
+    lookback: TimeDelta,
+) -> Option<DateTime<Utc>> {
+    occurrences(cron_expr, timezone, now, lookback).pop()
+}
+
+/// 取得 now 之前(含) lookback 範圍內所有的排程時間，依時間先後排序
+fn occurrences(
+    cron_expr: &str,
+    timezone: Tz,
+    now: DateTime<Utc>,
+    lookback: TimeDelta,
+) -> Vec<DateTime<Utc>> {
+    let cron = match Cron::new(cron_expr)
+        .with_seconds_required()
+        .with_dom_and_dow()
+        .parse()
+    {
+        Ok(cron) => cron,
+        Err(_) => return Vec::new(),
+    };
+
+    cron.iter_from((now - lookback).with_timezone(&timezone))
+        .map(|time| time.with_timezone(&Utc))
+        .take_while(|time| *time <= now)
+        .collect()
+}
+
+/// 排程觸發時對應的排程時間，用來當作執行記錄的唯一鍵
+pub fn tick_time(cron_expr: &str, timezone: Tz) -> DateTime<Utc> {
+    let now = Utc::now();
+    latest_occurrence(cron_expr, timezone, now + TICK_SKEW, TICK_LOOKBACK).unwrap_or(now)
+}
+
+/// 最近一次的排程是否沒有被執行，上次服務中止時仍在執行的也視為錯過
+fn is_missed(last: &JobRun, scheduled_time: DateTime<Utc>) -> bool {
+    match last.scheduled_time {
+        Some(time) if time >= scheduled_time => last.status == "interrupted",
+        _ => true,
+    }
+}
+
+/// 將上次服務中止時仍在執行的記錄標記為 interrupted，需在排程啟動前呼叫，
+/// 啟用租約時其他實例可能正在執行，改由取得各工作的租約時只標記前一個持有者的記錄
+pub async fn mark_interrupted() {
+    if lease::LEASE.is_enabled() {
+        return;
+    }
+
+    if let Err(why) = JobRun::mark_interrupted().await {
+        logging::error_file_async(format!("{:?}", why));
+    }
+}
+
+/// 依執行記錄找出服務停止期間錯過的排程，每個工作只補執行依交易日規則需要執行的最近一次，
+/// 沒有任何記錄的工作視為第一次部署而不補執行，重新啟動前已順延的排程會繼續等待，
+/// 停用的工作不補執行
+pub async fn catch_up(names: &[&'static str]) {
+    resume_deferred().await;
+
+    let now = Utc::now();
+    let jobs = JOBS.list();
+
+    for name in names {
+        let job = match jobs
+            .iter()
+            .find(|job| job.name == *name && job.schedule.enabled)
+        {
+            Some(job) => job,
+            None => continue,
+        };
+        let timezone = job.schedule.timezone;
+
+        // 最近一次的排程可能因非交易日而不執行，要往前找到需要執行的那一次
+        let mut target: Option<(DateTime<Utc>, NaiveDate)> = None;
+        for scheduled_time in occurrences(&job.schedule.cron, timezone, now, CATCH_UP_LOOKBACK)
+            .into_iter()
+            .rev()
+        {
+            let date = scheduled_time.with_timezone(&timezone).date_naive();
+            if let Some(run_date) = super::run_date(job.schedule.rule, date).await {
+                target = Some((scheduled_time, run_date));
+                break;
+            }
+        }
+        let (scheduled_time, run_date) = match target {
+            Some(target) => target,
+            None => continue,
+        };
+
+        // 先取得租約，讓前一個持有者留下的執行中記錄標記為中斷後再判斷是否錯過
+        if !lease::LEASE.acquire(name).await {
+            continue;
+        }
+
+        match JobRun::fetch_last_scheduled(name).await {
+            Ok(Some(last)) if is_missed(&last, scheduled_time) => {}
+            Ok(_) => continue,
+            Err(why) => {
+                logging::error_file_async(format!("{:?}", why));
+                continue;
+            }
+        }
+
+        logging::info_file_async(format!(
+            "補執行錯過的工作 {} {}",
+            name,
+            scheduled_time.with_timezone(&timezone)
+        ));
+
+        let handle = tokio::spawn(super::run_scheduled(name, Trigger::CatchUp(scheduled_time)));
+        // 順延到之後才執行的工作不等待，避免卡住其他工作的補執行
+        if run_date <= Utc::now().with_timezone(&timezone).date_naive() {
+            let _ = handle.await;
+        }
+    }
+}
+
+/// 開盤時間內重新啟動時以當天最近一次的排程時間補執行盤中的工作，
+/// 經由 run_scheduled 取得租約並以執行記錄避免與其他實例重複執行
+pub async fn resume_intraday(names: &[&'static str]) {
+    let now = Utc::now();
+    let jobs = JOBS.list();
+
+    for name in names {
+        let job = match jobs
+            .iter()
+            .find(|job| job.name == *name && job.schedule.enabled)
+        {
+            Some(job) => job,
+            None => continue,
+        };
+
+        if let Some(scheduled_time) = latest_occurrence(
+            &job.schedule.cron,
+            job.schedule.timezone,
+            now,
+            INTRADAY_LOOKBACK,
+        ) {
+            tokio::spawn(super::run_scheduled(name, Trigger::CatchUp(scheduled_time)));
+        }
+    }
+}
+
+/// 繼續等待重新啟動前已順延的排程
+async fn resume_deferred() {
+    let deferred = match JobRun::fetch_deferred().await {
+        Ok(deferred) => deferred,
+        Err(why) => {
+            logging::error_file_async(format!("{:?}", why));
+            return;
+        }
+    };
+    let jobs = JOBS.list();
+
+    for record in deferred {
+        let job = match jobs
+            .iter()
+            .find(|job| job.name == record.job_name && job.schedule.enabled)
+        {
+            Some(job) => job,
+            None => continue,
+        };
+        let (scheduled_time, run_at) = match (record.scheduled_time, record.deferred_time) {
+            (Some(scheduled_time), Some(run_at)) => (scheduled_time, run_at),
+            _ => continue,
+        };
+
+        tokio::spawn(super::resume_deferred(
+            job.name,
+            job.dated
+                .then(|| parameter_date(&record.parameters))
+                .flatten(),
+            Trigger::CatchUp(scheduled_time.with_timezone(&Utc)),
+            run_at,
+        ));
+    }
+}
+
+/// 從執行參數 {"date":"2024-01-02"} 取得指定的日期
+fn parameter_date(parameters: &str) -> Option<NaiveDate> {
+    let value: serde_json::Value = serde_json::from_str(parameters).ok()?;
+    NaiveDate::parse_from_str(value.get("date")?.as_str()?, "%Y-%m-%d").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, TimeZone};
+    use chrono_tz::Asia::Taipei;
+
+    use super::*;
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    #[test]
+    fn test_latest_occurrence() {
+        let now = utc(2024, 1, 3, 6, 0);
+        // 台北時間 15:00 為 UTC 07:00
+        assert_eq!(
+            latest_occurrence("0 0 15 * * *", Taipei, now, CATCH_UP_LOOKBACK),
+            Some(utc(2024, 1, 2, 7, 0))
+        );
+        assert_eq!(
+            latest_occurrence(
+                "0 0 15 * * *",
+                Taipei,
+                utc(2024, 1, 3, 7, 0),
+                CATCH_UP_LOOKBACK
+            ),
+            Some(utc(2024, 1, 3, 7, 0))
+        );
+        assert_eq!(
+            latest_occurrence("0 0 7 * * *", Tz::UTC, now, CATCH_UP_LOOKBACK),
+            Some(utc(2024, 1, 2, 7, 0))
+        );
+        // 台北時間週日 06:00 為 UTC 週六 22:00，2023-12-30 為週六
+        assert_eq!(
+            latest_occurrence("0 0 6 * * Sun", Taipei, now, CATCH_UP_LOOKBACK),
+            Some(utc(2023, 12, 30, 22, 0))
+        );
+        assert_eq!(
+            latest_occurrence("0 0 15 * * *", Taipei, now, TICK_LOOKBACK),
+            None
+        );
+        assert_eq!(
+            latest_occurrence("invalid", Taipei, now, CATCH_UP_LOOKBACK),
+            None
+        );
+    }
+
+    #[test]
+    fn test_occurrences() {
+        let now = utc(2024, 1, 3, 6, 0);
+        assert_eq!(
+            occurrences("0 0 15 * * *", Taipei, now, TimeDelta::days(3)),
+            vec![
+                utc(2023, 12, 31, 7, 0),
+                utc(2024, 1, 1, 7, 0),
+                utc(2024, 1, 2, 7, 0)
+            ]
+        );
+        assert!(occurrences("invalid", Taipei, now, CATCH_UP_LOOKBACK).is_empty());
+    }
+
+    #[test]
+    fn test_parameter_date() {
+        assert_eq!(
+            parameter_date(r#"{"date": "2024-01-02"}"#),
+            NaiveDate::from_ymd_opt(2024, 1, 2)
+        );
+        assert_eq!(parameter_date("{}"), None);
+    }
+
+    #[test]
+    fn test_is_missed() {
+        let scheduled_time = utc(2024, 1, 2, 7, 0);
+        let last = |time: DateTime<Utc>, status: &str| JobRun {
+            scheduled_time: Some(time.with_timezone(&Local)),
+            status: status.to_string(),
+            ..Default::default()
+        };
+
+        assert!(!is_missed(
+            &last(scheduled_time, "succeeded"),
+            scheduled_time
+        ));
+        assert!(!is_missed(&last(scheduled_time, "failed"), scheduled_time));
+        assert!(!is_missed(
+            &last(scheduled_time, "deferred"),
+            scheduled_time
+        ));
+        assert!(is_missed(
+            &last(scheduled_time, "interrupted"),
+            scheduled_time
+        ));
+        assert!(is_missed(
+            &last(utc(2024, 1, 1, 7, 0), "succeeded"),
+            scheduled_time
+        ));
+    }
+}
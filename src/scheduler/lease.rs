@@ -0,0 +1,186 @@
+use std::{collections::HashSet, env, process, sync::Mutex, time::Duration};
+
+use once_cell::sync::Lazy;
+
+use crate::{
+    config::{self, SETTINGS},
+    database::table::job_run::JobRun,
+    logging, nosql,
+    util::metrics,
+};
+
+/// 未設定時租約的有效時間
+const DEFAULT_TTL: Duration = Duration::from_secs(30);
+
+/// 租約在 Redis 內的 key 前綴，後面接工作名稱
+const KEY_PREFIX: &str = "scheduler:lease:";
+
+/// 目前實例的排程工作租約
+pub static LEASE: Lazy<Lease> = Lazy::new(|| Lease::new(&SETTINGS.scheduler.lease));
+
+/// 以 Redis 的租約讓多個實例中只有持有租約的實例執行排程工作，
+/// 每個實例定期嘗試取得或續約，持有的實例停止續約超過有效時間後由其他實例接手
+pub struct Lease {
+    enabled: bool,
+    ttl: Duration,
+    /// 租約的值，用來確認租約是否為目前實例所持有
+    owner: String,
+    /// 目前實例持有租約的工作
+    held: Mutex<HashSet<&'static str>>,
+}
+
+impl Lease {
+    pub fn new(config: &config::SchedulerLease) -> Self {
+        let ttl = match config.ttl_seconds {
+            0 => DEFAULT_TTL,
+            seconds => Duration::from_secs(seconds),
+        };
+
+        Lease {
+            enabled: config.enabled,
+            ttl,
+            owner: format!(
+                "{}-{}-{:08x}",
+                env::var("HOSTNAME").unwrap_or_default(),
+                process::id(),
+                rand::random::<u32>()
+            ),
+            held: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// 取得或續約工作的租約，回傳目前實例是否可以執行，未啟用時一律可以執行，
+    /// Redis 無法連線時也執行，由 job_runs 的記錄避免同一個排程時間重複執行
+    pub async fn acquire(&self, name: &'static str) -> bool {
+        if !self.enabled {
+            return true;
+        }
+
+        let ttl = self.ttl.as_millis() as u64;
+        match nosql::redis::CLIENT
+            .acquire_lease(&key(name), &self.owner, ttl)
+            .await
+        {
+            Ok(held) => {
+                // 剛取得租約時前一個持有者的租約已過期，將它留下的執行中記錄標記為中斷
+                if self.update(name, held) && held {
+                    if let Err(why) = JobRun::interrupt_others(name, &self.owner).await {
+                        logging::error_file_async(format!("{:?}", why));
+                    }
+                }
+                held
+            }
+            Err(why) => {
+                logging::error_file_async(format!("{:?}", why));
+                true
+            }
+        }
+    }
+
+    /// 是否以租約協調多個實例
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// 目前實例的識別，記錄在 job_runs.instance
+    pub fn owner(&self) -> &str {
+        &self.owner
+    }
+
+    /// 記錄租約的變化，只在取得或失去租約時輸出日誌，回傳租約是否有變化
+    fn update(&self, name: &'static str, held: bool) -> bool {
+        let changed = match self.held.lock() {
+            Ok(mut names) if held => names.insert(name),
+            Ok(mut names) => names.remove(name),
+            Err(_) => false,
+        };
+
+        if changed {
+            let msg = if held {
+                format!("取得工作 {} 的租約({})", name, self.owner)
+            } else {
+                format!("工作 {} 的租約已由其他實例持有", name)
+            };
+            logging::info_file_async(msg);
+            metrics::record_lease(name, held);
+        }
+
+        changed
+    }
+
+    /// 每隔有效時間的三分之一對所有工作取得或續約一次，
+    /// 持有租約的實例停止續約後其他實例最晚在租約過期後的下一輪接手
+    pub async fn keep_alive(&'static self, names: Vec<&'static str>) {
+        if !self.enabled || names.is_empty() {
+            return;
+        }
+
+        let mut interval = tokio::time::interval(self.ttl / 3);
+        loop {
+            interval.tick().await;
+            for name in &names {
+                self.acquire(name).await;
+            }
+        }
+    }
+
+    /// 釋放目前實例持有的所有租約，讓其他實例不必等到租約過期就能接手，在服務停止時呼叫
+    pub async fn release_all(&self) {
+        let names: Vec<&'static str> = match self.held.lock() {
+            Ok(mut names) => names.drain().collect(),
+            Err(_) => return,
+        };
+
+        for name in names {
+            if let Err(why) = nosql::redis::CLIENT
+                .release_lease(&key(name), &self.owner)
+                .await
+            {
+                logging::error_file_async(format!("{:?}", why));
+            }
+            metrics::record_lease(name, false);
+        }
+    }
+}
+
+fn key(name: &str) -> String {
+    format!("{}{}", KEY_PREFIX, name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_acquire_when_disabled() {
+        let lease = Lease::new(&config::SchedulerLease::default());
+        assert_eq!(lease.ttl, DEFAULT_TTL);
+        assert!(lease.acquire("closing").await);
+        assert!(lease.held.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_failover() {
+        dotenv::dotenv().ok();
+        let config = config::SchedulerLease {
+            enabled: true,
+            ttl_seconds: 1,
+        };
+        let leader = Lease::new(&config);
+        let standby = Lease::new(&config);
+
+        assert!(leader.acquire("lease_test").await);
+        assert!(!standby.acquire("lease_test").await);
+        assert!(leader.acquire("lease_test").await);
+
+        // 領導者停止續約，租約過期後由備援接手
+        tokio::time::sleep(Duration::from_millis(1200)).await;
+        assert!(standby.acquire("lease_test").await);
+        assert!(!leader.acquire("lease_test").await);
+
+        standby.release_all().await;
+        assert!(leader.acquire("lease_test").await);
+        leader.release_all().await;
+    }
+}
@@ -0,0 +1,522 @@
+use std::{env, future::Future, sync::Arc};
+
+use anyhow::{anyhow, bail, Context, Error, Result};
+use chrono::{DateTime, Local, NaiveDate, TimeDelta, Utc};
+use chrono_tz::Tz;
+use futures::FutureExt;
+use tokio_cron_scheduler::{Job, JobScheduler};
+
+use crate::{
+    backfill::{
+        delisted_company, dividend, financial_statement, isin, net_asset_value_per_share,
+        qualified_foreign_institutional_investor, revenue, stock_weight,
+    },
+    bot::{self, notifier::NotifyEvent},
+    calculation,
+    calendar::TradingDayRule,
+    config, declare, event,
+    event::ddns,
+    logging,
+};
+
+use self::registry::{JobOutcome, Schedule, Trigger};
+
+/// 排程工作的執行記錄與錯過排程的補執行
+pub mod history;
+/// 多個實例間排程工作的租約
+pub mod lease;
+/// 排程工作的註冊表
+pub mod registry;
+
+/// 未設定時區時排程使用的時區
+const DEFAULT_TIMEZONE: Tz = chrono_tz::Asia::Taipei;
+
+/// 服務停止期間錯過時需要在啟動後補執行的工作，提醒類與盤中的工作錯過後再執行已無意義
+const CATCH_UP_JOBS: &[&str] = &[
+    "net_asset_value_per_share_emerging",
+    "payout_ratio",
+    "quarter_eps",
+    "financial_statement_quarter",
+    "annual_eps",
+    "financial_statement_annual",
+    "net_asset_value_per_share_zero_value",
+    "revenue",
+    "isin",
+    "delisted_company",
+    "adjusted_price",
+    "stock_weight",
+    "closing",
+    "dividend",
+    "qualified_foreign_institutional_investor",
+];
+
+/// 開盤期間持續執行的工作，開盤時間內重新啟動時需要補執行當天的排程
+const INTRADAY_JOBS: &[&str] = &["trace_stock_price", "intraday_quote"];
+
+/// 啟動排程
+pub async fn start(sched: &JobScheduler) -> Result<()> {
+    history::mark_interrupted().await;
+    run_cron(sched).await.context("Failed to run cron jobs")?;
+    let enabled = registry::JOBS
+        .list()
+        .into_iter()
+        .filter(|job| job.schedule.enabled)
+        .map(|job| job.name)
+        .collect();
+    tokio::spawn(lease::LEASE.keep_alive(enabled));
+    tokio::spawn(history::catch_up(CATCH_UP_JOBS));
+
+    //若在開盤埘間重啟服務定時任務會無法觸發，所以在啟動時要以當天的排程補執行盤中的工作，
+    //與排程觸發相同需取得租約，當天的排程已由其他實例執行時不會重複執行
+    if declare::StockExchange::TWSE.is_open() {
+        history::resume_intraday(INTRADAY_JOBS).await;
+    }
+
+    let msg = format!(
+        "StockCrawler 已啟動\r\nRust OS/Arch: {}/{}\r\n",
+        env::consts::OS,
+        env::consts::ARCH
+    );
+
+    bot::notifier::send(NotifyEvent::Startup, &msg).await;
+
+    Ok(())
+}
+
+async fn run_cron(sched: &JobScheduler) -> Result<()> {
+    //let sched = JobScheduler::new().await?;
+    //                 sec  min   hour   day of month   month   day of week   year
+    //let expression = "0   30   9,12,15     1,15       May-Aug  Mon,Wed,Fri  2018/2";
+    // 以下為預設的排程(台北時間)，可在 app.json 的 scheduler.jobs 依工作名稱覆蓋
+
+    let jobs = vec![
+        // 01:00 更新興櫃股票的每股淨值
+        create_job(
+            "net_asset_value_per_share_emerging",
+            "0 0 1 * * *",
+            net_asset_value_per_share::emerging::execute,
+        ),
+        // 02:30 更新盈餘分配率
+        create_job("payout_ratio", "0 30 2 * * *", dividend::payout_ratio::execute),
+        // 03:00 更新台股季度財報
+        create_job("quarter_eps", "0 0 3 * * *", event::taiwan_stock::quarter_eps::execute),
+        // 04:00 更新台股季度財報(ROE、ROA為零的數據)
+        create_job(
+            "financial_statement_quarter",
+            "0 0 4 * * *",
+            financial_statement::quarter::execute,
+        ),
+        // 05:00 更新台股年度財報(僅有eps 等少數欄位的資料)
+        create_job("annual_eps", "0 0 5 * * *", event::taiwan_stock::annual_eps::execute),
+        // 05:00 更新台股年度財報
+        create_job(
+            "financial_statement_annual",
+            "0 0 5 * * *",
+            financial_statement::annual::execute,
+        ),
+        // 05:00 從yahoo取得每股淨值數據，將未下市但每股淨值為零的股票更新其數據
+        create_job(
+            "net_asset_value_per_share_zero_value",
+            "0 0 5 * * *",
+            net_asset_value_per_share::zero_value::execute,
+        ),
+        // 05:00 取得台股的營收
+        create_job("revenue", "0 0 5 * * *", revenue::execute),
+        // 05:00 更新台股國際證券識別碼
+        create_job("isin", "0 0 5 * * *", isin::execute),
+        // 05:00 更新下市的股票
+        create_job("delisted_company", "0 0 5 * * *", delisted_company::execute),
+        // 每週日 06:00 重新計算所有股票的還原股價調整因子
+        create_job(
+            "adjusted_price",
+            "0 0 6 * * Sun",
+            calculation::adjusted_price::execute_all,
+        ),
+        // 08:00 提醒本日除權息的股票
+        create_job("ex_dividend", "0 0 8 * * *", event::taiwan_stock::ex_dividend::execute),
+        // 08:00 提醒本日發放股利的股票(只通知自已有的股票)
+        create_job("payable_date", "0 0 8 * * *", event::taiwan_stock::payable_date::execute),
+        // 08:00 提醒本日開始公開申購的股票
+        create_job("public", "0 0 8 * * *", || async {
+            event::taiwan_stock::public::execute().await
+            //Ok(())
+        }),
+        // 09:00 更新股票權值佔比
+        create_job("stock_weight", "0 0 9 * * *", stock_weight::execute),
+        // 09:00 提醒本日已達高低標的股票有那些
+        create_job("trace_stock_price", "0 0 9 * * *", event::trace::stock_price::execute),
+        // 09:00 記錄觀察清單內股票的盤中一分鐘K線
+        create_job(
+            "intraday_quote",
+            "0 0 9 * * *",
+            event::taiwan_stock::intraday_quote::execute,
+        ),
+        // 15:00 取得收盤報價數據，手動觸發時可指定日期
+        create_dated_job("closing", "0 0 15 * * *", |date| async move {
+            let date = date.unwrap_or_else(|| Local::now().date_naive());
+            event::taiwan_stock::closing::execute_for(date).await
+        }),
+        // 21:00 資料庫內尚未有年度配息數據的股票取出後向第三方查詢後更新回資料庫
+        create_job("dividend", "0 0 21 * * *", dividend::execute),
+        // 22:00 外資持股狀態
+        create_job(
+            "qualified_foreign_institutional_investor",
+            "0 0 22 * * *",
+            qualified_foreign_institutional_investor::execute,
+        ),
+        // 每分鐘更新一次ddns的ip
+        create_job("ddns", "0 * * * * *", ddns::refresh),
+    ];
+
+    let jobs = jobs.into_iter().collect::<Result<Vec<_>>>()?;
+    let names: Vec<&str> = jobs.iter().map(|(name, _)| *name).collect();
+    check_job_names(&config::SETTINGS.scheduler, &names)?;
+
+    // 停用的工作只註冊到 registry，仍可手動執行
+    for (name, job) in jobs {
+        if let Some(job) = job {
+            sched
+                .add(job.clone())
+                .await
+                .context("Failed to add job to scheduler")?;
+            registry::JOBS.attach(sched, name, job);
+        }
+    }
+
+    sched.start().await.context("Failed to start scheduler")
+}
+
+pub trait Scheduler {
+    fn is_weekend(&self) -> bool;
+}
+
+/// 設定檔內的工作名稱都必須存在，避免名稱打錯的設定被默默忽略
+fn check_job_names(config: &config::Scheduler, names: &[&str]) -> Result<()> {
+    let mut unknown: Vec<&str> = config
+        .jobs
+        .keys()
+        .map(String::as_str)
+        .filter(|name| !names.contains(name))
+        .collect();
+
+    if !unknown.is_empty() {
+        unknown.sort_unstable();
+        bail!("Unknown job names in scheduler.jobs: {}", unknown.join(", "));
+    }
+
+    Ok(())
+}
+
+/// 以設定檔內同名工作的設定覆蓋程式內的預設排程
+fn schedule(config: &config::Scheduler, name: &str, default_cron: &str) -> Result<Schedule> {
+    let job = config.jobs.get(name).cloned().unwrap_or_default();
+    let cron = if job.cron.is_empty() {
+        default_cron.to_string()
+    } else {
+        job.cron
+    };
+    let timezone = match [job.timezone.as_str(), config.timezone.as_str()]
+        .into_iter()
+        .find(|timezone| !timezone.is_empty())
+    {
+        Some(timezone) => timezone.parse::<Tz>().map_err(|why| {
+            anyhow!(
+                "Failed to parse the timezone {} of job {} because {}",
+                timezone,
+                name,
+                why
+            )
+        })?,
+        None => DEFAULT_TIMEZONE,
+    };
+    let rule = if job.trading_day.is_empty() {
+        TradingDayRule::Always
+    } else {
+        job.trading_day.parse::<TradingDayRule>().map_err(|why| {
+            anyhow!(
+                "Failed to parse the trading day rule of job {} because {}",
+                name,
+                why
+            )
+        })?
+    };
+
+    Ok(Schedule {
+        cron,
+        timezone,
+        enabled: job.enabled,
+        concurrency: job.concurrency.max(1),
+        rule,
+    })
+}
+
+/// 建立排程工作並註冊到 registry::JOBS，讓工作也能被手動觸發，停用的工作不回傳 Job
+fn create_job<F, Fut>(
+    name: &'static str,
+    cron_expr: &'static str,
+    task: F,
+) -> Result<(&'static str, Option<Job>)>
+where
+    F: Fn() -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), Error>> + Send + 'static,
+{
+    build_job(name, cron_expr, false, move |_| task())
+}
+
+/// 建立可指定日期執行的排程工作，由排程觸發時日期為 None
+fn create_dated_job<F, Fut>(
+    name: &'static str,
+    cron_expr: &'static str,
+    task: F,
+) -> Result<(&'static str, Option<Job>)>
+where
+    F: Fn(Option<NaiveDate>) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), Error>> + Send + 'static,
+{
+    build_job(name, cron_expr, true, task)
+}
+
+fn build_job<F, Fut>(
+    name: &'static str,
+    cron_expr: &'static str,
+    dated: bool,
+    task: F,
+) -> Result<(&'static str, Option<Job>)>
+where
+    F: Fn(Option<NaiveDate>) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), Error>> + Send + 'static,
+{
+    let schedule = schedule(&config::SETTINGS.scheduler, name, cron_expr)?;
+    let cron = schedule.cron.clone();
+    let timezone = schedule.timezone;
+    // 停用的工作也要建立 Job，讓設定錯誤的 cron 在啟動時就報錯
+    let job = Job::new_async_tz(schedule.cron.as_str(), timezone, move |_uuid, _l| {
+        Box::pin(run_scheduled(
+            name,
+            Trigger::Schedule(history::tick_time(&cron, timezone)),
+        ))
+    })
+    .context(format!("Failed to create job {}({})", name, schedule.cron))?;
+    let enabled = schedule.enabled;
+
+    registry::JOBS.register(
+        name,
+        schedule,
+        dated,
+        Arc::new(move |date| task(date).boxed()),
+    );
+
+    Ok((name, enabled.then_some(job)))
+}
+
+/// 執行排程觸發的工作並記錄失敗的原因，租約由其他實例持有時不執行
+async fn run_scheduled(name: &'static str, trigger: Trigger) {
+    if !lease::LEASE.acquire(name).await {
+        return;
+    }
+
+    match dispatch(name, trigger).await {
+        Ok(Some(JobOutcome::Failed(why))) => logging::error_file_async(format!(
+            "Failed to execute task {}({}) because {}",
+            name, trigger, why
+        )),
+        Err(why) => logging::error_file_async(format!(
+            "Failed to execute task {}({}) because {:?}",
+            name, trigger, why
+        )),
+        _ => {}
+    }
+}
+
+/// 依工作的交易日規則決定執行的日期，需要順延時記錄在 job_runs 後等到執行日的同一時間再執行，
+/// 不需執行時回傳 None
+async fn dispatch(name: &'static str, trigger: Trigger) -> Result<Option<JobOutcome>> {
+    let job = registry::JOBS
+        .list()
+        .into_iter()
+        .find(|job| job.name == name)
+        .ok_or_else(|| anyhow!("Job {} not found", name))?;
+    let scheduled_time = trigger
+        .scheduled_time()
+        .unwrap_or_else(Utc::now)
+        .with_timezone(&job.schedule.timezone);
+    let scheduled_date = scheduled_time.date_naive();
+
+    let run_date = match run_date(job.schedule.rule, scheduled_date).await {
+        Some(date) => date,
+        None => {
+            logging::info_file_async(format!(
+                "{} 不是交易日，不執行工作 {}",
+                scheduled_date, name
+            ));
+            return Ok(None);
+        }
+    };
+    let date = job.dated.then_some(run_date);
+
+    if run_date > scheduled_date {
+        let run_at = (scheduled_time + TimeDelta::days((run_date - scheduled_date).num_days()))
+            .with_timezone(&Local);
+        match registry::JOBS.defer(name, date, trigger, run_at).await {
+            Ok(true) => {}
+            Ok(false) => return Ok(Some(JobOutcome::Skipped)),
+            // 無法記錄時仍在記憶體內等待，只是重新啟動後不會再執行
+            Err(why) => logging::error_file_async(format!("{:?}", why)),
+        }
+        logging::info_file_async(format!("工作 {} 順延至 {} 執行", name, run_at));
+        sleep_until(run_at).await;
+    }
+
+    let outcome = registry::JOBS.run(name, date, trigger)?.await?;
+
+    Ok(Some(outcome))
+}
+
+/// 依交易日規則取得實際執行的日期，無法取得交易日曆時改以週一至週五為交易日
+pub(crate) async fn run_date(rule: TradingDayRule, date: NaiveDate) -> Option<NaiveDate> {
+    match rule.run_date(date).await {
+        Ok(run_date) => run_date,
+        Err(why) => {
+            logging::error_file_async(format!(
+                "Failed to get the run date of {} by {} because {:?}",
+                date, rule, why
+            ));
+            rule.run_date_by_weekday(date)
+        }
+    }
+}
+
+/// 繼續等待重新啟動前已順延的排程，時間已過時立即執行
+pub(crate) async fn resume_deferred(
+    name: &'static str,
+    date: Option<NaiveDate>,
+    trigger: Trigger,
+    run_at: DateTime<Local>,
+) {
+    logging::info_file_async(format!("工作 {} 繼續等待至 {} 執行", name, run_at));
+    sleep_until(run_at).await;
+
+    if !lease::LEASE.acquire(name).await {
+        return;
+    }
+
+    let outcome = match registry::JOBS.run(name, date, trigger) {
+        Ok(handle) => handle.await.map_err(Error::from),
+        Err(why) => Err(why),
+    };
+
+    match outcome {
+        Ok(JobOutcome::Failed(why)) => logging::error_file_async(format!(
+            "Failed to execute deferred task {} because {}",
+            name, why
+        )),
+        Err(why) => logging::error_file_async(format!(
+            "Failed to execute deferred task {} because {:?}",
+            name, why
+        )),
+        _ => {}
+    }
+}
+
+async fn sleep_until(run_at: DateTime<Local>) {
+    if let Ok(delay) = (run_at - Local::now()).to_std() {
+        tokio::time::sleep(delay).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // 注意這個慣用法：在 tests 模組中，從外部範疇匯入所有名字。
+    use super::*;
+
+    async fn run() -> Result<()> {
+        let sched = JobScheduler::new().await?;
+        let every_minute = Job::new_async("* * * * * *", |_uuid, _l| {
+            Box::pin(async move {
+                println!("_uuid {:?} now: {:?}", _uuid, chrono::Local::now());
+                dbg!("_uuid {:?} now: {:?}", _uuid, chrono::Local::now());
+                logging::debug_file_async(format!(
+                    "_uuid {:?} now: {:?}",
+                    _uuid,
+                    chrono::Local::now()
+                ));
+            })
+        })?;
+        sched.add(every_minute).await?;
+
+        sched.start().await?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_schedule() {
+        let mut config = config::Scheduler::default();
+        config.jobs.insert(
+            "closing".to_string(),
+            config::JobSchedule {
+                cron: "0 30 15 * * *".to_string(),
+                concurrency: 2,
+                trading_day: "trading_days_after:1".to_string(),
+                ..Default::default()
+            },
+        );
+        config.jobs.insert(
+            "ddns".to_string(),
+            config::JobSchedule {
+                timezone: "UTC".to_string(),
+                enabled: false,
+                ..Default::default()
+            },
+        );
+
+        let closing = schedule(&config, "closing", "0 0 15 * * *").unwrap();
+        assert_eq!(closing.cron, "0 30 15 * * *");
+        assert_eq!(closing.timezone, DEFAULT_TIMEZONE);
+        assert_eq!(closing.concurrency, 2);
+        assert_eq!(closing.rule, TradingDayRule::TradingDaysAfter(1));
+        assert!(closing.enabled);
+
+        let ddns = schedule(&config, "ddns", "0 * * * * *").unwrap();
+        assert_eq!(ddns.cron, "0 * * * * *");
+        assert_eq!(ddns.timezone, Tz::UTC);
+        assert_eq!(ddns.concurrency, 1);
+        assert_eq!(ddns.rule, TradingDayRule::Always);
+        assert!(!ddns.enabled);
+
+        assert_eq!(
+            schedule(&config, "revenue", "0 0 5 * * *").unwrap(),
+            Schedule::new("0 0 5 * * *")
+        );
+
+        config.jobs.get_mut("ddns").unwrap().trading_day = "weekday".to_string();
+        assert!(schedule(&config, "ddns", "0 * * * * *").is_err());
+
+        config.timezone = "Asia/Nowhere".to_string();
+        assert!(schedule(&config, "revenue", "0 0 5 * * *").is_err());
+    }
+
+    #[test]
+    fn test_check_job_names() {
+        let mut config = config::Scheduler::default();
+        config
+            .jobs
+            .insert("closing".to_string(), config::JobSchedule::default());
+        assert!(check_job_names(&config, &["closing", "ddns"]).is_ok());
+
+        config
+            .jobs
+            .insert("closeing".to_string(), config::JobSchedule::default());
+        assert!(check_job_names(&config, &["closing", "ddns"]).is_err());
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_split() {
+        dotenv::dotenv().ok();
+        run().await.expect("TODO: panic message");
+        //sleep(Duration::from_secs(240)).await;
+        //loop {}
+        //println!("split: {:?}, elapsed time: {:?}", result, end);
+    }
+}
@@ -0,0 +1,502 @@
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+};
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use chrono_tz::Tz;
+use futures::future::BoxFuture;
+use once_cell::sync::{Lazy, OnceCell};
+use tokio::task::{AbortHandle, JoinHandle};
+use tokio_cron_scheduler::{Job, JobScheduler};
+
+use crate::{
+    calendar::TradingDayRule, database::table::job_run, logging, scheduler::lease, util::metrics,
+};
+
+/// 排程工作的執行內容，date 為 None 時表示以今天執行
+pub type JobTask = Arc<dyn Fn(Option<NaiveDate>) -> BoxFuture<'static, Result<()>> + Send + Sync>;
+
+/// 所有已註冊的排程工作
+pub static JOBS: Lazy<JobRegistry> = Lazy::new(JobRegistry::with_history);
+
+/// 工作執行的結果
+#[derive(Debug, Clone, PartialEq)]
+pub enum JobOutcome {
+    Succeeded,
+    Failed(String),
+    Cancelled,
+    /// 同一個排程時間已有執行記錄，不再重複執行
+    Skipped,
+}
+
+impl fmt::Display for JobOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JobOutcome::Succeeded => write!(f, "succeeded"),
+            JobOutcome::Failed(_) => write!(f, "failed"),
+            JobOutcome::Cancelled => write!(f, "cancelled"),
+            JobOutcome::Skipped => write!(f, "skipped"),
+        }
+    }
+}
+
+/// 工作的觸發方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Trigger {
+    /// 由排程觸發，帶入對應的排程時間
+    Schedule(DateTime<Utc>),
+    /// 啟動時補執行服務停止期間錯過的排程
+    CatchUp(DateTime<Utc>),
+    Manual,
+}
+
+impl Trigger {
+    /// 對應的排程時間，手動觸發時為 None
+    pub fn scheduled_time(&self) -> Option<DateTime<Utc>> {
+        match self {
+            Trigger::Schedule(time) | Trigger::CatchUp(time) => Some(*time),
+            Trigger::Manual => None,
+        }
+    }
+}
+
+impl fmt::Display for Trigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Trigger::Schedule(_) => write!(f, "schedule"),
+            Trigger::CatchUp(_) => write!(f, "catch_up"),
+            Trigger::Manual => write!(f, "manual"),
+        }
+    }
+}
+
+/// 工作的排程設定
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    /// cron 表示式(秒 分 時 日 月 星期)，以 timezone 的時間計算
+    pub cron: String,
+    pub timezone: Tz,
+    /// false 時不由排程觸發也不補執行，仍可手動執行
+    pub enabled: bool,
+    /// 同時執行的上限
+    pub concurrency: usize,
+    /// 依交易日決定是否執行的規則，手動觸發時不受規則限制
+    pub rule: TradingDayRule,
+}
+
+impl Schedule {
+    /// 以台北時間計算、同時只執行一個的排程
+    pub fn new(cron: &str) -> Self {
+        Schedule {
+            cron: cron.to_string(),
+            timezone: chrono_tz::Asia::Taipei,
+            enabled: true,
+            concurrency: 1,
+            rule: TradingDayRule::Always,
+        }
+    }
+}
+
+/// 一次執行的記錄
+#[derive(Debug, Clone)]
+pub struct JobRun {
+    pub started_at: DateTime<Local>,
+    pub finished_at: DateTime<Local>,
+    pub outcome: JobOutcome,
+}
+
+/// 工作目前的狀態
+#[derive(Debug, Clone)]
+pub struct JobStatus {
+    pub name: &'static str,
+    pub schedule: Schedule,
+    /// 是否可指定日期執行
+    pub dated: bool,
+    /// 執行中時為最早開始執行的時間
+    pub running_since: Option<DateTime<Local>>,
+    pub last_run: Option<JobRun>,
+}
+
+struct Running {
+    id: u64,
+    started_at: DateTime<Local>,
+    abort: AbortHandle,
+}
+
+struct JobEntry {
+    name: &'static str,
+    schedule: Schedule,
+    dated: bool,
+    task: JobTask,
+    job: Option<Job>,
+    running: Vec<Running>,
+    last_run: Option<JobRun>,
+}
+
+/// 排程工作的註冊表，可查詢狀態、手動觸發與取消執行中的工作
+pub struct JobRegistry {
+    next_id: AtomicU64,
+    /// 是否將每次執行寫入 job_runs 資料表
+    history: bool,
+    scheduler: OnceCell<JobScheduler>,
+    entries: RwLock<Vec<JobEntry>>,
+}
+
+impl JobRegistry {
+    pub fn new() -> Self {
+        JobRegistry {
+            next_id: AtomicU64::new(1),
+            history: false,
+            scheduler: OnceCell::new(),
+            entries: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// 建立會將每次執行寫入 job_runs 資料表的註冊表，並以記錄確保同一個排程時間只執行一次
+    pub fn with_history() -> Self {
+        JobRegistry {
+            history: true,
+            ..JobRegistry::new()
+        }
+    }
+
+    /// 註冊工作，同名的工作會被取代
+    pub fn register(&self, name: &'static str, schedule: Schedule, dated: bool, task: JobTask) {
+        let mut entries = match self.entries.write() {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+        let entry = JobEntry {
+            name,
+            schedule,
+            dated,
+            task,
+            job: None,
+            running: Vec::new(),
+            last_run: None,
+        };
+
+        match entries.iter_mut().find(|e| e.name == name) {
+            Some(exist) => *exist = entry,
+            None => entries.push(entry),
+        }
+    }
+
+    /// 記錄工作在排程器內對應的 Job，用來查詢下次執行的時間
+    pub fn attach(&self, scheduler: &JobScheduler, name: &str, job: Job) {
+        let _ = self.scheduler.set(scheduler.clone());
+        if let Ok(mut entries) = self.entries.write() {
+            if let Some(entry) = entries.iter_mut().find(|e| e.name == name) {
+                entry.job = Some(job);
+            }
+        }
+    }
+
+    /// 排程器是否已啟動
+    pub async fn is_started(&self) -> bool {
+        match self.scheduler.get() {
+            Some(scheduler) => scheduler.inited().await,
+            None => false,
+        }
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries
+            .read()
+            .map(|entries| entries.iter().any(|e| e.name == name))
+            .unwrap_or(false)
+    }
+
+    /// 執行工作，同一個工作同時執行的數量不超過排程設定的上限，回傳的 JoinHandle 會在工作結束後取得結果
+    pub fn run(
+        &'static self,
+        name: &str,
+        date: Option<NaiveDate>,
+        trigger: Trigger,
+    ) -> Result<JoinHandle<JobOutcome>> {
+        let mut entries = self
+            .entries
+            .write()
+            .map_err(|why| anyhow!("Failed to entries.write because {:?}", why))?;
+        let entry = entries
+            .iter_mut()
+            .find(|e| e.name == name)
+            .ok_or_else(|| anyhow!("Job {} not found", name))?;
+
+        if entry.running.len() >= entry.schedule.concurrency.max(1) {
+            bail!(
+                "Job {} is already running {} of {} since {}",
+                name,
+                entry.running.len(),
+                entry.schedule.concurrency,
+                entry.running[0].started_at
+            );
+        }
+
+        if date.is_some() && !entry.dated {
+            bail!("Job {} does not accept a date", name);
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let started_at = Local::now();
+        let context = logging::Context {
+            job: Some(entry.name),
+            run_id: Some(format!("{}-{}", started_at.format("%Y%m%d%H%M%S"), id)),
+            symbol: None,
+        };
+        let record = self.history.then(|| run_record(entry.name, date, trigger));
+        // 取消時工作的 future 會被丟棄，所以記錄的序號要另外保存
+        let claimed: Arc<OnceCell<i64>> = Arc::new(OnceCell::new());
+        let serial = Arc::clone(&claimed);
+        let task = (entry.task)(date);
+        let handle = tokio::spawn(logging::scope(context.clone(), async move {
+            if let Some(mut record) = record {
+                match record.claim().await {
+                    Ok(Some(id)) => {
+                        let _ = serial.set(id);
+                    }
+                    Ok(None) => return Ok(false),
+                    // 資料庫異常時仍然執行，避免所有的排程工作都停擺
+                    Err(why) => logging::error_file_async(format!("{:?}", why)),
+                }
+            }
+
+            task.await.map(|_| true)
+        }));
+        entry.running.push(Running {
+            id,
+            started_at,
+            abort: handle.abort_handle(),
+        });
+
+        let name = entry.name;
+        Ok(tokio::spawn(logging::scope(context, async move {
+            logging::info_file_async(format!("Job {} started", name));
+            let outcome = match handle.await {
+                Ok(Ok(true)) => JobOutcome::Succeeded,
+                Ok(Ok(false)) => JobOutcome::Skipped,
+                Ok(Err(why)) => JobOutcome::Failed(format!("{:?}", why)),
+                Err(why) if why.is_cancelled() => JobOutcome::Cancelled,
+                Err(why) => JobOutcome::Failed(format!("{:?}", why)),
+            };
+            metrics::record_job(
+                name,
+                &outcome,
+                (Local::now() - started_at).to_std().unwrap_or_default(),
+            );
+            logging::info_file_async(format!("Job {} {}", name, outcome));
+            if let Some(serial) = claimed.get() {
+                let error = match &outcome {
+                    JobOutcome::Failed(why) => why.as_str(),
+                    _ => "",
+                };
+                if let Err(why) =
+                    job_run::JobRun::finish(*serial, &outcome.to_string(), error).await
+                {
+                    logging::error_file_async(format!("{:?}", why));
+                }
+            }
+            self.finish(name, id, started_at, outcome.clone());
+
+            outcome
+        })))
+    }
+
+    /// 記錄排程順延到 run_at 才執行，讓重新啟動後可以繼續等待，
+    /// 同一個排程時間已有記錄時回傳 false 表示不需再執行
+    pub async fn defer(
+        &self,
+        name: &str,
+        date: Option<NaiveDate>,
+        trigger: Trigger,
+        run_at: DateTime<Local>,
+    ) -> Result<bool> {
+        if !self.history {
+            return Ok(true);
+        }
+
+        run_record(name, date, trigger).defer(run_at).await
+    }
+
+    fn finish(&self, name: &str, id: u64, started_at: DateTime<Local>, outcome: JobOutcome) {
+        if let Ok(mut entries) = self.entries.write() {
+            if let Some(entry) = entries.iter_mut().find(|e| e.name == name) {
+                entry.running.retain(|r| r.id != id);
+                entry.last_run = Some(JobRun {
+                    started_at,
+                    finished_at: Local::now(),
+                    outcome,
+                });
+            }
+        }
+    }
+
+    /// 取消工作所有執行中的實例
+    pub fn cancel(&self, name: &str) -> Result<()> {
+        let entries = self
+            .entries
+            .read()
+            .map_err(|why| anyhow!("Failed to entries.read because {:?}", why))?;
+        let entry = entries
+            .iter()
+            .find(|e| e.name == name)
+            .ok_or_else(|| anyhow!("Job {} not found", name))?;
+
+        if entry.running.is_empty() {
+            bail!("Job {} is not running", name);
+        }
+
+        for running in &entry.running {
+            running.abort.abort();
+        }
+
+        Ok(())
+    }
+
+    /// 依註冊順序取得所有工作的狀態
+    pub fn list(&self) -> Vec<JobStatus> {
+        self.entries
+            .read()
+            .map(|entries| {
+                entries
+                    .iter()
+                    .map(|e| JobStatus {
+                        name: e.name,
+                        schedule: e.schedule.clone(),
+                        dated: e.dated,
+                        running_since: e.running.iter().map(|r| r.started_at).min(),
+                        last_run: e.last_run.clone(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// 取得工作下次被排程觸發的時間
+    pub async fn next_run_at(&self, name: &str) -> Option<DateTime<Local>> {
+        let job = self
+            .entries
+            .read()
+            .ok()?
+            .iter()
+            .find(|e| e.name == name)
+            .and_then(|e| e.job.clone())?;
+        let mut scheduler = self.scheduler.get()?.clone();
+
+        scheduler
+            .next_tick_for_job(job.guid())
+            .await
+            .ok()
+            .flatten()
+            .map(|tick| tick.with_timezone(&Local))
+    }
+}
+
+/// 建立工作的執行記錄，date 會記錄在參數內
+fn run_record(name: &str, date: Option<NaiveDate>, trigger: Trigger) -> job_run::JobRun {
+    let parameters = match date {
+        Some(date) => serde_json::json!({ "date": date.to_string() }),
+        None => serde_json::json!({}),
+    };
+
+    let mut record = job_run::JobRun::new(
+        name,
+        &trigger.to_string(),
+        trigger
+            .scheduled_time()
+            .map(|time| time.with_timezone(&Local)),
+        parameters.to_string(),
+    );
+    record.instance = lease::LEASE.owner().to_string();
+    record
+}
+
+impl Default for JobRegistry {
+    fn default() -> Self {
+        JobRegistry::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::FutureExt;
+
+    use super::*;
+
+    static REGISTRY: Lazy<JobRegistry> = Lazy::new(JobRegistry::new);
+
+    #[tokio::test]
+    async fn test_run_and_cancel() {
+        REGISTRY.register(
+            "ok",
+            Schedule::new("0 0 0 * * *"),
+            true,
+            Arc::new(|_| async { Ok(()) }.boxed()),
+        );
+        REGISTRY.register(
+            "sleep",
+            Schedule::new("0 0 0 * * *"),
+            false,
+            Arc::new(|_| {
+                async {
+                    tokio::time::sleep(Duration::from_secs(60)).await;
+                    Ok(())
+                }
+                .boxed()
+            }),
+        );
+
+        let date = NaiveDate::from_ymd_opt(2024, 1, 2);
+        let outcome = REGISTRY
+            .run("ok", date, Trigger::Manual)
+            .unwrap()
+            .await
+            .unwrap();
+        assert_eq!(outcome, JobOutcome::Succeeded);
+        assert!(REGISTRY.run("sleep", date, Trigger::Manual).is_err());
+        assert!(REGISTRY.run("missing", None, Trigger::Manual).is_err());
+
+        let handle = REGISTRY.run("sleep", None, Trigger::Manual).unwrap();
+        assert!(REGISTRY.run("sleep", None, Trigger::Manual).is_err());
+        REGISTRY.cancel("sleep").unwrap();
+        assert_eq!(handle.await.unwrap(), JobOutcome::Cancelled);
+        assert!(REGISTRY.cancel("sleep").is_err());
+
+        REGISTRY.register(
+            "parallel",
+            Schedule {
+                concurrency: 2,
+                ..Schedule::new("0 0 0 * * *")
+            },
+            false,
+            Arc::new(|_| {
+                async {
+                    tokio::time::sleep(Duration::from_secs(60)).await;
+                    Ok(())
+                }
+                .boxed()
+            }),
+        );
+        let first = REGISTRY.run("parallel", None, Trigger::Manual).unwrap();
+        let second = REGISTRY.run("parallel", None, Trigger::Manual).unwrap();
+        assert!(REGISTRY.run("parallel", None, Trigger::Manual).is_err());
+        REGISTRY.cancel("parallel").unwrap();
+        assert_eq!(first.await.unwrap(), JobOutcome::Cancelled);
+        assert_eq!(second.await.unwrap(), JobOutcome::Cancelled);
+
+        let status = REGISTRY.list();
+        assert_eq!(status.len(), 3);
+        assert!(status.iter().all(|s| s.running_since.is_none()));
+        assert_eq!(
+            status[1].last_run.as_ref().map(|r| r.outcome.clone()),
+            Some(JobOutcome::Cancelled)
+        );
+    }
+}
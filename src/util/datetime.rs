@@ -2,6 +2,12 @@ use chrono::{DateTime, Datelike, Local, NaiveDate, Weekday};
 
 use crate::logging;
 
+/// 未指定起始日期時的查詢起點，早於所有的歷史資料
+pub const EARLIEST_DATE: NaiveDate = match NaiveDate::from_ymd_opt(1900, 1, 1) {
+    Some(date) => date,
+    None => panic!("invalid date"),
+};
+
 /// A trait representing the weekend concept.
 pub trait Weekend {
     /// Determines if a given date is a weekend.
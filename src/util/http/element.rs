@@ -1,9 +1,32 @@
 use anyhow::{anyhow, Result};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
-use scraper::{Html, Selector};
+use scraper::{ElementRef, Html, Selector};
 
-use crate::util::text;
+use crate::util::{metrics, text};
+
+/// 記錄網頁內容無法解析並回傳對應的錯誤，通常代表網站改版或被阻擋
+pub fn parse_failure(url: &str, msg: String) -> anyhow::Error {
+    metrics::record_parse_failure(&super::site(url));
+    anyhow!(msg)
+}
+
+/// 以 CSS selector 選取文件中的元素，selector 無效或選不到任何元素時記錄該站點的解析失敗
+pub fn select<'a>(
+    document: &'a Html,
+    url: &str,
+    css_selector: &str,
+) -> Result<Vec<ElementRef<'a>>> {
+    let selector = Selector::parse(css_selector).map_err(|why| {
+        parse_failure(url, format!("Failed to Selector::parse because: {:?}", why))
+    })?;
+    let elements: Vec<ElementRef<'a>> = document.select(&selector).collect();
+    if elements.is_empty() {
+        metrics::record_parse_failure(&super::site(url));
+    }
+
+    Ok(elements)
+}
 
 /// Extracts the text value of an element selected by a given CSS selector.
 ///
@@ -120,16 +143,77 @@ pub struct GetOneElementText<'a> {
 pub fn get_one_element(target: GetOneElementText<'_>) -> Result<String> {
     //let text = util::http::get(target.url, None).await?;
     // let document = Html::parse_document(&text);
-    let selector = Selector::parse(target.selector)
-        .map_err(|why| anyhow!("Failed to Selector::parse because: {:?}", why))?;
+    let selector = Selector::parse(target.selector).map_err(|why| {
+        parse_failure(
+            target.url,
+            format!("Failed to Selector::parse because: {:?}", why),
+        )
+    })?;
+    target
+        .document
+        .select(&selector)
+        .next()
+        .and_then(|element| parse_value(&element, target.element))
+        .ok_or_else(|| {
+            parse_failure(
+                target.url,
+                format!("The element not found from {}", target.url),
+            )
+        })
+}
+
+/// 與 `get_one_element` 相同，但用於不一定存在的元素，找不到時不視為解析失敗
+pub fn find_one_element(target: &GetOneElementText<'_>) -> Option<String> {
+    let selector = Selector::parse(target.selector).ok()?;
     target
         .document
         .select(&selector)
         .next()
         .and_then(|element| parse_value(&element, target.element))
-        .ok_or_else(|| anyhow!("The element not found from {}", target.url))
 }
 
 pub fn get_one_element_as_decimal(target: GetOneElementText<'_>) -> Result<Decimal> {
     text::parse_decimal(&get_one_element(target)?, None)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_one_element() {
+        let target = |selector| GetOneElementText {
+            stock_symbol: "2330",
+            url: "https://example.com/quote/2330",
+            selector,
+            element: "span",
+            document: Html::parse_document(r#"<div id="price"><span>123.5</span></div>"#),
+        };
+
+        assert_eq!(get_one_element(target("#price")).unwrap(), "123.5");
+        assert!(get_one_element(target("#volume")).is_err());
+        assert!(get_one_element(target("#[")).is_err());
+        assert_eq!(
+            find_one_element(&target("#price")),
+            Some("123.5".to_string())
+        );
+        assert_eq!(find_one_element(&target("#volume")), None);
+    }
+
+    #[test]
+    fn test_select() {
+        let document =
+            Html::parse_document("<table><tr><td>1</td></tr><tr><td>2</td></tr></table>");
+
+        assert_eq!(
+            select(&document, "https://example.com", "tr")
+                .unwrap()
+                .len(),
+            2
+        );
+        assert!(select(&document, "https://example.com", "li")
+            .unwrap()
+            .is_empty());
+        assert!(select(&document, "https://example.com", "#[").is_err());
+    }
+}
@@ -1,27 +1,29 @@
 use std::{
     collections::HashMap,
+    fmt,
     time::{Duration, Instant},
 };
 
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use once_cell::sync::{Lazy, OnceCell};
-use reqwest::{header, header::SET_COOKIE, Client, Method, RequestBuilder, Response};
+use reqwest::{header, header::SET_COOKIE, Client, Method, RequestBuilder, Response, StatusCode};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use tokio::sync::Semaphore;
 
-use crate::{logging::Logger, util};
+use crate::{
+    logging::Logger,
+    util::{self, metrics},
+};
 
 pub mod element;
 pub mod user_agent;
 
+/// The maximum number of concurrent requests, eight times the number of available CPU cores.
+static MAX_CONCURRENT_REQUESTS: Lazy<usize> = Lazy::new(|| num_cpus::get() * 8);
+
 /// A semaphore for limiting concurrent requests.
-///
-/// The initial number of permits is set to eight times the number of available CPU cores.
-static SEMAPHORE: Lazy<Semaphore> = Lazy::new(|| {
-    let cpus = num_cpus::get();
-    Semaphore::new(cpus * 8)
-});
+static SEMAPHORE: Lazy<Semaphore> = Lazy::new(|| Semaphore::new(*MAX_CONCURRENT_REQUESTS));
 
 /// A singleton instance of the reqwest client.
 static CLIENT: OnceCell<Client> = OnceCell::new();
@@ -56,6 +58,46 @@ impl TextForceBig5 for Response {
     }
 }
 
+/// Errors caused by the site itself rather than by the requested content:
+/// the request could not be sent, the server answered with an error status,
+/// or the response body could not be decoded.
+#[derive(Debug)]
+pub enum SiteError {
+    Transport(String),
+    Status(String),
+    Parse(String),
+}
+
+impl fmt::Display for SiteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SiteError::Transport(msg) | SiteError::Status(msg) | SiteError::Parse(msg) => {
+                write!(f, "{}", msg)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SiteError {}
+
+/// Returns true if the error, or any error it wraps, is a `SiteError`.
+pub fn is_site_error(why: &anyhow::Error) -> bool {
+    why.chain().any(|cause| cause.is::<SiteError>())
+}
+
+/// Returns the number of requests currently holding a semaphore permit.
+pub fn permits_in_use() -> usize {
+    MAX_CONCURRENT_REQUESTS.saturating_sub(SEMAPHORE.available_permits())
+}
+
+/// Returns the host of the URL, used as the site label of the crawler metrics.
+fn site(url: &str) -> String {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
 /// Returns the reqwest client singleton instance or creates one if it doesn't exist.
 ///
 /// # Returns
@@ -96,11 +138,13 @@ fn get_client() -> Result<&'static Client> {
 /// * `Result<RES>`: The deserialized response, or an error if the request fails or the response cannot be deserialized.
 pub async fn get_json<RES: DeserializeOwned>(url: &str) -> Result<RES> {
     //send(Method::GET, url, None, None::<fn(_) -> _>)
-    get_response(url, None)
-        .await?
+    check_status(url, get_response(url, None).await?)?
         .json::<RES>()
         .await
-        .map_err(|e| anyhow!("Error parsing response JSON: {:?}", e))
+        .map_err(|e| {
+            metrics::record_parse_failure(&site(url));
+            SiteError::Parse(format!("Error parsing response JSON: {:?}", e)).into()
+        })
 }
 
 pub async fn get_response(url: &str, headers: Option<header::HeaderMap>) -> Result<Response> {
@@ -117,8 +161,7 @@ pub async fn get_response(url: &str, headers: Option<header::HeaderMap>) -> Resu
 ///
 /// * `Result<String>`: The response text, or an error if the request fails or the response cannot be parsed.
 pub async fn get(url: &str, headers: Option<header::HeaderMap>) -> Result<String> {
-    get_response(url, headers)
-        .await?
+    check_status(url, get_response(url, headers).await?)?
         .text()
         .await
         .map_err(|e| anyhow!("Error parsing response text: {:?}", e))
@@ -145,8 +188,7 @@ pub fn extract_cookies(response: &Response) -> Option<String> {
 ///
 /// * `Result<String>`: The Big5 encoded response text, or an error if the request fails or the response cannot be parsed.
 pub async fn get_use_big5(url: &str) -> Result<String> {
-    send(Method::GET, url, None, None::<fn(_) -> _>)
-        .await?
+    check_status(url, send(Method::GET, url, None, None::<fn(_) -> _>).await?)?
         .text_force_big5()
         .await
         .map_err(|e| anyhow!("Error parsing response text use BIG5: {:?}", e))
@@ -196,12 +238,46 @@ where
     /*res.json::<RES>()
     .await
     .map_err(|why| anyhow!("Error parsing response JSON: {:?}", why))*/
-    let res_body = res.text().await.map_err(|e| anyhow!("Error reading response body: {}", e))?;
+    let res_body = check_status(url, res)?
+        .text()
+        .await
+        .map_err(|e| anyhow!("Error reading response body: {}", e))?;
 
     // Print the response body
     //println!("Response body: {}", res_body);
 
-    serde_json::from_str(&res_body).map_err(|e| anyhow!("Error parsing response JSON({}): {:?}", &res_body, e))
+    serde_json::from_str(&res_body).map_err(|e| {
+        metrics::record_parse_failure(&site(url));
+        SiteError::Parse(format!(
+            "Error parsing response JSON({}): {:?}",
+            &res_body, e
+        ))
+        .into()
+    })
+}
+
+/// Performs an HTTP POST request with a JSON body and returns the raw response.
+///
+/// Useful for endpoints that reply with an empty body (e.g. 204 No Content),
+/// where the caller only needs to check the status code.
+///
+/// # Arguments
+///
+/// * `url`: The URL to send the POST request to.
+/// * `headers`: An optional set of headers to include with the request.
+/// * `req`: The request payload to serialize as JSON.
+pub async fn post_response_use_json<REQ: Serialize>(
+    url: &str,
+    headers: Option<header::HeaderMap>,
+    req: &REQ,
+) -> Result<Response> {
+    send(
+        Method::POST,
+        url,
+        headers,
+        Some(|rb: RequestBuilder| rb.json(req)),
+    )
+    .await
 }
 
 /// Performs an HTTP POST request with form data and specified headers, and returns the response as text.
@@ -221,7 +297,7 @@ pub async fn post(
     headers: Option<header::HeaderMap>,
     params: Option<HashMap<&str, &str>>,
 ) -> Result<String> {
-    send(
+    let res = send(
         Method::POST,
         url,
         headers,
@@ -233,10 +309,12 @@ pub async fn post(
             }
         }),
     )
-    .await?
-    .text()
-    .await
-    .map_err(|why| anyhow!("Error parsing response text: {:?}", why))
+    .await?;
+
+    check_status(url, res)?
+        .text()
+        .await
+        .map_err(|why| anyhow!("Error parsing response text: {:?}", why))
 }
 
 const MAX_RETRIES: usize = 2;
@@ -277,6 +355,7 @@ async fn send(
     body: Option<impl FnOnce(RequestBuilder) -> RequestBuilder>,
 ) -> Result<Response> {
     let visit_log = format!("{method}:{url}");
+    let site = site(url);
     let client = get_client()?;
     let mut rb = client.request(method, url);
 
@@ -296,9 +375,15 @@ async fn send(
         let permit = SEMAPHORE.acquire().await;
         let start = Instant::now();
         let res = rb_clone.send().await;
-        let elapsed = start.elapsed().as_millis();
+        let duration = start.elapsed();
+        let elapsed = duration.as_millis();
 
         drop(permit);
+        metrics::record_request(
+            &site,
+            res.as_ref().ok().map(|response| response.status().as_u16()),
+            duration,
+        );
 
         match res {
             Ok(response) => {
@@ -318,11 +403,23 @@ async fn send(
         }
     }
 
-    Err(anyhow!(
+    Err(SiteError::Transport(format!(
         "Failed to send request to {} after {} attempts",
-        url,
-        MAX_RETRIES
+        url, MAX_RETRIES
     ))
+    .into())
+}
+
+/// Rejects responses whose status means the site is unavailable (5xx or 429),
+/// other statuses are left to the caller because some sites answer a missing
+/// symbol with 404.
+fn check_status(url: &str, response: Response) -> Result<Response> {
+    let status = response.status();
+    if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+        return Err(SiteError::Status(format!("{} responded with {}", url, status)).into());
+    }
+
+    Ok(response)
 }
 
 #[cfg(test)]
@@ -334,6 +431,14 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_is_site_error() {
+        let why = anyhow::Error::from(SiteError::Status("example.com responded with 503".into()))
+            .context("Failed to fetch");
+        assert!(is_site_error(&why));
+        assert!(!is_site_error(&anyhow!("data is empty")));
+    }
+
     #[tokio::test]
     async fn test_request() {
         let url = concat_string!(
@@ -0,0 +1,124 @@
+use std::time::Duration;
+
+use ::metrics::{counter, gauge, histogram};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use once_cell::sync::Lazy;
+
+use crate::{cache::SHARE, database, logging, scheduler::registry::JobOutcome, util::http};
+
+/// 請求與排程工作耗時的分桶(秒)
+const DURATION_BUCKETS: &[f64] = &[
+    0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 300.0, 900.0,
+];
+
+/// 全域的 Prometheus recorder，第一次使用時安裝，安裝失敗時不記錄任何指標
+static HANDLE: Lazy<Option<PrometheusHandle>> = Lazy::new(|| {
+    let installed = PrometheusBuilder::new()
+        .set_buckets_for_metric(Matcher::Suffix("_seconds".to_string()), DURATION_BUCKETS)
+        .and_then(|builder| builder.install_recorder());
+
+    match installed {
+        Ok(handle) => Some(handle),
+        Err(why) => {
+            logging::error_file_async(format!(
+                "Failed to install prometheus recorder because {:?}",
+                why
+            ));
+            None
+        }
+    }
+});
+
+/// 安裝 recorder，在此之前記錄的指標都會被丟棄，所以要在啟動時先呼叫
+pub fn init() {
+    Lazy::force(&HANDLE);
+}
+
+/// 以 Prometheus 的文字格式輸出目前所有的指標
+pub fn render() -> String {
+    let handle = match HANDLE.as_ref() {
+        Some(handle) => handle,
+        None => return String::new(),
+    };
+
+    // 快取大小、連線池等狀態只在抓取指標時取樣
+    gauge!("http_request_permits_in_use").set(http::permits_in_use() as f64);
+    for (name, size) in SHARE.sizes() {
+        gauge!("cache_entries", "cache" => name).set(size as f64);
+    }
+    let pool = database::get_connection();
+    let idle = pool.num_idle();
+    gauge!("database_pool_connections", "state" => "idle").set(idle as f64);
+    gauge!("database_pool_connections", "state" => "in_use")
+        .set(pool.size().saturating_sub(idle as u32) as f64);
+
+    handle.run_upkeep();
+    handle.render()
+}
+
+/// 記錄一次對外部網站的請求，status 為 None 表示連線失敗或逾時
+pub fn record_request(site: &str, status: Option<u16>, elapsed: Duration) {
+    let status = match status {
+        Some(code) => format!("{}xx", code / 100),
+        None => "error".to_string(),
+    };
+
+    counter!("crawler_requests_total", "site" => site.to_string(), "status" => status).increment(1);
+    histogram!("crawler_request_duration_seconds", "site" => site.to_string())
+        .record(elapsed.as_secs_f64());
+}
+
+/// 記錄報價站點的成功率與是否暫停使用
+pub fn record_site_health(site: &'static str, success_rate: f64, open: bool) {
+    gauge!("crawler_site_success_rate", "site" => site).set(success_rate);
+    gauge!("crawler_site_circuit_open", "site" => site).set(if open { 1.0 } else { 0.0 });
+}
+
+/// 記錄網站回應的內容無法解析，通常代表網站改版或被阻擋
+pub fn record_parse_failure(site: &str) {
+    counter!("crawler_parse_failures_total", "site" => site.to_string()).increment(1);
+}
+
+/// 記錄排程工作執行的結果與耗時
+pub fn record_job(name: &'static str, outcome: &JobOutcome, elapsed: Duration) {
+    let outcome = outcome.to_string();
+    counter!("scheduler_job_runs_total", "job" => name, "outcome" => outcome.clone()).increment(1);
+    histogram!("scheduler_job_duration_seconds", "job" => name, "outcome" => outcome)
+        .record(elapsed.as_secs_f64());
+}
+
+/// 記錄目前實例是否持有排程工作的租約
+pub fn record_lease(name: &'static str, held: bool) {
+    gauge!("scheduler_lease_held", "job" => name).set(if held { 1.0 } else { 0.0 });
+}
+
+/// 記錄 TTL 快取的查詢是否命中
+pub fn record_ttl_lookup(cache: &'static str, hit: bool) {
+    let result = if hit { "hit" } else { "miss" };
+    counter!("cache_ttl_lookups_total", "cache" => cache, "result" => result).increment(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_render() {
+        dotenv::dotenv().ok();
+        init();
+
+        record_request("metrics.test", Some(200), Duration::from_millis(120));
+        record_request("metrics.test", None, Duration::from_secs(3));
+        record_parse_failure("metrics.test");
+        record_job("closing", &JobOutcome::Succeeded, Duration::from_secs(42));
+        record_ttl_lookup("daily_quote", true);
+
+        let text = render();
+        assert!(text.contains(r#"crawler_requests_total{site="metrics.test",status="2xx"} 1"#));
+        assert!(text.contains(r#"crawler_requests_total{site="metrics.test",status="error"} 1"#));
+        assert!(text.contains(r#"crawler_parse_failures_total{site="metrics.test"} 1"#));
+        assert!(text.contains("scheduler_job_duration_seconds_bucket"));
+        assert!(text.contains(r#"cache_entries{cache="stocks"}"#));
+        assert!(text.contains("http_request_permits_in_use"));
+    }
+}
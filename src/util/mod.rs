@@ -3,6 +3,8 @@ use std::cmp::max;
 pub mod datetime;
 pub mod http;
 pub mod map;
+/// Prometheus 指標
+pub mod metrics;
 pub mod text;
 
 /// 文數字間的轉換